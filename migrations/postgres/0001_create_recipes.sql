@@ -0,0 +1,44 @@
+-- Recipes and the ingredients and tags belonging to them, see `PgRecipeRepository`.
+
+CREATE TABLE recipes (
+    id UUID PRIMARY KEY,
+    name TEXT NOT NULL,
+    -- `Difficulty` as its `u8` value, 1 for easy to 4 for expert
+    difficulty SMALLINT NOT NULL CHECK (difficulty BETWEEN 1 AND 4),
+    prep_minutes INTEGER NOT NULL CHECK (prep_minutes BETWEEN 0 AND 65535),
+    cook_minutes INTEGER NOT NULL CHECK (cook_minutes BETWEEN 0 AND 65535),
+    rest_minutes INTEGER NOT NULL CHECK (rest_minutes BETWEEN 0 AND 65535),
+    servings INTEGER NOT NULL CHECK (servings BETWEEN 0 AND 65535),
+    description TEXT NOT NULL,
+    -- One numbered step per line, see `Recipe::directions_text`
+    directions TEXT NOT NULL,
+    -- The primary image, empty for none
+    img BYTEA NOT NULL,
+    status TEXT NOT NULL CHECK (status IN ('draft', 'published', 'archived')),
+    version BIGINT NOT NULL CHECK (version BETWEEN 0 AND 4294967295),
+    created_at TIMESTAMPTZ NOT NULL,
+    updated_at TIMESTAMPTZ NOT NULL
+);
+
+CREATE INDEX recipes_status_name_idx ON recipes (status, lower(name) COLLATE "C", id);
+
+CREATE TABLE ingredients (
+    id UUID PRIMARY KEY,
+    recipe_id UUID NOT NULL REFERENCES recipes (id) ON DELETE CASCADE,
+    name TEXT NOT NULL,
+    amount DOUBLE PRECISION NOT NULL,
+    unit TEXT NOT NULL,
+    note TEXT
+);
+
+CREATE INDEX ingredients_recipe_id_idx ON ingredients (recipe_id);
+
+CREATE TABLE recipe_tags (
+    recipe_id UUID NOT NULL REFERENCES recipes (id) ON DELETE CASCADE,
+    tag TEXT NOT NULL,
+    -- `RecipeTag::normalized`, which tags are compared by
+    normalized TEXT NOT NULL,
+    PRIMARY KEY (recipe_id, normalized)
+);
+
+CREATE INDEX recipe_tags_normalized_idx ON recipe_tags (normalized);
@@ -0,0 +1,5 @@
+-- The fields of a recipe without a column or table of their own, like its nutrition, allergens, equipment,
+-- secondary images and the durations and images of its steps, as a JSON object. Recipes stored before had none
+-- of them kept, and read as they did before from an empty object.
+
+ALTER TABLE recipes ADD COLUMN details JSONB NOT NULL DEFAULT '{}';
@@ -0,0 +1,12 @@
+-- Every version a recipe was created or updated with, as the whole recipe serialized to JSON, see
+-- `RecipeRepository::history`. The history of a recipe stored before starts at its next update, until then
+-- it is only the stored version.
+
+CREATE TABLE recipe_revisions (
+    recipe_id UUID NOT NULL REFERENCES recipes (id) ON DELETE CASCADE,
+    version BIGINT NOT NULL CHECK (version BETWEEN 0 AND 4294967295),
+    author TEXT,
+    recorded_at TIMESTAMPTZ NOT NULL,
+    recipe JSONB NOT NULL,
+    PRIMARY KEY (recipe_id, version)
+);
@@ -0,0 +1,4 @@
+-- `list` now lowercases names under the "C" collation, which only lowercases ASCII letters, so the index
+-- it sorts by has to as well.
+DROP INDEX recipes_status_name_idx;
+CREATE INDEX recipes_status_name_idx ON recipes (status, lower(name COLLATE "C"), id);
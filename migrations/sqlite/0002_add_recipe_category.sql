@@ -0,0 +1,5 @@
+-- The `Category` every recipe has, as its snake case name. Recipes stored before they had a category are main
+-- courses, like `Category::default`.
+
+ALTER TABLE recipes ADD COLUMN category TEXT NOT NULL DEFAULT 'main_course'
+    CHECK (category IN ('appetizer', 'main_course', 'dessert', 'beverage', 'side', 'breakfast'));
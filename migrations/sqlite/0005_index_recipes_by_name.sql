@@ -0,0 +1,2 @@
+-- Lets `list` walk published recipes in name order instead of sorting them.
+CREATE INDEX recipes_status_name_idx ON recipes (status, lower(name), id);
@@ -0,0 +1,205 @@
+use std::fmt;
+use uuid::Uuid;
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+use super::diet::PLANT_BASED;
+use super::Recipe;
+
+/// A common food allergen that a recipe can declare.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize), serde(rename_all = "snake_case"))]
+pub enum Allergen {
+    Gluten,
+    Dairy,
+    Eggs,
+    Peanuts,
+    TreeNuts,
+    Soy,
+    Fish,
+    Shellfish,
+    Sesame,
+}
+
+impl fmt::Display for Allergen {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Allergen::Gluten => "Gluten",
+            Allergen::Dairy => "Dairy",
+            Allergen::Eggs => "Eggs",
+            Allergen::Peanuts => "Peanuts",
+            Allergen::TreeNuts => "Tree nuts",
+            Allergen::Soy => "Soy",
+            Allergen::Fish => "Fish",
+            Allergen::Shellfish => "Shellfish",
+            Allergen::Sesame => "Sesame",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+/// Words in an ingredient name that suggest an allergen. A keyword matches a whole word of the name or
+/// its plural, so "eggplant" does not match "egg". Coconut is not listed as a tree nut since most
+/// allergen labelling rules do not treat it as one.
+const KEYWORDS: &[(&str, Allergen)] = &[
+    ("flour", Allergen::Gluten),
+    ("wheat", Allergen::Gluten),
+    ("bread", Allergen::Gluten),
+    ("breadcrumb", Allergen::Gluten),
+    ("pasta", Allergen::Gluten),
+    ("spaghetti", Allergen::Gluten),
+    ("noodle", Allergen::Gluten),
+    ("barley", Allergen::Gluten),
+    ("rye", Allergen::Gluten),
+    ("semolina", Allergen::Gluten),
+    ("couscous", Allergen::Gluten),
+    ("milk", Allergen::Dairy),
+    ("buttermilk", Allergen::Dairy),
+    ("butter", Allergen::Dairy),
+    ("cheese", Allergen::Dairy),
+    ("cream", Allergen::Dairy),
+    ("yogurt", Allergen::Dairy),
+    ("yoghurt", Allergen::Dairy),
+    ("ghee", Allergen::Dairy),
+    ("parmesan", Allergen::Dairy),
+    ("mozzarella", Allergen::Dairy),
+    ("egg", Allergen::Eggs),
+    ("mayonnaise", Allergen::Eggs),
+    ("peanut", Allergen::Peanuts),
+    ("almond", Allergen::TreeNuts),
+    ("walnut", Allergen::TreeNuts),
+    ("cashew", Allergen::TreeNuts),
+    ("pecan", Allergen::TreeNuts),
+    ("pistachio", Allergen::TreeNuts),
+    ("hazelnut", Allergen::TreeNuts),
+    ("macadamia", Allergen::TreeNuts),
+    ("soy", Allergen::Soy),
+    ("soya", Allergen::Soy),
+    ("tofu", Allergen::Soy),
+    ("edamame", Allergen::Soy),
+    ("miso", Allergen::Soy),
+    ("tempeh", Allergen::Soy),
+    ("fish", Allergen::Fish),
+    ("salmon", Allergen::Fish),
+    ("tuna", Allergen::Fish),
+    ("cod", Allergen::Fish),
+    ("anchovy", Allergen::Fish),
+    ("anchovies", Allergen::Fish),
+    ("sardine", Allergen::Fish),
+    ("shrimp", Allergen::Shellfish),
+    ("prawn", Allergen::Shellfish),
+    ("crab", Allergen::Shellfish),
+    ("lobster", Allergen::Shellfish),
+    ("clam", Allergen::Shellfish),
+    ("mussel", Allergen::Shellfish),
+    ("oyster", Allergen::Shellfish),
+    ("scallop", Allergen::Shellfish),
+    ("sesame", Allergen::Sesame),
+    ("tahini", Allergen::Sesame),
+];
+
+/// An allergen found by `Recipe::detect_allergens`, along with the ingredient and keyword that triggered it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AllergenMatch {
+    /// The allergen that was detected
+    pub allergen: Allergen,
+    /// The id of the ingredient whose name matched
+    pub ingredient_id: Uuid,
+    /// The name of the ingredient whose name matched
+    pub ingredient_name: String,
+    /// The keyword that matched the ingredient name
+    pub keyword: &'static str,
+}
+
+impl Recipe {
+    /// Scans the ingredient names case-insensitively against a built-in keyword table and reports every
+    /// allergen found, sorted by allergen. Plant based ingredients such as "peanut butter" or "coconut milk"
+    /// are not reported as dairy or eggs, the same exceptions `DietRules::default` makes for animal products.
+    /// Matching is by keyword only, so review the matches before declaring them with `set_allergens`.
+    pub fn detect_allergens(&self) -> Vec<AllergenMatch> {
+        let mut matches = Vec::new();
+        for ingredient in &self.ingredients {
+            let plant_based = PLANT_BASED.iter().any(|exception| mentions(&ingredient.name, exception));
+            for (keyword, allergen) in KEYWORDS {
+                if plant_based && matches!(allergen, Allergen::Dairy | Allergen::Eggs) {
+                    continue;
+                }
+                if mentions(&ingredient.name, keyword) {
+                    matches.push(AllergenMatch {
+                        allergen: *allergen,
+                        ingredient_id: ingredient.id,
+                        ingredient_name: ingredient.name.clone(),
+                        keyword,
+                    });
+                }
+            }
+        }
+        matches.sort_by(|a, b| a.allergen.cmp(&b.allergen).then_with(|| a.ingredient_name.cmp(&b.ingredient_name)));
+        matches
+    }
+}
+
+/// Whether an ingredient `name` mentions `keyword`, ignoring case. A single word keyword must match a whole
+/// word of the name or its plural, a keyword of several words must appear anywhere in the name.
+pub(super) fn mentions(name: &str, keyword: &str) -> bool {
+    let name = name.to_lowercase();
+    let keyword = keyword.trim().to_lowercase();
+    if keyword.is_empty() {
+        return false;
+    }
+    if keyword.contains(char::is_whitespace) {
+        return name.contains(&keyword);
+    }
+    name.split(|c: char| !c.is_alphabetic()).any(|word| match word.strip_prefix(keyword.as_str()) {
+        Some(suffix) => matches!(suffix, "" | "s" | "es"),
+        None => false,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::testing::{ingredient, recipe};
+    use crate::models::Unit;
+
+    fn detected(names: &[&str]) -> Vec<(Allergen, String)> {
+        let ingredients = names.iter().map(|name| ingredient(name, 1.0, Unit::Cup));
+        let built = recipe("Mix").ingredients(ingredients).build().expect("the recipe should build");
+        built.detect_allergens().into_iter().map(|found| (found.allergen, found.ingredient_name)).collect()
+    }
+
+    #[test]
+    fn detects_allergens_by_keyword_ignoring_case() {
+        assert_eq!(
+            detected(&["All-Purpose Flour", "peanut butter", "whole milk", "Eggs"]),
+            [
+                (Allergen::Gluten, "All-Purpose Flour".to_string()),
+                (Allergen::Dairy, "whole milk".to_string()),
+                (Allergen::Eggs, "Eggs".to_string()),
+                (Allergen::Peanuts, "peanut butter".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn plant_based_butters_and_milks_are_not_dairy() {
+        assert_eq!(
+            detected(&["peanut butter", "almond butter", "coconut milk", "vegan mayonnaise"]),
+            [
+                (Allergen::Peanuts, "peanut butter".to_string()),
+                (Allergen::TreeNuts, "almond butter".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn coconut_and_eggplant_are_not_allergens() {
+        assert_eq!(detected(&["coconut", "shredded coconut", "eggplant"]), []);
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn allergens_serialize_as_lowercase_strings() {
+        assert_eq!(serde_json::to_string(&Allergen::TreeNuts).expect("should serialize"), "\"tree_nuts\"");
+        assert_eq!(serde_json::from_str::<Allergen>("\"gluten\"").ok(), Some(Allergen::Gluten));
+    }
+}
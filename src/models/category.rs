@@ -0,0 +1,123 @@
+use std::error::Error;
+use std::fmt;
+use std::str::FromStr;
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+/// The kind of dish a recipe makes, used to navigate a `Cookbook` with `Cookbook::by_category`.
+///
+/// Unlike tags, and unlike the optional `Course`, every recipe has exactly one category, which the builder
+/// requires. Recipes stored before they had a category are read as a `MainCourse`, the default.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum Category {
+    Appetizer,
+    #[default]
+    MainCourse,
+    Dessert,
+    Beverage,
+    Side,
+    Breakfast,
+}
+
+impl Category {
+    /// Every category, in the order a menu lists them.
+    pub const ALL: [Category; 6] = [
+        Category::Breakfast,
+        Category::Appetizer,
+        Category::MainCourse,
+        Category::Side,
+        Category::Dessert,
+        Category::Beverage,
+    ];
+
+    /// The name the category is stored and serialized under, e.g. `"main_course"`.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Category::Appetizer => "appetizer",
+            Category::MainCourse => "main_course",
+            Category::Dessert => "dessert",
+            Category::Beverage => "beverage",
+            Category::Side => "side",
+            Category::Breakfast => "breakfast",
+        }
+    }
+}
+
+impl FromStr for Category {
+    type Err = ParseCategoryError;
+
+    /// Parses a category from its name, ignoring case and treating spaces, hyphens and underscores alike, so
+    /// "Main Course", "main-course" and "main_course" are all a `MainCourse`.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let name = s.trim().to_lowercase().replace([' ', '-'], "_");
+        Category::ALL
+            .into_iter()
+            .find(|category| category.as_str() == name)
+            .ok_or_else(|| ParseCategoryError(s.to_string()))
+    }
+}
+
+impl fmt::Display for Category {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let s = match self {
+            Category::Appetizer => "Appetizer",
+            Category::MainCourse => "Main Course",
+            Category::Dessert => "Dessert",
+            Category::Beverage => "Beverage",
+            Category::Side => "Side",
+            Category::Breakfast => "Breakfast",
+        };
+        write!(f, "{}", s)
+    }
+}
+
+/// The error returned when text cannot be parsed into a `Category`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseCategoryError(pub String);
+
+impl fmt::Display for ParseCategoryError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "unknown category \"{}\", expected one of appetizer, main course, dessert, beverage, side or breakfast",
+            self.0
+        )
+    }
+}
+
+impl Error for ParseCategoryError {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn categories_parse_ignoring_case_and_separators() {
+        assert_eq!("dessert".parse(), Ok(Category::Dessert));
+        assert_eq!(" BEVERAGE ".parse(), Ok(Category::Beverage));
+        for name in ["Main Course", "main-course", "main_course", "MAIN COURSE"] {
+            assert_eq!(name.parse(), Ok(Category::MainCourse), "{}", name);
+        }
+        assert_eq!("maincourse".parse::<Category>(), Err(ParseCategoryError("maincourse".to_string())));
+        assert!("".parse::<Category>().is_err());
+    }
+
+    #[test]
+    fn every_category_parses_back_from_its_name_and_display() {
+        for category in Category::ALL {
+            assert_eq!(category.as_str().parse(), Ok(category));
+            assert_eq!(category.to_string().parse(), Ok(category));
+        }
+        assert_eq!(Category::MainCourse.to_string(), "Main Course");
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn categories_serialize_as_their_name() {
+        assert_eq!(serde_json::to_string(&Category::MainCourse).expect("should serialize"), "\"main_course\"");
+        let parsed: Category = serde_json::from_str("\"beverage\"").expect("should deserialize");
+        assert_eq!(parsed, Category::Beverage);
+    }
+}
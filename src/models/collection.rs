@@ -0,0 +1,433 @@
+use std::collections::HashSet;
+use std::error::Error;
+use std::fmt;
+use chrono::{DateTime, Utc};
+use uuid::Uuid;
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+use super::{Cookbook, CookbookView, Recipe, RecipeStatus};
+
+/// A named, ordered group of recipes such as "Weeknight dinners", holding the ids of its recipes.
+///
+/// A recipe appears at most once. Ids are not checked against any `Cookbook`, so a collection may still
+/// hold the id of a recipe that has since been deleted. Listing its recipes only returns those with one
+/// status, the published ones unless asked for another, see `Cookbook::collection_recipes`.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(try_from = "RawCollection"))]
+pub struct Collection {
+    /// The id of the collection
+    id: Uuid,
+    /// The name of the collection
+    name: String,
+    /// The description of the collection
+    description: String,
+    /// The id of the user who owns the collection
+    owner: Uuid,
+    /// The ids of the recipes in the collection, in order
+    recipes: Vec<Uuid>,
+    /// When the collection was created, serialized as RFC 3339
+    created_at: DateTime<Utc>,
+    /// When the collection was last modified, serialized as RFC 3339
+    updated_at: DateTime<Utc>,
+}
+
+impl Collection {
+    pub fn builder() -> CollectionBuilder {
+        CollectionBuilder::new()
+    }
+
+    pub fn id(&self) -> Uuid {
+        self.id
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn description(&self) -> &str {
+        &self.description
+    }
+
+    pub fn owner(&self) -> Uuid {
+        self.owner
+    }
+
+    /// The ids of the recipes in the collection, in order.
+    pub fn recipe_ids(&self) -> &[Uuid] {
+        &self.recipes
+    }
+
+    pub fn created_at(&self) -> DateTime<Utc> {
+        self.created_at
+    }
+
+    pub fn updated_at(&self) -> DateTime<Utc> {
+        self.updated_at
+    }
+
+    pub fn len(&self) -> usize {
+        self.recipes.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.recipes.is_empty()
+    }
+
+    pub fn contains(&self, recipe_id: &Uuid) -> bool {
+        self.recipes.contains(recipe_id)
+    }
+
+    pub fn set_name(&mut self, name: impl Into<String>) {
+        self.name = name.into();
+        self.touch();
+    }
+
+    pub fn set_description(&mut self, description: impl Into<String>) {
+        self.description = description.into();
+        self.touch();
+    }
+
+    /// Adds a recipe to the end of the collection, returning `false` and leaving the collection unchanged if
+    /// it is already in it.
+    pub fn add_recipe(&mut self, recipe_id: Uuid) -> bool {
+        if self.contains(&recipe_id) {
+            return false;
+        }
+        self.recipes.push(recipe_id);
+        self.touch();
+        true
+    }
+
+    /// Removes a recipe from the collection, returning whether it was in it.
+    pub fn remove_recipe(&mut self, recipe_id: &Uuid) -> bool {
+        let Some(index) = self.recipes.iter().position(|id| id == recipe_id) else {
+            return false;
+        };
+        self.recipes.remove(index);
+        self.touch();
+        true
+    }
+
+    /// Puts the recipes in the order of `order`, which must hold every recipe in the collection exactly once.
+    pub fn reorder(&mut self, order: Vec<Uuid>) -> Result<(), ReorderError> {
+        let current: HashSet<&Uuid> = self.recipes.iter().collect();
+        let mut seen = HashSet::new();
+        for id in &order {
+            if !current.contains(id) {
+                return Err(ReorderError::Unknown(*id));
+            }
+            if !seen.insert(*id) {
+                return Err(ReorderError::Duplicate(*id));
+            }
+        }
+        if let Some(id) = self.recipes.iter().find(|id| !seen.contains(*id)) {
+            return Err(ReorderError::Missing(*id));
+        }
+        self.recipes = order;
+        self.touch();
+        Ok(())
+    }
+
+    /// Records that the collection was just modified, every mutator calls this.
+    fn touch(&mut self) {
+        self.updated_at = Utc::now();
+    }
+}
+
+/// A builder for `Collection`, obtained through `Collection::builder`.
+#[derive(Debug, Clone, Default)]
+pub struct CollectionBuilder {
+    /// The id of the collection, yet to be set
+    id: Option<Uuid>,
+    /// The name of the collection, yet to be set
+    name: Option<String>,
+    /// The description of the collection, empty unless set
+    description: String,
+    /// The id of the user who owns the collection, yet to be set
+    owner: Option<Uuid>,
+    /// The ids of the recipes in the collection, in order
+    recipes: Vec<Uuid>,
+    /// When the collection was created, now unless set
+    created_at: Option<DateTime<Utc>>,
+    /// When the collection was last modified, the creation time unless set
+    updated_at: Option<DateTime<Utc>>,
+}
+
+impl CollectionBuilder {
+    pub(crate) fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the id, only needed when rehydrating an existing collection. A fresh id is generated otherwise.
+    pub fn id(mut self, id: Uuid) -> Self {
+        self.id = Some(id);
+        self
+    }
+
+    pub fn name(mut self, name: impl Into<String>) -> Self {
+        self.name = Some(name.into());
+        self
+    }
+
+    pub fn description(mut self, description: impl Into<String>) -> Self {
+        self.description = description.into();
+        self
+    }
+
+    pub fn owner(mut self, owner: Uuid) -> Self {
+        self.owner = Some(owner);
+        self
+    }
+
+    /// Adds a recipe to the end of the collection, ignoring it if it was already added.
+    pub fn recipe(mut self, recipe_id: Uuid) -> Self {
+        if !self.recipes.contains(&recipe_id) {
+            self.recipes.push(recipe_id);
+        }
+        self
+    }
+
+    pub fn recipes(self, recipe_ids: impl IntoIterator<Item = Uuid>) -> Self {
+        recipe_ids.into_iter().fold(self, CollectionBuilder::recipe)
+    }
+
+    pub fn created_at(mut self, created_at: DateTime<Utc>) -> Self {
+        self.created_at = Some(created_at);
+        self
+    }
+
+    pub fn updated_at(mut self, updated_at: DateTime<Utc>) -> Self {
+        self.updated_at = Some(updated_at);
+        self
+    }
+
+    /// Builds the `Collection`, the name must not be blank.
+    pub fn build(self) -> Result<Collection, CollectionBuildError> {
+        let name = self.name
+            .filter(|name| !name.trim().is_empty())
+            .ok_or(CollectionBuildError::MissingName)?;
+        let owner = self.owner.ok_or(CollectionBuildError::MissingOwner)?;
+        let created_at = self.created_at.unwrap_or_else(Utc::now);
+        Ok(Collection {
+            id: self.id.unwrap_or_else(Uuid::now_v7),
+            name,
+            description: self.description,
+            owner,
+            recipes: self.recipes,
+            created_at,
+            updated_at: self.updated_at.unwrap_or(created_at),
+        })
+    }
+}
+
+/// A collection as stored, checked when deserialized in case it was written by hand.
+#[cfg(feature = "serde")]
+#[derive(Deserialize)]
+struct RawCollection {
+    id: Uuid,
+    name: String,
+    #[serde(default)]
+    description: String,
+    owner: Uuid,
+    #[serde(default)]
+    recipes: Vec<Uuid>,
+    created_at: DateTime<Utc>,
+    updated_at: DateTime<Utc>,
+}
+
+#[cfg(feature = "serde")]
+impl TryFrom<RawCollection> for Collection {
+    type Error = CollectionBuildError;
+
+    /// Rejects a blank name and a recipe listed more than once, which a built collection never has.
+    fn try_from(raw: RawCollection) -> Result<Self, Self::Error> {
+        if raw.name.trim().is_empty() {
+            return Err(CollectionBuildError::MissingName);
+        }
+        let mut seen = HashSet::new();
+        if let Some(id) = raw.recipes.iter().find(|id| !seen.insert(**id)) {
+            return Err(CollectionBuildError::DuplicateRecipe(*id));
+        }
+        Ok(Collection {
+            id: raw.id,
+            name: raw.name,
+            description: raw.description,
+            owner: raw.owner,
+            recipes: raw.recipes,
+            created_at: raw.created_at,
+            updated_at: raw.updated_at,
+        })
+    }
+}
+
+/// The error returned when a `CollectionBuilder` is missing a required field at build time, or when a
+/// deserialized collection is not one a builder could have built.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CollectionBuildError {
+    /// The name was not set or is blank
+    MissingName,
+    /// The owner was not set, every collection belongs to a user
+    MissingOwner,
+    /// A recipe is in the collection more than once, only possible in a deserialized collection
+    DuplicateRecipe(Uuid),
+}
+
+impl fmt::Display for CollectionBuildError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CollectionBuildError::MissingName => write!(f, "cannot build Collection: name was not set"),
+            CollectionBuildError::MissingOwner => write!(f, "cannot build Collection: owner was not set"),
+            CollectionBuildError::DuplicateRecipe(id) => {
+                write!(f, "cannot build Collection: recipe {} is in it more than once", id)
+            }
+        }
+    }
+}
+
+impl Error for CollectionBuildError {}
+
+/// The error returned by `Collection::reorder` when the new order is not a rearrangement of the recipes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ReorderError {
+    /// A recipe in the collection is not in the new order
+    Missing(Uuid),
+    /// The new order has a recipe that is not in the collection
+    Unknown(Uuid),
+    /// The new order has a recipe more than once
+    Duplicate(Uuid),
+}
+
+impl fmt::Display for ReorderError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ReorderError::Missing(id) => write!(f, "recipe {} is missing from the new order", id),
+            ReorderError::Unknown(id) => write!(f, "recipe {} is not in the collection", id),
+            ReorderError::Duplicate(id) => write!(f, "recipe {} appears more than once in the new order", id),
+        }
+    }
+}
+
+impl Error for ReorderError {}
+
+impl Cookbook {
+    /// The published recipes of `collection` in its order, see `CookbookView::collection_recipes`.
+    pub fn collection_recipes(&self, collection: &Collection, offset: usize, limit: usize) -> Vec<&Recipe> {
+        self.with_status(RecipeStatus::Published).collection_recipes(collection, offset, limit)
+    }
+}
+
+impl<'a> CookbookView<'a> {
+    /// The recipes of `collection` in its order, skipping ids of recipes that are not in the cookbook, such
+    /// as deleted ones, or that do not have the view's status. The first `offset` recipes found are skipped
+    /// and at most `limit` are returned.
+    pub fn collection_recipes(&self, collection: &Collection, offset: usize, limit: usize) -> Vec<&'a Recipe> {
+        collection.recipes.iter().filter_map(|id| self.get(id)).skip(offset).take(limit).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::testing::recipe;
+
+    fn collection(recipe_ids: impl IntoIterator<Item = Uuid>) -> Collection {
+        let builder = Collection::builder().name("Weeknight dinners").owner(Uuid::now_v7()).recipes(recipe_ids);
+        builder.build().expect("the collection should build")
+    }
+
+    #[test]
+    fn recipes_keep_the_order_they_were_added_and_reordered_in() {
+        let ids: Vec<Uuid> = (0..3).map(|_| Uuid::now_v7()).collect();
+        let mut dinners = collection([ids[2], ids[0]]);
+        assert!(dinners.add_recipe(ids[1]));
+        assert_eq!(dinners.recipe_ids(), [ids[2], ids[0], ids[1]]);
+        dinners.reorder(vec![ids[1], ids[2], ids[0]]).expect("the order has every recipe once");
+        assert_eq!(dinners.recipe_ids(), [ids[1], ids[2], ids[0]]);
+        assert!(dinners.remove_recipe(&ids[2]));
+        assert!(!dinners.remove_recipe(&ids[2]));
+        assert_eq!(dinners.recipe_ids(), [ids[1], ids[0]]);
+    }
+
+    #[test]
+    fn adding_a_recipe_twice_keeps_one_copy() {
+        let id = Uuid::now_v7();
+        let mut dinners = collection([id, id]);
+        assert_eq!(dinners.len(), 1);
+        let updated_at = dinners.updated_at();
+        assert!(!dinners.add_recipe(id));
+        assert_eq!((dinners.len(), dinners.updated_at()), (1, updated_at));
+    }
+
+    #[test]
+    fn reordering_must_keep_every_recipe_exactly_once() {
+        let (a, b, stranger) = (Uuid::now_v7(), Uuid::now_v7(), Uuid::now_v7());
+        let mut dinners = collection([a, b]);
+        assert_eq!(dinners.reorder(vec![b]), Err(ReorderError::Missing(a)));
+        assert_eq!(dinners.reorder(vec![b, a, b]), Err(ReorderError::Duplicate(b)));
+        assert_eq!(dinners.reorder(vec![b, stranger]), Err(ReorderError::Unknown(stranger)));
+        assert_eq!(dinners.recipe_ids(), [a, b]);
+    }
+
+    #[test]
+    fn listing_skips_recipes_that_were_deleted() {
+        let built: Vec<Recipe> = ["Soup", "Stew", "Salad", "Toast"]
+            .into_iter()
+            .map(|name| recipe(name).status(RecipeStatus::Published).build().expect("the recipe should build"))
+            .collect();
+        let dinners = collection(built.iter().map(Recipe::id));
+        let mut cookbook: Cookbook = built.into_iter().collect();
+        cookbook.remove(&dinners.recipe_ids()[1]);
+        let names = |offset, limit| -> Vec<&str> {
+            cookbook.collection_recipes(&dinners, offset, limit).into_iter().map(Recipe::name).collect()
+        };
+        assert_eq!(names(0, 10), ["Soup", "Salad", "Toast"]);
+        assert_eq!(names(1, 1), ["Salad"]);
+        assert!(names(3, 10).is_empty());
+    }
+
+    #[test]
+    fn listing_only_returns_recipes_with_the_status_asked_for() {
+        let built = |name, status| recipe(name).status(status).build().expect("the recipe should build");
+        let draft = built("Soup draft", RecipeStatus::Draft);
+        let stew = built("Stew", RecipeStatus::Published);
+        let mut archived = built("Old salad", RecipeStatus::Published);
+        archived.archive().expect("published recipes can be archived");
+        let dinners = collection([draft.id(), stew.id(), archived.id()]);
+        let cookbook: Cookbook = [draft, stew, archived].into_iter().collect();
+        let names = |status| -> Vec<&str> {
+            let view = cookbook.with_status(status);
+            view.collection_recipes(&dinners, 0, 10).into_iter().map(Recipe::name).collect()
+        };
+        let published: Vec<&str> = cookbook.collection_recipes(&dinners, 0, 10).into_iter().map(Recipe::name).collect();
+        assert_eq!(published, ["Stew"]);
+        assert_eq!(names(RecipeStatus::Published), ["Stew"]);
+        assert_eq!(names(RecipeStatus::Draft), ["Soup draft"]);
+        assert_eq!(names(RecipeStatus::Archived), ["Old salad"]);
+    }
+
+    #[test]
+    fn a_collection_needs_a_name_and_an_owner() {
+        let unnamed = Collection::builder().name("  ").owner(Uuid::now_v7()).build();
+        assert_eq!(unnamed.err(), Some(CollectionBuildError::MissingName));
+        assert_eq!(Collection::builder().name("Baking").build().err(), Some(CollectionBuildError::MissingOwner));
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn collections_survive_json_but_duplicate_recipes_and_blank_names_are_rejected() {
+        let id = Uuid::now_v7();
+        let dinners = collection([id, Uuid::now_v7()]);
+        let json = serde_json::to_value(&dinners).expect("the collection should serialize");
+        let parsed: Collection = serde_json::from_value(json.clone()).expect("the collection should deserialize");
+        assert_eq!(parsed, dinners);
+
+        let mut duplicated = json.clone();
+        duplicated["recipes"] = serde_json::json!([id, id]);
+        let err = serde_json::from_value::<Collection>(duplicated).expect_err("a recipe is listed twice");
+        assert!(err.to_string().contains(&CollectionBuildError::DuplicateRecipe(id).to_string()));
+        let mut unnamed = json;
+        unnamed["name"] = serde_json::json!(" ");
+        assert!(serde_json::from_value::<Collection>(unnamed).is_err());
+    }
+}
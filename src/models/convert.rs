@@ -0,0 +1,315 @@
+use std::error::Error;
+use std::fmt;
+use uuid::Uuid;
+use super::{Ingredient, Quantity, Recipe, Unit};
+
+/// The physical dimension a unit measures, only units of the same dimension can be converted between.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Dimension {
+    Volume,
+    Mass,
+}
+
+/// Every convertible unit with its dimension and its size in the base unit of that dimension,
+/// milliliters for volume and grams for mass. US customary definitions are used for the
+/// imperial volume units.
+const CONVERSIONS: &[(Unit, Dimension, f64)] = &[
+    (Unit::Teaspoon, Dimension::Volume, 4.928_921_593_75),
+    (Unit::Tablespoon, Dimension::Volume, 14.786_764_781_25),
+    (Unit::FluidOunce, Dimension::Volume, 29.573_529_562_5),
+    (Unit::Cup, Dimension::Volume, 236.588_236_5),
+    (Unit::Milliliter, Dimension::Volume, 1.0),
+    (Unit::Liter, Dimension::Volume, 1000.0),
+    (Unit::Gram, Dimension::Mass, 1.0),
+    (Unit::Kilogram, Dimension::Mass, 1000.0),
+    (Unit::Ounce, Dimension::Mass, 28.349_523_125),
+    (Unit::Pound, Dimension::Mass, 453.592_37),
+];
+
+/// Looks up the dimension of `unit` and its size in the base unit of that dimension.
+fn lookup(unit: &Unit) -> Result<(Dimension, f64), ConversionError> {
+    CONVERSIONS
+        .iter()
+        .find(|(u, _, _)| u == unit)
+        .map(|(_, dimension, factor)| (*dimension, *factor))
+        .ok_or_else(|| ConversionError::UnsupportedUnit(unit.clone()))
+}
+
+/// How converted amounts are rounded.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Rounding {
+    /// Amounts are left exactly as computed
+    Exact,
+    /// Amounts are rounded to values that are practical to measure in a kitchen, metric amounts of
+    /// 10 or more to a whole number and smaller ones to one decimal place, imperial amounts to the
+    /// nearest eighth
+    #[default]
+    Cooking,
+}
+
+impl Rounding {
+    fn round(&self, amount: f64, unit: &Unit) -> f64 {
+        match self {
+            Rounding::Exact => amount,
+            Rounding::Cooking => match unit {
+                Unit::Milliliter | Unit::Gram if amount >= 10.0 => amount.round(),
+                Unit::Milliliter | Unit::Gram | Unit::Liter | Unit::Kilogram => (amount * 10.0).round() / 10.0,
+                _ => (amount * 8.0).round() / 8.0,
+            },
+        }
+    }
+}
+
+impl Quantity {
+    /// Converts the quantity into `unit`, which must measure the same dimension (volume or mass).
+    ///
+    /// Conversions are computed in `f64` from the factors in a single table, so results are exact to
+    /// roughly 15 significant digits, compare them with a small epsilon rather than `==`.
+    pub fn convert_to(&self, unit: Unit) -> Result<Quantity, ConversionError> {
+        if *self.unit() == unit {
+            return Ok(self.clone());
+        }
+        let (from_dimension, from_factor) = lookup(self.unit())?;
+        let (to_dimension, to_factor) = lookup(&unit)?;
+        if from_dimension != to_dimension {
+            return Err(ConversionError::IncompatibleUnits { from: self.unit().clone(), to: unit });
+        }
+        Ok(Quantity::new(self.amount() * from_factor / to_factor, unit))
+    }
+
+    /// Whether `other` is within `epsilon` of this quantity once converted into this quantity's unit,
+    /// quantities that cannot be converted are never approximately equal.
+    pub fn approx_eq(&self, other: &Quantity, epsilon: f64) -> bool {
+        other
+            .convert_to(self.unit().clone())
+            .map(|other| (self.amount() - other.amount()).abs() <= epsilon)
+            .unwrap_or(false)
+    }
+
+    /// Converts the quantity into `unit`, using `grams_per_ml` to convert between volume and mass. The
+    /// density must be a finite, positive number.
+    pub fn convert_with_density(&self, unit: Unit, grams_per_ml: f64) -> Result<Quantity, ConversionError> {
+        if !(grams_per_ml.is_finite() && grams_per_ml > 0.0) {
+            return Err(ConversionError::InvalidDensity);
+        }
+        let (from_dimension, from_factor) = lookup(self.unit())?;
+        let (to_dimension, to_factor) = lookup(&unit)?;
+        let base = self.amount() * from_factor;
+        let base = match (from_dimension, to_dimension) {
+            (Dimension::Volume, Dimension::Mass) => base * grams_per_ml,
+            (Dimension::Mass, Dimension::Volume) => base / grams_per_ml,
+            _ => base,
+        };
+        Ok(Quantity::new(base / to_factor, unit))
+    }
+
+    /// Converts the quantity into milliliters or grams, or liters and kilograms for amounts of 1000 or more.
+    pub fn to_metric(&self, rounding: Rounding) -> Result<Quantity, ConversionError> {
+        let (dimension, factor) = lookup(self.unit())?;
+        let base = self.amount() * factor;
+        let unit = match (dimension, base >= 1000.0) {
+            (Dimension::Volume, false) => Unit::Milliliter,
+            (Dimension::Volume, true) => Unit::Liter,
+            (Dimension::Mass, false) => Unit::Gram,
+            (Dimension::Mass, true) => Unit::Kilogram,
+        };
+        self.convert_to(unit.clone()).map(|q| Quantity::new(rounding.round(q.amount(), &unit), unit))
+    }
+
+    /// Converts the quantity into the largest of cups, tablespoons and teaspoons, or pounds and ounces,
+    /// that keeps the amount at 1 or more.
+    pub fn to_imperial(&self, rounding: Rounding) -> Result<Quantity, ConversionError> {
+        let (dimension, factor) = lookup(self.unit())?;
+        let base = self.amount() * factor;
+        let candidates: &[Unit] = match dimension {
+            Dimension::Volume => &[Unit::Cup, Unit::Tablespoon, Unit::Teaspoon],
+            Dimension::Mass => &[Unit::Pound, Unit::Ounce],
+        };
+        let unit = candidates
+            .iter()
+            .find(|unit| lookup(unit).map(|(_, f)| base >= f).unwrap_or(false))
+            .unwrap_or(&candidates[candidates.len() - 1])
+            .clone();
+        self.convert_to(unit.clone()).map(|q| Quantity::new(rounding.round(q.amount(), &unit), unit))
+    }
+}
+
+impl Recipe {
+    /// Returns a copy of the recipe with every ingredient converted to metric units, along with the ids of
+    /// the ingredients measured in an unrecognized unit, which are left untouched. Counts, pinches and
+    /// "to taste" quantities are not tied to a measuring system and are left untouched without being reported.
+    pub fn to_metric(&self, rounding: Rounding) -> (Recipe, Vec<Uuid>) {
+        self.convert_ingredients(|quantity| quantity.to_metric(rounding))
+    }
+
+    /// Returns a copy of the recipe with every ingredient converted to imperial units, along with the ids of
+    /// the ingredients measured in an unrecognized unit, which are left untouched. Counts, pinches and
+    /// "to taste" quantities are not tied to a measuring system and are left untouched without being reported.
+    pub fn to_imperial(&self, rounding: Rounding) -> (Recipe, Vec<Uuid>) {
+        self.convert_ingredients(|quantity| quantity.to_imperial(rounding))
+    }
+
+    /// The summed mass of the ingredients in grams. `None` if any ingredient is measured in anything but a
+    /// mass unit, including volumes, pieces, pinches and "to taste", since weighing it would take a density
+    /// or a size the recipe does not record. A recipe without ingredients weighs 0 grams.
+    pub fn total_mass_grams(&self) -> Option<f64> {
+        self.ingredients
+            .iter()
+            .map(|ingredient| ingredient.quantity.convert_to(Unit::Gram).ok().map(|grams| grams.amount()))
+            .sum()
+    }
+
+    fn convert_ingredients<F>(&self, convert: F) -> (Recipe, Vec<Uuid>)
+    where
+        F: Fn(&Quantity) -> Result<Quantity, ConversionError>,
+    {
+        let mut unconverted = Vec::new();
+        let mut converted = self.clone();
+        converted.ingredients = self.ingredients
+            .iter()
+            .map(|ingredient| match convert(&ingredient.quantity) {
+                Ok(quantity) => Ingredient { quantity, ..ingredient.clone() },
+                Err(_) => {
+                    if let Unit::Other(_) = ingredient.quantity.unit() {
+                        unconverted.push(ingredient.id);
+                    }
+                    ingredient.clone()
+                }
+            })
+            .collect();
+        (converted, unconverted)
+    }
+}
+
+/// The error returned when a `Quantity` cannot be converted into another unit.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ConversionError {
+    /// The units measure different dimensions, e.g. mass and volume
+    IncompatibleUnits { from: Unit, to: Unit },
+    /// The unit has no known size, e.g. pieces or an unrecognized unit
+    UnsupportedUnit(Unit),
+    /// The density given to convert between volume and mass was zero, negative or not finite
+    InvalidDensity,
+}
+
+impl fmt::Display for ConversionError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ConversionError::IncompatibleUnits { from, to } => {
+                write!(f, "cannot convert {} to {} without a density", from, to)
+            }
+            ConversionError::UnsupportedUnit(unit) => write!(f, "cannot convert quantities measured in {}", unit),
+            ConversionError::InvalidDensity => write!(f, "the density must be a positive number"),
+        }
+    }
+}
+
+impl Error for ConversionError {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::testing::{ingredient, recipe};
+
+    fn assert_converts(from: Quantity, to: Quantity) {
+        let converted = from.convert_to(to.unit().clone()).expect("the units should be compatible");
+        assert!(converted.approx_eq(&to, 1e-9), "{:?} converted to {:?}, expected {:?}", from, converted, to);
+    }
+
+    #[test]
+    fn converts_between_units_of_the_same_dimension() {
+        let table = [
+            (Quantity::new(1.0, Unit::Cup), Quantity::new(236.588_236_5, Unit::Milliliter)),
+            (Quantity::new(1.0, Unit::Tablespoon), Quantity::new(3.0, Unit::Teaspoon)),
+            (Quantity::new(1.0, Unit::Cup), Quantity::new(16.0, Unit::Tablespoon)),
+            (Quantity::new(1.0, Unit::Cup), Quantity::new(8.0, Unit::FluidOunce)),
+            (Quantity::new(1.0, Unit::Liter), Quantity::new(1000.0, Unit::Milliliter)),
+            (Quantity::new(1.0, Unit::Ounce), Quantity::new(28.349_523_125, Unit::Gram)),
+            (Quantity::new(1.0, Unit::Pound), Quantity::new(16.0, Unit::Ounce)),
+            (Quantity::new(1.0, Unit::Kilogram), Quantity::new(1000.0, Unit::Gram)),
+        ];
+        for (from, to) in table {
+            assert_converts(from.clone(), to.clone());
+            assert_converts(to, from);
+        }
+    }
+
+    #[test]
+    fn refuses_to_convert_between_volume_and_mass_without_a_density() {
+        assert_eq!(
+            Quantity::new(1.0, Unit::Cup).convert_to(Unit::Gram),
+            Err(ConversionError::IncompatibleUnits { from: Unit::Cup, to: Unit::Gram })
+        );
+        assert_eq!(
+            Quantity::new(2.0, Unit::Piece).convert_to(Unit::Gram),
+            Err(ConversionError::UnsupportedUnit(Unit::Piece))
+        );
+        let water = Quantity::new(1.0, Unit::Cup).convert_with_density(Unit::Gram, 1.0).expect("should convert");
+        assert!(water.approx_eq(&Quantity::new(236.588_236_5, Unit::Gram), 1e-9));
+    }
+
+    #[test]
+    fn rejects_densities_that_are_not_positive_and_finite() {
+        for density in [0.0, -1.0, f64::NAN, f64::INFINITY] {
+            let converted = Quantity::new(1.0, Unit::Cup).convert_with_density(Unit::Gram, density);
+            assert_eq!(converted, Err(ConversionError::InvalidDensity), "density {}", density);
+        }
+    }
+
+    #[test]
+    fn rounds_conversions_for_cooking() {
+        let cup = Quantity::new(1.0, Unit::Cup);
+        assert_eq!(cup.to_metric(Rounding::Cooking), Ok(Quantity::new(237.0, Unit::Milliliter)));
+        assert_eq!(cup.to_metric(Rounding::Exact), Ok(Quantity::new(236.588_236_5, Unit::Milliliter)));
+        assert_eq!(Quantity::new(5.0, Unit::Cup).to_metric(Rounding::Cooking), Ok(Quantity::new(1.2, Unit::Liter)));
+        let milliliters = Quantity::new(250.0, Unit::Milliliter);
+        assert_eq!(milliliters.to_imperial(Rounding::Cooking), Ok(Quantity::new(1.0, Unit::Cup)));
+        let grams = Quantity::new(500.0, Unit::Gram);
+        assert_eq!(grams.to_imperial(Rounding::Cooking), Ok(Quantity::new(1.125, Unit::Pound)));
+    }
+
+    #[test]
+    fn converting_a_recipe_reports_unrecognized_units() {
+        let cloves = ingredient("garlic", 2.0, Unit::Other("cloves".to_string()));
+        let built = recipe("Stew")
+            .ingredient(ingredient("stock", 2.0, Unit::Cup))
+            .ingredient(ingredient("salt", 0.0, Unit::ToTaste))
+            .ingredient(cloves.clone())
+            .build()
+            .expect("the recipe should build");
+        let (metric, unconverted) = built.to_metric(Rounding::Cooking);
+        assert_eq!(unconverted, [cloves.id()]);
+        let units: Vec<_> = metric.ingredients().iter().map(|ingredient| ingredient.quantity().clone()).collect();
+        assert!(units.contains(&Quantity::new(473.0, Unit::Milliliter)));
+        assert!(units.contains(&Quantity::to_taste()));
+        assert!(units.contains(cloves.quantity()));
+    }
+
+    #[test]
+    fn converted_quantities_are_equal_within_an_epsilon() {
+        assert!(Quantity::new(1000.0, Unit::Gram).approx_eq(&Quantity::new(1.0, Unit::Kilogram), 1e-9));
+        assert!(Quantity::new(3.0, Unit::Teaspoon).approx_eq(&Quantity::new(1.0, Unit::Tablespoon), 1e-9));
+        assert!(!Quantity::new(3.1, Unit::Teaspoon).approx_eq(&Quantity::new(1.0, Unit::Tablespoon), 1e-9));
+        assert!(!Quantity::new(1.0, Unit::Gram).approx_eq(&Quantity::new(1.0, Unit::Milliliter), 1e-9));
+    }
+
+    #[test]
+    fn the_total_mass_sums_mass_units_and_is_unknown_with_anything_else() {
+        let bread = recipe("Bread")
+            .ingredient(ingredient("flour", 0.5, Unit::Kilogram))
+            .ingredient(ingredient("butter", 4.0, Unit::Ounce))
+            .ingredient(ingredient("salt", 10.0, Unit::Gram))
+            .build()
+            .expect("the recipe should build");
+        let grams = bread.total_mass_grams().expect("every ingredient is weighed");
+        assert!((grams - (500.0 + 4.0 * 28.349_523_125 + 10.0)).abs() < 1e-9, "{}", grams);
+
+        let mut with_eggs = bread.clone();
+        with_eggs.add_ingredient(ingredient("eggs", 2.0, Unit::Piece));
+        assert_eq!(with_eggs.total_mass_grams(), None);
+        let mut with_milk = bread;
+        with_milk.add_ingredient(ingredient("milk", 1.0, Unit::Cup));
+        assert_eq!(with_milk.total_mass_grams(), None);
+        assert_eq!(recipe("Water").build().expect("the recipe should build").total_mass_grams(), Some(0.0));
+    }
+}
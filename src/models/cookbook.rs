@@ -0,0 +1,907 @@
+use std::cmp::Ordering;
+use std::collections::hash_map::{self, HashMap};
+use std::collections::HashSet;
+use std::error::Error;
+use std::fmt;
+#[cfg(feature = "serde")]
+use std::{fs, io, path::Path};
+use uuid::Uuid;
+#[cfg(feature = "serde")]
+use super::ValidationErrors;
+use super::{Category, Course, Difficulty, Equipment, Ingredient, Recipe, RecipeStatus, RecipeTag};
+
+/// A collection of recipes, keyed by their id.
+///
+/// Queries such as `search`, `filter` and `by_tag` only return published recipes. Drafts and archived
+/// recipes are found through `get`, `iter` and `by_status`, and queried through `with_status`.
+#[derive(Debug, Clone, Default)]
+pub struct Cookbook {
+    recipes: HashMap<Uuid, Recipe>,
+}
+
+impl Cookbook {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a recipe, failing with the recipe handed back if one with the same id is already present.
+    pub fn add(&mut self, recipe: Recipe) -> Result<(), DuplicateRecipe> {
+        match self.recipes.entry(recipe.id()) {
+            hash_map::Entry::Occupied(_) => Err(DuplicateRecipe(Box::new(recipe))),
+            hash_map::Entry::Vacant(entry) => {
+                entry.insert(recipe);
+                Ok(())
+            }
+        }
+    }
+
+    /// Adds a recipe, replacing and returning any recipe with the same id.
+    pub fn insert(&mut self, recipe: Recipe) -> Option<Recipe> {
+        self.recipes.insert(recipe.id(), recipe)
+    }
+
+    /// Replaces the stored recipe with `recipe` if the stored one is still at `expected_version`, the version
+    /// the caller read before editing, returning the replaced recipe. Fails with
+    /// `ConcurrencyError::VersionMismatch` if someone else updated it in the meantime, in which case the caller
+    /// should read the recipe again and redo the edit. The stored version always goes up, even if `recipe`
+    /// was not modified, the stored `created_at` is kept and `updated_at` is set to the time of the update,
+    /// whatever the timestamps of `recipe` say.
+    pub fn update(&mut self, mut recipe: Recipe, expected_version: u32) -> Result<Recipe, ConcurrencyError> {
+        let stored = self.recipes.get_mut(&recipe.id()).ok_or(ConcurrencyError::NotFound(recipe.id()))?;
+        recipe.check_update(stored.version(), expected_version)?;
+        recipe.stamp_update(stored.created_at());
+        Ok(std::mem::replace(stored, recipe))
+    }
+
+    /// Removes the recipe with the given id, returning it if it was present.
+    pub fn remove(&mut self, id: &Uuid) -> Option<Recipe> {
+        self.recipes.remove(id)
+    }
+
+    /// Removes recipes that duplicate another one, returning how many were removed. Recipes are duplicates
+    /// when their names are the same ignoring case and surrounding whitespace, and their ingredients have the
+    /// same names, compared the same way, and quantities, compared as displayed. Ids, directions and every
+    /// other field are ignored.
+    ///
+    /// The cookbook does not remember the order recipes were added in, so of each group of duplicates the
+    /// one created first is kept, and of those created at the same time the one with the smallest id.
+    pub fn dedupe(&mut self) -> usize {
+        let mut recipes: Vec<&Recipe> = self.recipes.values().collect();
+        recipes.sort_by(|a, b| a.created_at().cmp(&b.created_at()).then_with(|| a.id().cmp(&b.id())));
+        let mut seen = HashSet::new();
+        let duplicates: Vec<Uuid> = recipes
+            .into_iter()
+            .filter(|recipe| !seen.insert(content_key(recipe)))
+            .map(Recipe::id)
+            .collect();
+        for id in &duplicates {
+            self.recipes.remove(id);
+        }
+        duplicates.len()
+    }
+
+    pub fn get(&self, id: &Uuid) -> Option<&Recipe> {
+        self.recipes.get(id)
+    }
+
+    pub fn contains(&self, id: &Uuid) -> bool {
+        self.recipes.contains_key(id)
+    }
+
+    pub fn len(&self) -> usize {
+        self.recipes.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.recipes.is_empty()
+    }
+
+    /// Iterates over the recipes in no particular order.
+    pub fn iter(&self) -> impl Iterator<Item = &Recipe> {
+        self.recipes.values()
+    }
+
+    /// Every recipe with `status`, sorted by name ignoring case, e.g. the drafts still being worked on.
+    pub fn by_status(&self, status: RecipeStatus) -> Vec<&Recipe> {
+        let mut recipes: Vec<&Recipe> = self.recipes.values().filter(|recipe| recipe.status() == status).collect();
+        recipes.sort_by(|a, b| by_name(a, b));
+        recipes
+    }
+
+    /// The recipes with `status`, to run the queries of the cookbook on e.g. its drafts, which the cookbook's
+    /// own queries leave out.
+    pub fn with_status(&self, status: RecipeStatus) -> CookbookView<'_> {
+        CookbookView { recipes: &self.recipes, status }
+    }
+
+    /// The published recipes sorted with `cmp`, see `CookbookView::sorted_by`.
+    pub fn sorted_by<F: FnMut(&Recipe, &Recipe) -> Ordering>(&self, cmp: F) -> Vec<&Recipe> {
+        self.published().sorted_by(cmp)
+    }
+
+    /// The published recipes sorted by name, ignoring case, see `CookbookView::sorted_by_name`.
+    pub fn sorted_by_name(&self) -> Vec<&Recipe> {
+        self.published().sorted_by_name()
+    }
+
+    /// The published recipes sorted by total duration, see `CookbookView::sorted_by_duration`.
+    pub fn sorted_by_duration(&self) -> Vec<&Recipe> {
+        self.published().sorted_by_duration()
+    }
+
+    /// The published recipes sorted by difficulty, see `CookbookView::sorted_by_difficulty`.
+    pub fn sorted_by_difficulty(&self) -> Vec<&Recipe> {
+        self.published().sorted_by_difficulty()
+    }
+
+    /// The published recipes tagged `tag`, see `CookbookView::by_tag`.
+    pub fn by_tag(&self, tag: &str) -> Vec<&Recipe> {
+        self.published().by_tag(tag)
+    }
+
+    /// The published recipes tagged with all of `tags`, see `CookbookView::by_tags_all`.
+    pub fn by_tags_all(&self, tags: &[&str]) -> Vec<&Recipe> {
+        self.published().by_tags_all(tags)
+    }
+
+    /// The published recipes tagged with any of `tags`, see `CookbookView::by_tags_any`.
+    pub fn by_tags_any(&self, tags: &[&str]) -> Vec<&Recipe> {
+        self.published().by_tags_any(tags)
+    }
+
+    /// The published recipes with a tag in `namespace`, see `CookbookView::by_tag_namespace`.
+    pub fn by_tag_namespace(&self, namespace: &str) -> Vec<&Recipe> {
+        self.published().by_tag_namespace(namespace)
+    }
+
+    /// The published recipes in `category`, see `CookbookView::by_category`.
+    pub fn by_category(&self, category: Category) -> Vec<&Recipe> {
+        self.published().by_category(category)
+    }
+
+    /// The published recipes served as `course`, see `CookbookView::by_course`.
+    pub fn by_course(&self, course: Course) -> Vec<&Recipe> {
+        self.published().by_course(course)
+    }
+
+    /// The published recipes that need none of the equipment in `excluded`, see
+    /// `CookbookView::without_equipment`.
+    pub fn without_equipment(&self, excluded: &[&str]) -> Vec<&Recipe> {
+        self.published().without_equipment(excluded)
+    }
+
+    /// The published recipes at or below `max_difficulty` and `max_duration`, see `CookbookView::filter`.
+    pub fn filter(&self, max_difficulty: Option<Difficulty>, max_duration: Option<u16>) -> Vec<&Recipe> {
+        self.published().filter(max_difficulty, max_duration)
+    }
+
+    /// The published recipes matching every word of `query`, see `CookbookView::search`.
+    pub fn search(&self, query: &str) -> Vec<&Recipe> {
+        self.published().search(query)
+    }
+
+    /// The published recipes with an ingredient matching each of `names`, see
+    /// `CookbookView::with_ingredients`.
+    pub fn with_ingredients(&self, names: &[&str]) -> Vec<&Recipe> {
+        self.published().with_ingredients(names)
+    }
+
+    /// The ingredients of the recipe with the given id not matched by any of `have`, sorted by name, using
+    /// the same matching as `Cookbook::with_ingredients`. Empty if there is no such recipe.
+    pub fn missing_ingredients(&self, recipe_id: &Uuid, have: &[&str]) -> Vec<&Ingredient> {
+        self.recipes.get(recipe_id).map_or_else(Vec::new, |recipe| missing(recipe, &normalize_names(have)))
+    }
+
+    /// Loads a cookbook from a JSON file holding an array of recipes. Each recipe is checked with
+    /// `Recipe::validate_for_status`, and one that cannot be parsed, is invalid or repeats an earlier id is left
+    /// out and reported with its index in the array instead of failing the whole load. Only a file that cannot
+    /// be read or is not a JSON array fails outright.
+    #[cfg(feature = "serde")]
+    pub fn load_json(path: impl AsRef<Path>) -> Result<(Cookbook, Vec<(usize, LoadError)>), LoadError> {
+        let json = fs::read_to_string(path)?;
+        let entries: Vec<serde_json::Value> = serde_json::from_str(&json)?;
+        let mut cookbook = Cookbook::new();
+        let mut errors = Vec::new();
+        for (index, entry) in entries.into_iter().enumerate() {
+            let loaded = serde_json::from_value::<Recipe>(entry)
+                .map_err(LoadError::from)
+                .and_then(|recipe| recipe.validate_for_status().map(|_| recipe).map_err(LoadError::Invalid))
+                .and_then(|recipe| cookbook.add(recipe).map_err(|err| LoadError::Duplicate(err.0.id())));
+            if let Err(err) = loaded {
+                errors.push((index, err));
+            }
+        }
+        Ok((cookbook, errors))
+    }
+
+    fn published(&self) -> CookbookView<'_> {
+        self.with_status(RecipeStatus::Published)
+    }
+}
+
+/// The recipes of a `Cookbook` with one status, returned by `Cookbook::with_status`.
+///
+/// The queries of a cookbook are those of the view of its published recipes, and a view of the drafts or the
+/// archived recipes answers them for those instead, e.g. `cookbook.with_status(RecipeStatus::Draft).search("soup")`.
+#[derive(Debug, Clone, Copy)]
+pub struct CookbookView<'a> {
+    /// Every recipe of the cookbook, whatever its status
+    pub(crate) recipes: &'a HashMap<Uuid, Recipe>,
+    status: RecipeStatus,
+}
+
+impl<'a> CookbookView<'a> {
+    pub fn status(&self) -> RecipeStatus {
+        self.status
+    }
+
+    /// The recipe with `id`, if it has the view's status.
+    pub fn get(&self, id: &Uuid) -> Option<&'a Recipe> {
+        self.recipes.get(id).filter(|recipe| recipe.status() == self.status)
+    }
+
+    /// Iterates over the recipes in no particular order.
+    pub fn iter(&self) -> impl Iterator<Item = &'a Recipe> {
+        let status = self.status;
+        self.recipes.values().filter(move |recipe| recipe.status() == status)
+    }
+
+    /// The recipes sorted with `cmp`, recipes it considers equal are sorted by id so the order is always the
+    /// same.
+    pub fn sorted_by<F: FnMut(&Recipe, &Recipe) -> Ordering>(&self, mut cmp: F) -> Vec<&'a Recipe> {
+        let mut recipes: Vec<&'a Recipe> = self.iter().collect();
+        recipes.sort_by(|a, b| cmp(a, b).then_with(|| a.id().cmp(&b.id())));
+        recipes
+    }
+
+    /// The recipes sorted by name, ignoring case. Names are compared after lowercasing, without any
+    /// locale-aware collation.
+    pub fn sorted_by_name(&self) -> Vec<&'a Recipe> {
+        self.sorted_by(by_name)
+    }
+
+    /// The recipes sorted by total duration, shortest first, and then by name ignoring case.
+    pub fn sorted_by_duration(&self) -> Vec<&'a Recipe> {
+        self.sorted_by(|a, b| a.duration().cmp(&b.duration()).then_with(|| by_name(a, b)))
+    }
+
+    /// The recipes sorted by difficulty, easiest first, and then by name ignoring case.
+    pub fn sorted_by_difficulty(&self) -> Vec<&'a Recipe> {
+        self.sorted_by(|a, b| a.difficulty().cmp(b.difficulty()).then_with(|| by_name(a, b)))
+    }
+
+    /// The recipes tagged `tag`, compared case-insensitively, sorted by name.
+    pub fn by_tag(&self, tag: &str) -> Vec<&'a Recipe> {
+        self.by_tags_all(&[tag])
+    }
+
+    /// The recipes tagged with all of `tags`, compared case-insensitively, sorted by name.
+    pub fn by_tags_all(&self, tags: &[&str]) -> Vec<&'a Recipe> {
+        let tags: Vec<RecipeTag> = tags.iter().map(|tag| RecipeTag::lookup(tag)).collect();
+        self.filter_sorted(|recipe| tags.iter().all(|tag| recipe.tags().contains(tag)))
+    }
+
+    /// The recipes tagged with any of `tags`, compared case-insensitively, sorted by name.
+    pub fn by_tags_any(&self, tags: &[&str]) -> Vec<&'a Recipe> {
+        let tags: Vec<RecipeTag> = tags.iter().map(|tag| RecipeTag::lookup(tag)).collect();
+        self.filter_sorted(|recipe| tags.iter().any(|tag| recipe.tags().contains(tag)))
+    }
+
+    /// The recipes with at least one tag in `namespace`, such as `"cuisine"`, compared case-insensitively,
+    /// sorted by name. To find a particular value, pass the whole tag to `by_tag`, e.g. `"cuisine:thai"`.
+    pub fn by_tag_namespace(&self, namespace: &str) -> Vec<&'a Recipe> {
+        self.filter_sorted(|recipe| recipe.tags().iter().any(|tag| tag.in_namespace(namespace)))
+    }
+
+    /// The recipes in `category`, sorted by name.
+    pub fn by_category(&self, category: Category) -> Vec<&'a Recipe> {
+        self.filter_sorted(|recipe| recipe.category() == category)
+    }
+
+    /// The recipes served as `course`, sorted by name. Recipes without a course are never included.
+    pub fn by_course(&self, course: Course) -> Vec<&'a Recipe> {
+        self.filter_sorted(|recipe| recipe.course() == Some(course))
+    }
+
+    /// The recipes that need none of the equipment in `excluded`, sorted by name, e.g. `&["oven"]` for
+    /// the recipes that can be made without an oven. Names are compared as with `Recipe::needs_equipment`.
+    pub fn without_equipment(&self, excluded: &[&str]) -> Vec<&'a Recipe> {
+        let excluded: Vec<Equipment> = excluded.iter().map(|name| Equipment::new(name)).collect();
+        self.filter_sorted(|recipe| !excluded.iter().any(|equipment| recipe.equipment().contains(equipment)))
+    }
+
+    /// The recipes at or below `max_difficulty` that take at most `max_duration` minutes in total, sorted
+    /// by duration and then name. A limit of `None` is not applied.
+    pub fn filter(&self, max_difficulty: Option<Difficulty>, max_duration: Option<u16>) -> Vec<&'a Recipe> {
+        let mut recipes: Vec<&'a Recipe> = self
+            .iter()
+            .filter(|recipe| max_difficulty.is_none_or(|max| *recipe.difficulty() <= max))
+            .filter(|recipe| max_duration.is_none_or(|max| recipe.duration() <= max))
+            .collect();
+        recipes.sort_by(|a, b| a.duration().cmp(&b.duration()).then_with(|| by_name(a, b)));
+        recipes
+    }
+
+    /// The recipes whose name, description and directions together contain every word of `query`,
+    /// ignoring case and word order. Recipes with a word in their name come first, then those with a word
+    /// in their description, then the rest, each group sorted by name. A blank query matches nothing.
+    pub fn search(&self, query: &str) -> Vec<&'a Recipe> {
+        let words: Vec<String> = query.split_whitespace().map(str::to_lowercase).collect();
+        if words.is_empty() {
+            return Vec::new();
+        }
+        let mut matches: Vec<(usize, &'a Recipe)> = self
+            .iter()
+            .filter_map(|recipe| {
+                let fields = [
+                    recipe.name().to_lowercase(),
+                    recipe.description().to_lowercase(),
+                    recipe.steps().iter().map(|step| step.text().to_lowercase()).collect::<Vec<_>>().join("\n"),
+                ];
+                let all_found = words.iter().all(|word| fields.iter().any(|field| field.contains(word.as_str())));
+                let rank = fields.iter().position(|field| words.iter().any(|word| field.contains(word.as_str())))?;
+                all_found.then_some((rank, recipe))
+            })
+            .collect();
+        matches.sort_by(|(a_rank, a), (b_rank, b)| a_rank.cmp(b_rank).then_with(|| by_name(a, b)));
+        matches.into_iter().map(|(_, recipe)| recipe).collect()
+    }
+
+    /// The recipes with an ingredient matching each of `names`, where a name matches an ingredient whose
+    /// name contains it, ignoring case. Recipes needing the fewest other ingredients come first, then
+    /// they are sorted by name. Blank names are ignored and if none are left nothing matches.
+    pub fn with_ingredients(&self, names: &[&str]) -> Vec<&'a Recipe> {
+        let names = normalize_names(names);
+        if names.is_empty() {
+            return Vec::new();
+        }
+        let mut matches: Vec<(usize, &'a Recipe)> = self
+            .iter()
+            .filter(|recipe| {
+                names.iter().all(|name| recipe.ingredients().iter().any(|ingredient| matches_name(ingredient, name)))
+            })
+            .map(|recipe| (missing(recipe, &names).len(), recipe))
+            .collect();
+        matches.sort_by(|(a_missing, a), (b_missing, b)| a_missing.cmp(b_missing).then_with(|| by_name(a, b)));
+        matches.into_iter().map(|(_, recipe)| recipe).collect()
+    }
+
+    /// The recipes matching `predicate`, sorted by name and then id so the order is deterministic.
+    fn filter_sorted<P: Fn(&Recipe) -> bool>(&self, predicate: P) -> Vec<&'a Recipe> {
+        let mut recipes: Vec<&'a Recipe> = self.iter().filter(|recipe| predicate(recipe)).collect();
+        recipes.sort_by(|a, b| by_name(a, b));
+        recipes
+    }
+}
+
+/// Orders recipes by name ignoring case, without any locale-aware collation, and recipes with the same name
+/// by id.
+pub(crate) fn by_name(a: &Recipe, b: &Recipe) -> Ordering {
+    a.name().to_lowercase().cmp(&b.name().to_lowercase()).then_with(|| a.id().cmp(&b.id()))
+}
+
+/// Trims and lowercases ingredient names for matching, dropping blank ones.
+fn normalize_names(names: &[&str]) -> Vec<String> {
+    names.iter().map(|name| name.trim().to_lowercase()).filter(|name| !name.is_empty()).collect()
+}
+
+/// Whether the ingredient's name contains `name`, which must already be normalized.
+fn matches_name(ingredient: &Ingredient, name: &str) -> bool {
+    ingredient.normalized_name().contains(name)
+}
+
+/// The ingredients of `recipe` not matched by any of the normalized `have` names, sorted by name.
+fn missing<'a>(recipe: &'a Recipe, have: &[String]) -> Vec<&'a Ingredient> {
+    let mut missing: Vec<&Ingredient> = recipe
+        .ingredients()
+        .iter()
+        .filter(|ingredient| !have.iter().any(|name| matches_name(ingredient, name)))
+        .collect();
+    missing.sort_by(|a, b| a.normalized_name().cmp(&b.normalized_name()).then_with(|| a.id().cmp(&b.id())));
+    missing
+}
+
+impl FromIterator<Recipe> for Cookbook {
+    /// Collects recipes into a cookbook, a later recipe replaces an earlier one with the same id.
+    fn from_iter<I: IntoIterator<Item = Recipe>>(iter: I) -> Self {
+        Self { recipes: iter.into_iter().map(|recipe| (recipe.id(), recipe)).collect() }
+    }
+}
+
+impl Extend<Recipe> for Cookbook {
+    /// Adds recipes, a later recipe replaces an earlier one with the same id.
+    fn extend<I: IntoIterator<Item = Recipe>>(&mut self, iter: I) {
+        self.recipes.extend(iter.into_iter().map(|recipe| (recipe.id(), recipe)));
+    }
+}
+
+impl IntoIterator for Cookbook {
+    type Item = Recipe;
+    type IntoIter = hash_map::IntoValues<Uuid, Recipe>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.recipes.into_values()
+    }
+}
+
+impl<'a> IntoIterator for &'a Cookbook {
+    type Item = &'a Recipe;
+    type IntoIter = hash_map::Values<'a, Uuid, Recipe>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.recipes.values()
+    }
+}
+
+/// What `Cookbook::dedupe` compares recipes by, their normalized name and their ingredients' normalized names
+/// and displayed quantities, sorted.
+fn content_key(recipe: &Recipe) -> (String, Vec<(String, String)>) {
+    let mut ingredients: Vec<(String, String)> = recipe
+        .ingredients()
+        .iter()
+        .map(|ingredient| (ingredient.normalized_name(), ingredient.quantity().to_string()))
+        .collect();
+    ingredients.sort();
+    (recipe.name().trim().to_lowercase(), ingredients)
+}
+
+/// The error returned when adding a recipe whose id is already in the `Cookbook`, holding the rejected recipe.
+#[derive(Debug, Clone)]
+pub struct DuplicateRecipe(pub Box<Recipe>);
+
+impl fmt::Display for DuplicateRecipe {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "a recipe with id {} is already in the cookbook", self.0.id())
+    }
+}
+
+impl Error for DuplicateRecipe {}
+
+impl Recipe {
+    /// Checks that an update of the recipe stored at version `stored` was based on that version, and moves
+    /// the version of this recipe past it. Shared by `Cookbook::update` and every `RecipeRepository`.
+    pub(crate) fn check_update(&mut self, stored: u32, expected_version: u32) -> Result<(), ConcurrencyError> {
+        if stored != expected_version {
+            return Err(ConcurrencyError::VersionMismatch { expected: expected_version, actual: stored });
+        }
+        self.version = self.version.max(stored.saturating_add(1));
+        Ok(())
+    }
+}
+
+/// The error returned by `Cookbook::update` and `RecipeRepository::update` when the recipe cannot be updated.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ConcurrencyError {
+    /// The stored recipe is at a different version than the one the update was based on
+    VersionMismatch { expected: u32, actual: u32 },
+    /// No recipe with the id is in the cookbook
+    NotFound(Uuid),
+}
+
+impl fmt::Display for ConcurrencyError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ConcurrencyError::VersionMismatch { expected, actual } => {
+                write!(f, "recipe was changed concurrently, expected version {} but found {}", expected, actual)
+            }
+            ConcurrencyError::NotFound(id) => write!(f, "no recipe with id {} in the cookbook", id),
+        }
+    }
+}
+
+impl Error for ConcurrencyError {}
+
+/// The error returned when loading a `Cookbook` from JSON, either for the whole file or for a single recipe.
+#[cfg(feature = "serde")]
+#[derive(Debug)]
+pub enum LoadError {
+    /// The file could not be read
+    Io(io::Error),
+    /// The file or a recipe in it is not valid JSON of the expected shape
+    Json(serde_json::Error),
+    /// A recipe was parsed but failed `Recipe::validate`
+    Invalid(ValidationErrors),
+    /// A recipe has the same id as an earlier one in the file
+    Duplicate(Uuid),
+}
+
+#[cfg(feature = "serde")]
+impl fmt::Display for LoadError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LoadError::Io(err) => write!(f, "cannot read cookbook: {}", err),
+            LoadError::Json(err) => write!(f, "invalid cookbook JSON: {}", err),
+            LoadError::Invalid(errors) => write!(f, "invalid recipe: {}", errors),
+            LoadError::Duplicate(id) => write!(f, "a recipe with id {} was already loaded", id),
+        }
+    }
+}
+
+#[cfg(feature = "serde")]
+impl Error for LoadError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            LoadError::Io(err) => Some(err),
+            LoadError::Json(err) => Some(err),
+            LoadError::Invalid(errors) => Some(errors),
+            LoadError::Duplicate(_) => None,
+        }
+    }
+}
+
+#[cfg(feature = "serde")]
+impl From<io::Error> for LoadError {
+    fn from(err: io::Error) -> Self {
+        LoadError::Io(err)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl From<serde_json::Error> for LoadError {
+    fn from(err: serde_json::Error) -> Self {
+        LoadError::Json(err)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{DateTime, Utc};
+    use super::*;
+    use crate::models::testing::{ingredient, recipe, tag};
+    use crate::models::Unit;
+
+    fn published(name: &str) -> Recipe {
+        recipe(name).status(RecipeStatus::Published).build().expect("the recipe should build")
+    }
+
+    fn tagged(name: &str, tags: &[&str]) -> Recipe {
+        let builder = recipe(name).status(RecipeStatus::Published).tags(tags.iter().map(|name| tag(name)));
+        builder.build().expect("the recipe should build")
+    }
+
+    fn timed(name: &str, difficulty: Difficulty, cook_minutes: u16) -> Recipe {
+        let builder = recipe(name).status(RecipeStatus::Published).difficulty(difficulty).cook_time(cook_minutes);
+        builder.build().expect("the recipe should build")
+    }
+
+    fn written(name: &str, description: &str, directions: &str) -> Recipe {
+        let builder = recipe(name).status(RecipeStatus::Published).description(description).directions(directions);
+        builder.build().expect("the recipe should build")
+    }
+
+    fn cooked_with(name: &str, ingredients: &[&str]) -> Recipe {
+        let ingredients = ingredients.iter().map(|name| ingredient(name, 1.0, Unit::Cup));
+        let builder = recipe(name).status(RecipeStatus::Published).ingredients(ingredients);
+        builder.build().expect("the recipe should build")
+    }
+
+    fn names(recipes: &[&Recipe]) -> Vec<String> {
+        recipes.iter().map(|recipe| recipe.name().to_string()).collect()
+    }
+
+    #[test]
+    fn adding_finding_and_removing_recipes() {
+        let mut cookbook = Cookbook::new();
+        let soup = published("Soup");
+        cookbook.add(soup.clone()).expect("the cookbook is empty");
+        assert_eq!((cookbook.len(), cookbook.get(&soup.id())), (1, Some(&soup)));
+        assert!(cookbook.contains(&soup.id()));
+
+        let mut renamed = soup.clone();
+        renamed.set_name("Stew");
+        let rejected = cookbook.add(renamed.clone()).expect_err("the id is already taken");
+        assert_eq!(*rejected.0, renamed);
+        assert_eq!(cookbook.get(&soup.id()).map(Recipe::name), Some("Soup"));
+        assert_eq!(cookbook.insert(renamed.clone()), Some(soup.clone()));
+        assert_eq!(cookbook.get(&soup.id()).map(Recipe::name), Some("Stew"));
+
+        assert_eq!(cookbook.remove(&soup.id()), Some(renamed));
+        assert_eq!(cookbook.remove(&soup.id()), None);
+        assert!(cookbook.is_empty());
+        assert_eq!(cookbook.get(&soup.id()), None);
+    }
+
+    #[test]
+    fn cookbooks_collect_from_and_iterate_into_recipes() {
+        let cookbook: Cookbook = ["Soup", "Bread", "Salad"].map(published).into_iter().collect();
+        assert_eq!(cookbook.len(), 3);
+        let mut borrowed: Vec<&str> = (&cookbook).into_iter().map(Recipe::name).collect();
+        borrowed.sort_unstable();
+        assert_eq!(borrowed, ["Bread", "Salad", "Soup"]);
+        let mut owned: Vec<String> = cookbook.into_iter().map(|recipe| recipe.name().to_string()).collect();
+        owned.sort_unstable();
+        assert_eq!(owned, ["Bread", "Salad", "Soup"]);
+    }
+
+    #[test]
+    fn filtering_by_tags_ignores_case_and_sorts_by_name() {
+        let cookbook: Cookbook = [
+            tagged("Tofu curry", &["vegan", "spicy"]),
+            tagged("Falafel", &["Vegan", "quick"]),
+            tagged("Chili", &["spicy", "quick"]),
+        ]
+        .into_iter()
+        .collect();
+        assert_eq!(names(&cookbook.by_tag("VEGAN")), ["Falafel", "Tofu curry"]);
+        assert_eq!(names(&cookbook.by_tags_all(&["vegan", "spicy"])), ["Tofu curry"]);
+        assert_eq!(names(&cookbook.by_tags_all(&["quick", "Spicy"])), ["Chili"]);
+        assert_eq!(names(&cookbook.by_tags_any(&["vegan", "quick"])), ["Chili", "Falafel", "Tofu curry"]);
+        assert_eq!(names(&cookbook.by_tags_any(&["spicy"])), ["Chili", "Tofu curry"]);
+        assert!(cookbook.by_tag("dessert").is_empty());
+        assert!(cookbook.by_tags_any(&[]).is_empty());
+    }
+
+    #[test]
+    fn filtering_by_difficulty_and_duration_sorts_by_duration() {
+        let cookbook: Cookbook = [
+            timed("Roast", Difficulty::Hard, 90),
+            timed("Omelette", Difficulty::Easy, 10),
+            timed("Risotto", Difficulty::Medium, 40),
+            timed("Salad", Difficulty::Easy, 5),
+            timed("Souffle", Difficulty::Expert, 30),
+        ]
+        .into_iter()
+        .collect();
+        assert_eq!(names(&cookbook.filter(None, None)), ["Salad", "Omelette", "Souffle", "Risotto", "Roast"]);
+        assert_eq!(names(&cookbook.filter(Some(Difficulty::Medium), None)), ["Salad", "Omelette", "Risotto"]);
+        assert_eq!(names(&cookbook.filter(None, Some(30))), ["Salad", "Omelette", "Souffle"]);
+        assert_eq!(names(&cookbook.filter(Some(Difficulty::Easy), Some(5))), ["Salad"]);
+        assert_eq!(names(&cookbook.filter(Some(Difficulty::Medium), Some(30))), ["Salad", "Omelette"]);
+        assert!(cookbook.filter(Some(Difficulty::Easy), Some(1)).is_empty());
+    }
+
+    #[test]
+    fn sorting_is_case_insensitive_and_ties_are_broken_the_same_way_every_time() {
+        let first_toast = timed("toast", Difficulty::Easy, 5);
+        let second_toast = timed("Toast", Difficulty::Easy, 5);
+        let recipes = [
+            timed("banana bread", Difficulty::Medium, 60),
+            second_toast.clone(),
+            timed("Apple pie", Difficulty::Hard, 60),
+            first_toast.clone(),
+            timed("Curry", Difficulty::Medium, 30),
+        ];
+        let cookbook: Cookbook = recipes.clone().into_iter().collect();
+        let reversed: Cookbook = recipes.into_iter().rev().collect();
+
+        let by_name = ["Apple pie", "banana bread", "Curry", "toast", "Toast"];
+        assert_eq!(names(&cookbook.sorted_by_name()), by_name);
+        assert_eq!(names(&cookbook.sorted_by_duration()), ["toast", "Toast", "Curry", "Apple pie", "banana bread"]);
+        assert_eq!(names(&cookbook.sorted_by_difficulty()), ["toast", "Toast", "banana bread", "Curry", "Apple pie"]);
+        let ids: Vec<Uuid> = cookbook.sorted_by_name().iter().map(|recipe| recipe.id()).collect();
+        assert_eq!(ids[3..], [first_toast.id(), second_toast.id()]);
+        let reversed_ids: Vec<Uuid> = reversed.sorted_by_name().iter().map(|recipe| recipe.id()).collect();
+        assert_eq!(ids, reversed_ids);
+
+        let by_length = cookbook.sorted_by(|a, b| a.name().len().cmp(&b.name().len()));
+        assert_eq!(names(&by_length), ["toast", "Toast", "Curry", "Apple pie", "banana bread"]);
+    }
+
+    #[test]
+    fn every_query_breaks_ties_by_name_ignoring_case() {
+        let baked = |name| {
+            let builder = recipe(name).status(RecipeStatus::Published).tag(tag("baking"));
+            builder.ingredients([ingredient("flour", 1.0, Unit::Cup)]).build().expect("the recipe should build")
+        };
+        let cookbook: Cookbook = [baked("Banana bread"), baked("apple pie")].into_iter().collect();
+        let by_name = ["apple pie", "Banana bread"];
+        assert_eq!(names(&cookbook.by_status(RecipeStatus::Published)), by_name);
+        assert_eq!(names(&cookbook.filter(None, None)), by_name);
+        assert_eq!(names(&cookbook.search("a")), by_name);
+        assert_eq!(names(&cookbook.with_ingredients(&["flour"])), by_name);
+        assert_eq!(names(&cookbook.by_tag("baking")), by_name);
+    }
+
+    #[test]
+    fn drafts_and_archived_recipes_are_only_queried_when_asked_for() {
+        let draft = recipe("Soup draft").tag(tag("vegan")).build().expect("the recipe should build");
+        let mut archived = published("Old soup");
+        archived.archive().expect("published recipes can be archived");
+        let cookbook: Cookbook = [published("Soup"), draft.clone(), archived.clone()].into_iter().collect();
+
+        assert_eq!(names(&cookbook.search("soup")), ["Soup"]);
+        assert!(cookbook.by_tag("vegan").is_empty());
+        let drafts = cookbook.with_status(RecipeStatus::Draft);
+        assert_eq!(drafts.status(), RecipeStatus::Draft);
+        assert_eq!(names(&drafts.search("soup")), ["Soup draft"]);
+        assert_eq!(names(&drafts.by_tag("vegan")), ["Soup draft"]);
+        assert_eq!(names(&drafts.sorted_by_name()), ["Soup draft"]);
+        assert_eq!(names(&drafts.filter(None, None)), ["Soup draft"]);
+        let archive = cookbook.with_status(RecipeStatus::Archived);
+        assert_eq!(names(&archive.sorted_by_name()), ["Old soup"]);
+        assert_eq!(archive.iter().map(Recipe::id).collect::<Vec<_>>(), [archived.id()]);
+        assert_eq!(archive.find_similar(&draft.id(), 5).len(), 1);
+    }
+
+    #[test]
+    fn search_ranks_name_matches_before_description_and_directions() {
+        let cookbook: Cookbook = [
+            written("Stew", "Slow cooked beef", "Brown the garlic first"),
+            written("Garlic bread", "Crusty", "Bake"),
+            written("Aioli", "A garlic sauce", "Whisk"),
+            written("Toast", "Crunchy", "Rub with GARLIC"),
+            written("Salad", "Crisp", "Toss"),
+        ]
+        .into_iter()
+        .collect();
+        assert_eq!(names(&cookbook.search("garlic")), ["Garlic bread", "Aioli", "Stew", "Toast"]);
+        assert_eq!(names(&cookbook.search("  Garlic ")), names(&cookbook.search("garlic")));
+    }
+
+    #[test]
+    fn search_matches_every_word_in_any_order_and_never_a_blank_query() {
+        let cookbook: Cookbook = [
+            written("Beef stew", "Slow cooked with carrots", "Simmer"),
+            written("Carrot cake", "Sweet", "Bake"),
+            written("Roast beef", "Sunday lunch", "Roast"),
+        ]
+        .into_iter()
+        .collect();
+        assert_eq!(names(&cookbook.search("carrot beef")), ["Beef stew"]);
+        assert_eq!(names(&cookbook.search("BEEF carrot")), ["Beef stew"]);
+        assert_eq!(names(&cookbook.search("beef")), ["Beef stew", "Roast beef"]);
+        assert!(cookbook.search("beef chocolate").is_empty());
+        assert!(cookbook.search("").is_empty());
+        assert!(cookbook.search(" \t ").is_empty());
+    }
+
+    #[test]
+    fn with_ingredients_needs_every_name_and_sorts_by_fewest_missing() {
+        let cookbook: Cookbook = [
+            cooked_with("Paella", &["Chicken thighs", "rice", "saffron", "peas"]),
+            cooked_with("Chicken and rice", &["chicken breast", "Basmati rice"]),
+            cooked_with("Risotto", &["arborio rice", "stock"]),
+            cooked_with("Roast chicken", &["chicken", "lemon"]),
+        ]
+        .into_iter()
+        .collect();
+        assert_eq!(names(&cookbook.with_ingredients(&["CHICKEN", "rice"])), ["Chicken and rice", "Paella"]);
+        assert_eq!(names(&cookbook.with_ingredients(&["rice"])), ["Chicken and rice", "Risotto", "Paella"]);
+        assert!(cookbook.with_ingredients(&["chicken", "tofu"]).is_empty());
+        assert!(cookbook.with_ingredients(&[" ", ""]).is_empty());
+    }
+
+    #[test]
+    fn missing_ingredients_lists_what_a_partial_pantry_lacks() {
+        let paella = cooked_with("Paella", &["chicken thighs", "rice", "saffron", "peas"]);
+        let id = paella.id();
+        let cookbook: Cookbook = [paella].into_iter().collect();
+        let missing = cookbook.missing_ingredients(&id, &["Chicken", "rice"]);
+        assert_eq!(missing.iter().map(|ingredient| ingredient.name()).collect::<Vec<_>>(), ["peas", "saffron"]);
+        assert_eq!(cookbook.missing_ingredients(&id, &[]).len(), 4);
+        assert!(cookbook.missing_ingredients(&id, &["chicken", "rice", "saffron", "frozen peas", "peas"]).is_empty());
+        assert!(cookbook.missing_ingredients(&Uuid::now_v7(), &["rice"]).is_empty());
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn loading_json_keeps_the_good_entries_and_reports_the_bad_ones() {
+        let good = recipe("Soup")
+            .status(RecipeStatus::Published)
+            .ingredient(ingredient("water", 1.0, Unit::Liter))
+            .build()
+            .expect("the recipe should build");
+        let invalid = recipe("Stew").status(RecipeStatus::Published).build().expect("the recipe should build");
+        let entries = serde_json::json!([good, { "name": 3 }, invalid, good]);
+        let path = std::env::temp_dir().join(format!("recipe_api-cookbook-{}.json", Uuid::new_v4()));
+        fs::write(&path, entries.to_string()).expect("the file should be written");
+        let loaded = Cookbook::load_json(&path);
+        fs::remove_file(&path).expect("the file should be removed");
+
+        let (cookbook, errors) = loaded.expect("the file is a JSON array");
+        assert_eq!(names(&cookbook.sorted_by_name()), ["Soup"]);
+        let indices: Vec<usize> = errors.iter().map(|(index, _)| *index).collect();
+        assert_eq!(indices, [1, 2, 3]);
+        assert!(matches!(errors[0].1, LoadError::Json(_)));
+        assert!(matches!(errors[1].1, LoadError::Invalid(_)));
+        assert!(matches!(errors[2].1, LoadError::Duplicate(id) if id == good.id()));
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn loading_fails_outright_for_a_missing_file() {
+        let path = std::env::temp_dir().join(format!("recipe_api-missing-{}.json", Uuid::new_v4()));
+        assert!(matches!(Cookbook::load_json(path), Err(LoadError::Io(_))));
+    }
+
+    #[test]
+    fn a_stale_update_is_rejected_until_the_recipe_is_read_again() {
+        let mut soup = published("Soup");
+        soup.set_servings(3);
+        soup.set_servings(4);
+        let id = soup.id();
+        let mut cookbook = Cookbook::new();
+        cookbook.add(soup).expect("the cookbook is empty");
+
+        let mut a = cookbook.get(&id).cloned().expect("the recipe is stored");
+        let mut b = cookbook.get(&id).cloned().expect("the recipe is stored");
+        assert_eq!((a.version(), a.etag()), (3, b.etag()));
+        a.set_name("Tomato soup");
+        cookbook.update(a, 3).expect("A read the current version");
+        b.set_servings(6);
+        let err = cookbook.update(b, 3).expect_err("B read a stale version");
+        assert_eq!(err, ConcurrencyError::VersionMismatch { expected: 3, actual: 4 });
+        assert_eq!(cookbook.get(&id).map(Recipe::servings), Some(4));
+
+        let mut b = cookbook.get(&id).cloned().expect("the recipe is stored");
+        assert_eq!(b.etag(), format!("\"{}-4\"", id.simple()));
+        b.set_servings(6);
+        cookbook.update(b, 4).expect("B read the current version");
+        let stored = cookbook.get(&id).expect("the recipe is stored");
+        assert_eq!((stored.name(), stored.servings(), stored.version()), ("Tomato soup", 6, 5));
+        let missing = published("Bread");
+        assert_eq!(cookbook.update(missing.clone(), 1), Err(ConcurrencyError::NotFound(missing.id())));
+    }
+
+    #[test]
+    fn updates_keep_the_stored_created_at_and_set_updated_at() {
+        let soup = published("Soup");
+        let (id, created_at, version) = (soup.id(), soup.created_at(), soup.version());
+        let mut cookbook = Cookbook::new();
+        cookbook.add(soup.clone()).expect("the cookbook is empty");
+        let tampered = soup.into_builder().created_at(DateTime::UNIX_EPOCH).updated_at(DateTime::UNIX_EPOCH);
+        let before_update = Utc::now();
+        cookbook.update(tampered.build().expect("the recipe should build"), version).expect("the version is current");
+        let stored = cookbook.get(&id).expect("the recipe is stored");
+        assert_eq!(stored.created_at(), created_at, "the tampered created_at should be discarded");
+        assert!(stored.updated_at() >= before_update, "updated_at should be the time of the update");
+    }
+
+    #[test]
+    fn dedupe_keeps_the_first_of_recipes_with_the_same_name_and_ingredients() {
+        let earlier = chrono::Utc::now() - chrono::Duration::minutes(5);
+        let with = |name: &str| {
+            recipe(name)
+                .ingredient(ingredient("Flour", 200.0, Unit::Gram))
+                .ingredient(ingredient("egg", 2.0, Unit::Piece))
+        };
+        let first = with("Pancakes").created_at(earlier).build().expect("the recipe should build");
+        let copy = with(" pancakes ").directions("Fry").build().expect("the recipe should build");
+        let more_flour = recipe("Pancakes")
+            .ingredient(ingredient("flour", 300.0, Unit::Gram))
+            .ingredient(ingredient("egg", 2.0, Unit::Piece))
+            .build()
+            .expect("the recipe should build");
+        let mut cookbook: Cookbook = [copy, first.clone(), more_flour.clone()].into_iter().collect();
+        assert_eq!(cookbook.dedupe(), 1);
+        assert_eq!(cookbook.len(), 2);
+        assert!(cookbook.contains(&first.id()) && cookbook.contains(&more_flour.id()));
+        assert_eq!(cookbook.dedupe(), 0);
+    }
+
+    #[test]
+    fn recipes_are_filtered_by_tag_namespace() {
+        let cookbook: Cookbook = [
+            tagged("Pad thai", &["cuisine:thai", "quick"]),
+            tagged("Curry", &["Cuisine:Indian"]),
+            tagged("Turkey", &["occasion:thanksgiving"]),
+            tagged("Notes", &["cuisine"]),
+        ]
+        .into_iter()
+        .collect();
+        assert_eq!(names(&cookbook.by_tag_namespace("cuisine")), ["Curry", "Pad thai"]);
+        assert_eq!(names(&cookbook.by_tag("CUISINE:THAI")), ["Pad thai"]);
+        assert!(cookbook.by_tag_namespace("diet").is_empty());
+    }
+
+    #[test]
+    fn recipes_are_filtered_by_category() {
+        let categorized = |name: &str, category: Category| {
+            let builder = recipe(name).status(RecipeStatus::Published).category(category);
+            builder.build().expect("the recipe should build")
+        };
+        let draft = recipe("Sorbet").category(Category::Dessert).build().expect("the recipe should build");
+        let cookbook: Cookbook = [
+            categorized("Tiramisu", Category::Dessert),
+            categorized("Brownies", Category::Dessert),
+            categorized("Lemonade", Category::Beverage),
+            categorized("Lasagna", Category::MainCourse),
+            draft,
+        ]
+        .into_iter()
+        .collect();
+        assert_eq!(names(&cookbook.by_category(Category::Dessert)), ["Brownies", "Tiramisu"]);
+        assert_eq!(names(&cookbook.by_category("main course".parse().expect("should parse"))), ["Lasagna"]);
+        assert!(cookbook.by_category(Category::Breakfast).is_empty());
+    }
+}
@@ -0,0 +1,311 @@
+use std::error::Error;
+use std::fmt;
+use std::io;
+use uuid::Uuid;
+use super::{Ingredient, Quantity, Recipe, RecipeBuildError, RecipeTag, Unit};
+
+/// The columns written by `export_csv`. Only `name`, `difficulty`, `servings`, `description`, `category` and
+/// `directions` are required by `import_csv`, and columns may appear in any order.
+pub const CSV_HEADER: [&str; 12] = [
+    "id",
+    "name",
+    "difficulty",
+    "prep_minutes",
+    "cook_minutes",
+    "rest_minutes",
+    "servings",
+    "description",
+    "category",
+    "ingredients",
+    "directions",
+    "tags",
+];
+
+const REQUIRED_COLUMNS: [&str; 6] = ["name", "difficulty", "servings", "description", "category", "directions"];
+
+/// Writes `recipes` as CSV with a `CSV_HEADER` row, one row per recipe.
+///
+/// Ingredients are written to a single column as a semicolon-delimited list of `name:amount:unit`
+/// entries, e.g. `flour:200:g;salt:0:to taste`, followed by `, note` for an ingredient with a note, as in
+/// `onion:1:piece, finely diced`. A semicolon in a note is written as a comma. Tags are written as a
+/// comma-delimited list. The directions are
+/// written one numbered step per line. Ingredient ids, step durations and any other fields are not
+/// exported.
+pub fn export_csv<W: io::Write>(recipes: &[&Recipe], writer: W) -> Result<(), CsvError> {
+    let mut writer = ::csv::Writer::from_writer(writer);
+    writer.write_record(CSV_HEADER)?;
+    for recipe in recipes {
+        writer.write_record(recipe.to_csv_record())?;
+    }
+    writer.flush().map_err(|err| CsvError::Csv(err.into()))?;
+    Ok(())
+}
+
+/// Reads recipes from CSV in the format written by `export_csv`, returning one result per row so a
+/// malformed row does not stop the rest from being imported. Only a missing or unreadable header fails
+/// the whole import. Rows without an id are given a fresh one, and ingredients are always given fresh ids.
+pub fn import_csv<R: io::Read>(reader: R) -> Result<Vec<Result<Recipe, CsvError>>, CsvError> {
+    let mut reader = ::csv::Reader::from_reader(reader);
+    let headers = reader.headers()?.clone();
+    let columns: Vec<Option<usize>> = CSV_HEADER
+        .iter()
+        .map(|column| headers.iter().position(|header| header.trim().eq_ignore_ascii_case(column)))
+        .collect();
+    for column in REQUIRED_COLUMNS {
+        if !headers.iter().any(|header| header.trim().eq_ignore_ascii_case(column)) {
+            return Err(CsvError::MissingColumn(column));
+        }
+    }
+    Ok(reader
+        .records()
+        .map(|record| {
+            let record = record?;
+            let line = record.position().map_or(0, |position| position.line());
+            let fields: Vec<&str> = columns
+                .iter()
+                .map(|column| column.and_then(|i| record.get(i)).unwrap_or(""))
+                .collect();
+            Recipe::from_csv_record(&fields).map_err(|err| err.at_line(line))
+        })
+        .collect())
+}
+
+impl Recipe {
+    /// The fields of the recipe in `CSV_HEADER` order, see `export_csv` for the format.
+    pub fn to_csv_record(&self) -> Vec<String> {
+        let mut ingredients: Vec<&Ingredient> = self.ingredients.iter().collect();
+        ingredients.sort_by_key(|ingredient| ingredient.normalized_name());
+        let ingredients: Vec<String> = ingredients
+            .into_iter()
+            .map(|ingredient| {
+                let quantity = ingredient.quantity();
+                let entry = format!("{}:{}:{}", ingredient.name(), quantity.amount(), quantity.unit());
+                match ingredient.note() {
+                    Some(note) => format!("{}, {}", entry, note.replace(';', ",")),
+                    None => entry,
+                }
+            })
+            .collect();
+        let mut tags: Vec<&str> = self.tags.iter().map(|tag| tag.as_str()).collect();
+        tags.sort_by_key(|tag| tag.to_lowercase());
+        vec![
+            self.id.to_string(),
+            self.name.clone(),
+            self.difficulty.to_string(),
+            self.times.prep_minutes.to_string(),
+            self.times.cook_minutes.to_string(),
+            self.times.rest_minutes.to_string(),
+            self.servings.to_string(),
+            self.description.clone(),
+            self.category.to_string(),
+            ingredients.join(";"),
+            self.directions_text(),
+            tags.join(","),
+        ]
+    }
+
+    /// Builds a recipe from fields in `CSV_HEADER` order, see `export_csv` for the format. Empty fields are
+    /// treated as not set, except for the description. Errors carry no line number, `import_csv` fills it in.
+    pub fn from_csv_record(fields: &[&str]) -> Result<Recipe, CsvError> {
+        let raw = |column: &str| {
+            let i = CSV_HEADER.iter().position(|c| *c == column).expect("column is in CSV_HEADER");
+            fields.get(i).map(|field| field.trim())
+        };
+        let field = |column: &str| raw(column).filter(|field| !field.is_empty());
+        let mut builder = Recipe::builder();
+        if let Some(id) = field("id") {
+            builder = builder.id(Uuid::parse_str(id).map_err(|err| invalid("id", err))?);
+        }
+        if let Some(name) = field("name") {
+            builder = builder.name(name);
+        }
+        if let Some(difficulty) = field("difficulty") {
+            builder = builder.difficulty(difficulty.parse().map_err(|err| invalid("difficulty", err))?);
+        }
+        if let Some(minutes) = field("prep_minutes") {
+            builder = builder.prep_time(minutes.parse().map_err(|err| invalid("prep_minutes", err))?);
+        }
+        if let Some(minutes) = field("cook_minutes") {
+            builder = builder.cook_time(minutes.parse().map_err(|err| invalid("cook_minutes", err))?);
+        }
+        if let Some(minutes) = field("rest_minutes") {
+            builder = builder.rest_time(minutes.parse().map_err(|err| invalid("rest_minutes", err))?);
+        }
+        if let Some(servings) = field("servings") {
+            builder = builder.servings(servings.parse().map_err(|err| invalid("servings", err))?);
+        }
+        // An empty description is still a description.
+        if let Some(description) = raw("description") {
+            builder = builder.description(description);
+        }
+        if let Some(category) = field("category") {
+            builder = builder.category(category.parse().map_err(|err| invalid("category", err))?);
+        }
+        if let Some(directions) = field("directions") {
+            builder = builder.directions(directions);
+        }
+        for entry in field("ingredients").unwrap_or("").split(';').map(str::trim).filter(|entry| !entry.is_empty()) {
+            builder = builder.ingredient(parse_ingredient(entry)?);
+        }
+        for tag in field("tags").unwrap_or("").split(',').map(str::trim).filter(|tag| !tag.is_empty()) {
+            builder = builder.tag(RecipeTag::new(tag).map_err(|err| invalid("tags", err))?);
+        }
+        builder.build().map_err(|error| CsvError::InvalidRecipe { line: None, error })
+    }
+}
+
+/// Parses a `name:amount:unit` entry with an optional `, note`, splitting from the right so the name may
+/// itself contain colons.
+fn parse_ingredient(entry: &str) -> Result<Ingredient, CsvError> {
+    let mut parts = entry.rsplitn(3, ':');
+    let (Some(unit), Some(amount), Some(name)) = (parts.next(), parts.next(), parts.next()) else {
+        return Err(invalid("ingredients", format!("expected name:amount:unit, found \"{}\"", entry)));
+    };
+    let (unit, note) = match unit.split_once(',') {
+        Some((unit, note)) => (unit, Some(note.trim())),
+        None => (unit, None),
+    };
+    let amount: f64 = amount.trim().parse().map_err(|err| invalid("ingredients", err))?;
+    let Ok(unit) = unit.parse::<Unit>();
+    let ingredient = Ingredient::new(Uuid::now_v7(), name.trim(), Quantity::new(amount, unit));
+    Ok(match note {
+        Some(note) => ingredient.with_note(note),
+        None => ingredient,
+    })
+}
+
+fn invalid(column: &'static str, err: impl fmt::Display) -> CsvError {
+    CsvError::InvalidField { line: None, column, message: err.to_string() }
+}
+
+/// The error returned when recipes cannot be read from or written to CSV.
+#[derive(Debug)]
+pub enum CsvError {
+    /// The CSV could not be read or written
+    Csv(::csv::Error),
+    /// The header row lacks a required column
+    MissingColumn(&'static str),
+    /// A field could not be parsed
+    InvalidField { line: Option<u64>, column: &'static str, message: String },
+    /// The row is missing fields a recipe requires
+    InvalidRecipe { line: Option<u64>, error: RecipeBuildError },
+}
+
+impl CsvError {
+    /// The line of the CSV the error was found on, counting the header as line 1, if known.
+    pub fn line(&self) -> Option<u64> {
+        match self {
+            CsvError::Csv(err) => err.position().map(|position| position.line()),
+            CsvError::MissingColumn(_) => Some(1),
+            CsvError::InvalidField { line, .. } | CsvError::InvalidRecipe { line, .. } => *line,
+        }
+    }
+
+    fn at_line(self, line: u64) -> Self {
+        match self {
+            CsvError::InvalidField { column, message, .. } => CsvError::InvalidField { line: Some(line), column, message },
+            CsvError::InvalidRecipe { error, .. } => CsvError::InvalidRecipe { line: Some(line), error },
+            err => err,
+        }
+    }
+}
+
+impl fmt::Display for CsvError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CsvError::Csv(err) => return write!(f, "{}", err),
+            CsvError::MissingColumn(column) => return write!(f, "missing required column \"{}\"", column),
+            CsvError::InvalidField { line: Some(line), .. } | CsvError::InvalidRecipe { line: Some(line), .. } => {
+                write!(f, "line {}: ", line)?
+            }
+            CsvError::InvalidField { line: None, .. } | CsvError::InvalidRecipe { line: None, .. } => {}
+        }
+        match self {
+            CsvError::InvalidField { column, message, .. } => write!(f, "invalid {}: {}", column, message),
+            CsvError::InvalidRecipe { error, .. } => write!(f, "{}", error),
+            CsvError::Csv(_) | CsvError::MissingColumn(_) => Ok(()),
+        }
+    }
+}
+
+impl Error for CsvError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            CsvError::Csv(err) => Some(err),
+            CsvError::InvalidRecipe { error, .. } => Some(error),
+            CsvError::MissingColumn(_) | CsvError::InvalidField { .. } => None,
+        }
+    }
+}
+
+impl From<::csv::Error> for CsvError {
+    fn from(err: ::csv::Error) -> Self {
+        CsvError::Csv(err)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::testing::{ingredient, recipe, tag};
+
+    fn export(recipes: &[&Recipe]) -> String {
+        let mut out = Vec::new();
+        export_csv(recipes, &mut out).expect("the recipes should export");
+        String::from_utf8(out).expect("CSV is UTF-8")
+    }
+
+    #[test]
+    fn two_recipes_round_trip() {
+        let pancakes = recipe("Pancakes")
+            .prep_time(5)
+            .cook_time(15)
+            .description("Fluffy, with \"real\" butter")
+            .directions("Whisk\nFry")
+            .ingredient(ingredient("flour", 200.0, Unit::Gram))
+            .ingredient(ingredient("onion", 1.0, Unit::Piece).with_note("finely diced"))
+            .tags([tag("breakfast"), tag("quick")])
+            .build()
+            .expect("the recipe should build");
+        let soup = recipe("Soup").ingredient(ingredient("salt", 0.0, Unit::ToTaste)).build().expect("should build");
+
+        let csv = export(&[&pancakes, &soup]);
+        let imported: Vec<Recipe> = import_csv(csv.as_bytes())
+            .expect("the header should be read")
+            .into_iter()
+            .collect::<Result<_, _>>()
+            .expect("every row should import");
+        assert_eq!(imported.len(), 2);
+        for (original, imported) in [&pancakes, &soup].into_iter().zip(&imported) {
+            assert_eq!(imported.id(), original.id());
+            assert_eq!(imported.to_csv_record(), original.to_csv_record());
+        }
+        assert!(csv.contains("flour:200:g;onion:1:piece, finely diced"), "{}", csv);
+    }
+
+    #[test]
+    fn malformed_rows_report_their_line_without_stopping_the_import() {
+        let csv = "name,difficulty,cook_minutes,servings,description,category,directions,ingredients\n\
+                   Soup,Easy,20,2,Warm,Main Course,Simmer,water:1:l\n\
+                   Stew,Impossible,90,2,Hearty,Main Course,Braise,\n\
+                   Salad,Easy,5,1,Fresh,Side,Toss,lettuce:lots:head\n\
+                   Toast,Easy,3,1,Crunchy,Breakfast,,\n\
+                   Punch,Easy,5,8,Fruity,Cocktail,Stir,\n";
+        let rows = import_csv(csv.as_bytes()).expect("the header should be read");
+        assert_eq!(rows.len(), 5);
+        assert_eq!(rows[0].as_ref().map(Recipe::name).ok(), Some("Soup"));
+        let lines: Vec<Option<u64>> = rows[1..].iter().map(|row| row.as_ref().err().and_then(CsvError::line)).collect();
+        assert_eq!(lines, [Some(3), Some(4), Some(5), Some(6)]);
+        assert!(matches!(rows[1], Err(CsvError::InvalidField { column: "difficulty", .. })));
+        assert!(matches!(rows[2], Err(CsvError::InvalidField { column: "ingredients", .. })));
+        assert!(matches!(rows[3], Err(CsvError::InvalidRecipe { error: RecipeBuildError::MissingDirections, .. })));
+        assert!(matches!(rows[4], Err(CsvError::InvalidField { column: "category", .. })));
+    }
+
+    #[test]
+    fn a_header_without_a_required_column_fails_the_whole_import() {
+        let err = import_csv("name,difficulty\nSoup,Easy\n".as_bytes()).expect_err("columns are missing");
+        assert!(matches!(err, CsvError::MissingColumn("servings")));
+        assert_eq!(err.line(), Some(1));
+    }
+}
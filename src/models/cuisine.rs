@@ -0,0 +1,218 @@
+use std::error::Error;
+use std::fmt;
+use std::str::FromStr;
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use super::Recipe;
+
+/// The culinary tradition a recipe comes from.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum Cuisine {
+    American,
+    Chinese,
+    French,
+    Greek,
+    Indian,
+    Italian,
+    Japanese,
+    Korean,
+    Mexican,
+    MiddleEastern,
+    Spanish,
+    Thai,
+    Vietnamese,
+    /// Any cuisine not covered by the other variants
+    Other(String),
+}
+
+impl FromStr for Cuisine {
+    type Err = std::convert::Infallible;
+
+    /// Parses a cuisine from its name, ignoring case. Unrecognized cuisines are returned as
+    /// `Cuisine::Other`, so this never fails.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let normalized = s.trim().to_lowercase().replace(['-', '_'], " ");
+        let cuisine = match normalized.as_str() {
+            "american" => Cuisine::American,
+            "chinese" => Cuisine::Chinese,
+            "french" => Cuisine::French,
+            "greek" => Cuisine::Greek,
+            "indian" => Cuisine::Indian,
+            "italian" => Cuisine::Italian,
+            "japanese" => Cuisine::Japanese,
+            "korean" => Cuisine::Korean,
+            "mexican" => Cuisine::Mexican,
+            "middle eastern" => Cuisine::MiddleEastern,
+            "spanish" => Cuisine::Spanish,
+            "thai" => Cuisine::Thai,
+            "vietnamese" => Cuisine::Vietnamese,
+            _ => Cuisine::Other(s.trim().to_string()),
+        };
+        Ok(cuisine)
+    }
+}
+
+impl fmt::Display for Cuisine {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let s = match self {
+            Cuisine::American => "American",
+            Cuisine::Chinese => "Chinese",
+            Cuisine::French => "French",
+            Cuisine::Greek => "Greek",
+            Cuisine::Indian => "Indian",
+            Cuisine::Italian => "Italian",
+            Cuisine::Japanese => "Japanese",
+            Cuisine::Korean => "Korean",
+            Cuisine::Mexican => "Mexican",
+            Cuisine::MiddleEastern => "Middle Eastern",
+            Cuisine::Spanish => "Spanish",
+            Cuisine::Thai => "Thai",
+            Cuisine::Vietnamese => "Vietnamese",
+            Cuisine::Other(s) => s,
+        };
+        write!(f, "{}", s)
+    }
+}
+
+/// Serializes known cuisines as their lowercase name and `Cuisine::Other` as it was given.
+#[cfg(feature = "serde")]
+impl Serialize for Cuisine {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        match self {
+            Cuisine::Other(s) => serializer.serialize_str(s),
+            cuisine => serializer.serialize_str(&cuisine.to_string().to_lowercase()),
+        }
+    }
+}
+
+/// Deserializes a cuisine from a string, unrecognized cuisines become `Cuisine::Other`.
+#[cfg(feature = "serde")]
+impl<'de> Deserialize<'de> for Cuisine {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let s = String::deserialize(deserializer)?;
+        let Ok(cuisine) = s.parse();
+        Ok(cuisine)
+    }
+}
+
+/// Where a recipe fits in a meal, used to navigate a `Cookbook` with `Cookbook::by_course`. Unlike its
+/// `Category`, a recipe need not have a course.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "lowercase"))]
+pub enum Course {
+    Appetizer,
+    Main,
+    Side,
+    Dessert,
+    Drink,
+    Breakfast,
+}
+
+impl FromStr for Course {
+    type Err = ParseCourseError;
+
+    /// Parses a course from its name or a common synonym, ignoring case.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "appetizer" | "appetizers" | "starter" | "starters" => Ok(Course::Appetizer),
+            "main" | "mains" | "main course" | "entree" | "entrée" => Ok(Course::Main),
+            "side" | "sides" | "side dish" => Ok(Course::Side),
+            "dessert" | "desserts" => Ok(Course::Dessert),
+            "drink" | "drinks" | "beverage" | "beverages" => Ok(Course::Drink),
+            "breakfast" => Ok(Course::Breakfast),
+            _ => Err(ParseCourseError(s.to_string())),
+        }
+    }
+}
+
+impl fmt::Display for Course {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let s = match self {
+            Course::Appetizer => "Appetizer",
+            Course::Main => "Main",
+            Course::Side => "Side",
+            Course::Dessert => "Dessert",
+            Course::Drink => "Drink",
+            Course::Breakfast => "Breakfast",
+        };
+        write!(f, "{}", s)
+    }
+}
+
+/// The error returned when text cannot be parsed into a `Course`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseCourseError(pub String);
+
+impl fmt::Display for ParseCourseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "invalid course \"{}\"", self.0)
+    }
+}
+
+impl Error for ParseCourseError {}
+
+impl Recipe {
+    /// Promotes a tag naming a course, such as "dessert" or "starter", into the `course` field and removes
+    /// the tag, returning the course. Nothing changes if the course is already set or no tag names one.
+    pub fn infer_course_from_tags(&mut self) -> Option<Course> {
+        if self.course.is_some() {
+            return None;
+        }
+        let (tag, course) = self.tags
+            .iter()
+            .find_map(|tag| tag.as_str().parse::<Course>().ok().map(|course| (tag.clone(), course)))?;
+        self.tags.remove(&tag);
+        self.course = Some(course);
+        self.touch();
+        Some(course)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::testing::{recipe, tag};
+    use crate::models::RecipeTag;
+
+    #[test]
+    fn a_course_tag_is_promoted_into_the_field() {
+        let mut built = recipe("Brownies")
+            .tags([tag("chocolate"), tag("Desserts")])
+            .build()
+            .expect("the recipe should build");
+        assert_eq!(built.infer_course_from_tags(), Some(Course::Dessert));
+        assert_eq!(built.course(), Some(Course::Dessert));
+        assert_eq!(built.tags().iter().map(RecipeTag::as_str).collect::<Vec<_>>(), ["chocolate"]);
+        assert_eq!(built.infer_course_from_tags(), None);
+    }
+
+    #[test]
+    fn a_course_already_set_is_not_replaced() {
+        let mut built = recipe("Toast").course(Course::Breakfast).tag(tag("side")).build().expect("should build");
+        assert_eq!(built.infer_course_from_tags(), None);
+        assert_eq!(built.course(), Some(Course::Breakfast));
+        assert_eq!(built.tags().len(), 1);
+    }
+
+    #[test]
+    fn cuisines_and_courses_parse_ignoring_case() {
+        assert_eq!("middle-eastern".parse(), Ok(Cuisine::MiddleEastern));
+        assert_eq!(" Scandinavian ".parse(), Ok(Cuisine::Other("Scandinavian".to_string())));
+        assert_eq!(Cuisine::MiddleEastern.to_string(), "Middle Eastern");
+        assert_eq!("Entrée".parse(), Ok(Course::Main));
+        assert_eq!("brunch".parse::<Course>(), Err(ParseCourseError("brunch".to_string())));
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn unknown_cuisines_round_trip_through_json() {
+        let other = Cuisine::Other("Scandinavian".to_string());
+        let json = serde_json::to_string(&other).expect("the cuisine should serialize");
+        assert_eq!(json, "\"Scandinavian\"");
+        assert_eq!(serde_json::from_str::<Cuisine>(&json).ok(), Some(other));
+        assert_eq!(serde_json::to_string(&Cuisine::Thai).expect("should serialize"), "\"thai\"");
+        assert_eq!(serde_json::from_str::<Cuisine>("\"THAI\"").ok(), Some(Cuisine::Thai));
+        assert_eq!(serde_json::to_string(&Course::Main).expect("should serialize"), "\"main\"");
+    }
+}
@@ -0,0 +1,242 @@
+use std::collections::HashSet;
+use std::fmt;
+use uuid::Uuid;
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+use super::allergen::mentions;
+use super::Recipe;
+
+/// A diet a recipe can be suitable for.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize), serde(rename_all = "lowercase"))]
+pub enum Diet {
+    Vegan,
+    Vegetarian,
+    Pescatarian,
+}
+
+impl Diet {
+    /// Every diet, from strictest to most permissive.
+    pub const ALL: [Diet; 3] = [Diet::Vegan, Diet::Vegetarian, Diet::Pescatarian];
+
+    /// The diets a recipe suitable for this diet is also suitable for, including this one. A vegan
+    /// recipe is also vegetarian and pescatarian.
+    pub fn implied(&self) -> &'static [Diet] {
+        match self {
+            Diet::Vegan => &Diet::ALL,
+            Diet::Vegetarian => &[Diet::Vegetarian, Diet::Pescatarian],
+            Diet::Pescatarian => &[Diet::Pescatarian],
+        }
+    }
+
+    /// The tag that explicitly declares a recipe suitable for this diet.
+    fn tag(&self) -> &'static str {
+        match self {
+            Diet::Vegan => "vegan",
+            Diet::Vegetarian => "vegetarian",
+            Diet::Pescatarian => "pescatarian",
+        }
+    }
+}
+
+impl fmt::Display for Diet {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Diet::Vegan => "Vegan",
+            Diet::Vegetarian => "Vegetarian",
+            Diet::Pescatarian => "Pescatarian",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+/// Plant based ingredients named after the animal products they replace, which are neither animal products
+/// for a diet nor dairy or eggs for an allergen. Shared by `DietRules::default` and `Recipe::detect_allergens`.
+pub(super) const PLANT_BASED: &[&str] = &[
+    "coconut milk", "coconut cream", "almond milk", "soy milk", "oat milk", "rice milk",
+    "peanut butter", "almond butter", "cocoa butter", "vegan",
+];
+
+/// The keywords `Recipe::classify_diet_with` looks for in ingredient names, matched case-insensitively
+/// like the allergen keywords. Start from `DietRules::default()` and push extra terms to extend them.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DietRules {
+    /// Meat, which rules out every diet
+    pub meat: Vec<String>,
+    /// Fish and shellfish, which rule out vegetarian and vegan
+    pub fish: Vec<String>,
+    /// Other animal products such as dairy, eggs and honey, which rule out vegan
+    pub animal_products: Vec<String>,
+    /// Ingredient names containing any of these are never treated as meat, fish or animal products,
+    /// e.g. "coconut milk"
+    pub exceptions: Vec<String>,
+}
+
+impl Default for DietRules {
+    fn default() -> Self {
+        let strings = |terms: &[&str]| terms.iter().map(|term| term.to_string()).collect();
+        DietRules {
+            meat: strings(&[
+                "beef", "pork", "chicken", "lamb", "mutton", "veal", "venison", "turkey", "duck", "bacon",
+                "ham", "sausage", "chorizo", "salami", "pepperoni", "prosciutto", "pancetta", "lard", "gelatin",
+            ]),
+            fish: strings(&[
+                "fish", "salmon", "tuna", "cod", "trout", "halibut", "anchovy", "anchovies", "sardine", "shrimp",
+                "prawn", "crab", "lobster", "clam", "mussel", "oyster", "scallop", "squid", "octopus",
+            ]),
+            animal_products: strings(&[
+                "milk", "buttermilk", "butter", "cheese", "cream", "yogurt", "yoghurt", "ghee", "parmesan",
+                "mozzarella", "egg", "mayonnaise", "honey",
+            ]),
+            exceptions: strings(PLANT_BASED),
+        }
+    }
+}
+
+/// The result of `Recipe::classify_diet_with`.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct DietClassification {
+    /// The diets the recipe is suitable for, from its ingredients or its tags
+    pub diets: HashSet<Diet>,
+    /// Ingredients that contradict a diet the recipe is explicitly tagged with
+    pub conflicts: Vec<DietConflict>,
+}
+
+/// An ingredient that contradicts a diet tag on the recipe, e.g. "fish sauce" in a recipe tagged vegan.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DietConflict {
+    /// The diet the recipe is tagged with
+    pub diet: Diet,
+    /// The id of the contradicting ingredient
+    pub ingredient_id: Uuid,
+    /// The name of the contradicting ingredient
+    pub ingredient_name: String,
+    /// The keyword that matched the ingredient name
+    pub keyword: String,
+}
+
+impl fmt::Display for DietConflict {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "recipe is tagged {} but \"{}\" looks like {}",
+            self.diet.tag(), self.ingredient_name, self.keyword
+        )
+    }
+}
+
+impl Recipe {
+    /// The diets the recipe is suitable for using `DietRules::default()`, see `classify_diet_with`.
+    pub fn classify_diet(&self) -> HashSet<Diet> {
+        self.classify_diet_with(&DietRules::default()).diets
+    }
+
+    /// Whether the recipe is suitable for `diet` using `DietRules::default()`, handy for filtering recipes.
+    pub fn suits_diet(&self, diet: Diet) -> bool {
+        self.classify_diet().contains(&diet)
+    }
+
+    /// Classifies the recipe by checking its ingredient names against `rules`. A diet tag such as "vegan"
+    /// declares the recipe suitable for that diet even if an ingredient rules it out, in which case every
+    /// contradicting ingredient is reported as a conflict for the user to resolve.
+    pub fn classify_diet_with(&self, rules: &DietRules) -> DietClassification {
+        // Every diet ruled out by an ingredient, along with the ingredient and keyword that ruled it out.
+        let mut excluded: Vec<(Diet, Uuid, &str, &str)> = Vec::new();
+        for ingredient in &self.ingredients {
+            let name = ingredient.name.as_str();
+            if rules.exceptions.iter().any(|exception| mentions(name, exception)) {
+                continue;
+            }
+            let categories: [(&[String], &[Diet]); 3] = [
+                (&rules.meat, &Diet::ALL),
+                (&rules.fish, &[Diet::Vegan, Diet::Vegetarian]),
+                (&rules.animal_products, &[Diet::Vegan]),
+            ];
+            for (keywords, diets) in categories {
+                if let Some(keyword) = keywords.iter().find(|keyword| mentions(name, keyword)) {
+                    excluded.extend(diets.iter().map(|diet| (*diet, ingredient.id, name, keyword.as_str())));
+                }
+            }
+        }
+
+        let tagged: Vec<Diet> = Diet::ALL
+            .into_iter()
+            .filter(|diet| self.tags.iter().any(|tag| tag.normalized() == diet.tag()))
+            .collect();
+        let mut classification = DietClassification::default();
+        for diet in Diet::ALL {
+            if !excluded.iter().any(|(excluded, ..)| *excluded == diet) {
+                classification.diets.insert(diet);
+            }
+        }
+        for diet in tagged {
+            classification.diets.extend(diet.implied());
+            let conflicts = excluded.iter().filter(|(excluded, ..)| *excluded == diet);
+            for (_, ingredient_id, name, keyword) in conflicts {
+                classification.conflicts.push(DietConflict {
+                    diet,
+                    ingredient_id: *ingredient_id,
+                    ingredient_name: name.to_string(),
+                    keyword: keyword.to_string(),
+                });
+            }
+        }
+        classification
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::testing::{ingredient, recipe, tag};
+    use crate::models::Unit;
+
+    fn dish(name: &str, ingredients: &[&str], tags: &[&str]) -> Recipe {
+        recipe(name)
+            .ingredients(ingredients.iter().map(|name| ingredient(name, 1.0, Unit::Piece)))
+            .tags(tags.iter().map(|name| tag(name)))
+            .build()
+            .expect("the recipe should build")
+    }
+
+    #[test]
+    fn a_tofu_stir_fry_suits_every_diet() {
+        let stir_fry = dish("Stir-fry", &["Firm tofu", "broccoli", "soy sauce", "coconut milk"], &[]);
+        assert_eq!(stir_fry.classify_diet(), HashSet::from(Diet::ALL));
+        assert!(stir_fry.suits_diet(Diet::Vegan));
+    }
+
+    #[test]
+    fn a_salmon_dish_is_only_pescatarian() {
+        let salmon = dish("Baked salmon", &["salmon fillets", "lemon", "butter"], &[]);
+        assert_eq!(salmon.classify_diet(), HashSet::from([Diet::Pescatarian]));
+        assert!(!salmon.suits_diet(Diet::Vegetarian));
+        assert!(dish("Steak", &["Beef steak"], &[]).classify_diet().is_empty());
+    }
+
+    #[test]
+    fn a_vegan_tag_contradicted_by_fish_sauce_is_a_conflict() {
+        let curry = dish("Green curry", &["tofu", "fish sauce"], &["vegan"]);
+        let classification = curry.classify_diet_with(&DietRules::default());
+        assert_eq!(classification.diets, HashSet::from(Diet::ALL));
+        let conflicts: Vec<(Diet, &str, &str)> = classification
+            .conflicts
+            .iter()
+            .map(|conflict| (conflict.diet, conflict.ingredient_name.as_str(), conflict.keyword.as_str()))
+            .collect();
+        assert_eq!(conflicts, [(Diet::Vegan, "fish sauce", "fish")]);
+        assert_eq!(
+            classification.conflicts[0].to_string(),
+            "recipe is tagged vegan but \"fish sauce\" looks like fish"
+        );
+    }
+
+    #[test]
+    fn rules_can_be_extended_with_extra_terms() {
+        let mut rules = DietRules::default();
+        let sandwich = dish("Sandwich", &["bread", "quorn"], &[]);
+        assert_eq!(sandwich.classify_diet_with(&rules).diets, HashSet::from(Diet::ALL));
+        rules.meat.push("quorn".to_string());
+        assert!(sandwich.classify_diet_with(&rules).diets.is_empty());
+    }
+}
@@ -0,0 +1,254 @@
+use std::collections::hash_map::DefaultHasher;
+use std::collections::HashMap;
+use std::fmt;
+use std::hash::{Hash, Hasher};
+#[cfg(feature = "serde")]
+use serde::Serialize;
+use super::{CookingTimes, Difficulty, DurationMinutes, Ingredient, Recipe, RecipeTag};
+
+/// A field that differs between two versions of a recipe.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+pub struct Change<T> {
+    pub old: T,
+    pub new: T,
+}
+
+impl<T: PartialEq> Change<T> {
+    /// A change from `old` to `new`, or `None` if they are equal.
+    fn between(old: T, new: T) -> Option<Change<T>> {
+        if old == new {
+            None
+        } else {
+            Some(Change { old, new })
+        }
+    }
+}
+
+/// What changed between two versions of a recipe, obtained through `Recipe::diff`.
+#[derive(Debug, Clone, PartialEq, Default)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+pub struct RecipeDiff {
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
+    pub name: Option<Change<String>>,
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
+    pub difficulty: Option<Change<Difficulty>>,
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
+    pub times: Option<Change<CookingTimes>>,
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
+    pub servings: Option<Change<u16>>,
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
+    pub description: Option<Change<String>>,
+    /// The directions rendered as numbered lines, see `Recipe::directions_text`
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
+    pub directions: Option<Change<String>>,
+    /// Ingredients only in the new version, sorted by name
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Vec::is_empty"))]
+    pub ingredients_added: Vec<Ingredient>,
+    /// Ingredients only in the old version, sorted by name
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Vec::is_empty"))]
+    pub ingredients_removed: Vec<Ingredient>,
+    /// Ingredients in both versions whose name, quantity or note changed, sorted by new name
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Vec::is_empty"))]
+    pub ingredients_changed: Vec<Change<Ingredient>>,
+    /// Tags only in the new version, sorted
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Vec::is_empty"))]
+    pub tags_added: Vec<RecipeTag>,
+    /// Tags only in the old version, sorted
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Vec::is_empty"))]
+    pub tags_removed: Vec<RecipeTag>,
+    /// Whether any image was added, removed or changed, compared by hash
+    pub images_changed: bool,
+}
+
+impl RecipeDiff {
+    /// Whether the two versions are the same in every compared field.
+    pub fn is_empty(&self) -> bool {
+        *self == RecipeDiff::default()
+    }
+}
+
+impl fmt::Display for RecipeDiff {
+    /// Displays the changes on one line for logging, e.g.
+    /// `name: "Pancakes" -> "Crêpes"; +ingredient 2 tbsp butter; -tag quick`.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut parts = Vec::new();
+        if let Some(Change { old, new }) = &self.name {
+            parts.push(format!("name: {:?} -> {:?}", old, new));
+        }
+        if let Some(Change { old, new }) = &self.difficulty {
+            parts.push(format!("difficulty: {} -> {}", old, new));
+        }
+        if let Some(Change { old, new }) = &self.times {
+            parts.push(format!("time: {} -> {}", DurationMinutes(old.total()), DurationMinutes(new.total())));
+        }
+        if let Some(Change { old, new }) = &self.servings {
+            parts.push(format!("servings: {} -> {}", old, new));
+        }
+        if self.description.is_some() {
+            parts.push("description changed".to_string());
+        }
+        if self.directions.is_some() {
+            parts.push("directions changed".to_string());
+        }
+        parts.extend(self.ingredients_added.iter().map(|ingredient| format!("+ingredient {}", ingredient)));
+        parts.extend(self.ingredients_removed.iter().map(|ingredient| format!("-ingredient {}", ingredient)));
+        parts.extend(
+            self.ingredients_changed
+                .iter()
+                .map(|Change { old, new }| format!("ingredient: {} -> {}", old, new)),
+        );
+        parts.extend(self.tags_added.iter().map(|tag| format!("+tag {}", tag.as_str())));
+        parts.extend(self.tags_removed.iter().map(|tag| format!("-tag {}", tag.as_str())));
+        if self.images_changed {
+            parts.push("images changed".to_string());
+        }
+        if parts.is_empty() {
+            write!(f, "no changes")
+        } else {
+            write!(f, "{}", parts.join("; "))
+        }
+    }
+}
+
+impl Recipe {
+    /// What changed going from this version of the recipe to `other`. Ingredients are matched by id, so an
+    /// ingredient that kept its id but was renamed, re-measured or given another note is reported as changed
+    /// rather than swapped.
+    pub fn diff(&self, other: &Recipe) -> RecipeDiff {
+        let old_ingredients: HashMap<_, _> = self.ingredients.iter().map(|i| (i.id, i)).collect();
+        let new_ingredients: HashMap<_, _> = other.ingredients.iter().map(|i| (i.id, i)).collect();
+        let mut ingredients_added: Vec<Ingredient> = new_ingredients
+            .iter()
+            .filter(|(id, _)| !old_ingredients.contains_key(id))
+            .map(|(_, ingredient)| (*ingredient).clone())
+            .collect();
+        let mut ingredients_removed: Vec<Ingredient> = old_ingredients
+            .iter()
+            .filter(|(id, _)| !new_ingredients.contains_key(id))
+            .map(|(_, ingredient)| (*ingredient).clone())
+            .collect();
+        let mut ingredients_changed: Vec<Change<Ingredient>> = new_ingredients
+            .iter()
+            .filter_map(|(id, new)| {
+                let old = old_ingredients.get(id)?;
+                (old.name != new.name || old.quantity != new.quantity || old.note != new.note)
+                    .then(|| Change { old: (*old).clone(), new: (*new).clone() })
+            })
+            .collect();
+        ingredients_added.sort_by(|a, b| a.name.cmp(&b.name));
+        ingredients_removed.sort_by(|a, b| a.name.cmp(&b.name));
+        ingredients_changed.sort_by(|a, b| a.new.name.cmp(&b.new.name));
+
+        let mut tags_added: Vec<RecipeTag> = other.tags.difference(&self.tags).cloned().collect();
+        let mut tags_removed: Vec<RecipeTag> = self.tags.difference(&other.tags).cloned().collect();
+        tags_added.sort_by_key(RecipeTag::normalized);
+        tags_removed.sort_by_key(RecipeTag::normalized);
+
+        RecipeDiff {
+            name: Change::between(self.name.clone(), other.name.clone()),
+            difficulty: Change::between(self.difficulty, other.difficulty),
+            times: Change::between(self.times, other.times),
+            servings: Change::between(self.servings, other.servings),
+            description: Change::between(self.description.clone(), other.description.clone()),
+            directions: Change::between(self.directions_text(), other.directions_text()),
+            ingredients_added,
+            ingredients_removed,
+            ingredients_changed,
+            tags_added,
+            tags_removed,
+            images_changed: self.images_hash() != other.images_hash(),
+        }
+    }
+
+    /// A hash of every image, so images can be compared without keeping both sets of bytes around.
+    fn images_hash(&self) -> u64 {
+        let mut hasher = DefaultHasher::new();
+        for image in &self.images {
+            image.id().hash(&mut hasher);
+            image.bytes().hash(&mut hasher);
+            image.caption().hash(&mut hasher);
+            image.is_primary().hash(&mut hasher);
+        }
+        hasher.finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::testing::{ingredient, recipe, tag, PNG};
+    use crate::models::{Quantity, Unit};
+
+    fn pancakes() -> Recipe {
+        recipe("Pancakes")
+            .ingredient(ingredient("flour", 2.0, Unit::Cup))
+            .ingredient(ingredient("milk", 1.0, Unit::Cup))
+            .tags([tag("breakfast"), tag("quick")])
+            .build()
+            .expect("the recipe should build")
+    }
+
+    #[test]
+    fn identical_recipes_have_an_empty_diff() {
+        let pancakes = pancakes();
+        let diff = pancakes.diff(&pancakes.clone());
+        assert!(diff.is_empty());
+        assert_eq!(diff.to_string(), "no changes");
+    }
+
+    #[test]
+    fn a_single_changed_field_is_the_only_change() {
+        let old = pancakes();
+        let mut new = old.clone();
+        new.set_name("Crêpes");
+        let diff = old.diff(&new);
+        assert_eq!(diff.name, Some(Change { old: "Pancakes".to_string(), new: "Crêpes".to_string() }));
+        assert_eq!(diff, RecipeDiff { name: diff.name.clone(), ..RecipeDiff::default() });
+        assert_eq!(diff.to_string(), r#"name: "Pancakes" -> "Crêpes""#);
+    }
+
+    #[test]
+    fn swapping_an_ingredient_reports_it_added_and_removed() {
+        let old = pancakes();
+        let mut new = old.clone();
+        let milk = old.ingredients().iter().find(|i| i.name() == "milk").expect("the milk is there").clone();
+        new.remove_ingredient(&milk.id());
+        let oat_milk = ingredient("oat milk", 1.0, Unit::Cup);
+        new.add_ingredient(oat_milk.clone());
+        new.remove_tag("quick");
+        new.add_tag(tag("vegan"));
+
+        let diff = old.diff(&new);
+        assert_eq!((diff.ingredients_added, diff.ingredients_removed), (vec![oat_milk], vec![milk]));
+        assert!(diff.ingredients_changed.is_empty());
+        assert_eq!((diff.tags_added, diff.tags_removed), (vec![tag("vegan")], vec![tag("quick")]));
+        assert!(diff.name.is_none() && !diff.images_changed);
+    }
+
+    #[test]
+    fn a_remeasured_ingredient_and_a_new_image_are_changes() {
+        let old = pancakes();
+        let mut new = old.clone();
+        let flour = old.ingredients().iter().find(|i| i.name() == "flour").expect("the flour is there").clone();
+        let more_flour = Ingredient::new(flour.id(), "flour", Quantity::new(3.0, Unit::Cup));
+        new.remove_ingredient(&flour.id());
+        new.add_ingredient(more_flour.clone());
+        new.set_img(PNG.to_vec());
+
+        let diff = old.diff(&new);
+        assert_eq!(diff.ingredients_changed, [Change { old: flour, new: more_flour }]);
+        assert!(diff.images_changed);
+        assert_eq!(diff.to_string(), "ingredient: 2 cup flour -> 3 cup flour; images changed");
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn only_the_changes_are_serialized() {
+        let old = pancakes();
+        let mut new = old.clone();
+        new.set_servings(6);
+        let json = serde_json::to_value(old.diff(&new)).expect("the diff should serialize");
+        assert_eq!(json, serde_json::json!({ "servings": { "old": 2, "new": 6 }, "images_changed": false }));
+    }
+}
@@ -0,0 +1,152 @@
+use std::fmt;
+use std::hash::{Hash, Hasher};
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+/// A piece of special equipment a recipe needs, such as a stand mixer or a 9x13 pan.
+///
+/// The name is normalized when the equipment is created, trimmed, lowercased and with its whitespace
+/// collapsed, so `"Dutch Oven"` and `" dutch  oven"` are the same equipment. Equipment is compared by name
+/// alone, the note is ignored.
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(from = "RawEquipment"))]
+pub struct Equipment {
+    /// The normalized name of the equipment
+    name: String,
+    /// A note about the equipment, e.g. "or a heavy pot with a lid"
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
+    note: Option<String>,
+}
+
+impl Equipment {
+    pub fn new(name: &str) -> Self {
+        Self { name: normalize(name), note: None }
+    }
+
+    /// Sets the note, a blank note is the same as none.
+    pub fn with_note(mut self, note: impl Into<String>) -> Self {
+        let note = note.into();
+        self.note = (!note.trim().is_empty()).then(|| note.trim().to_string());
+        self
+    }
+
+    /// The normalized name, e.g. `"dutch oven"`.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn note(&self) -> Option<&str> {
+        self.note.as_deref()
+    }
+}
+
+impl fmt::Display for Equipment {
+    /// Formats the equipment as e.g. `"dutch oven"` or `"dutch oven (or a heavy pot with a lid)"`.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.name)?;
+        if let Some(note) = &self.note {
+            write!(f, " ({})", note)?;
+        }
+        Ok(())
+    }
+}
+
+impl PartialEq for Equipment {
+    fn eq(&self, other: &Self) -> bool {
+        self.name == other.name
+    }
+}
+
+impl Eq for Equipment {}
+
+impl Hash for Equipment {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.name.hash(state);
+    }
+}
+
+/// Equipment as stored, normalized when deserialized in case it was written by hand.
+#[cfg(feature = "serde")]
+#[derive(Deserialize)]
+struct RawEquipment {
+    name: String,
+    #[serde(default)]
+    note: Option<String>,
+}
+
+#[cfg(feature = "serde")]
+impl From<RawEquipment> for Equipment {
+    fn from(raw: RawEquipment) -> Self {
+        let equipment = Equipment::new(&raw.name);
+        match raw.note {
+            Some(note) => equipment.with_note(note),
+            None => equipment,
+        }
+    }
+}
+
+/// Trims and lowercases `name` and collapses its whitespace.
+fn normalize(name: &str) -> String {
+    name.split_whitespace().map(str::to_lowercase).collect::<Vec<_>>().join(" ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::testing::recipe;
+    use crate::models::{Cookbook, Recipe, RecipeStatus};
+
+    fn needing(name: &str, equipment: &[&str]) -> Recipe {
+        let builder = recipe(name).status(RecipeStatus::Published);
+        let builder = builder.equipment(equipment.iter().map(|name| Equipment::new(name)));
+        builder.build().expect("the recipe should build")
+    }
+
+    #[test]
+    fn equipment_differing_in_case_and_spacing_is_only_kept_once() {
+        assert_eq!(Equipment::new(" Dutch  Oven ").name(), "dutch oven");
+        let built = recipe("Stew")
+            .equipment_item(Equipment::new("Dutch Oven").with_note("or a heavy pot"))
+            .equipment([Equipment::new("dutch oven"), Equipment::new("Ladle")])
+            .build()
+            .expect("the recipe should build");
+        assert_eq!(built.equipment().len(), 2);
+        let oven = built.equipment().get(&Equipment::new("DUTCH OVEN")).expect("the oven should be kept");
+        assert_eq!(oven.note(), Some("or a heavy pot"));
+        assert_eq!(oven.to_string(), "dutch oven (or a heavy pot)");
+        assert_eq!(Equipment::new("Whisk").with_note("  ").note(), None);
+    }
+
+    #[test]
+    fn recipes_needing_excluded_equipment_are_left_out() {
+        let cookbook: Cookbook = [
+            needing("Roast", &["Oven", "roasting tin"]),
+            needing("Salad", &[]),
+            needing("Stir fry", &["wok"]),
+        ]
+        .into_iter()
+        .collect();
+        let names = |recipes: Vec<&Recipe>| recipes.iter().map(|recipe| recipe.name().to_string()).collect::<Vec<_>>();
+        assert_eq!(names(cookbook.without_equipment(&["oven"])), ["Salad", "Stir fry"]);
+        assert_eq!(names(cookbook.without_equipment(&[" OVEN", "Wok"])), ["Salad"]);
+        assert_eq!(names(cookbook.without_equipment(&[])), ["Roast", "Salad", "Stir fry"]);
+    }
+
+    #[test]
+    fn equipment_is_listed_by_name_in_exports() {
+        let stew = needing("Stew", &["Ladle", "Dutch Oven"]);
+        assert!(stew.to_markdown().contains("\n## Equipment\n\n- dutch oven\n- ladle\n"));
+        assert!(stew.to_plain_text(80).contains("\nEquipment\n- dutch oven\n- ladle\n"));
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn equipment_is_normalized_when_deserialized() {
+        let json = serde_json::json!({ "name": "Stand  Mixer", "note": "with the paddle" });
+        let mixer: Equipment = serde_json::from_value(json).expect("should deserialize");
+        assert_eq!((mixer.name(), mixer.note()), ("stand mixer", Some("with the paddle")));
+        let json = serde_json::to_value(Equipment::new("Wok")).expect("should serialize");
+        assert_eq!(json, serde_json::json!({ "name": "wok" }));
+    }
+}
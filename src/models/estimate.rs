@@ -0,0 +1,173 @@
+use super::{Difficulty, Recipe};
+
+/// The weights and thresholds used to estimate how difficult a recipe is, see `DifficultyModel::estimate`.
+///
+/// The default model is a rough heuristic tuned on typical home recipes. Its fields are public so callers
+/// can adjust it to their own collection.
+#[derive(Debug, Clone, PartialEq)]
+pub struct DifficultyModel {
+    /// Points for each ingredient
+    pub per_ingredient: f64,
+    /// Points for each step
+    pub per_step: f64,
+    /// Points for each hour of prep and cook time, resting time is not counted
+    pub per_hour: f64,
+    /// Techniques that make a recipe harder, matched case-insensitively anywhere in the directions
+    pub hard_keywords: Vec<String>,
+    /// Points added for each hard technique found
+    pub hard_keyword_points: f64,
+    /// Words that mark a recipe as easy, matched the same way
+    pub easy_keywords: Vec<String>,
+    /// Points taken away for each easy word found
+    pub easy_keyword_points: f64,
+    /// The lowest score estimated as `Difficulty::Medium`
+    pub medium: f64,
+    /// The lowest score estimated as `Difficulty::Hard`
+    pub hard: f64,
+    /// The lowest score estimated as `Difficulty::Expert`
+    pub expert: f64,
+}
+
+impl Default for DifficultyModel {
+    fn default() -> Self {
+        let words = |words: &[&str]| words.iter().map(|word| word.to_string()).collect();
+        Self {
+            per_ingredient: 1.0,
+            per_step: 2.0,
+            per_hour: 4.0,
+            hard_keywords: words(&[
+                "temper", "laminate", "sous vide", "emulsify", "caramelize", "flambe", "clarify", "proof", "deglaze",
+            ]),
+            hard_keyword_points: 10.0,
+            easy_keywords: words(&["microwave", "no-bake", "no bake"]),
+            easy_keyword_points: 10.0,
+            medium: 15.0,
+            hard: 35.0,
+            expert: 60.0,
+        }
+    }
+}
+
+impl DifficultyModel {
+    /// The difficulty score of `recipe`, the sum of the points for its ingredients, steps, prep and cook time
+    /// and the techniques in its directions. Never negative.
+    pub fn score(&self, recipe: &Recipe) -> f64 {
+        let directions: String = recipe.steps().iter().map(|step| step.text().to_lowercase() + "\n").collect();
+        let found = |keywords: &[String]| {
+            keywords.iter().filter(|keyword| directions.contains(&keyword.to_lowercase())).count() as f64
+        };
+        let hours = (f64::from(recipe.prep_minutes()) + f64::from(recipe.cook_minutes())) / 60.0;
+        let score = recipe.ingredient_count() as f64 * self.per_ingredient
+            + recipe.steps().len() as f64 * self.per_step
+            + hours * self.per_hour
+            + found(&self.hard_keywords) * self.hard_keyword_points
+            - found(&self.easy_keywords) * self.easy_keyword_points;
+        score.max(0.0)
+    }
+
+    /// The difficulty a score falls into with this model's thresholds.
+    pub fn classify(&self, score: f64) -> Difficulty {
+        if score >= self.expert {
+            Difficulty::Expert
+        } else if score >= self.hard {
+            Difficulty::Hard
+        } else if score >= self.medium {
+            Difficulty::Medium
+        } else {
+            Difficulty::Easy
+        }
+    }
+
+    /// Estimates how difficult `recipe` is from its content alone. This is a heuristic, it ignores the
+    /// recipe's own `difficulty` and can easily be wrong for unusual recipes.
+    pub fn estimate(&self, recipe: &Recipe) -> Difficulty {
+        self.classify(self.score(recipe))
+    }
+}
+
+impl Difficulty {
+    /// Estimates how difficult `recipe` is with the default `DifficultyModel`, a heuristic.
+    pub fn estimate(recipe: &Recipe) -> Difficulty {
+        DifficultyModel::default().estimate(recipe)
+    }
+}
+
+impl Recipe {
+    /// Estimates how difficult the recipe is with the default `DifficultyModel`, a heuristic that may
+    /// disagree with the recipe's own `difficulty`.
+    pub fn estimate_difficulty(&self) -> Difficulty {
+        Difficulty::estimate(self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::testing::{ingredient, recipe};
+    use crate::models::{Step, Unit};
+
+    #[test]
+    fn a_three_ingredient_ten_minute_recipe_is_easy() {
+        let toast = recipe("Cheese toast")
+            .cook_time(10)
+            .ingredients([
+                ingredient("bread", 2.0, Unit::Piece),
+                ingredient("cheese", 50.0, Unit::Gram),
+                ingredient("butter", 1.0, Unit::Tablespoon),
+            ])
+            .build()
+            .expect("the recipe should build");
+        assert_eq!(toast.estimate_difficulty(), Difficulty::Easy);
+    }
+
+    #[test]
+    fn a_twenty_five_step_croissant_recipe_is_expert() {
+        let steps = (1..=25).map(|i| match i {
+            5 => Step::new("Laminate the butter into the dough"),
+            20 => Step::new("Proof until doubled"),
+            _ => Step::new(format!("Fold and rest, round {}", i)),
+        });
+        let croissants = recipe("Croissants")
+            .prep_time(120)
+            .cook_time(20)
+            .steps(steps)
+            .ingredients(["flour", "butter", "milk", "sugar", "yeast", "salt", "egg"].map(|name| {
+                ingredient(name, 1.0, Unit::Piece)
+            }))
+            .build()
+            .expect("the recipe should build");
+        assert_eq!(Difficulty::estimate(&croissants), Difficulty::Expert);
+        let auto = croissants.edit().difficulty_auto().build().expect("the recipe should build");
+        assert_eq!(*auto.difficulty(), Difficulty::Expert);
+    }
+
+    #[test]
+    fn tuning_the_model_changes_a_borderline_recipe() {
+        let salad = recipe("Salad")
+            .steps(["Wash", "Chop", "Whisk the dressing", "Toss", "Season"].map(Step::new))
+            .ingredients(["lettuce", "tomato", "cucumber", "oil", "vinegar", "salt"].map(|name| {
+                ingredient(name, 1.0, Unit::Piece)
+            }))
+            .build()
+            .expect("the recipe should build");
+        let model = DifficultyModel::default();
+        // Six ingredients, the fixture's "Cook" step and five more, and ten minutes of cooking.
+        assert!((model.score(&salad) - (6.0 + 12.0 + 10.0 / 60.0 * 4.0)).abs() < 1e-9);
+        assert_eq!(model.estimate(&salad), Difficulty::Medium);
+        let lenient = DifficultyModel { medium: 20.0, ..DifficultyModel::default() };
+        assert_eq!(lenient.estimate(&salad), Difficulty::Easy);
+        let tough = DifficultyModel {
+            hard_keywords: vec!["whisk".to_string()],
+            hard_keyword_points: 20.0,
+            ..DifficultyModel::default()
+        };
+        assert_eq!(tough.estimate(&salad), Difficulty::Hard);
+    }
+
+    #[test]
+    fn easy_words_lower_the_score_but_never_below_zero() {
+        let fudge = recipe("Fudge").directions("No-bake: microwave the chocolate").build().expect("should build");
+        assert_eq!(DifficultyModel::default().score(&fudge), 0.0);
+        assert_eq!(fudge.estimate_difficulty(), Difficulty::Easy);
+    }
+}
@@ -0,0 +1,542 @@
+use std::error::Error;
+use std::fmt;
+use uuid::Uuid;
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Deserializer, Serialize};
+use super::Recipe;
+
+/// An image format recognized from the leading bytes of an image.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "lowercase"))]
+pub enum ImageFormat {
+    Png,
+    Jpeg,
+    Gif,
+    WebP,
+}
+
+impl ImageFormat {
+    /// Sniffs the format from the magic bytes at the start of `bytes`, returning `None` for empty or
+    /// unrecognized data. Only the header is checked, not that the rest of the image is well formed.
+    pub fn detect(bytes: &[u8]) -> Option<ImageFormat> {
+        match bytes {
+            [0x89, b'P', b'N', b'G', 0x0D, 0x0A, 0x1A, 0x0A, ..] => Some(ImageFormat::Png),
+            [0xFF, 0xD8, 0xFF, ..] => Some(ImageFormat::Jpeg),
+            [b'G', b'I', b'F', b'8', b'7' | b'9', b'a', ..] => Some(ImageFormat::Gif),
+            [b'R', b'I', b'F', b'F', _, _, _, _, b'W', b'E', b'B', b'P', ..] => Some(ImageFormat::WebP),
+            _ => None,
+        }
+    }
+
+    /// The MIME type of the format, e.g. `"image/png"`.
+    pub fn mime_type(&self) -> &'static str {
+        match self {
+            ImageFormat::Png => "image/png",
+            ImageFormat::Jpeg => "image/jpeg",
+            ImageFormat::Gif => "image/gif",
+            ImageFormat::WebP => "image/webp",
+        }
+    }
+}
+
+impl fmt::Display for ImageFormat {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let s = match self {
+            ImageFormat::Png => "PNG",
+            ImageFormat::Jpeg => "JPEG",
+            ImageFormat::Gif => "GIF",
+            ImageFormat::WebP => "WebP",
+        };
+        write!(f, "{}", s)
+    }
+}
+
+/// Image bytes that have been checked to be a PNG, JPEG, GIF or WebP image no larger than a size limit.
+#[derive(Clone, PartialEq, Eq)]
+pub struct ImageData {
+    bytes: Vec<u8>,
+    format: ImageFormat,
+}
+
+impl ImageData {
+    /// The size limit used by `from_bytes`, in bytes.
+    pub const DEFAULT_MAX_BYTES: usize = Recipe::MAX_IMG_BYTES;
+
+    /// Checks that `bytes` are a recognized image no larger than `DEFAULT_MAX_BYTES`.
+    pub fn from_bytes(bytes: Vec<u8>) -> Result<Self, ImageError> {
+        Self::from_bytes_with_limit(bytes, Self::DEFAULT_MAX_BYTES)
+    }
+
+    /// Checks that `bytes` are a recognized image no larger than `max_bytes`.
+    pub fn from_bytes_with_limit(bytes: Vec<u8>, max_bytes: usize) -> Result<Self, ImageError> {
+        let format = Self::check(&bytes, max_bytes)?;
+        Ok(Self { bytes, format })
+    }
+
+    /// Checks `bytes` like `from_bytes_with_limit` without taking ownership, returning their format.
+    pub(crate) fn check(bytes: &[u8], max_bytes: usize) -> Result<ImageFormat, ImageError> {
+        if bytes.is_empty() {
+            return Err(ImageError::Empty);
+        }
+        if bytes.len() > max_bytes {
+            return Err(ImageError::TooLarge { max: max_bytes, actual: bytes.len() });
+        }
+        ImageFormat::detect(bytes).ok_or(ImageError::UnrecognizedFormat)
+    }
+
+    pub fn bytes(&self) -> &[u8] {
+        &self.bytes
+    }
+
+    pub fn into_bytes(self) -> Vec<u8> {
+        self.bytes
+    }
+
+    pub fn format(&self) -> ImageFormat {
+        self.format
+    }
+
+    /// The MIME type of the image, e.g. `"image/png"`.
+    pub fn content_type(&self) -> &'static str {
+        self.format.mime_type()
+    }
+}
+
+impl fmt::Debug for ImageData {
+    /// Formats the format and the length of the bytes rather than the bytes themselves.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ImageData")
+            .field("bytes", &format_args!("<{} bytes>", self.bytes.len()))
+            .field("format", &self.format)
+            .finish()
+    }
+}
+
+/// The error returned when bytes are rejected as an image.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ImageError {
+    /// There are no bytes at all
+    Empty,
+    /// The bytes are not a PNG, JPEG, GIF or WebP image
+    UnrecognizedFormat,
+    /// The image is larger than the size limit, in bytes
+    TooLarge { max: usize, actual: usize },
+    /// The image could not be decoded, e.g. because it is truncated
+    Decode(String),
+    /// The image could not be re-encoded
+    Encode(String),
+}
+
+impl fmt::Display for ImageError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ImageError::Empty => write!(f, "image is empty"),
+            ImageError::UnrecognizedFormat => write!(f, "image is not a PNG, JPEG, GIF or WebP image"),
+            ImageError::TooLarge { max, actual } => {
+                write!(f, "image is {} bytes, larger than the limit of {} bytes", actual, max)
+            }
+            ImageError::Decode(err) => write!(f, "image could not be decoded: {}", err),
+            ImageError::Encode(err) => write!(f, "image could not be encoded: {}", err),
+        }
+    }
+}
+
+impl Error for ImageError {}
+
+/// The content type recorded for images whose format is not recognized.
+const UNKNOWN_CONTENT_TYPE: &str = "application/octet-stream";
+
+/// One of the pictures of a recipe.
+#[derive(Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+pub struct RecipeImage {
+    /// The id of the image
+    id: Uuid,
+    /// The image itself, serialized as base64
+    #[cfg_attr(feature = "serde", serde(serialize_with = "super::img_base64::serialize"))]
+    bytes: Vec<u8>,
+    /// The MIME type of the image, detected from its bytes
+    content_type: String,
+    /// A caption describing the image
+    caption: Option<String>,
+    /// Whether this is the main picture of the recipe, a recipe has at most one
+    is_primary: bool,
+    /// The format of the image, sniffed once when the bytes are set rather than on every lookup
+    #[cfg_attr(feature = "serde", serde(skip))]
+    format: Option<ImageFormat>,
+}
+
+impl RecipeImage {
+    /// Creates an image with a freshly generated id, detecting its content type from `bytes`.
+    pub fn new(bytes: Vec<u8>) -> Self {
+        let format = ImageFormat::detect(&bytes);
+        let content_type = format.map_or(UNKNOWN_CONTENT_TYPE, |format| format.mime_type()).to_string();
+        Self { id: Uuid::now_v7(), bytes, content_type, caption: None, is_primary: false, format }
+    }
+
+    pub fn with_caption(mut self, caption: impl Into<String>) -> Self {
+        self.caption = Some(caption.into());
+        self
+    }
+
+    /// Marks the image as the main picture of the recipe.
+    pub fn primary(mut self) -> Self {
+        self.is_primary = true;
+        self
+    }
+
+    /// Gives the image the id it was stored under, for backends that store images apart from their recipe.
+    #[cfg(feature = "serde")]
+    pub(crate) fn with_id(mut self, id: Uuid) -> Self {
+        self.id = id;
+        self
+    }
+
+    pub fn id(&self) -> Uuid {
+        self.id
+    }
+
+    pub fn bytes(&self) -> &[u8] {
+        &self.bytes
+    }
+
+    pub fn content_type(&self) -> &str {
+        &self.content_type
+    }
+
+    pub fn caption(&self) -> Option<&str> {
+        self.caption.as_deref()
+    }
+
+    pub fn is_primary(&self) -> bool {
+        self.is_primary
+    }
+
+    /// The format of the image, `None` if it is not recognized.
+    pub fn format(&self) -> Option<ImageFormat> {
+        self.format
+    }
+
+    /// A key identifying a thumbnail of this image no larger than `max_dim`, e.g. for caching the result
+    /// of `thumbnail`. The bytes of an image never change once it is created, so the key stays valid.
+    pub fn thumbnail_cache_key(&self, max_dim: u32) -> String {
+        format!("{}-{}", self.id, max_dim)
+    }
+}
+
+impl From<ImageData> for RecipeImage {
+    fn from(data: ImageData) -> Self {
+        RecipeImage::new(data.into_bytes())
+    }
+}
+
+impl fmt::Debug for RecipeImage {
+    /// Formats every field except the bytes, which are summarized by their length.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("RecipeImage")
+            .field("id", &self.id)
+            .field("bytes", &format_args!("<{} bytes>", self.bytes.len()))
+            .field("content_type", &self.content_type)
+            .field("caption", &self.caption)
+            .field("is_primary", &self.is_primary)
+            .finish()
+    }
+}
+
+/// Deserializes an image, the content type is detected from the bytes rather than trusted.
+#[cfg(feature = "serde")]
+impl<'de> Deserialize<'de> for RecipeImage {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        #[derive(Deserialize)]
+        struct Fields {
+            #[serde(default = "Uuid::now_v7")]
+            id: Uuid,
+            #[serde(deserialize_with = "super::img_base64::deserialize")]
+            bytes: Vec<u8>,
+            #[serde(default)]
+            caption: Option<String>,
+            #[serde(default)]
+            is_primary: bool,
+        }
+
+        let fields = Fields::deserialize(deserializer)?;
+        let mut image = RecipeImage::new(fields.bytes);
+        image.id = fields.id;
+        image.caption = fields.caption;
+        image.is_primary = fields.is_primary;
+        Ok(image)
+    }
+}
+
+/// Adds `image` to `images`, clearing the primary flag of the others if it is primary.
+pub(crate) fn push(images: &mut Vec<RecipeImage>, image: RecipeImage) {
+    if image.is_primary {
+        images.iter_mut().for_each(|other| other.is_primary = false);
+    }
+    images.push(image);
+}
+
+/// Deserializes images from either a list of images or a legacy single base64 `img`, which becomes the
+/// primary image. Only the first image marked primary stays primary.
+#[cfg(feature = "serde")]
+pub(crate) fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Vec<RecipeImage>, D::Error> {
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum Images {
+        Legacy(String),
+        Images(Vec<RecipeImage>),
+    }
+
+    Ok(match Images::deserialize(deserializer)? {
+        Images::Legacy(img) => {
+            let bytes = super::img_base64::decode(&img)
+                .ok_or_else(|| serde::de::Error::custom("img is not valid base64"))?;
+            if bytes.is_empty() {
+                Vec::new()
+            } else {
+                vec![RecipeImage::new(bytes).primary()]
+            }
+        }
+        Images::Images(mut images) => {
+            let mut seen_primary = false;
+            for image in images.iter_mut().filter(|image| image.is_primary) {
+                image.is_primary = !seen_primary;
+                seen_primary = true;
+            }
+            images
+        }
+    })
+}
+
+impl Recipe {
+    /// The largest picture accepted by `Recipe::validate`, in bytes.
+    pub const MAX_IMG_BYTES: usize = 5 * 1024 * 1024;
+
+    /// Every picture of the recipe.
+    pub fn images(&self) -> &[RecipeImage] {
+        &self.images
+    }
+
+    /// The main picture of the recipe, if one is marked primary.
+    pub fn primary_image(&self) -> Option<&RecipeImage> {
+        self.images.iter().find(|image| image.is_primary)
+    }
+
+    /// The bytes of the primary image, empty if there is none.
+    pub fn img(&self) -> &[u8] {
+        self.primary_image().map_or(&[], |image| image.bytes())
+    }
+
+    /// The format of the primary image, `None` if there is none or its format is not recognized.
+    /// The format is detected when the image is set, so this is cheap.
+    pub fn img_format(&self) -> Option<ImageFormat> {
+        self.primary_image().and_then(RecipeImage::format)
+    }
+
+    /// Whether the recipe has any pictures.
+    pub fn has_img(&self) -> bool {
+        !self.images.is_empty()
+    }
+
+    /// Replaces the primary image with one made from `img`, returning its detected format. An empty
+    /// `img` just removes the primary image.
+    pub fn set_img(&mut self, img: Vec<u8>) -> Option<ImageFormat> {
+        self.images.retain(|image| !image.is_primary);
+        self.touch();
+        if img.is_empty() {
+            return None;
+        }
+        let image = RecipeImage::new(img).primary();
+        let format = image.format();
+        self.images.push(image);
+        format
+    }
+
+    /// Adds a picture, if it is primary the previous primary image stops being primary.
+    pub fn add_image(&mut self, image: RecipeImage) {
+        push(&mut self.images, image);
+        self.touch();
+    }
+
+    /// Removes the picture with the given id, returning it if it was present.
+    pub fn remove_image(&mut self, id: &Uuid) -> Option<RecipeImage> {
+        let index = self.images.iter().position(|image| image.id == *id)?;
+        self.touch();
+        Some(self.images.remove(index))
+    }
+
+    /// Replaces every picture without counting it as a modification, for backends that store images apart
+    /// from their recipe.
+    #[cfg(feature = "serde")]
+    pub(crate) fn restore_images(&mut self, images: Vec<RecipeImage>) {
+        self.images.clear();
+        for image in images {
+            push(&mut self.images, image);
+        }
+    }
+
+    /// Makes the picture with the given id the primary image, returning `false` if there is no such picture.
+    pub fn set_primary_image(&mut self, id: &Uuid) -> bool {
+        if !self.images.iter().any(|image| image.id == *id) {
+            return false;
+        }
+        for image in &mut self.images {
+            image.is_primary = image.id == *id;
+        }
+        self.touch();
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::testing::{ingredient, recipe, JPEG, PNG};
+    use crate::models::{RecipeBuildError, Unit, ValidationError};
+
+    #[test]
+    fn detects_formats_from_their_magic_bytes() {
+        assert_eq!(ImageFormat::detect(&PNG), Some(ImageFormat::Png));
+        assert_eq!(ImageFormat::detect(&JPEG), Some(ImageFormat::Jpeg));
+        assert_eq!(ImageFormat::detect(b"GIF89a\x01\x00"), Some(ImageFormat::Gif));
+        assert_eq!(ImageFormat::detect(b"RIFF\x24\x00\x00\x00WEBPVP8 "), Some(ImageFormat::WebP));
+        assert_eq!(ImageFormat::detect(&[]), None);
+        assert_eq!(ImageFormat::detect(b"not an image"), None);
+        assert_eq!(ImageFormat::detect(&PNG[..4]), None);
+    }
+
+    #[test]
+    fn image_data_accepts_each_format_and_records_its_content_type() {
+        let headers: [(&[u8], &str); 4] = [
+            (&PNG, "image/png"),
+            (&JPEG, "image/jpeg"),
+            (b"GIF87a\x01\x00", "image/gif"),
+            (b"RIFF\x24\x00\x00\x00WEBPVP8 ", "image/webp"),
+        ];
+        for (bytes, content_type) in headers {
+            let image = ImageData::from_bytes(bytes.to_vec()).expect("the header should be recognized");
+            assert_eq!(image.content_type(), content_type);
+            assert_eq!(image.bytes(), bytes);
+        }
+    }
+
+    #[test]
+    fn image_data_rejects_truncated_oversized_and_fake_images() {
+        assert_eq!(ImageData::from_bytes(JPEG[..2].to_vec()), Err(ImageError::UnrecognizedFormat));
+        assert_eq!(ImageData::from_bytes(b"just some text, not a PNG".to_vec()), Err(ImageError::UnrecognizedFormat));
+        assert_eq!(ImageData::from_bytes(Vec::new()), Err(ImageError::Empty));
+
+        let mut huge = PNG.to_vec();
+        huge.resize(ImageData::DEFAULT_MAX_BYTES + 1, 0);
+        let actual = huge.len();
+        assert_eq!(
+            ImageData::from_bytes(huge),
+            Err(ImageError::TooLarge { max: ImageData::DEFAULT_MAX_BYTES, actual })
+        );
+        assert_eq!(
+            ImageData::from_bytes_with_limit(PNG.to_vec(), 4),
+            Err(ImageError::TooLarge { max: 4, actual: PNG.len() })
+        );
+    }
+
+    #[test]
+    fn try_build_rejects_invalid_images() {
+        let rejected = recipe("Soup").img(b"plain text".to_vec()).try_build();
+        assert_eq!(rejected, Err(RecipeBuildError::InvalidImage(ImageError::UnrecognizedFormat)));
+        let built = recipe("Soup").img(JPEG.to_vec()).try_build().expect("the recipe should build");
+        assert_eq!(built.img_format(), Some(ImageFormat::Jpeg));
+    }
+
+    #[test]
+    fn the_primary_image_format_is_exposed_on_the_recipe() {
+        let built = recipe("Soup").img(PNG.to_vec()).build().expect("the recipe should build");
+        assert_eq!(built.img_format(), Some(ImageFormat::Png));
+        assert_eq!(built.primary_image().map(RecipeImage::content_type), Some("image/png"));
+        assert_eq!(recipe("Soup").build().expect("the recipe should build").img_format(), None);
+    }
+
+    #[test]
+    fn validation_rejects_unrecognized_images_but_not_missing_ones() {
+        let water = ingredient("water", 1.0, Unit::Liter);
+        let garbage = recipe("Soup").ingredient(water.clone()).img(b"garbage".to_vec()).build().expect("should build");
+        assert_eq!(
+            garbage.validate().expect_err("the image is not recognized").errors(),
+            [ValidationError::UnrecognizedImage { field: "images" }]
+        );
+        assert_eq!(recipe("Soup").ingredient(water).build().expect("should build").validate(), Ok(()));
+    }
+
+    #[test]
+    fn validation_rejects_images_over_the_size_limit() {
+        let mut huge = PNG.to_vec();
+        huge.resize(Recipe::MAX_IMG_BYTES + 1, 0);
+        let water = ingredient("water", 1.0, Unit::Liter);
+        let built = recipe("Soup").ingredient(water).img(huge).build().expect("the recipe should build");
+        assert_eq!(
+            built.validate().expect_err("the image is too large").errors(),
+            [ValidationError::ImageTooLarge {
+                field: "images",
+                max: Recipe::MAX_IMG_BYTES,
+                actual: Recipe::MAX_IMG_BYTES + 1,
+            }]
+        );
+    }
+
+    #[test]
+    fn set_img_records_the_format_and_an_empty_buffer_removes_the_image() {
+        let mut built = recipe("Soup").build().expect("the recipe should build");
+        assert!(!built.has_img());
+        assert_eq!(built.set_img(JPEG.to_vec()), Some(ImageFormat::Jpeg));
+        assert!(built.has_img());
+        assert_eq!((built.img(), built.img_format()), (&JPEG[..], Some(ImageFormat::Jpeg)));
+        assert_eq!(built.set_img(Vec::new()), None);
+        assert!(!built.has_img());
+        assert!(built.img().is_empty());
+    }
+
+    fn primaries(recipe: &Recipe) -> Vec<Uuid> {
+        recipe.images().iter().filter(|image| image.is_primary()).map(RecipeImage::id).collect()
+    }
+
+    #[test]
+    fn at_most_one_of_several_images_is_primary() {
+        let first = RecipeImage::new(PNG.to_vec()).primary();
+        let second = RecipeImage::new(JPEG.to_vec());
+        let third = RecipeImage::new(PNG.to_vec());
+        let mut built = recipe("Soup").image(first).image(second.clone()).build().expect("the recipe should build");
+        built.add_image(third.clone().primary());
+        assert_eq!(built.images().len(), 3);
+        assert_eq!(primaries(&built), [third.id()]);
+        assert_eq!(built.img(), &PNG[..]);
+
+        assert!(built.set_primary_image(&second.id()));
+        assert_eq!(primaries(&built), [second.id()]);
+        assert_eq!(built.img(), &JPEG[..]);
+        assert!(!built.set_primary_image(&Uuid::now_v7()));
+        assert_eq!(primaries(&built), [second.id()]);
+
+        assert_eq!(built.remove_image(&second.id()).map(|image| image.id()), Some(second.id()));
+        assert!(primaries(&built).is_empty());
+        assert!(built.img().is_empty());
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn legacy_payloads_with_a_single_img_deserialize_as_the_primary_image() {
+        let mut json = serde_json::to_value(recipe("Soup").build().expect("the recipe should build"))
+            .expect("the recipe should serialize");
+        let fields = json.as_object_mut().expect("a recipe serializes to an object");
+        fields.remove("images");
+        fields.insert("img".to_string(), crate::models::img_base64::encode(&PNG).into());
+        let legacy: Recipe = serde_json::from_value(json.clone()).expect("the recipe should deserialize");
+        assert_eq!(legacy.images().len(), 1);
+        assert_eq!((legacy.img(), legacy.img_format()), (&PNG[..], Some(ImageFormat::Png)));
+
+        json["img"] = "".into();
+        let empty: Recipe = serde_json::from_value(json.clone()).expect("the recipe should deserialize");
+        assert!(!empty.has_img());
+        json["img"] = "not base64!".into();
+        assert!(serde_json::from_value::<Recipe>(json).is_err());
+    }
+}
@@ -0,0 +1,300 @@
+use std::error::Error;
+use std::fmt;
+use uuid::Uuid;
+use super::quantity::parse_amount;
+use super::{Ingredient, ParseQuantityError, Quantity, Unit};
+
+/// Units that count items rather than measure them, kept as `Unit::Other` as written, e.g. "2 cloves garlic".
+const COUNT_UNITS: &[&str] = &[
+    "bunch", "can", "clove", "dash", "handful", "head", "package", "slice", "sprig", "stalk", "stick",
+];
+
+/// Unicode vulgar fractions and the fractions they are read as.
+const VULGAR_FRACTIONS: &[(char, &str)] = &[
+    ('½', "1/2"),
+    ('⅓', "1/3"),
+    ('⅔', "2/3"),
+    ('¼', "1/4"),
+    ('¾', "3/4"),
+    ('⅕', "1/5"),
+    ('⅖', "2/5"),
+    ('⅗', "3/5"),
+    ('⅘', "4/5"),
+    ('⅙', "1/6"),
+    ('⅚', "5/6"),
+    ('⅛', "1/8"),
+    ('⅜', "3/8"),
+    ('⅝', "5/8"),
+    ('⅞', "7/8"),
+];
+
+impl Ingredient {
+    /// Parses an ingredient from a line of free text such as `"2 1/2 cups all-purpose flour, sifted"`, giving it
+    /// a new id.
+    ///
+    /// The line is read as an amount, a unit, the name and a preparation note after the first comma, which
+    /// is stored in `note`. The amount may be a whole number, a decimal, a fraction, a unicode fraction such
+    /// as `½` or a mixed number, and a range such as `"2-3"` or `"2 to 3"` is read as its upper bound. The
+    /// unit may be any name or abbreviation `Unit` recognizes, singular or plural, or a counting word such as
+    /// "cloves", and a parenthetical right after the amount or unit, as in `"1 cup (240 ml) milk"`, is added
+    /// to the note. A line with an amount but no unit is counted in `Unit::Piece`, and a line with neither,
+    /// such as `"salt to taste"`, is to taste.
+    pub fn parse(line: &str) -> Result<Ingredient, IngredientParseError> {
+        let line = line.trim().trim_start_matches(['-', '*', '•']).trim();
+        if line.is_empty() {
+            return Err(IngredientParseError::Empty);
+        }
+        let normalized = normalize(line);
+        let (head, mut notes) = match normalized.split_once(',') {
+            Some((head, note)) => (head, vec![note.trim().to_string()]),
+            None => (normalized.as_str(), Vec::new()),
+        };
+
+        let mut words: Vec<&str> = head.split_whitespace().collect();
+        let mut to_taste = false;
+        if notes.first().is_some_and(|note| note.eq_ignore_ascii_case("to taste")) {
+            to_taste = true;
+        }
+        if words.len() >= 2 && words[words.len() - 2..].join(" ").eq_ignore_ascii_case("to taste") {
+            words.truncate(words.len() - 2);
+            to_taste = true;
+        }
+        let tokens = split_ranges(&words);
+
+        let mut i = 0;
+        let (mut amount, mut attached_unit) = read_number(&tokens, &mut i)?;
+        if amount.is_some() && attached_unit.is_none() && matches!(tokens.get(i).copied(), Some("-" | "to")) {
+            let mut j = i + 1;
+            if let (Some(upper), unit) = read_number(&tokens, &mut j)? {
+                amount = Some(upper);
+                attached_unit = unit;
+                i = j;
+            }
+        }
+        if amount.is_none()
+            && tokens.get(i).is_some_and(|word| word.eq_ignore_ascii_case("a") || word.eq_ignore_ascii_case("an"))
+            && tokens.get(i + 1).is_some_and(|word| known_unit(word).is_some())
+        {
+            amount = Some(1.0);
+            i += 1;
+        }
+        let mut asides = take_aside(&tokens, &mut i);
+
+        let unit = match attached_unit {
+            Some(suffix) => Some(known_unit(suffix).unwrap_or_else(|| Unit::Other(suffix.to_string()))),
+            None => read_unit(&tokens, &mut i),
+        };
+        if unit.is_some() && tokens.get(i).is_some_and(|word| word.eq_ignore_ascii_case("of")) {
+            i += 1;
+        }
+        asides.extend(take_aside(&tokens, &mut i));
+
+        let name = tokens[i..].join(" ");
+        if name.is_empty() {
+            return Err(IngredientParseError::MissingName(line.to_string()));
+        }
+        let quantity = match (amount, unit) {
+            (Some(amount), Some(unit)) => Quantity::new(amount, unit),
+            (Some(amount), None) => Quantity::new(amount, Unit::Piece),
+            (None, Some(unit)) => Quantity::new(1.0, unit),
+            (None, None) => Quantity::to_taste(),
+        };
+        if to_taste && quantity.unit() == &Unit::ToTaste {
+            notes.retain(|note| !note.eq_ignore_ascii_case("to taste"));
+        }
+        asides.append(&mut notes);
+        asides.retain(|note| !note.is_empty());
+        let ingredient = Ingredient::new(Uuid::now_v7(), name, quantity);
+        Ok(if asides.is_empty() { ingredient } else { ingredient.with_note(asides.join(", ")) })
+    }
+
+    /// Parses every non-empty line of `text` with `Ingredient::parse`.
+    pub fn parse_list(text: &str) -> Vec<Result<Ingredient, IngredientParseError>> {
+        text.lines().filter(|line| !line.trim().is_empty()).map(Ingredient::parse).collect()
+    }
+}
+
+/// Replaces unicode fractions with plain ones, so `"1½"` reads as `"1 1/2"`, and dashes with hyphens.
+fn normalize(line: &str) -> String {
+    let mut normalized = String::with_capacity(line.len());
+    for c in line.chars() {
+        match VULGAR_FRACTIONS.iter().find(|(vulgar, _)| *vulgar == c) {
+            Some((_, fraction)) => {
+                normalized.push(' ');
+                normalized.push_str(fraction);
+                normalized.push(' ');
+            }
+            None if c == '–' || c == '—' => normalized.push('-'),
+            None => normalized.push(c),
+        }
+    }
+    normalized
+}
+
+/// Splits words such as `"2-3"` or `"2-3g"` into `"2"`, `"-"`, `"3g"`, leaving words such as `"all-purpose"` alone.
+fn split_ranges<'a>(words: &[&'a str]) -> Vec<&'a str> {
+    let mut tokens = Vec::with_capacity(words.len());
+    for word in words {
+        match word.split_once('-') {
+            Some((lower, upper))
+                if lower.starts_with(|c: char| c.is_ascii_digit())
+                    && upper.starts_with(|c: char| c.is_ascii_digit()) =>
+            {
+                tokens.extend([lower, "-", upper]);
+            }
+            _ => tokens.push(word),
+        }
+    }
+    tokens
+}
+
+/// Reads a whole, decimal, fractional or mixed number starting at `tokens[i]`, also returning any unit
+/// written right after it, as in `"200g"`.
+fn read_number<'a>(tokens: &[&'a str], i: &mut usize) -> Result<(Option<f64>, Option<&'a str>), IngredientParseError> {
+    let mut amount = None;
+    while let Some(token) = tokens.get(*i) {
+        let split = token.find(|c: char| !(c.is_ascii_digit() || c == '.' || c == '/')).unwrap_or(token.len());
+        if split == 0 {
+            break;
+        }
+        let value = parse_amount(&token[..split]).map_err(IngredientParseError::from)?;
+        amount = Some(amount.unwrap_or(0.0) + value);
+        *i += 1;
+        if split < token.len() {
+            return Ok((amount, Some(&token[split..])));
+        }
+    }
+    Ok((amount, None))
+}
+
+/// Reads a unit starting at `tokens[i]`, including two-word units such as "fl oz".
+fn read_unit(tokens: &[&str], i: &mut usize) -> Option<Unit> {
+    if let (Some(first), Some(second)) = (tokens.get(*i), tokens.get(*i + 1)) {
+        let unit = format!("{} {}", first, second).parse::<Unit>().unwrap_or_else(|e| match e {});
+        if unit == Unit::FluidOunce {
+            *i += 2;
+            return Some(unit);
+        }
+    }
+    let unit = known_unit(tokens.get(*i)?)?;
+    *i += 1;
+    Some(unit)
+}
+
+/// The unit named by `word`, if it is one `Unit` recognizes or a counting word.
+fn known_unit(word: &str) -> Option<Unit> {
+    match word.parse::<Unit>().unwrap_or_else(|e| match e {}) {
+        Unit::Other(_) => {
+            let word = word.trim_end_matches('.').to_lowercase();
+            let singular = [word.as_str(), word.trim_end_matches('s'), word.trim_end_matches("es")];
+            singular.iter().any(|word| COUNT_UNITS.contains(word)).then_some(Unit::Other(word))
+        }
+        unit => Some(unit),
+    }
+}
+
+/// Takes a parenthetical such as `"(240 ml)"` starting at `tokens[i]`, returning its text without the parentheses.
+fn take_aside(tokens: &[&str], i: &mut usize) -> Vec<String> {
+    if !tokens.get(*i).is_some_and(|token| token.starts_with('(')) {
+        return Vec::new();
+    }
+    let Some(end) = tokens[*i..].iter().position(|token| token.ends_with(')')) else {
+        return Vec::new();
+    };
+    let aside = tokens[*i..=*i + end].join(" ");
+    *i += end + 1;
+    vec![aside.trim_start_matches('(').trim_end_matches(')').trim().to_string()]
+}
+
+/// The error returned when a line of text cannot be parsed into an `Ingredient`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum IngredientParseError {
+    /// The line was empty
+    Empty,
+    /// The line has an amount or unit but no name, holding the line
+    MissingName(String),
+    /// The amount could not be read as a number or fraction
+    InvalidAmount(String),
+}
+
+impl fmt::Display for IngredientParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            IngredientParseError::Empty => write!(f, "cannot parse ingredient from empty text"),
+            IngredientParseError::MissingName(line) => write!(f, "no ingredient name in \"{}\"", line),
+            IngredientParseError::InvalidAmount(amount) => write!(f, "invalid ingredient amount \"{}\"", amount),
+        }
+    }
+}
+
+impl Error for IngredientParseError {}
+
+impl From<ParseQuantityError> for IngredientParseError {
+    fn from(err: ParseQuantityError) -> Self {
+        match err {
+            ParseQuantityError::Empty => IngredientParseError::Empty,
+            ParseQuantityError::InvalidAmount(amount) => IngredientParseError::InvalidAmount(amount),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn other(unit: &str) -> Unit {
+        Unit::Other(unit.to_string())
+    }
+
+    #[test]
+    fn parses_a_corpus_of_real_world_lines() {
+        let corpus: Vec<(&str, f64, Unit, &str, Option<&str>)> = vec![
+            ("2 1/2 cups all-purpose flour, sifted", 2.5, Unit::Cup, "all-purpose flour", Some("sifted")),
+            ("1 tbsp olive oil", 1.0, Unit::Tablespoon, "olive oil", None),
+            ("3 tablespoons unsalted butter, melted", 3.0, Unit::Tablespoon, "unsalted butter", Some("melted")),
+            ("½ tsp baking soda", 0.5, Unit::Teaspoon, "baking soda", None),
+            ("1½ cups whole milk", 1.5, Unit::Cup, "whole milk", None),
+            ("¾ cup sugar", 0.75, Unit::Cup, "sugar", None),
+            ("2-3 cloves garlic, minced", 3.0, other("cloves"), "garlic", Some("minced")),
+            ("2 to 3 tomatoes, diced", 3.0, Unit::Piece, "tomatoes", Some("diced")),
+            ("200g dark chocolate, chopped", 200.0, Unit::Gram, "dark chocolate", Some("chopped")),
+            ("1 kg potatoes", 1.0, Unit::Kilogram, "potatoes", None),
+            ("500 ml chicken stock", 500.0, Unit::Milliliter, "chicken stock", None),
+            ("1 cup (240 ml) buttermilk", 1.0, Unit::Cup, "buttermilk", Some("240 ml")),
+            ("4 fl oz heavy cream", 4.0, Unit::FluidOunce, "heavy cream", None),
+            ("1 lb ground beef", 1.0, Unit::Pound, "ground beef", None),
+            ("8 oz cream cheese, softened", 8.0, Unit::Ounce, "cream cheese", Some("softened")),
+            ("2 large eggs", 2.0, Unit::Piece, "large eggs", None),
+            ("3 eggs, beaten", 3.0, Unit::Piece, "eggs", Some("beaten")),
+            ("a pinch of nutmeg", 1.0, Unit::Pinch, "nutmeg", None),
+            ("salt to taste", 0.0, Unit::ToTaste, "salt", None),
+            ("freshly ground black pepper, to taste", 0.0, Unit::ToTaste, "freshly ground black pepper", None),
+            ("1 can chickpeas, drained and rinsed", 1.0, other("can"), "chickpeas", Some("drained and rinsed")),
+            ("2 sprigs fresh thyme", 2.0, other("sprigs"), "fresh thyme", None),
+            ("0.5 l water", 0.5, Unit::Liter, "water", None),
+            ("- 1 onion, finely chopped", 1.0, Unit::Piece, "onion", Some("finely chopped")),
+        ];
+        assert!(corpus.len() >= 20);
+        for (line, amount, unit, name, note) in corpus {
+            let ingredient = Ingredient::parse(line).unwrap_or_else(|err| panic!("{:?} should parse: {}", line, err));
+            let quantity = ingredient.quantity();
+            assert!((quantity.amount() - amount).abs() < 1e-9, "{:?} has amount {}", line, quantity.amount());
+            assert_eq!((quantity.unit(), ingredient.name(), ingredient.note()), (&unit, name, note), "{:?}", line);
+        }
+    }
+
+    #[test]
+    fn rejects_lines_without_a_name_or_with_a_bad_amount() {
+        assert_eq!(Ingredient::parse("   "), Err(IngredientParseError::Empty));
+        assert_eq!(Ingredient::parse("2 cups"), Err(IngredientParseError::MissingName("2 cups".to_string())));
+        assert!(matches!(Ingredient::parse("1/0 cup milk"), Err(IngredientParseError::InvalidAmount(_))));
+    }
+
+    #[test]
+    fn parse_list_skips_blank_lines() {
+        let parsed = Ingredient::parse_list("1 cup rice\n\n  \n2 cups water\ncups\n");
+        assert_eq!(parsed.len(), 3);
+        assert_eq!(parsed[1].as_ref().map(Ingredient::name), Ok("water"));
+        assert!(parsed[2].is_err());
+    }
+}
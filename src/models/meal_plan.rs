@@ -0,0 +1,285 @@
+use std::collections::BTreeMap;
+use std::error::Error;
+use std::fmt;
+use std::str::FromStr;
+use chrono::NaiveDate;
+use uuid::Uuid;
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+use super::{Cookbook, Recipe, ShoppingList};
+
+/// The meal of the day a planned recipe is for, ordered through the day.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "lowercase"))]
+pub enum MealSlot {
+    Breakfast,
+    Lunch,
+    Dinner,
+    Snack,
+}
+
+impl FromStr for MealSlot {
+    type Err = ParseMealSlotError;
+
+    /// Parses a meal slot from its name, ignoring case.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "breakfast" => Ok(MealSlot::Breakfast),
+            "lunch" => Ok(MealSlot::Lunch),
+            "dinner" => Ok(MealSlot::Dinner),
+            "snack" => Ok(MealSlot::Snack),
+            _ => Err(ParseMealSlotError(s.to_string())),
+        }
+    }
+}
+
+impl fmt::Display for MealSlot {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let s = match self {
+            MealSlot::Breakfast => "Breakfast",
+            MealSlot::Lunch => "Lunch",
+            MealSlot::Dinner => "Dinner",
+            MealSlot::Snack => "Snack",
+        };
+        write!(f, "{}", s)
+    }
+}
+
+/// The error returned when text cannot be parsed into a `MealSlot`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseMealSlotError(pub String);
+
+impl fmt::Display for ParseMealSlotError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "invalid meal slot \"{}\"", self.0)
+    }
+}
+
+impl Error for ParseMealSlotError {}
+
+/// A recipe planned for a meal.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct MealPlanEntry {
+    /// The meal the recipe is for
+    pub slot: MealSlot,
+    /// The id of the planned recipe
+    pub recipe_id: Uuid,
+    /// How many servings to make, `None` for as many as the recipe makes
+    #[cfg_attr(feature = "serde", serde(default, skip_serializing_if = "Option::is_none"))]
+    pub servings: Option<u16>,
+}
+
+/// A planned entry whose recipe is not in the `Cookbook`, e.g. because it was deleted, reported by
+/// `MealPlan::missing_recipes`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MissingRecipe {
+    pub date: NaiveDate,
+    pub slot: MealSlot,
+    pub recipe_id: Uuid,
+}
+
+/// Recipes planned for the meals of a range of days. Serializes with the days as ISO 8601 dates, e.g.
+/// `{"id": ..., "name": ..., "days": {"2024-03-01": [...]}}`.
+///
+/// The plan only holds recipe ids, which are resolved against a `Cookbook`. Entries whose recipe is not in
+/// the cookbook are skipped by `shopping_list` and `total_time`, use `missing_recipes` to find them.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct MealPlan {
+    /// The id of the plan
+    id: Uuid,
+    /// The name of the plan, e.g. "Week 12"
+    name: String,
+    /// The planned recipes of each day, sorted by slot
+    #[cfg_attr(feature = "serde", serde(default))]
+    days: BTreeMap<NaiveDate, Vec<MealPlanEntry>>,
+}
+
+impl MealPlan {
+    /// An empty plan with a new id.
+    pub fn new(name: impl Into<String>) -> Self {
+        Self { id: Uuid::now_v7(), name: name.into(), days: BTreeMap::new() }
+    }
+
+    pub fn id(&self) -> Uuid {
+        self.id
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn set_name(&mut self, name: impl Into<String>) {
+        self.name = name.into();
+    }
+
+    /// Plans `recipe_id` for `slot` on `date`, after anything already planned for that slot. `servings`
+    /// overrides how many servings to make, `None` keeps the recipe's own servings.
+    pub fn add(&mut self, date: NaiveDate, slot: MealSlot, recipe_id: Uuid, servings: Option<u16>) {
+        let entries = self.days.entry(date).or_default();
+        let index = entries.partition_point(|entry| entry.slot <= slot);
+        entries.insert(index, MealPlanEntry { slot, recipe_id, servings });
+    }
+
+    /// Removes `recipe_id` from `slot` on `date`, returning the removed entry if it was planned.
+    pub fn remove(&mut self, date: NaiveDate, slot: MealSlot, recipe_id: &Uuid) -> Option<MealPlanEntry> {
+        let entries = self.days.get_mut(&date)?;
+        let index = entries.iter().position(|entry| entry.slot == slot && entry.recipe_id == *recipe_id)?;
+        let removed = entries.remove(index);
+        if entries.is_empty() {
+            self.days.remove(&date);
+        }
+        Some(removed)
+    }
+
+    /// Removes everything planned for `date`, returning the removed entries.
+    pub fn clear_day(&mut self, date: NaiveDate) -> Vec<MealPlanEntry> {
+        self.days.remove(&date).unwrap_or_default()
+    }
+
+    /// The entries planned for `date`, sorted by slot.
+    pub fn entries(&self, date: NaiveDate) -> &[MealPlanEntry] {
+        self.days.get(&date).map_or(&[], Vec::as_slice)
+    }
+
+    /// The days with anything planned, in order.
+    pub fn dates(&self) -> impl Iterator<Item = NaiveDate> + '_ {
+        self.days.keys().copied()
+    }
+
+    /// Every entry with its date, in order.
+    pub fn iter(&self) -> impl Iterator<Item = (NaiveDate, &MealPlanEntry)> {
+        self.days.iter().flat_map(|(date, entries)| entries.iter().map(move |entry| (*date, entry)))
+    }
+
+    /// The entries whose recipe is not in `cookbook`.
+    pub fn missing_recipes(&self, cookbook: &Cookbook) -> Vec<MissingRecipe> {
+        self.iter()
+            .filter(|(_, entry)| !cookbook.contains(&entry.recipe_id))
+            .map(|(date, entry)| MissingRecipe { date, slot: entry.slot, recipe_id: entry.recipe_id })
+            .collect()
+    }
+
+    /// The combined shopping list for every planned recipe found in `cookbook`. Entries with a servings
+    /// override are scaled to it, and entries overridden to zero servings are left out.
+    pub fn shopping_list(&self, cookbook: &Cookbook) -> ShoppingList {
+        let recipes: Vec<(&Recipe, f64)> = self
+            .iter()
+            .filter(|(_, entry)| entry.servings != Some(0))
+            .filter_map(|(_, entry)| {
+                let recipe = cookbook.get(&entry.recipe_id)?;
+                let factor = match entry.servings {
+                    Some(servings) if recipe.servings() > 0 => f64::from(servings) / f64::from(recipe.servings()),
+                    _ => 1.0,
+                };
+                Some((recipe, factor))
+            })
+            .collect();
+        ShoppingList::from_scaled(&recipes).expect("servings factors are positive")
+    }
+
+    /// The prep and cook time in minutes of every recipe planned for `date` and found in `cookbook`, resting
+    /// time is not counted.
+    pub fn total_time(&self, date: NaiveDate, cookbook: &Cookbook) -> u32 {
+        self.entries(date)
+            .iter()
+            .filter_map(|entry| cookbook.get(&entry.recipe_id))
+            .map(|recipe| u32::from(recipe.prep_minutes()) + u32::from(recipe.cook_minutes()))
+            .sum()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::testing::{ingredient, recipe};
+    use crate::models::{ShoppingItem, Unit};
+
+    fn day(d: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(2024, 3, d).expect("the date should be valid")
+    }
+
+    fn cookbook() -> (Cookbook, Recipe, Recipe) {
+        let porridge = recipe("Porridge")
+            .prep_time(5)
+            .cook_time(10)
+            .servings(2)
+            .ingredient(ingredient("oats", 100.0, Unit::Gram))
+            .ingredient(ingredient("milk", 400.0, Unit::Milliliter))
+            .build()
+            .expect("the recipe should build");
+        let stew = recipe("Stew")
+            .prep_time(20)
+            .cook_time(90)
+            .rest_time(15)
+            .servings(4)
+            .ingredient(ingredient("beef", 800.0, Unit::Gram))
+            .ingredient(ingredient("milk", 100.0, Unit::Milliliter))
+            .build()
+            .expect("the recipe should build");
+        let cookbook = [porridge.clone(), stew.clone()].into_iter().collect();
+        (cookbook, porridge, stew)
+    }
+
+    #[test]
+    fn entries_are_kept_by_day_and_slot() {
+        let (porridge, stew) = (Uuid::now_v7(), Uuid::now_v7());
+        let mut plan = MealPlan::new("Week 10");
+        plan.add(day(2), MealSlot::Dinner, stew, None);
+        plan.add(day(1), MealSlot::Snack, porridge, None);
+        plan.add(day(1), MealSlot::Breakfast, porridge, Some(1));
+        plan.add(day(1), MealSlot::Dinner, stew, None);
+        assert_eq!(plan.dates().collect::<Vec<_>>(), [day(1), day(2)]);
+        let slots: Vec<MealSlot> = plan.entries(day(1)).iter().map(|entry| entry.slot).collect();
+        assert_eq!(slots, [MealSlot::Breakfast, MealSlot::Dinner, MealSlot::Snack]);
+        assert_eq!(plan.entries(day(1))[0].servings, Some(1));
+
+        let removed = plan.remove(day(2), MealSlot::Dinner, &stew).expect("the stew is planned");
+        assert_eq!(removed.recipe_id, stew);
+        assert_eq!(plan.dates().count(), 1);
+        assert_eq!(plan.clear_day(day(1)).len(), 3);
+        assert!(plan.entries(day(1)).is_empty());
+        assert_eq!(" Dinner ".parse(), Ok(MealSlot::Dinner));
+        assert!("brunch".parse::<MealSlot>().is_err());
+    }
+
+    #[test]
+    fn the_shopping_list_scales_to_the_planned_servings_and_skips_missing_recipes() {
+        let (cookbook, porridge, stew) = cookbook();
+        let deleted = Uuid::now_v7();
+        let mut plan = MealPlan::new("Week 10");
+        plan.add(day(1), MealSlot::Breakfast, porridge.id(), Some(1));
+        plan.add(day(1), MealSlot::Dinner, stew.id(), None);
+        plan.add(day(2), MealSlot::Breakfast, porridge.id(), Some(4));
+        plan.add(day(2), MealSlot::Lunch, deleted, None);
+        plan.add(day(2), MealSlot::Dinner, stew.id(), Some(0));
+        let list = plan.shopping_list(&cookbook);
+        let listed: Vec<String> = list.items().iter().map(ShoppingItem::to_string).collect();
+        assert_eq!(listed, ["800 g beef", "1100 ml milk", "250 g oats"]);
+        let missing = MissingRecipe { date: day(2), slot: MealSlot::Lunch, recipe_id: deleted };
+        assert_eq!(plan.missing_recipes(&cookbook), [missing]);
+    }
+
+    #[test]
+    fn the_total_time_of_a_day_sums_prep_and_cook_time() {
+        let (cookbook, porridge, stew) = cookbook();
+        let mut plan = MealPlan::new("Week 10");
+        plan.add(day(1), MealSlot::Breakfast, porridge.id(), None);
+        plan.add(day(1), MealSlot::Dinner, stew.id(), None);
+        plan.add(day(1), MealSlot::Snack, Uuid::now_v7(), None);
+        assert_eq!(plan.total_time(day(1), &cookbook), 125);
+        assert_eq!(plan.total_time(day(2), &cookbook), 0);
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn days_serialize_as_iso_dates() {
+        let mut plan = MealPlan::new("Week 10");
+        plan.add(day(1), MealSlot::Dinner, Uuid::now_v7(), Some(2));
+        let json = serde_json::to_value(&plan).expect("the plan should serialize");
+        assert_eq!(json["days"]["2024-03-01"][0]["slot"], "dinner");
+        assert_eq!(serde_json::from_value::<MealPlan>(json).ok(), Some(plan));
+    }
+}
@@ -1,7 +1,98 @@
 use std::collections::HashSet;
+use std::error::Error;
+use std::fmt;
+use std::str::FromStr;
+use std::hash::{Hash, Hasher};
+use std::path::Path;
+use std::{fs, io};
+use chrono::{DateTime, Utc};
 use uuid::Uuid;
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+#[cfg(feature = "serde")]
+pub(crate) mod img_base64;
+mod allergen;
+mod category;
+mod collection;
+mod convert;
+mod cookbook;
+#[cfg(feature = "csv")]
+mod csv;
+mod cuisine;
+mod diet;
+mod diff;
+mod equipment;
+mod estimate;
+mod image;
+mod ingredient_parse;
+mod meal_plan;
+mod new_recipe;
+mod normalize;
+mod nutrition;
+mod quantity;
+mod rating;
+mod recipe_id;
+mod revision;
+#[cfg(feature = "sqlx")]
+mod row;
+mod shopping;
+mod similar;
+mod source;
+mod status;
+mod step;
+mod substitutions;
+#[cfg(test)]
+mod testing;
+mod text;
+#[cfg(feature = "image-processing")]
+mod processing;
+mod times;
+mod typed_builder;
+mod validation;
+
+pub use allergen::{Allergen, AllergenMatch};
+pub use category::{Category, ParseCategoryError};
+pub use collection::{Collection, CollectionBuildError, CollectionBuilder, ReorderError};
+pub use convert::{ConversionError, Rounding};
+pub use cookbook::{ConcurrencyError, Cookbook, CookbookView, DuplicateRecipe};
+#[cfg(feature = "serde")]
+pub use cookbook::LoadError;
+#[cfg(feature = "csv")]
+pub use self::csv::{export_csv, import_csv, CsvError, CSV_HEADER};
+pub use cuisine::{Course, Cuisine, ParseCourseError};
+pub use diet::{Diet, DietClassification, DietConflict, DietRules};
+pub use diff::{Change, RecipeDiff};
+pub use equipment::Equipment;
+pub use estimate::DifficultyModel;
+pub use image::{ImageData, ImageError, ImageFormat, RecipeImage};
+pub use ingredient_parse::IngredientParseError;
+pub use meal_plan::{MealPlan, MealPlanEntry, MealSlot, MissingRecipe, ParseMealSlotError};
+pub use new_recipe::{NewIngredient, NewRecipe};
+pub use normalize::{normalize_ingredient_name, normalize_ingredient_name_with, SINGULAR_EXCEPTIONS};
+pub use nutrition::Nutrition;
+pub use quantity::{ParseQuantityError, Quantity, Unit};
+pub use rating::{Rating, RatingSummary, Ratings, RecipeWithStats};
+pub use recipe_id::RecipeId;
+pub use revision::{RecipeHistory, RecipeRevision, RevisionError};
+pub use shopping::{shopping_list, ShoppingItem, ShoppingList};
+pub use similar::SimilarityWeights;
+pub use source::{Source, SourceUrlError};
+pub use status::{RecipeStatus, StatusError};
+pub use step::Step;
+pub use substitutions::{suggest_substitutes, Replacement, Substitution, SubstitutionRule, SubstitutionTable};
+pub use times::{CookingTimes, DurationMinutes, ParseDurationError};
+pub use typed_builder::{Missing, Set, TypedRecipeBuilder};
+pub use validation::{
+    validate_description, validate_duration, validate_name, validate_tag, ValidationError, ValidationErrors,
+};
 
 /// Represents a single recipe one would find in a cookbook.
+///
+/// Equality ignores the pictures, see the `PartialEq` impl and `Recipe::img_eq`. Recipes are not `Eq` or
+/// `Hash`, key collections of recipes by `RecipeId` instead.
+#[derive(Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct Recipe {
     /// The id of the recipe
     id: Uuid,
@@ -9,154 +100,2257 @@ pub struct Recipe {
     name: String,
     /// The difficulty rating of the recipe
     difficulty: Difficulty,
-    /// The estimated duration of the recipe in minutes
-    duration: u16,
+    /// How long the recipe takes to prepare, cook and rest
+    #[cfg_attr(feature = "serde", serde(flatten))]
+    times: CookingTimes,
+    /// The number of servings the recipe makes
+    servings: u16,
     /// The description of the recipe
     description: String,
+    /// The kind of dish the recipe makes. Defaults to a main course for legacy data
+    #[cfg_attr(feature = "serde", serde(default))]
+    category: Category,
     /// The ingredients needed for the recipe
     ingredients: HashSet<Ingredient>,
-    /// The directions to create the recipe
-    directions: String,
+    /// The steps to create the recipe, in order. Also accepts legacy free text directions when deserializing
+    #[cfg_attr(feature = "serde", serde(alias = "directions", deserialize_with = "step::deserialize"))]
+    steps: Vec<Step>,
     /// Optional tags that help describe the recipe
     tags: HashSet<RecipeTag>,
-    /// The picture of the recipe
-    img: Vec<u8>,
+    /// The pictures of the recipe, at most one of which is primary. Also accepts a legacy single base64
+    /// `img` when deserializing
+    #[cfg_attr(feature = "serde", serde(default, alias = "img", deserialize_with = "image::deserialize"))]
+    images: Vec<RecipeImage>,
+    /// Nutrition facts for the recipe, if known. Unknown nutrition serializes as `null` rather than as
+    /// zeros, so it can be told apart from a recipe with no calories
+    #[cfg_attr(feature = "serde", serde(default))]
+    nutrition: Option<Nutrition>,
+    /// The allergens declared for the recipe
+    #[cfg_attr(feature = "serde", serde(default))]
+    allergens: HashSet<Allergen>,
+    /// The special equipment the recipe needs, such as a stand mixer, beyond basic pots, pans and utensils
+    #[cfg_attr(feature = "serde", serde(default))]
+    equipment: HashSet<Equipment>,
+    /// The culinary tradition the recipe comes from, if known
+    #[cfg_attr(feature = "serde", serde(default))]
+    cuisine: Option<Cuisine>,
+    /// Where the recipe fits in a meal, if known
+    #[cfg_attr(feature = "serde", serde(default))]
+    course: Option<Course>,
+    /// When the recipe was created, serialized as RFC 3339. Defaults to the Unix epoch for legacy data
+    #[cfg_attr(feature = "serde", serde(default))]
+    created_at: DateTime<Utc>,
+    /// When the recipe was last modified, serialized as RFC 3339. Defaults to the Unix epoch for legacy data
+    #[cfg_attr(feature = "serde", serde(default))]
+    updated_at: DateTime<Utc>,
+    /// Where the recipe came from, if known. A source with nothing recorded is stored as `None`
+    #[cfg_attr(feature = "serde", serde(default, deserialize_with = "source::deserialize"))]
+    source: Option<Source>,
+    /// The id of the recipe this one was forked from, if any
+    #[cfg_attr(feature = "serde", serde(default))]
+    forked_from: Option<Uuid>,
+    /// How many times the recipe has been modified, starting at 1. Defaults to 1 for legacy data
+    #[cfg_attr(feature = "serde", serde(default = "first_version"))]
+    version: u32,
+    /// Where the recipe is in its lifecycle. Defaults to published for legacy data, which predates drafts
+    #[cfg_attr(feature = "serde", serde(default = "legacy_status"))]
+    status: RecipeStatus,
+}
+
+/// The version of a recipe that has not been modified yet.
+fn first_version() -> u32 {
+    1
+}
+
+/// The status of a recipe stored before recipes had one, which was visible to everyone.
+#[cfg(feature = "serde")]
+fn legacy_status() -> RecipeStatus {
+    RecipeStatus::Published
 }
 
 impl Recipe {
+    /// A draft recipe with the given fields, a main course without any optional fields set. Prefer
+    /// `Recipe::builder`, which names each field and checks the required ones are there.
+    #[allow(clippy::too_many_arguments)]
     pub fn new(
         id: Uuid,
         name: String,
         difficulty: Difficulty,
-        duration: u16,
+        times: CookingTimes,
+        servings: u16,
         description: String,
         ingredients: HashSet<Ingredient>,
-        directions: String,
+        mut steps: Vec<Step>,
         tags: HashSet<RecipeTag>,
         img: Vec<u8>
     ) -> Self {
-        Self { id, name, difficulty, duration, description, ingredients, directions, tags, img }
+        step::renumber(&mut steps);
+        let now = Utc::now();
+        Self {
+            id,
+            name,
+            difficulty,
+            times,
+            servings,
+            description,
+            category: Category::default(),
+            ingredients,
+            steps,
+            tags,
+            images: if img.is_empty() { Vec::new() } else { vec![RecipeImage::new(img).primary()] },
+            nutrition: None,
+            allergens: HashSet::new(),
+            equipment: HashSet::new(),
+            cuisine: None,
+            course: None,
+            created_at: now,
+            updated_at: now,
+            source: None,
+            forked_from: None,
+            version: first_version(),
+            status: RecipeStatus::Draft,
+        }
     }
+
     pub fn builder() -> RecipeBuilder {
         RecipeBuilder::new()
     }
+
+    pub fn id(&self) -> Uuid {
+        self.id
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn difficulty(&self) -> &Difficulty {
+        &self.difficulty
+    }
+
+    pub fn times(&self) -> &CookingTimes {
+        &self.times
+    }
+
+    pub fn prep_minutes(&self) -> u16 {
+        self.times.prep_minutes
+    }
+
+    pub fn cook_minutes(&self) -> u16 {
+        self.times.cook_minutes
+    }
+
+    pub fn rest_minutes(&self) -> u16 {
+        self.times.rest_minutes
+    }
+
+    /// The total time of the recipe in minutes, the saturating sum of its prep, cook and rest times.
+    pub fn duration(&self) -> u16 {
+        self.times.total()
+    }
+
+    /// The total time of the recipe in minutes, same as `duration`, named to set it apart from the split
+    /// prep, cook and rest times.
+    pub fn total_duration(&self) -> u16 {
+        self.times.total()
+    }
+
+    /// The total time formatted for display, e.g. `"45 min"`, `"1 hr"` or `"2 hr 15 min"`, see `DurationMinutes`.
+    pub fn duration_display(&self) -> String {
+        DurationMinutes(self.duration()).to_string()
+    }
+
+    pub fn servings(&self) -> u16 {
+        self.servings
+    }
+
+    pub fn description(&self) -> &str {
+        &self.description
+    }
+
+    pub fn ingredients(&self) -> &HashSet<Ingredient> {
+        &self.ingredients
+    }
+
+    /// The number of distinct ingredients. Ingredients are stored in a set keyed by id, so adding an
+    /// ingredient with the same id as an existing one does not increase the count.
+    pub fn ingredient_count(&self) -> usize {
+        self.ingredients.len()
+    }
+
+    /// Every unit the ingredients are measured in, e.g. for grouping a shopping list.
+    pub fn units_used(&self) -> HashSet<Unit> {
+        self.ingredients.iter().map(|ingredient| ingredient.quantity.unit().clone()).collect()
+    }
+
+    pub fn steps(&self) -> &[Step] {
+        &self.steps
+    }
+
+    /// The steps rendered as numbered lines of text, e.g. `"1. Preheat the oven\n2. Mix the flour and sugar"`.
+    pub fn directions_text(&self) -> String {
+        step::render(&self.steps)
+    }
+
+    pub fn tags(&self) -> &HashSet<RecipeTag> {
+        &self.tags
+    }
+
+    /// The tags in `namespace`, compared case-insensitively, sorted by their normalized form.
+    pub fn tags_in_namespace(&self, namespace: &str) -> Vec<&RecipeTag> {
+        let mut tags: Vec<&RecipeTag> = self.tags.iter().filter(|tag| tag.in_namespace(namespace)).collect();
+        tags.sort_by_key(|tag| tag.normalized());
+        tags
+    }
+
+    pub fn nutrition(&self) -> Option<&Nutrition> {
+        self.nutrition.as_ref()
+    }
+
+    pub fn allergens(&self) -> &HashSet<Allergen> {
+        &self.allergens
+    }
+
+    pub fn equipment(&self) -> &HashSet<Equipment> {
+        &self.equipment
+    }
+
+    /// Whether the recipe needs the equipment named `name`, compared the way `Equipment` compares names, so
+    /// `"oven"` does not match a `"dutch oven"`.
+    pub fn needs_equipment(&self, name: &str) -> bool {
+        self.equipment.contains(&Equipment::new(name))
+    }
+
+    pub fn cuisine(&self) -> Option<&Cuisine> {
+        self.cuisine.as_ref()
+    }
+
+    pub fn course(&self) -> Option<Course> {
+        self.course
+    }
+
+    pub fn category(&self) -> Category {
+        self.category
+    }
+
+    pub fn created_at(&self) -> DateTime<Utc> {
+        self.created_at
+    }
+
+    pub fn updated_at(&self) -> DateTime<Utc> {
+        self.updated_at
+    }
+
+    /// How many times the recipe has been modified, starting at 1 when it is built.
+    pub fn version(&self) -> u32 {
+        self.version
+    }
+
+    /// An HTTP entity tag for this version of the recipe, derived from its id and version only, e.g.
+    /// `"67e5504410b1426f9247bb680e5fe0c8-3"` with the quotes. Clients send it back in `If-Match` to update
+    /// the version they read, see `Cookbook::update`.
+    pub fn etag(&self) -> String {
+        format!("\"{}-{}\"", self.id.simple(), self.version)
+    }
+
+    pub fn source(&self) -> Option<&Source> {
+        self.source.as_ref()
+    }
+
+    pub fn forked_from(&self) -> Option<Uuid> {
+        self.forked_from
+    }
+
+    pub fn set_name(&mut self, name: impl Into<String>) {
+        self.name = name.into();
+        self.touch();
+    }
+
+    pub fn set_difficulty(&mut self, difficulty: Difficulty) {
+        self.difficulty = difficulty;
+        self.touch();
+    }
+
+    pub fn set_times(&mut self, times: CookingTimes) {
+        self.times = times;
+        self.touch();
+    }
+
+    pub fn set_prep_minutes(&mut self, prep_minutes: u16) {
+        self.times.prep_minutes = prep_minutes;
+        self.touch();
+    }
+
+    pub fn set_cook_minutes(&mut self, cook_minutes: u16) {
+        self.times.cook_minutes = cook_minutes;
+        self.touch();
+    }
+
+    pub fn set_rest_minutes(&mut self, rest_minutes: u16) {
+        self.times.rest_minutes = rest_minutes;
+        self.touch();
+    }
+
+    pub fn set_servings(&mut self, servings: u16) {
+        self.servings = servings;
+        self.touch();
+    }
+
+    pub fn set_description(&mut self, description: impl Into<String>) {
+        self.description = description.into();
+        self.touch();
+    }
+
+    /// Replaces the steps with ones parsed from free text directions, one step per non-empty line.
+    pub fn set_directions(&mut self, directions: &str) {
+        self.steps = step::parse(directions);
+        self.touch();
+    }
+
+    /// Inserts a step at `index`, shifting the steps after it down.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `index` is greater than the number of steps.
+    pub fn insert_step(&mut self, index: usize, step: Step) {
+        self.steps.insert(index, step);
+        step::renumber(&mut self.steps);
+        self.touch();
+    }
+
+    /// Removes the step at `index`, returning it if it exists.
+    pub fn remove_step(&mut self, index: usize) -> Option<Step> {
+        if index >= self.steps.len() {
+            return None;
+        }
+        let removed = self.steps.remove(index);
+        step::renumber(&mut self.steps);
+        self.touch();
+        Some(removed)
+    }
+
+    /// Moves the step at `from` to `to`, returning `false` if either index is out of bounds.
+    pub fn move_step(&mut self, from: usize, to: usize) -> bool {
+        if from >= self.steps.len() || to >= self.steps.len() {
+            return false;
+        }
+        let step = self.steps.remove(from);
+        self.steps.insert(to, step);
+        step::renumber(&mut self.steps);
+        self.touch();
+        true
+    }
+
+    /// Adds an ingredient to the recipe, returning `false` if an equal ingredient was already present, that
+    /// is one with the same id, or with the same name when both ids are nil. The existing one is kept.
+    pub fn add_ingredient(&mut self, ingredient: Ingredient) -> bool {
+        let added = self.ingredients.insert(ingredient);
+        if added {
+            self.touch();
+        }
+        added
+    }
+
+    /// Removes the ingredient with the given id, returning it if it was present.
+    pub fn remove_ingredient(&mut self, id: &Uuid) -> Option<Ingredient> {
+        let (removed, kept): (HashSet<Ingredient>, HashSet<Ingredient>) = std::mem::take(&mut self.ingredients)
+            .into_iter()
+            .partition(|ingredient| ingredient.id == *id);
+        self.ingredients = kept;
+        let removed = removed.into_iter().next();
+        if removed.is_some() {
+            self.touch();
+        }
+        removed
+    }
+
+    /// Adds a tag to the recipe, returning `false` if an equal tag was already present.
+    pub fn add_tag(&mut self, tag: RecipeTag) -> bool {
+        let added = self.tags.insert(tag);
+        if added {
+            self.touch();
+        }
+        added
+    }
+
+    /// Removes the tag matching `tag` case-insensitively, returning it if it was present.
+    pub fn remove_tag(&mut self, tag: &str) -> Option<RecipeTag> {
+        let removed = self.tags.take(&RecipeTag::lookup(tag));
+        if removed.is_some() {
+            self.touch();
+        }
+        removed
+    }
+
+    pub fn set_nutrition(&mut self, nutrition: Option<Nutrition>) {
+        self.nutrition = nutrition;
+        self.touch();
+    }
+
+    pub fn set_allergens(&mut self, allergens: HashSet<Allergen>) {
+        self.allergens = allergens;
+        self.touch();
+    }
+
+    pub fn set_equipment(&mut self, equipment: HashSet<Equipment>) {
+        self.equipment = equipment;
+        self.touch();
+    }
+
+    pub fn set_cuisine(&mut self, cuisine: Option<Cuisine>) {
+        self.cuisine = cuisine;
+        self.touch();
+    }
+
+    pub fn set_course(&mut self, course: Option<Course>) {
+        self.course = course;
+        self.touch();
+    }
+
+    pub fn set_category(&mut self, category: Category) {
+        self.category = category;
+        self.touch();
+    }
+
+    /// Sets where the recipe came from, a source with nothing recorded is stored as `None`.
+    pub fn set_source(&mut self, source: Option<Source>) {
+        self.source = source.filter(|source| !source.is_empty());
+        self.touch();
+    }
+
+    /// Records that the recipe was just modified and bumps its version, every mutator calls this.
+    fn touch(&mut self) {
+        self.updated_at = Utc::now();
+        self.version = self.version.saturating_add(1);
+    }
+
+    /// Stamps an update as a repository stores it: `created_at` is kept from the stored recipe and
+    /// `updated_at` is set to now, whatever the caller sent.
+    pub(crate) fn stamp_update(&mut self, created_at: DateTime<Utc>) {
+        self.created_at = created_at;
+        self.updated_at = Utc::now();
+    }
+
+    /// Returns a copy of the recipe with every ingredient quantity scaled from `servings` to `target_servings`.
+    /// Quantities without a numeric amount, such as "to taste", are left unchanged, as is every quantity
+    /// if the recipe has no servings recorded.
+    pub fn scale_to(&self, target_servings: u16) -> Recipe {
+        let factor = if self.servings == 0 {
+            1.0
+        } else {
+            f64::from(target_servings) / f64::from(self.servings)
+        };
+        let mut scaled = self.scale_ingredients(factor);
+        scaled.servings = target_servings;
+        scaled
+    }
+
+    /// Returns a copy of the recipe with every ingredient quantity multiplied by `factor`, keeping the same id.
+    /// Servings are scaled and rounded to the nearest whole serving, but never below one. Quantities that
+    /// are "to taste" or a pinch are left unchanged.
+    pub fn scale(&self, factor: f64) -> Result<Recipe, InvalidScaleFactor> {
+        if !factor.is_finite() || factor <= 0.0 {
+            return Err(InvalidScaleFactor(factor));
+        }
+        let mut scaled = self.scale_ingredients(factor);
+        scaled.servings = (f64::from(self.servings) * factor).round().clamp(1.0, f64::from(u16::MAX)) as u16;
+        Ok(scaled)
+    }
+
+    /// Like `scale`, but the scaled recipe is given a freshly generated id and fresh timestamps.
+    pub fn scale_into_new(&self, factor: f64) -> Result<Recipe, InvalidScaleFactor> {
+        let mut scaled = self.scale(factor)?;
+        scaled.id = Uuid::now_v7();
+        scaled.created_at = Utc::now();
+        scaled.updated_at = scaled.created_at;
+        scaled.version = first_version();
+        Ok(scaled)
+    }
+
+    fn scale_ingredients(&self, factor: f64) -> Recipe {
+        let mut scaled = self.clone();
+        scaled.ingredients = self.ingredients
+            .iter()
+            .map(|ingredient| Ingredient { quantity: ingredient.quantity.scale(factor), ..ingredient.clone() })
+            .collect();
+        scaled
+    }
+
+    /// A builder pre-populated with every field of the recipe, including its id, timestamps and version,
+    /// so it can be tweaked and rebuilt. The rebuilt recipe replaces this one, see `Cookbook::update`.
+    pub fn into_builder(self) -> RecipeBuilder {
+        RecipeBuilder {
+            id: Some(self.id),
+            name: Some(self.name),
+            difficulty: Some(self.difficulty),
+            estimate_difficulty: None,
+            prep_minutes: Some(self.times.prep_minutes),
+            cook_minutes: Some(self.times.cook_minutes),
+            duration_minutes: None,
+            rest_minutes: Some(self.times.rest_minutes),
+            servings: Some(self.servings),
+            description: Some(self.description),
+            category: Some(self.category),
+            ingredients: self.ingredients,
+            steps: self.steps,
+            tags: self.tags,
+            images: self.images,
+            nutrition: self.nutrition,
+            allergens: self.allergens,
+            equipment: self.equipment,
+            cuisine: self.cuisine,
+            course: self.course,
+            created_at: Some(self.created_at),
+            updated_at: Some(self.updated_at),
+            source: self.source,
+            forked_from: self.forked_from,
+            version: Some(self.version),
+            status: Some(self.status),
+        }
+    }
+
+    /// Like `into_builder`, but leaves the recipe as it is.
+    pub fn edit(&self) -> RecipeBuilder {
+        self.clone().into_builder()
+    }
+
+    /// A builder pre-populated with a copy of this recipe under a new id, recording this recipe in
+    /// `forked_from`. Ingredients are given new ids, and the timestamps are left unset so the fork is
+    /// stamped when it is built.
+    pub fn fork(&self) -> RecipeBuilder {
+        RecipeBuilder {
+            id: None,
+            name: Some(self.name.clone()),
+            difficulty: Some(self.difficulty),
+            estimate_difficulty: None,
+            prep_minutes: Some(self.times.prep_minutes),
+            cook_minutes: Some(self.times.cook_minutes),
+            duration_minutes: None,
+            rest_minutes: Some(self.times.rest_minutes),
+            servings: Some(self.servings),
+            description: Some(self.description.clone()),
+            category: Some(self.category),
+            ingredients: self.ingredients
+                .iter()
+                .map(|ingredient| Ingredient { id: Uuid::now_v7(), ..ingredient.clone() })
+                .collect(),
+            steps: self.steps.clone(),
+            tags: self.tags.clone(),
+            images: self.images.clone(),
+            nutrition: self.nutrition,
+            allergens: self.allergens.clone(),
+            equipment: self.equipment.clone(),
+            cuisine: self.cuisine.clone(),
+            course: self.course,
+            created_at: None,
+            updated_at: None,
+            source: self.source.clone(),
+            forked_from: Some(self.id),
+            version: None,
+            status: None,
+        }
+    }
+
+    /// Like `==`, but the pictures of the recipes must be equal as well.
+    pub fn img_eq(&self, other: &Recipe) -> bool {
+        self == other && self.images == other.images
+    }
+
+    /// Decomposes the recipe into its owned fields without cloning.
+    pub fn into_parts(self) -> RecipeParts {
+        RecipeParts {
+            id: self.id,
+            name: self.name,
+            difficulty: self.difficulty,
+            times: self.times,
+            servings: self.servings,
+            description: self.description,
+            category: self.category,
+            ingredients: self.ingredients,
+            steps: self.steps,
+            tags: self.tags,
+            images: self.images,
+            nutrition: self.nutrition,
+            allergens: self.allergens,
+            equipment: self.equipment,
+            cuisine: self.cuisine,
+            course: self.course,
+            created_at: self.created_at,
+            updated_at: self.updated_at,
+            source: self.source,
+            forked_from: self.forked_from,
+            version: self.version,
+            status: self.status,
+        }
+    }
+}
+
+impl fmt::Debug for Recipe {
+    /// Formats every field except the image, which is summarized by its length.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Recipe")
+            .field("id", &self.id)
+            .field("name", &self.name)
+            .field("difficulty", &self.difficulty)
+            .field("times", &self.times)
+            .field("servings", &self.servings)
+            .field("description", &self.description)
+            .field("category", &self.category)
+            .field("ingredients", &self.ingredients)
+            .field("steps", &self.steps)
+            .field("tags", &self.tags)
+            .field("images", &self.images)
+            .field("nutrition", &self.nutrition)
+            .field("allergens", &self.allergens)
+            .field("equipment", &self.equipment)
+            .field("cuisine", &self.cuisine)
+            .field("course", &self.course)
+            .field("created_at", &self.created_at)
+            .field("updated_at", &self.updated_at)
+            .field("source", &self.source)
+            .field("forked_from", &self.forked_from)
+            .field("version", &self.version)
+            .field("status", &self.status)
+            .finish()
+    }
+}
+
+/// Compares every field except the recipe's `images`, so two copies of a recipe whose pictures were re-encoded
+/// are still equal and `==` never hides a byte-by-byte comparison of large pictures. **Note:** this means `==`
+/// can hold between recipes with different pictures; use `Recipe::img_eq`, which compares the picture bytes in
+/// full, when the picture matters. Step pictures are still compared. The `created_at` and `updated_at`
+/// timestamps and the `version` are bookkeeping and are not compared either. As this compares content rather
+/// than identity, two versions of one recipe are not equal; compare `Recipe::recipe_id` for identity.
+impl PartialEq for Recipe {
+    fn eq(&self, other: &Self) -> bool {
+        self.id == other.id
+            && self.name == other.name
+            && self.difficulty == other.difficulty
+            && self.times == other.times
+            && self.servings == other.servings
+            && self.description == other.description
+            && self.category == other.category
+            && self.ingredients == other.ingredients
+            && self.steps == other.steps
+            && self.tags == other.tags
+            && self.nutrition == other.nutrition
+            && self.allergens == other.allergens
+            && self.equipment == other.equipment
+            && self.cuisine == other.cuisine
+            && self.course == other.course
+            && self.source == other.source
+            && self.forked_from == other.forked_from
+            && self.status == other.status
+    }
+}
+
+/// The error returned when scaling a recipe by a factor that is zero, negative or not finite.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct InvalidScaleFactor(pub f64);
+
+impl fmt::Display for InvalidScaleFactor {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "invalid scale factor {}, expected a positive number", self.0)
+    }
+}
+
+impl Error for InvalidScaleFactor {}
+
+/// The owned fields of a `Recipe`, obtained through `Recipe::into_parts`.
+pub struct RecipeParts {
+    pub id: Uuid,
+    pub name: String,
+    pub difficulty: Difficulty,
+    pub times: CookingTimes,
+    pub servings: u16,
+    pub description: String,
+    pub category: Category,
+    pub ingredients: HashSet<Ingredient>,
+    pub steps: Vec<Step>,
+    pub tags: HashSet<RecipeTag>,
+    pub images: Vec<RecipeImage>,
+    pub nutrition: Option<Nutrition>,
+    pub allergens: HashSet<Allergen>,
+    pub equipment: HashSet<Equipment>,
+    pub cuisine: Option<Cuisine>,
+    pub course: Option<Course>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+    pub source: Option<Source>,
+    pub forked_from: Option<Uuid>,
+    pub version: u32,
+    pub status: RecipeStatus,
 }
 
+/// A builder for `Recipe`, obtained through `Recipe::builder`.
+///
+/// The `name`, `difficulty`, `servings`, `description` and `category` fields, at least one of prep or cook time
+/// and at least one step are required, the rest time, ingredients, tags and the image are optional. The
+/// difficulty may be estimated with `difficulty_auto` instead of set. A fresh UUID v7 id is generated unless one
+/// is set, which is only needed when rehydrating an existing recipe. UUID v7 ids sort roughly by creation time,
+/// which keeps new rows close together in database indexes.
+///
+/// ```
+/// use recipe_api::models::{Category, Difficulty, Ingredient, Quantity, Recipe, RecipeTag, Unit};
+/// use uuid::Uuid;
+///
+/// let recipe = Recipe::builder()
+///     .name("Pancakes")
+///     .difficulty(Difficulty::Easy)
+///     .prep_time(10)
+///     .cook_time(15)
+///     .servings(4)
+///     .description("Fluffy weekend pancakes")
+///     .category(Category::Breakfast)
+///     .ingredient(Ingredient::new(Uuid::now_v7(), "flour", Quantity::new(1.5, Unit::Cup)))
+///     .ingredients([
+///         Ingredient::new(Uuid::now_v7(), "milk", Quantity::new(300.0, Unit::Milliliter)),
+///         Ingredient::new(Uuid::now_v7(), "egg", Quantity::new(2.0, Unit::Piece)),
+///     ])
+///     .directions("Whisk everything together\nFry ladlefuls until golden")
+///     .tags([RecipeTag::new("breakfast")?, RecipeTag::new("Sweet")?])
+///     .build()?;
+///
+/// assert_eq!(recipe.name(), "Pancakes");
+/// assert_eq!(recipe.duration(), 25);
+/// assert_eq!(recipe.ingredients().len(), 3);
+/// assert_eq!(recipe.directions_text(), "1. Whisk everything together\n2. Fry ladlefuls until golden");
+/// # Ok::<(), Box<dyn std::error::Error>>(())
+/// ```
 pub struct RecipeBuilder {
-    /// The id of the recipe, yet to be set
+    /// The id of the recipe, generated at build time if not set
     id: Option<Uuid>,
     /// The name of the recipe, yet to be set
     name: Option<String>,
     /// The difficulty rating of the recipe, yet to be set
     difficulty: Option<Difficulty>,
-    /// The estimated duration of the recipe in minutes, yet to be set
-    duration: Option<u16>,
+    /// The model to estimate the difficulty with instead, if set through `difficulty_auto`
+    estimate_difficulty: Option<DifficultyModel>,
+    /// The active preparation time in minutes, yet to be set
+    prep_minutes: Option<u16>,
+    /// The cooking time in minutes, yet to be set
+    cook_minutes: Option<u16>,
+    /// The single duration set through the deprecated `duration`, used as the cook time
+    duration_minutes: Option<u16>,
+    /// The resting time in minutes, yet to be set
+    rest_minutes: Option<u16>,
+    /// The number of servings the recipe makes, yet to be set
+    servings: Option<u16>,
     /// The description of the recipe, yet to be set
     description: Option<String>,
+    /// The kind of dish the recipe makes, yet to be set
+    category: Option<Category>,
     /// The ingredients needed for the recipe, yet to be set
     ingredients: HashSet<Ingredient>,
-    /// The directions to create the recipe, yet to be set
-    directions: Option<String>,
+    /// The steps to create the recipe, yet to be set
+    steps: Vec<Step>,
     /// Optional tags that help describe the recipe, yet to be set
     tags: HashSet<RecipeTag>,
-    /// The picture of the recipe, yet to be set
-    img: Option<Vec<u8>>,
+    /// The pictures of the recipe, yet to be set
+    images: Vec<RecipeImage>,
+    /// Nutrition facts for the recipe, yet to be set
+    nutrition: Option<Nutrition>,
+    /// The allergens declared for the recipe, yet to be set
+    allergens: HashSet<Allergen>,
+    /// The special equipment the recipe needs, yet to be set
+    equipment: HashSet<Equipment>,
+    /// The culinary tradition the recipe comes from, yet to be set
+    cuisine: Option<Cuisine>,
+    /// Where the recipe fits in a meal, yet to be set
+    course: Option<Course>,
+    /// When the recipe was created, defaults to now
+    created_at: Option<DateTime<Utc>>,
+    /// When the recipe was last modified, defaults to the creation time
+    updated_at: Option<DateTime<Utc>>,
+    /// Where the recipe came from, yet to be set
+    source: Option<Source>,
+    /// The id of the recipe this one was forked from, yet to be set
+    forked_from: Option<Uuid>,
+    /// The version of the recipe, 1 unless set
+    version: Option<u32>,
+    /// Where the recipe is in its lifecycle, a draft unless set
+    status: Option<RecipeStatus>,
 }
 
 impl RecipeBuilder {
-    fn new() -> Self {
+    pub(crate) fn new() -> Self {
         Self {
             id: None,
             name: None,
             difficulty: None,
-            duration: None,
+            estimate_difficulty: None,
+            prep_minutes: None,
+            cook_minutes: None,
+            duration_minutes: None,
+            rest_minutes: None,
+            servings: None,
             description: None,
+            category: None,
             ingredients: HashSet::new(),
-            directions: None,
+            steps: Vec::new(),
             tags: HashSet::new(),
-            img: None
+            images: Vec::new(),
+            nutrition: None,
+            allergens: HashSet::new(),
+            equipment: HashSet::new(),
+            cuisine: None,
+            course: None,
+            created_at: None,
+            updated_at: None,
+            source: None,
+            forked_from: None,
+            version: None,
+            status: None,
         }
     }
-    fn id(mut self, id: Uuid) -> Self {
+    pub fn id(mut self, id: Uuid) -> Self {
         self.id = Some(id);
         self
     }
 
-    fn name(mut self, name: String) -> Self {
-        self.name = Some(name);
+    pub fn name(mut self, name: impl Into<String>) -> Self {
+        self.name = Some(name.into());
         self
     }
 
-    fn difficulty(mut self, difficulty: Difficulty) -> Self {
+    pub fn difficulty(mut self, difficulty: Difficulty) -> Self {
         self.difficulty = Some(difficulty);
+        self.estimate_difficulty = None;
+        self
+    }
+
+    /// Estimates the difficulty from the rest of the recipe at build time with the default
+    /// `DifficultyModel`, instead of setting it.
+    pub fn difficulty_auto(self) -> Self {
+        self.difficulty_estimated(DifficultyModel::default())
+    }
+
+    /// Estimates the difficulty from the rest of the recipe at build time with `model`, instead of setting it.
+    pub fn difficulty_estimated(mut self, model: DifficultyModel) -> Self {
+        self.difficulty = None;
+        self.estimate_difficulty = Some(model);
+        self
+    }
+
+    /// Sets the cook time, recipes used to only record a single duration. Cannot be combined with
+    /// `prep_time` or `cook_time`, `build` fails with `RecipeBuildError::ConflictingDuration` if it is.
+    #[deprecated(note = "use `prep_time` and `cook_time` instead")]
+    pub fn duration(mut self, duration: u16) -> Self {
+        self.duration_minutes = Some(duration);
+        self
+    }
+
+    pub fn prep_time(mut self, prep_minutes: u16) -> Self {
+        self.prep_minutes = Some(prep_minutes);
+        self
+    }
+
+    pub fn cook_time(mut self, cook_minutes: u16) -> Self {
+        self.cook_minutes = Some(cook_minutes);
+        self
+    }
+
+    pub fn rest_time(mut self, rest_minutes: u16) -> Self {
+        self.rest_minutes = Some(rest_minutes);
+        self
+    }
+
+    pub fn servings(mut self, servings: u16) -> Self {
+        self.servings = Some(servings);
+        self
+    }
+
+    pub fn description(mut self, description: impl Into<String>) -> Self {
+        self.description = Some(description.into());
         self
     }
 
-    fn duration(mut self, duration: u16) -> Self {
-        self.duration = Some(duration);
+    /// Sets the steps from free text directions, one step per non-empty line, replacing any steps already added.
+    pub fn directions(mut self, directions: &str) -> Self {
+        self.steps = step::parse(directions);
         self
     }
 
-    fn description(mut self, description: String) -> Self {
-        self.description = Some(description);
+    pub fn step(mut self, step: impl Into<Step>) -> Self {
+        self.steps.push(step.into());
         self
     }
 
-    fn directions(mut self, directions: String) -> Self {
-        self.directions = Some(directions);
+    pub fn steps(mut self, steps: impl IntoIterator<Item = Step>) -> Self {
+        self.steps.extend(steps);
         self
     }
 
-    fn ingredient(mut self, ingredient: Ingredient) -> Self {
+    pub fn ingredient(mut self, ingredient: Ingredient) -> Self {
         self.ingredients.insert(ingredient);
         self
     }
 
-    fn tag(mut self, tag: RecipeTag) -> Self {
+    /// Adds every ingredient in `ingredients`, keeping the ones added before.
+    pub fn ingredients(mut self, ingredients: impl IntoIterator<Item = Ingredient>) -> Self {
+        self.ingredients.extend(ingredients);
+        self
+    }
+
+    pub fn tag(mut self, tag: RecipeTag) -> Self {
         self.tags.insert(tag);
         self
     }
 
-    fn img(mut self, img: Vec<u8>) -> Self {
-        self.img = Some(img);
+    /// Adds every tag in `tags`, keeping the ones added before.
+    pub fn tags(mut self, tags: impl IntoIterator<Item = RecipeTag>) -> Self {
+        self.tags.extend(tags);
         self
     }
 
-    fn build(mut self) -> Recipe {
-        Recipe {
-            id: self.id.take().expect("cannot build `Recipe` struct without id set"),
-            name: self.name.take().expect("cannot build `Recipe` struct without name set"),
-            difficulty: self.difficulty.take().expect("cannot build `Recipe` without difficulty set"),
-            duration: self.duration.take().expect("cannot build `Recipe` without duration set"),
-            description: self.description.take().expect("cannot build `Recipe` without description set"),
-            directions: self.directions.take().expect("cannot build `Recipe` without directions set"),
-            ingredients: self.ingredients,
-            tags: self.tags,
-            img: self.img.take().or(Some(Vec::new())).unwrap()
+    /// Adds `img` as the primary image, replacing any earlier primary image.
+    pub fn img(mut self, img: Vec<u8>) -> Self {
+        self.images.retain(|image| !image.is_primary());
+        if !img.is_empty() {
+            self.images.push(RecipeImage::new(img).primary());
         }
+        self
     }
-}
 
-/// Represents the difficulty of a recipe on a scale of 1 to 4.
-/// The `Easy` variant being the easiest kind of recipe to make and `Expert` variant being
-/// the most difficult kind of recipe to make.
-pub enum Difficulty {
-    Easy,
-    Medium,
-    Hard,
-    Expert,
-}
+    /// Like `img`, reading the picture from the file at `path`. Fails if the file cannot be read, or with
+    /// `io::ErrorKind::InvalidData` if it is not in a recognized image format, either way naming the path.
+    pub fn img_from_path(self, path: impl AsRef<Path>) -> Result<Self, io::Error> {
+        let path = path.as_ref();
+        let img = fs::read(path)
+            .map_err(|err| io::Error::new(err.kind(), format!("cannot read image {}: {}", path.display(), err)))?;
+        if ImageFormat::detect(&img).is_none() {
+            let message = format!("{} is not a recognized image format", path.display());
+            return Err(io::Error::new(io::ErrorKind::InvalidData, message));
+        }
+        Ok(self.img(img))
+    }
 
-/// An ingredient for for a recipe.
-pub struct Ingredient {
-    id: Uuid,
-    name: String,
-    unit: String,
-    measurement: String,
-}
+    /// Adds a picture, if it is primary any earlier primary image stops being primary.
+    pub fn image(mut self, image: RecipeImage) -> Self {
+        image::push(&mut self.images, image);
+        self
+    }
 
-/// A wrapper type for a `String`, that represents any optional tags for a recipe.
-pub struct RecipeTag {
-    tag: String
-}
+    pub fn nutrition(mut self, nutrition: Nutrition) -> Self {
+        self.nutrition = Some(nutrition);
+        self
+    }
+
+    pub fn allergen(mut self, allergen: Allergen) -> Self {
+        self.allergens.insert(allergen);
+        self
+    }
+
+    pub fn allergens(mut self, allergens: impl IntoIterator<Item = Allergen>) -> Self {
+        self.allergens.extend(allergens);
+        self
+    }
+
+    /// Adds a piece of equipment the recipe needs. If equipment with the same name was already added, the
+    /// earlier one and its note are kept.
+    pub fn equipment_item(mut self, equipment: Equipment) -> Self {
+        self.equipment.insert(equipment);
+        self
+    }
+
+    /// Adds every piece of equipment in `equipment` to any already added, see `equipment_item`.
+    pub fn equipment(self, equipment: impl IntoIterator<Item = Equipment>) -> Self {
+        equipment.into_iter().fold(self, RecipeBuilder::equipment_item)
+    }
+
+    pub fn cuisine(mut self, cuisine: Cuisine) -> Self {
+        self.cuisine = Some(cuisine);
+        self
+    }
+
+    pub fn course(mut self, course: Course) -> Self {
+        self.course = Some(course);
+        self
+    }
 
+    pub fn category(mut self, category: Category) -> Self {
+        self.category = Some(category);
+        self
+    }
+
+    pub fn created_at(mut self, created_at: DateTime<Utc>) -> Self {
+        self.created_at = Some(created_at);
+        self
+    }
+
+    pub fn updated_at(mut self, updated_at: DateTime<Utc>) -> Self {
+        self.updated_at = Some(updated_at);
+        self
+    }
+
+    /// Sets the version, only needed when rehydrating an existing recipe.
+    pub fn version(mut self, version: u32) -> Self {
+        self.version = Some(version);
+        self
+    }
+
+    /// Sets the status, only needed when rehydrating an existing recipe. New recipes start as drafts, see
+    /// `Recipe::publish`.
+    pub fn status(mut self, status: RecipeStatus) -> Self {
+        self.status = Some(status);
+        self
+    }
+
+    /// Sets where the recipe came from, a source with nothing recorded is ignored.
+    pub fn source(mut self, source: Source) -> Self {
+        self.source = Some(source).filter(|source| !source.is_empty());
+        self
+    }
+
+    /// Records the recipe this one was forked from, only needed when rehydrating an existing fork.
+    pub fn forked_from(mut self, id: Uuid) -> Self {
+        self.forked_from = Some(id);
+        self
+    }
+
+    /// The names of the required fields that have not been set yet.
+    fn missing_fields(&self) -> Vec<&'static str> {
+        let mut missing = Vec::new();
+        if self.name.is_none() {
+            missing.push("name");
+        }
+        if self.difficulty.is_none() && self.estimate_difficulty.is_none() {
+            missing.push("difficulty");
+        }
+        if self.prep_minutes.is_none() && self.cook_minutes.is_none() && self.duration_minutes.is_none() {
+            missing.push("duration");
+        }
+        if self.servings.is_none() {
+            missing.push("servings");
+        }
+        if self.description.is_none() {
+            missing.push("description");
+        }
+        if self.category.is_none() {
+            missing.push("category");
+        }
+        if self.steps.is_empty() {
+            missing.push("directions");
+        }
+        missing
+    }
+
+    /// Like `build`, but also checks every image with `ImageData::from_bytes`, failing with
+    /// `RecipeBuildError::InvalidImage` for the first one that is rejected.
+    pub fn try_build(self) -> Result<Recipe, RecipeBuildError> {
+        for image in &self.images {
+            ImageData::check(image.bytes(), ImageData::DEFAULT_MAX_BYTES).map_err(RecipeBuildError::InvalidImage)?;
+        }
+        self.build()
+    }
+
+    /// Builds the `Recipe`, if more than one required field is missing they are all reported
+    /// together with `RecipeBuildError::MissingFields`.
+    pub fn build(self) -> Result<Recipe, RecipeBuildError> {
+        if self.duration_minutes.is_some() && (self.prep_minutes.is_some() || self.cook_minutes.is_some()) {
+            return Err(RecipeBuildError::ConflictingDuration);
+        }
+        let missing = self.missing_fields();
+        if missing.len() > 1 {
+            return Err(RecipeBuildError::MissingFields(missing));
+        }
+        if self.steps.is_empty() {
+            return Err(RecipeBuildError::MissingDirections);
+        }
+        self.assemble()
+    }
+
+    /// Builds the `Recipe` from whatever is set without checking the directions, which `TypedRecipeBuilder`
+    /// leaves to `Recipe::validate` and a stored row may have none of.
+    fn assemble(mut self) -> Result<Recipe, RecipeBuildError> {
+        step::renumber(&mut self.steps);
+        let created_at = self.created_at.unwrap_or_else(Utc::now);
+        let estimate = self.estimate_difficulty.take();
+        let mut recipe = Recipe {
+            id: self.id.take().unwrap_or_else(Uuid::now_v7),
+            name: self.name.take().ok_or(RecipeBuildError::MissingName)?,
+            difficulty: match estimate {
+                // Replaced by the estimate once the rest of the recipe is assembled.
+                Some(_) => Difficulty::Easy,
+                None => self.difficulty.take().ok_or(RecipeBuildError::MissingDifficulty)?,
+            },
+            times: match (self.prep_minutes, self.cook_minutes.or(self.duration_minutes)) {
+                (None, None) => return Err(RecipeBuildError::MissingDuration),
+                (prep, cook) => CookingTimes::new(prep.unwrap_or(0), cook.unwrap_or(0), self.rest_minutes.unwrap_or(0)),
+            },
+            servings: self.servings.take().ok_or(RecipeBuildError::MissingServings)?,
+            description: self.description.take().ok_or(RecipeBuildError::MissingDescription)?,
+            category: self.category.take().ok_or(RecipeBuildError::MissingCategory)?,
+            steps: self.steps,
+            ingredients: self.ingredients,
+            tags: self.tags,
+            images: self.images,
+            nutrition: self.nutrition,
+            allergens: self.allergens,
+            equipment: self.equipment,
+            cuisine: self.cuisine,
+            course: self.course,
+            created_at,
+            updated_at: self.updated_at.unwrap_or(created_at),
+            source: self.source,
+            forked_from: self.forked_from,
+            version: self.version.unwrap_or_else(first_version),
+            status: self.status.unwrap_or_default(),
+        };
+        if let Some(model) = estimate {
+            recipe.difficulty = model.estimate(&recipe);
+        }
+        Ok(recipe)
+    }
+}
+
+/// The error returned when a `RecipeBuilder` is missing required fields at build time.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RecipeBuildError {
+    MissingName,
+    MissingDifficulty,
+    /// Neither a prep nor a cook time was set
+    MissingDuration,
+    MissingServings,
+    MissingDescription,
+    MissingCategory,
+    MissingDirections,
+    /// The deprecated single duration was set along with a prep or cook time
+    ConflictingDuration,
+    /// More than one required field was not set
+    MissingFields(Vec<&'static str>),
+    /// An image is empty, too large or not in a recognized format, only returned by `try_build`
+    InvalidImage(ImageError),
+}
+
+impl RecipeBuildError {
+    /// The names of the fields that were not set, empty if the error is not about missing fields.
+    pub fn fields(&self) -> Vec<&'static str> {
+        match self {
+            RecipeBuildError::MissingName => vec!["name"],
+            RecipeBuildError::MissingDifficulty => vec!["difficulty"],
+            RecipeBuildError::MissingDuration => vec!["duration"],
+            RecipeBuildError::MissingServings => vec!["servings"],
+            RecipeBuildError::MissingDescription => vec!["description"],
+            RecipeBuildError::MissingCategory => vec!["category"],
+            RecipeBuildError::MissingDirections => vec!["directions"],
+            RecipeBuildError::MissingFields(fields) => fields.clone(),
+            RecipeBuildError::ConflictingDuration | RecipeBuildError::InvalidImage(_) => Vec::new(),
+        }
+    }
+}
+
+impl fmt::Display for RecipeBuildError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RecipeBuildError::MissingFields(fields) => {
+                write!(f, "cannot build Recipe: {} were not set", fields.join(", "))
+            }
+            RecipeBuildError::ConflictingDuration => {
+                write!(f, "cannot build Recipe: duration cannot be set along with prep_time or cook_time")
+            }
+            RecipeBuildError::InvalidImage(err) => write!(f, "cannot build Recipe: {}", err),
+            _ => write!(f, "cannot build Recipe: {} was not set", self.fields()[0]),
+        }
+    }
+}
+
+impl Error for RecipeBuildError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            RecipeBuildError::InvalidImage(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+/// Represents the difficulty of a recipe on a scale of 1 to 4.
+/// The `Easy` variant being the easiest kind of recipe to make and `Expert` variant being
+/// the most difficult kind of recipe to make.
+///
+/// Difficulties are ordered from easiest to hardest, `Easy < Medium < Hard < Expert`, which matches
+/// the ordering of their `u8` representation so sorting in memory agrees with sorting the stored column.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "lowercase"))]
+pub enum Difficulty {
+    Easy,
+    Medium,
+    Hard,
+    Expert,
+}
+
+impl Difficulty {
+    /// Every difficulty, from easiest to hardest.
+    pub const ALL: [Difficulty; 4] = [Difficulty::Easy, Difficulty::Medium, Difficulty::Hard, Difficulty::Expert];
+
+    /// Every difficulty, from easiest to hardest, e.g. for filling a dropdown.
+    pub fn all() -> [Difficulty; 4] {
+        Difficulty::ALL
+    }
+
+    /// The next harder difficulty, or `None` if this is `Expert`.
+    pub fn harder(&self) -> Option<Difficulty> {
+        Difficulty::try_from(u8::from(*self) + 1).ok()
+    }
+
+    /// The next easier difficulty, or `None` if this is `Easy`.
+    pub fn easier(&self) -> Option<Difficulty> {
+        Difficulty::try_from(u8::from(*self) - 1).ok()
+    }
+}
+
+impl From<Difficulty> for u8 {
+    fn from(difficulty: Difficulty) -> Self {
+        match difficulty {
+            Difficulty::Easy => 1,
+            Difficulty::Medium => 2,
+            Difficulty::Hard => 3,
+            Difficulty::Expert => 4,
+        }
+    }
+}
+
+impl TryFrom<u8> for Difficulty {
+    type Error = InvalidDifficulty;
+
+    fn try_from(value: u8) -> Result<Self, Self::Error> {
+        match value {
+            1 => Ok(Difficulty::Easy),
+            2 => Ok(Difficulty::Medium),
+            3 => Ok(Difficulty::Hard),
+            4 => Ok(Difficulty::Expert),
+            _ => Err(InvalidDifficulty(value)),
+        }
+    }
+}
+
+/// The error returned when converting an integer outside of `1..=4` into a `Difficulty`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct InvalidDifficulty(pub u8);
+
+impl fmt::Display for InvalidDifficulty {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "invalid difficulty {}, expected a value from 1 to 4", self.0)
+    }
+}
+
+impl Error for InvalidDifficulty {}
+
+impl fmt::Display for Difficulty {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let s = match self {
+            Difficulty::Easy => "Easy",
+            Difficulty::Medium => "Medium",
+            Difficulty::Hard => "Hard",
+            Difficulty::Expert => "Expert",
+        };
+        f.write_str(s)
+    }
+}
+
+impl FromStr for Difficulty {
+    type Err = ParseDifficultyError;
+
+    /// Parses a difficulty from its name, case-insensitively, or from its number on the scale "1" to "4".
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "easy" | "1" => Ok(Difficulty::Easy),
+            "medium" | "2" => Ok(Difficulty::Medium),
+            "hard" | "3" => Ok(Difficulty::Hard),
+            "expert" | "4" => Ok(Difficulty::Expert),
+            _ => Err(ParseDifficultyError(s.to_string())),
+        }
+    }
+}
+
+/// The error returned when a string cannot be parsed into a `Difficulty`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseDifficultyError(pub String);
+
+impl fmt::Display for ParseDifficultyError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "unknown difficulty \"{}\", expected one of easy, medium, hard, expert or 1 to 4", self.0)
+    }
+}
+
+impl Error for ParseDifficultyError {}
+
+/// An ingredient for for a recipe.
+///
+/// Equality and hashing are based on `id` alone, two ingredients with the same id are the
+/// same ingredient regardless of their `name` or `quantity`, which are treated as metadata.
+/// Ingredients that have not been assigned an id yet (a nil id) are compared by their trimmed,
+/// lowercased name instead. Since the hash depends on these fields, an ingredient should not have
+/// its id (or name, if the id is nil) changed while it is stored in a `HashSet`.
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Ingredient {
+    /// The id of the ingredient
+    id: Uuid,
+    /// The name of the ingredient
+    name: String,
+    /// The amount of the ingredient
+    quantity: Quantity,
+    /// How the ingredient is prepared, e.g. "finely chopped"
+    #[cfg_attr(feature = "serde", serde(default, skip_serializing_if = "Option::is_none"))]
+    note: Option<String>,
+}
+
+impl Ingredient {
+    pub fn new(id: Uuid, name: impl Into<String>, quantity: Quantity) -> Self {
+        Self { id, name: name.into(), quantity, note: None }
+    }
+
+    /// Sets how the ingredient is prepared, a blank note is stored as `None`.
+    pub fn with_note(mut self, note: impl Into<String>) -> Self {
+        self.note = Some(note.into()).filter(|note| !note.trim().is_empty());
+        self
+    }
+
+    pub fn builder() -> IngredientBuilder {
+        IngredientBuilder::new()
+    }
+
+    /// Creates an ingredient from a loose unit and measurement, e.g. `"cups"` and `"1 1/2"`.
+    #[deprecated(note = "use `Ingredient::new` with a `Quantity` instead")]
+    pub fn from_measurement(
+        id: Uuid,
+        name: impl Into<String>,
+        unit: &str,
+        measurement: &str
+    ) -> Result<Self, ParseQuantityError> {
+        let quantity = Quantity::parse(&format!("{} {}", measurement, unit))?;
+        Ok(Self::new(id, name, quantity))
+    }
+
+    pub fn id(&self) -> Uuid {
+        self.id
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn quantity(&self) -> &Quantity {
+        &self.quantity
+    }
+
+    pub fn note(&self) -> Option<&str> {
+        self.note.as_deref()
+    }
+
+    /// The name of the ingredient trimmed and lowercased, used for comparing ingredients
+    /// that have not been assigned an id yet.
+    fn normalized_name(&self) -> String {
+        self.name.trim().to_lowercase()
+    }
+}
+
+/// A builder for `Ingredient`, obtained through `Ingredient::builder`.
+///
+/// The `name` and `quantity` fields are required, a fresh UUID v7 id is generated if none is set.
+pub struct IngredientBuilder {
+    /// The id of the ingredient, yet to be set
+    id: Option<Uuid>,
+    /// The name of the ingredient, yet to be set
+    name: Option<String>,
+    /// The amount of the ingredient, yet to be set
+    quantity: Option<Quantity>,
+    /// How the ingredient is prepared, yet to be set
+    note: Option<String>,
+}
+
+impl IngredientBuilder {
+    pub(crate) fn new() -> Self {
+        Self { id: None, name: None, quantity: None, note: None }
+    }
+
+    pub fn id(mut self, id: Uuid) -> Self {
+        self.id = Some(id);
+        self
+    }
+
+    pub fn name(mut self, name: impl Into<String>) -> Self {
+        self.name = Some(name.into());
+        self
+    }
+
+    pub fn quantity(mut self, quantity: Quantity) -> Self {
+        self.quantity = Some(quantity);
+        self
+    }
+
+    /// Sets how the ingredient is prepared, a blank note is ignored.
+    pub fn note(mut self, note: impl Into<String>) -> Self {
+        self.note = Some(note.into()).filter(|note| !note.trim().is_empty());
+        self
+    }
+
+    /// Builds the `Ingredient`, generating a new id if none was set. The name must not be blank.
+    pub fn build(self) -> Result<Ingredient, IngredientBuildError> {
+        let name = self.name
+            .filter(|name| !name.trim().is_empty())
+            .ok_or(IngredientBuildError::MissingName)?;
+        let quantity = self.quantity.ok_or(IngredientBuildError::MissingQuantity)?;
+        Ok(Ingredient { id: self.id.unwrap_or_else(Uuid::now_v7), name, quantity, note: self.note })
+    }
+}
+
+/// The error returned when an `IngredientBuilder` is missing a required field at build time.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum IngredientBuildError {
+    /// The name was not set or is blank
+    MissingName,
+    MissingQuantity,
+}
+
+impl fmt::Display for IngredientBuildError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            IngredientBuildError::MissingName => write!(f, "cannot build Ingredient: name was not set"),
+            IngredientBuildError::MissingQuantity => write!(f, "cannot build Ingredient: quantity was not set"),
+        }
+    }
+}
+
+impl Error for IngredientBuildError {}
+
+impl fmt::Display for Ingredient {
+    /// Displays the ingredient as it would be listed in a recipe, e.g. `2 cup flour`, `salt to taste` or
+    /// `1 onion, diced`.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.quantity.unit() {
+            Unit::ToTaste => write!(f, "{} {}", self.name, self.quantity)?,
+            _ => write!(f, "{} {}", self.quantity, self.name)?,
+        }
+        if let Some(note) = &self.note {
+            write!(f, ", {}", note)?;
+        }
+        Ok(())
+    }
+}
+
+impl PartialEq for Ingredient {
+    fn eq(&self, other: &Self) -> bool {
+        if self.id.is_nil() && other.id.is_nil() {
+            self.normalized_name() == other.normalized_name()
+        } else {
+            self.id == other.id
+        }
+    }
+}
+
+impl Eq for Ingredient {}
+
+impl Hash for Ingredient {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        if self.id.is_nil() {
+            self.normalized_name().hash(state);
+        } else {
+            self.id.hash(state);
+        }
+    }
+}
+
+/// A wrapper type for a `String`, that represents any optional tags for a recipe.
+///
+/// Tags are lowercased and trimmed when they are created, so `"Vegan"`, `"vegan "` and `"VEGAN"` are all
+/// the tag `"vegan"`. Deserialized tags are kept as given but compared the same way. A tag may be put in a
+/// namespace by prefixing it with one, as in `"cuisine:thai"`, only the first colon separates the two, so
+/// `"note:serve:cold"` is `"serve:cold"` in namespace `"note"`.
+/// Tags still serialize as plain strings, and deserialized tags are only checked by `Recipe::validate`.
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(transparent))]
+pub struct RecipeTag {
+    /// The tag, normalized unless it was deserialized
+    tag: String
+}
+
+impl RecipeTag {
+    /// The longest allowed tag in characters.
+    pub const MAX_LEN: usize = Recipe::MAX_TAG_LEN;
+
+    /// Creates a tag from `tag` trimmed and lowercased, see `normalized`. Fails if nothing is left, if it is
+    /// longer than `MAX_LEN` characters or if it contains a comma, which separates tags in CSV exports.
+    pub fn new(tag: impl Into<String>) -> Result<Self, TagError> {
+        let tag = tag.into();
+        let trimmed = tag.trim();
+        if trimmed.is_empty() {
+            return Err(TagError::Empty);
+        }
+        let len = trimmed.chars().count();
+        if len > RecipeTag::MAX_LEN {
+            return Err(TagError::TooLong { max: RecipeTag::MAX_LEN, actual: len });
+        }
+        if trimmed.contains(',') {
+            return Err(TagError::InvalidCharacter(','));
+        }
+        Ok(Self { tag: Self::lookup(trimmed).normalized() })
+    }
+
+    /// Splits `input` on commas and semicolons into normalized tags, in the order given, e.g.
+    /// `"vegan, gluten-free; quick"` gives `vegan`, `gluten-free` and `quick`. Blank and invalid fragments,
+    /// such as ones longer than `MAX_LEN`, are skipped, as are repeats of an earlier tag.
+    pub fn parse_many(input: &str) -> Vec<RecipeTag> {
+        let mut tags: Vec<RecipeTag> = Vec::new();
+        for tag in input.split([',', ';']).filter_map(|fragment| RecipeTag::new(fragment).ok()) {
+            if !tags.contains(&tag) {
+                tags.push(tag);
+            }
+        }
+        tags
+    }
+
+    /// A tag for looking up `tag` in a set of tags, which does not need to be valid.
+    fn lookup(tag: &str) -> Self {
+        Self { tag: tag.to_string() }
+    }
+
+    /// The tag, e.g. `"cuisine:thai"`.
+    pub fn as_str(&self) -> &str {
+        &self.tag
+    }
+
+    /// The namespace of the tag, e.g. `"cuisine"` for `"cuisine:thai"`, or `None` for a plain tag.
+    pub fn namespace(&self) -> Option<&str> {
+        self.split().0
+    }
+
+    /// The tag without its namespace, e.g. `"thai"` for `"cuisine:thai"`.
+    pub fn value(&self) -> &str {
+        self.split().1
+    }
+
+    /// Whether the tag is in `namespace`, compared case-insensitively.
+    pub fn in_namespace(&self, namespace: &str) -> bool {
+        self.namespace().is_some_and(|own| own.eq_ignore_ascii_case(namespace.trim()))
+    }
+
+    /// The tag trimmed and lowercased, with its namespace and value trimmed separately, this is the form
+    /// used for equality and hashing.
+    pub fn normalized(&self) -> String {
+        match self.split() {
+            (Some(namespace), value) => format!("{}:{}", namespace.to_lowercase(), value.to_lowercase()),
+            (None, value) => value.to_lowercase(),
+        }
+    }
+
+    /// Splits the tag at its first colon, if there is a non-blank namespace before it and a non-blank value
+    /// after it.
+    fn split(&self) -> (Option<&str>, &str) {
+        match self.tag.split_once(':') {
+            Some((namespace, value)) if !namespace.trim().is_empty() && !value.trim().is_empty() => {
+                (Some(namespace.trim()), value.trim())
+            }
+            _ => (None, self.tag.trim()),
+        }
+    }
+}
+
+impl fmt::Display for RecipeTag {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.split() {
+            (Some(namespace), value) => write!(f, "{}:{}", namespace, value),
+            (None, value) => write!(f, "{}", value),
+        }
+    }
+}
+
+impl PartialEq for RecipeTag {
+    fn eq(&self, other: &Self) -> bool {
+        self.normalized() == other.normalized()
+    }
+}
+
+impl Eq for RecipeTag {}
+
+impl Hash for RecipeTag {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.normalized().hash(state);
+    }
+}
+
+/// The error returned when text cannot be made into a `RecipeTag`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TagError {
+    /// The tag is empty or only whitespace
+    Empty,
+    /// The tag is longer than `RecipeTag::MAX_LEN`, lengths are counted in characters
+    TooLong { max: usize, actual: usize },
+    /// The tag contains a character that is not allowed, such as a comma
+    InvalidCharacter(char),
+}
+
+impl fmt::Display for TagError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TagError::Empty => write!(f, "tag must not be blank"),
+            TagError::TooLong { max, actual } => {
+                write!(f, "tag must be at most {} characters long, but is {}", max, actual)
+            }
+            TagError::InvalidCharacter(c) => write!(f, "tag must not contain '{}'", c),
+        }
+    }
+}
+
+impl Error for TagError {}
+
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+    use uuid::Uuid;
+    use super::testing::{full_recipe, ingredient, recipe, tag, JPEG, PNG};
+    use super::*;
+
+    #[test]
+    fn tags_differing_in_case_and_whitespace_are_one_tag() {
+        let tags: HashSet<RecipeTag> = ["Vegan", "vegan ", "VEGAN", " Quick"].into_iter().map(tag).collect();
+        assert_eq!(tags.len(), 2);
+        assert!(tags.contains(&tag("quick")));
+    }
+
+    #[test]
+    fn ingredients_without_ids_are_compared_by_name() {
+        let flour = |name: &str| Ingredient::new(Uuid::nil(), name, Quantity::new(1.0, Unit::Cup));
+        let ingredients: HashSet<Ingredient> = [flour("Flour"), flour(" flour "), flour("sugar")].into_iter().collect();
+        assert_eq!(ingredients.len(), 2);
+        let named_apart = [ingredient("flour", 1.0, Unit::Cup), ingredient("flour", 1.0, Unit::Cup)];
+        assert_eq!(named_apart.into_iter().collect::<HashSet<_>>().len(), 2);
+    }
+
+    #[test]
+    fn an_ingredient_added_twice_is_recorded_once() {
+        let flour = ingredient("flour", 1.0, Unit::Cup);
+        let built = recipe("Bread").ingredient(flour.clone()).ingredient(flour.clone()).build();
+        let built = built.expect("the recipe should build");
+        assert_eq!(built.ingredients().len(), 1);
+        assert!(built.ingredients().contains(&flour));
+    }
+
+    #[test]
+    fn build_errors_name_the_missing_field() {
+        let err = Recipe::builder()
+            .difficulty(Difficulty::Easy)
+            .cook_time(5)
+            .servings(1)
+            .description("")
+            .category(Category::Side)
+            .step("Cook");
+        let err = err.build().expect_err("the name is missing");
+        assert_eq!(err, RecipeBuildError::MissingName);
+        assert_eq!(err.to_string(), "cannot build Recipe: name was not set");
+    }
+
+    #[test]
+    fn ingredients_with_the_same_id_are_equal_whatever_their_names() {
+        let id = Uuid::now_v7();
+        let flour = Ingredient::new(id, "flour", Quantity::new(1.0, Unit::Cup));
+        let renamed = Ingredient::new(id, "bread flour", Quantity::new(2.0, Unit::Gram));
+        assert_eq!(flour, renamed);
+        assert_eq!([flour, renamed].into_iter().collect::<HashSet<_>>().len(), 1);
+    }
+
+    #[test]
+    fn tags_are_case_folded_on_insertion() {
+        assert_eq!(tag(" Vegan ").normalized(), "vegan");
+        let built = recipe("Salad").tag(tag("Vegan")).tags([tag("vegan "), tag("VEGAN")]).build();
+        let mut built = built.expect("the recipe should build");
+        assert_eq!(built.tags().len(), 1);
+        assert!(!built.add_tag(tag("vEgAn")));
+        assert_eq!(built.tags().iter().next().map(RecipeTag::as_str), Some("vegan"));
+    }
+
+    #[test]
+    fn tags_are_trimmed_lowercased_and_validated() {
+        assert_eq!(tag(" Vegan ").as_str(), "vegan");
+        assert_eq!(RecipeTag::new(""), Err(TagError::Empty));
+        assert_eq!(RecipeTag::new("   "), Err(TagError::Empty));
+        let long = "a".repeat(RecipeTag::MAX_LEN + 1);
+        let too_long = TagError::TooLong { max: RecipeTag::MAX_LEN, actual: RecipeTag::MAX_LEN + 1 };
+        assert_eq!(RecipeTag::new(&long), Err(too_long));
+        assert_eq!(RecipeTag::new("sweet,sour"), Err(TagError::InvalidCharacter(',')));
+    }
+
+    #[test]
+    fn compound_tag_input_is_split_into_distinct_tags() {
+        let parsed = RecipeTag::parse_many("vegan, gluten-free; quick");
+        assert_eq!(parsed, [tag("vegan"), tag("gluten-free"), tag("quick")]);
+        let too_long = "x".repeat(RecipeTag::MAX_LEN + 1);
+        let messy = RecipeTag::parse_many(&format!(" Vegan ;; vegan,{}, QUICK ;", too_long));
+        assert_eq!(messy.iter().map(RecipeTag::as_str).collect::<Vec<_>>(), ["vegan", "quick"]);
+        assert!(RecipeTag::parse_many(" ,; ").is_empty());
+    }
+
+    #[test]
+    fn each_missing_field_is_reported_on_its_own() {
+        let without = |field: &str| {
+            let mut builder = Recipe::builder();
+            if field != "name" {
+                builder = builder.name("Soup");
+            }
+            if field != "difficulty" {
+                builder = builder.difficulty(Difficulty::Easy);
+            }
+            if field != "duration" {
+                builder = builder.cook_time(5);
+            }
+            if field != "servings" {
+                builder = builder.servings(2);
+            }
+            if field != "description" {
+                builder = builder.description("Hot");
+            }
+            if field != "category" {
+                builder = builder.category(Category::MainCourse);
+            }
+            if field != "directions" {
+                builder = builder.step("Simmer");
+            }
+            builder.build()
+        };
+        let cases = [
+            ("name", RecipeBuildError::MissingName),
+            ("difficulty", RecipeBuildError::MissingDifficulty),
+            ("duration", RecipeBuildError::MissingDuration),
+            ("servings", RecipeBuildError::MissingServings),
+            ("description", RecipeBuildError::MissingDescription),
+            ("category", RecipeBuildError::MissingCategory),
+            ("directions", RecipeBuildError::MissingDirections),
+        ];
+        for (field, expected) in cases {
+            let err = without(field).expect_err("a field is missing");
+            assert_eq!(err.fields(), [field]);
+            assert_eq!(err, expected);
+        }
+        assert!(without("nothing").is_ok());
+    }
+
+    #[test]
+    fn every_missing_field_is_reported_together() {
+        let err = Recipe::builder().name("Soup").cook_time(5).category(Category::Side).build();
+        let err = err.expect_err("fields are missing");
+        assert_eq!(err, RecipeBuildError::MissingFields(vec!["difficulty", "servings", "description", "directions"]));
+        assert_eq!(err.fields(), ["difficulty", "servings", "description", "directions"]);
+        assert_eq!(err.to_string(), "cannot build Recipe: difficulty, servings, description, directions were not set");
+        let everything = Recipe::builder().build().expect_err("every field is missing").fields();
+        assert_eq!(everything, ["name", "difficulty", "duration", "servings", "description", "category", "directions"]);
+    }
+
+    #[test]
+    fn getters_return_what_was_built() {
+        let id = Uuid::now_v7();
+        let flour = Ingredient::new(Uuid::now_v7(), "flour", Quantity::new(2.0, Unit::Cup)).with_note("sifted");
+        let built = Recipe::builder()
+            .id(id)
+            .name("Bread")
+            .difficulty(Difficulty::Medium)
+            .prep_time(20)
+            .cook_time(40)
+            .servings(8)
+            .description("A plain loaf")
+            .category(Category::Side)
+            .ingredient(flour.clone())
+            .directions("Knead\nBake")
+            .tag(tag("baking"))
+            .img(vec![0x89, b'P', b'N', b'G', 0x0D, 0x0A, 0x1A, 0x0A])
+            .build()
+            .expect("the recipe should build");
+        assert_eq!(built.id(), id);
+        assert_eq!(built.name(), "Bread");
+        assert_eq!(*built.difficulty(), Difficulty::Medium);
+        assert_eq!(built.duration(), 60);
+        assert_eq!(built.description(), "A plain loaf");
+        assert_eq!(built.category(), Category::Side);
+        assert_eq!(built.directions_text(), "1. Knead\n2. Bake");
+        assert_eq!(built.img().len(), 8);
+        let stored = built.ingredients().iter().next().expect("the ingredient should be stored");
+        assert_eq!((stored.id(), stored.name(), stored.quantity()), (flour.id(), "flour", flour.quantity()));
+        assert_eq!(stored.note(), Some("sifted"));
+        assert_eq!(built.tags().iter().next().map(RecipeTag::as_str), Some("baking"));
+
+        let parts = built.clone().into_parts();
+        assert_eq!((parts.id, parts.name.as_str(), parts.servings), (id, "Bread", 8));
+        assert_eq!((parts.ingredients, parts.tags), (built.ingredients().clone(), built.tags().clone()));
+        assert_eq!(parts.images[0].bytes(), built.img());
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn a_fully_populated_recipe_survives_json() {
+        let recipe = super::testing::full_recipe();
+        let json = serde_json::to_value(&recipe).expect("the recipe should serialize");
+        assert!(json["images"][0]["bytes"].is_string(), "image bytes should be base64 text, got {}", json["images"][0]);
+        let parsed: Recipe = serde_json::from_value(json).expect("the recipe should deserialize");
+        assert!(parsed.img_eq(&recipe));
+        assert_eq!((parsed.created_at(), parsed.updated_at()), (recipe.created_at(), recipe.updated_at()));
+    }
+
+    #[test]
+    fn bulk_and_single_ingredients_and_tags_are_appended_together() {
+        let salt = ingredient("salt", 1.0, Unit::Pinch);
+        let built = recipe("Bread")
+            .ingredient(salt.clone())
+            .ingredients(vec![ingredient("flour", 500.0, Unit::Gram), ingredient("water", 300.0, Unit::Milliliter)])
+            .ingredient(ingredient("yeast", 7.0, Unit::Gram))
+            .ingredients(vec![salt])
+            .tag(tag("baking"))
+            .tags([tag("vegan"), tag("Baking")])
+            .tag(tag("bread"))
+            .build()
+            .expect("the recipe should build");
+        let mut names: Vec<&str> = built.ingredients().iter().map(Ingredient::name).collect();
+        names.sort_unstable();
+        assert_eq!(names, ["flour", "salt", "water", "yeast"]);
+        let mut tags: Vec<&str> = built.tags().iter().map(RecipeTag::as_str).collect();
+        tags.sort_unstable();
+        assert_eq!(tags, ["baking", "bread", "vegan"]);
+    }
+
+    #[test]
+    fn rebuilding_from_into_builder_only_changes_what_was_set() {
+        let original = full_recipe();
+        let renamed = original.clone().into_builder().name("Crêpes").build().expect("the recipe should build");
+        assert_eq!(renamed.name(), "Crêpes");
+        assert_eq!(renamed.ingredients(), original.ingredients());
+        assert_eq!(renamed.tags(), original.tags());
+        assert_eq!((renamed.id(), renamed.version()), (original.id(), original.version()));
+        assert_eq!((renamed.created_at(), renamed.updated_at()), (original.created_at(), original.updated_at()));
+        assert!(renamed.img_eq(&original.edit().name("Crêpes").build().expect("the recipe should build")));
+        let diff = original.diff(&renamed);
+        assert!(diff.name.is_some());
+        assert_eq!(RecipeDiff { name: None, ..diff }, RecipeDiff::default());
+        assert!(renamed.into_builder().name("Pancakes").build().expect("the recipe should build").img_eq(&original));
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn an_ingredient_note_is_only_serialized_when_present() {
+        let onion = ingredient("onion", 1.0, Unit::Piece);
+        let json = serde_json::to_value(&onion).expect("the ingredient should serialize");
+        assert!(json.get("note").is_none(), "{}", json);
+        let diced = onion.with_note("finely diced");
+        let json = serde_json::to_value(&diced).expect("the ingredient should serialize");
+        assert_eq!(json["note"], "finely diced");
+        let parsed: Ingredient = serde_json::from_value(json).expect("the ingredient should deserialize");
+        assert_eq!(parsed.note(), Some("finely diced"));
+        assert_eq!(ingredient("salt", 1.0, Unit::Pinch).with_note("  ").note(), None);
+    }
+
+    #[test]
+    fn adding_an_ingredient_twice_only_inserts_it_once() {
+        let mut built = recipe("Bread").build().expect("the recipe should build");
+        let flour = ingredient("flour", 1.0, Unit::Cup);
+        assert!(built.add_ingredient(flour.clone()));
+        let version = built.version();
+        let more_flour = Ingredient::new(flour.id(), "flour", Quantity::new(3.0, Unit::Cup));
+        assert!(!built.add_ingredient(more_flour));
+        assert_eq!((built.ingredients().len(), built.version()), (1, version));
+        let kept = built.ingredients().iter().next().expect("the ingredient should be kept");
+        assert_eq!(kept.quantity().amount(), 1.0);
+        assert!(built.add_ingredient(ingredient("salt", 1.0, Unit::Pinch)));
+        assert_eq!(built.ingredient_count(), 2);
+    }
+
+    #[test]
+    fn removing_a_missing_ingredient_changes_nothing() {
+        let flour = ingredient("flour", 1.0, Unit::Cup);
+        let mut built = recipe("Bread").ingredient(flour.clone()).build().expect("the recipe should build");
+        let version = built.version();
+        assert_eq!(built.remove_ingredient(&Uuid::now_v7()), None);
+        assert_eq!((built.ingredients().len(), built.version()), (1, version));
+        assert_eq!(built.remove_ingredient(&flour.id()).map(|removed| removed.id()), Some(flour.id()));
+        assert!(built.ingredients().is_empty());
+    }
+
+    #[test]
+    fn tags_are_removed_whatever_their_case() {
+        let mut built = recipe("Salad").tags([tag("Vegan"), tag("quick")]).build().expect("the recipe should build");
+        assert_eq!(built.remove_tag(" VEGAN ").as_ref().map(RecipeTag::as_str), Some("vegan"));
+        assert_eq!(built.remove_tag("vegan"), None);
+        assert_eq!(built.remove_tag("Quick").as_ref().map(RecipeTag::as_str), Some("quick"));
+        assert!(built.tags().is_empty());
+        assert!(built.add_tag(tag("vegan")));
+        assert_eq!(built.tags().len(), 1);
+    }
+
+    #[test]
+    fn difficulties_convert_to_and_from_u8() {
+        let expected = [(Difficulty::Easy, 1), (Difficulty::Medium, 2), (Difficulty::Hard, 3), (Difficulty::Expert, 4)];
+        for (difficulty, value) in expected {
+            assert_eq!(u8::from(difficulty), value);
+            assert_eq!(Difficulty::try_from(value), Ok(difficulty));
+        }
+        assert_eq!(Difficulty::try_from(0), Err(InvalidDifficulty(0)));
+        assert_eq!(Difficulty::try_from(5), Err(InvalidDifficulty(5)));
+        assert_eq!(InvalidDifficulty(5).to_string(), "invalid difficulty 5, expected a value from 1 to 4");
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn recipes_serialize_with_readable_field_values() {
+        let built = recipe("Tea").difficulty(Difficulty::Hard).tag(tag("Drinks")).build();
+        let built = built.expect("the recipe should build");
+        let json = serde_json::to_value(&built).expect("the recipe should serialize");
+        assert_eq!(json["difficulty"], "hard");
+        assert_eq!(json["tags"], serde_json::json!(["drinks"]));
+        assert_eq!(json["id"], built.id().hyphenated().to_string());
+        for difficulty in Difficulty::ALL {
+            let json = serde_json::to_string(&difficulty).expect("the difficulty should serialize");
+            assert_eq!(json, format!("\"{}\"", difficulty.to_string().to_lowercase()));
+            assert_eq!(serde_json::from_str::<Difficulty>(&json).ok(), Some(difficulty));
+        }
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn a_recipe_without_ingredients_tags_or_images_survives_json() {
+        let built = recipe("Water").build().expect("the recipe should build");
+        let json = serde_json::to_string(&built).expect("the recipe should serialize");
+        let parsed: Recipe = serde_json::from_str(&json).expect("the recipe should deserialize");
+        assert!(parsed.img_eq(&built));
+        assert!(parsed.ingredients().is_empty() && parsed.tags().is_empty() && !parsed.has_img());
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn unknown_difficulties_are_rejected_when_deserializing() {
+        let err = serde_json::from_str::<Difficulty>("\"impossible\"").expect_err("the difficulty is unknown");
+        assert!(err.to_string().contains("unknown variant `impossible`"), "unclear error: {}", err);
+    }
+
+    #[test]
+    fn difficulties_parse_from_their_names() {
+        assert_eq!("Medium".parse(), Ok(Difficulty::Medium));
+        assert_eq!("hard".parse(), Ok(Difficulty::Hard));
+        assert_eq!(Difficulty::Expert.to_string(), "Expert");
+        let err = "impossible".parse::<Difficulty>().expect_err("the difficulty is unknown");
+        assert_eq!(err, ParseDifficultyError("impossible".to_string()));
+    }
+
+    #[test]
+    fn every_difficulty_round_trips_through_text_and_numbers() {
+        assert_eq!(Difficulty::ALL, [Difficulty::Easy, Difficulty::Medium, Difficulty::Hard, Difficulty::Expert]);
+        for (i, difficulty) in Difficulty::ALL.into_iter().enumerate() {
+            let name = difficulty.to_string();
+            let number = u8::from(difficulty);
+            assert_eq!(usize::from(number), i + 1);
+            for text in [name.clone(), name.to_lowercase(), name.to_uppercase(), number.to_string()] {
+                assert_eq!(text.parse(), Ok(difficulty), "{:?} should parse", text);
+            }
+            assert_eq!(Difficulty::try_from(number), Ok(difficulty));
+        }
+        for rejected in ["5", "0", "impossible", ""] {
+            assert!(rejected.parse::<Difficulty>().is_err(), "{:?} should be rejected", rejected);
+        }
+        let err: Box<dyn Error> = Box::new(ParseDifficultyError("5".to_string()));
+        assert_eq!(err.to_string(), "unknown difficulty \"5\", expected one of easy, medium, hard, expert or 1 to 4");
+    }
+
+    #[test]
+    fn difficulties_sort_from_easiest_and_step_along_the_scale() {
+        let mut difficulties = vec![Difficulty::Hard, Difficulty::Easy, Difficulty::Expert, Difficulty::Medium];
+        difficulties.sort();
+        assert_eq!(difficulties, Difficulty::all());
+        assert!(Difficulty::Easy < Difficulty::Expert);
+        assert!(Difficulty::Medium < Difficulty::Hard);
+        assert_eq!(Difficulty::Easy.harder(), Some(Difficulty::Medium));
+        assert_eq!(Difficulty::Expert.harder(), None);
+        assert_eq!(Difficulty::Expert.easier(), Some(Difficulty::Hard));
+        assert_eq!(Difficulty::Easy.easier(), None);
+    }
+
+    #[test]
+    fn ingredient_count_dedupes_ingredients_with_the_same_id() {
+        let flour = ingredient("flour", 2.0, Unit::Cup);
+        let same_id = Ingredient::new(flour.id(), "plain flour", Quantity::new(250.0, Unit::Gram));
+        let built = recipe("Bread")
+            .ingredient(flour)
+            .ingredient(same_id)
+            .ingredient(ingredient("water", 1.0, Unit::Cup))
+            .ingredient(ingredient("salt", 1.0, Unit::Teaspoon))
+            .build()
+            .expect("the recipe should build");
+        assert_eq!(built.ingredient_count(), 3);
+        assert_eq!(built.units_used(), HashSet::from([Unit::Cup, Unit::Teaspoon]));
+        assert!(recipe("Toast").build().expect("the recipe should build").units_used().is_empty());
+    }
+
+    fn listed_ingredients(recipe: &Recipe) -> Vec<String> {
+        let mut ingredients: Vec<String> = recipe.ingredients().iter().map(Ingredient::to_string).collect();
+        ingredients.sort();
+        ingredients
+    }
+
+    #[test]
+    fn a_fork_has_a_new_id_the_same_content_and_points_at_its_original() {
+        let original = full_recipe();
+        let fork = original.fork().build().expect("the fork should build");
+        assert_ne!(fork.id(), original.id());
+        assert_eq!(fork.forked_from(), Some(original.id()));
+        assert_eq!(original.forked_from(), None);
+
+        assert_eq!(fork.name(), original.name());
+        assert_eq!((fork.difficulty(), fork.times()), (original.difficulty(), original.times()));
+        assert_eq!((fork.description(), fork.steps()), (original.description(), original.steps()));
+        assert_eq!((fork.tags(), fork.nutrition()), (original.tags(), original.nutrition()));
+        assert_eq!((fork.allergens(), fork.equipment()), (original.allergens(), original.equipment()));
+        assert_eq!((fork.cuisine(), fork.course()), (original.cuisine(), original.course()));
+        assert_eq!(fork.source(), original.source());
+        assert_eq!(fork.images(), original.images());
+        assert_eq!(listed_ingredients(&fork), listed_ingredients(&original));
+        let original_ids: HashSet<Uuid> = original.ingredients().iter().map(Ingredient::id).collect();
+        assert!(fork.ingredients().iter().all(|ingredient| !original_ids.contains(&ingredient.id())));
+    }
+
+    #[test]
+    fn forking_a_fork_points_at_the_fork() {
+        let original = full_recipe();
+        let fork = original.fork().name("Pancakes, my way").build().expect("the fork should build");
+        let fork_of_fork = fork.fork().build().expect("the fork should build");
+        assert_eq!(fork_of_fork.forked_from(), Some(fork.id()));
+        assert_eq!(fork_of_fork.name(), "Pancakes, my way");
+        assert_eq!(original.name(), "Pancakes");
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn forked_from_survives_serialization() {
+        let fork = full_recipe().fork().build().expect("the fork should build");
+        let json = serde_json::to_string(&fork).expect("the recipe should serialize");
+        let back: Recipe = serde_json::from_str(&json).expect("the recipe should deserialize");
+        assert_eq!(back.forked_from(), fork.forked_from());
+    }
+
+    #[test]
+    fn namespaced_tags_split_at_their_first_colon() {
+        let thai = tag(" Cuisine : Thai ");
+        assert_eq!((thai.namespace(), thai.value()), (Some("cuisine"), "thai"));
+        assert_eq!((thai.to_string(), thai.normalized()), ("cuisine:thai".to_string(), "cuisine:thai".to_string()));
+        assert_eq!(thai, tag("cuisine:thai"));
+        let quick = tag("quick");
+        assert_eq!((quick.namespace(), quick.value(), quick.to_string()), (None, "quick", "quick".to_string()));
+        let note = tag("note:serve:cold");
+        assert_eq!((note.namespace(), note.value()), (Some("note"), "serve:cold"));
+        assert_eq!(note.to_string(), "note:serve:cold");
+        for blank_side in [":thai", "cuisine:", " : "] {
+            assert_eq!(tag(blank_side).namespace(), None, "{:?}", blank_side);
+        }
+        assert_eq!(HashSet::from([tag("Occasion:Thanksgiving"), tag("occasion:thanksgiving")]).len(), 1);
+    }
+
+    #[test]
+    fn tags_are_filtered_by_namespace() {
+        let built = recipe("Curry")
+            .tags([tag("cuisine:thai"), tag("Cuisine:Indian"), tag("occasion:weeknight"), tag("spicy")])
+            .build()
+            .expect("the recipe should build");
+        let cuisines: Vec<String> = built.tags_in_namespace("CUISINE").iter().map(|tag| tag.to_string()).collect();
+        assert_eq!(cuisines, ["cuisine:indian", "cuisine:thai"]);
+        assert_eq!(built.tags_in_namespace("occasion").len(), 1);
+        assert!(built.tags_in_namespace("spicy").is_empty());
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn namespaced_tags_serialize_as_plain_strings() {
+        let json = serde_json::to_string(&tag("note:serve:cold")).expect("the tag should serialize");
+        assert_eq!(json, r#""note:serve:cold""#);
+        let back: RecipeTag = serde_json::from_str(&json).expect("the tag should deserialize");
+        assert_eq!((back.namespace(), back.value()), (Some("note"), "serve:cold"));
+    }
+
+    #[test]
+    fn the_constructor_takes_every_required_field_including_servings() {
+        let id = Uuid::now_v7();
+        let ingredients = HashSet::from([ingredient("water", 1.0, Unit::Liter)]);
+        let steps = vec![Step::new("Boil"), Step::new("Serve")];
+        let tags = HashSet::from([tag("quick")]);
+        let times = CookingTimes::new(5, 10, 0);
+        let (name, description, img) = ("Tea".to_string(), "Hot".to_string(), PNG.to_vec());
+        let built = Recipe::new(id, name, Difficulty::Easy, times, 4, description, ingredients, steps, tags, img);
+        assert_eq!((built.id(), built.name(), built.servings(), built.cook_minutes()), (id, "Tea", 4, 10));
+        assert_eq!(built.steps().iter().map(Step::position).collect::<Vec<_>>(), [1, 2]);
+        assert_eq!((built.category(), built.status(), built.version()), (Category::MainCourse, RecipeStatus::Draft, 1));
+        assert_eq!(built.img(), PNG);
+        assert_eq!(built.created_at(), built.updated_at());
+    }
+
+    #[test]
+    fn servings_are_required_and_must_not_be_zero() {
+        let built = recipe("Soup").servings(6).build().expect("the recipe should build");
+        assert_eq!(built.servings(), 6);
+        let empty = recipe("Soup").servings(0).ingredient(ingredient("water", 1.0, Unit::Liter)).build();
+        let errors = empty.expect("the recipe should build").validate().expect_err("zero servings is invalid");
+        assert_eq!(errors.errors(), [ValidationError::Zero { field: "servings" }]);
+    }
+
+    #[test]
+    #[allow(deprecated)]
+    fn ingredients_still_accept_the_old_unit_and_measurement_strings() {
+        let flour = Ingredient::from_measurement(Uuid::now_v7(), "flour", "cups", "1 1/2").expect("should parse");
+        assert_eq!(flour.quantity(), &Quantity::new(1.5, Unit::Cup));
+        assert!(Ingredient::from_measurement(Uuid::now_v7(), "flour", "cups", "1/0").is_err());
+    }
+
+    fn quantity_of(recipe: &Recipe, name: &str) -> Quantity {
+        let ingredient = recipe.ingredients().iter().find(|ingredient| ingredient.name() == name);
+        ingredient.expect("the ingredient should be in the recipe").quantity().clone()
+    }
+
+    fn sauce() -> Recipe {
+        recipe("Tomato sauce")
+            .servings(4)
+            .ingredient(ingredient("tomatoes", 800.0, Unit::Gram))
+            .ingredient(ingredient("olive oil", 1.5, Unit::Tablespoon))
+            .ingredient(ingredient("salt", 0.0, Unit::ToTaste))
+            .build()
+            .expect("the recipe should build")
+    }
+
+    #[test]
+    fn scaling_to_servings_doubles_and_halves_numeric_quantities() {
+        let doubled = sauce().scale_to(8);
+        assert_eq!(doubled.servings(), 8);
+        assert_eq!(quantity_of(&doubled, "tomatoes"), Quantity::new(1600.0, Unit::Gram));
+        assert_eq!(quantity_of(&doubled, "olive oil"), Quantity::new(3.0, Unit::Tablespoon));
+        assert_eq!(quantity_of(&doubled, "salt"), Quantity::to_taste());
+
+        let halved = sauce().scale_to(2);
+        assert_eq!(halved.servings(), 2);
+        assert_eq!(quantity_of(&halved, "tomatoes"), Quantity::new(400.0, Unit::Gram));
+        assert_eq!(quantity_of(&halved, "olive oil"), Quantity::new(0.75, Unit::Tablespoon));
+        assert_eq!(quantity_of(&halved, "salt"), Quantity::to_taste());
+    }
+
+    #[test]
+    fn scaling_by_a_factor_multiplies_quantities_but_not_seasoning() {
+        let cases = [(0.5, 2, 400.0, 0.75), (2.0, 8, 1600.0, 3.0), (3.0, 12, 2400.0, 4.5)];
+        for (factor, servings, tomatoes, oil) in cases {
+            let scaled = sauce().scale(factor).expect("the factor should be valid");
+            assert_eq!(scaled.servings(), servings);
+            assert_eq!(quantity_of(&scaled, "tomatoes"), Quantity::new(tomatoes, Unit::Gram));
+            assert_eq!(quantity_of(&scaled, "olive oil"), Quantity::new(oil, Unit::Tablespoon));
+            assert_eq!(quantity_of(&scaled, "salt"), Quantity::to_taste());
+        }
+        let tripled = sauce().scale(3.0).expect("the factor should be valid");
+        assert_eq!(quantity_of(&tripled, "olive oil").to_string(), "4 ½ tbsp");
+    }
+
+    #[test]
+    fn scaling_rejects_factors_that_are_not_positive() {
+        for factor in [0.0, -2.0, f64::NAN, f64::INFINITY] {
+            assert!(sauce().scale(factor).is_err(), "{} should be rejected", factor);
+        }
+    }
+
+    #[test]
+    fn only_scale_into_new_gives_the_recipe_a_fresh_id() {
+        let original = sauce();
+        assert_eq!(original.scale(2.0).expect("the factor should be valid").id(), original.id());
+        assert_ne!(original.scale_into_new(2.0).expect("the factor should be valid").id(), original.id());
+    }
+
+    #[test]
+    fn ingredient_builder_generates_an_id_and_requires_a_name() {
+        let flour = Ingredient::builder()
+            .name("flour")
+            .quantity(Quantity::new(2.0, Unit::Cup))
+            .build()
+            .expect("the ingredient should build");
+        assert!(!flour.id().is_nil());
+        let blank = Ingredient::builder().name("  ").quantity(Quantity::new(1.0, Unit::Cup)).build();
+        assert_eq!(blank, Err(IngredientBuildError::MissingName));
+        assert_eq!(Ingredient::builder().name("flour").build(), Err(IngredientBuildError::MissingQuantity));
+    }
+
+    #[test]
+    fn recipes_built_without_an_id_get_distinct_ones() {
+        let first = recipe("Soup").build().expect("the recipe should build");
+        let second = recipe("Soup").build().expect("the recipe should build");
+        assert_ne!(first.id(), second.id());
+        let id = Uuid::now_v7();
+        assert_eq!(recipe("Soup").id(id).build().expect("the recipe should build").id(), id);
+    }
+
+    #[test]
+    fn cloned_recipes_debug_without_dumping_image_bytes() {
+        let original = full_recipe();
+        let cloned = original.clone();
+        assert_eq!(cloned, original);
+        let debug = format!("{:?}", cloned);
+        assert!(debug.starts_with("Recipe { id: "), "{}", debug);
+        assert!(debug.contains("bytes: <8 bytes>"), "{}", debug);
+        assert!(debug.contains("img: <4 bytes>"), "{}", debug);
+        assert!(!debug.contains("137, 80, 78, 71"), "{}", debug);
+    }
+
+    #[test]
+    fn the_debug_output_of_a_recipe_with_a_large_image_stays_short() {
+        let mut img = PNG.to_vec();
+        img.resize(1024 * 1024, 0xAB);
+        let built = recipe("Soup").img(img).build().expect("the recipe should build");
+        let debug = format!("{:?}", built);
+        assert!(debug.len() < 1000, "{} characters of debug output", debug.len());
+        assert!(debug.contains("bytes: <1048576 bytes>"), "{}", debug);
+    }
+
+    #[test]
+    fn recipes_differing_only_in_their_picture_are_equal_but_not_img_eq() {
+        let id = Uuid::now_v7();
+        let pictured = |name: &str, bytes: &[u8]| {
+            recipe(name).id(id).image(RecipeImage::new(bytes.to_vec())).build().expect("the recipe should build")
+        };
+        let png = pictured("Soup", &PNG);
+        let jpeg = pictured("Soup", &JPEG);
+        assert_eq!(png, jpeg);
+        assert!(!png.img_eq(&jpeg));
+        assert!(png.img_eq(&png.clone()));
+        assert_ne!(png, pictured("Stew", &PNG));
+    }
+
+    #[test]
+    fn mutators_bump_updated_at_but_not_created_at() {
+        let mut built = recipe("Soup").build().expect("the recipe should build");
+        let (created_at, updated_at) = (built.created_at(), built.updated_at());
+        assert_eq!(created_at, updated_at);
+        std::thread::sleep(std::time::Duration::from_millis(2));
+        built.set_name("Stew");
+        assert_eq!(built.created_at(), created_at);
+        assert!(built.updated_at() > updated_at);
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn legacy_json_without_timestamps_defaults_to_the_epoch() {
+        let mut json = serde_json::to_value(recipe("Soup").build().expect("the recipe should build"))
+            .expect("the recipe should serialize");
+        assert!(json["created_at"].as_str().is_some_and(|at| DateTime::parse_from_rfc3339(at).is_ok()));
+        let fields = json.as_object_mut().expect("a recipe serializes to an object");
+        fields.remove("created_at");
+        fields.remove("updated_at");
+        let parsed: Recipe = serde_json::from_value(json).expect("the recipe should deserialize");
+        assert_eq!((parsed.created_at(), parsed.updated_at()), (DateTime::UNIX_EPOCH, DateTime::UNIX_EPOCH));
+    }
+
+    #[test]
+    fn images_are_read_from_a_path_and_anything_else_is_rejected_naming_the_path() {
+        let dir = std::env::temp_dir();
+        let (png, text) = (dir.join(format!("{}.png", Uuid::now_v7())), dir.join(format!("{}.txt", Uuid::now_v7())));
+        fs::write(&png, PNG).expect("write failed");
+        fs::write(&text, "not a picture").expect("write failed");
+        let pictured = recipe("Soup").img_from_path(&png).map(|builder| builder.build());
+        let not_an_image = recipe("Soup").img_from_path(&text).err().expect("text should be rejected");
+        let missing = dir.join(format!("{}.png", Uuid::now_v7()));
+        let unreadable = recipe("Soup").img_from_path(&missing).err().expect("a missing file should be rejected");
+        let _ = (fs::remove_file(&png), fs::remove_file(&text));
+
+        assert_eq!(pictured.expect("the image should be read").expect("the recipe should build").img(), PNG);
+        assert_eq!(not_an_image.kind(), io::ErrorKind::InvalidData);
+        assert!(not_an_image.to_string().contains(&text.display().to_string()));
+        assert_eq!(unreadable.kind(), io::ErrorKind::NotFound);
+        assert!(unreadable.to_string().contains(&missing.display().to_string()));
+    }
+}
@@ -0,0 +1,216 @@
+use std::collections::HashSet;
+#[cfg(feature = "serde")]
+use serde::Deserialize;
+use uuid::Uuid;
+use super::{
+    Allergen, Category, Cookbook, Course, Cuisine, Difficulty, Equipment, Ingredient, Nutrition, Quantity, Recipe,
+    RecipeBuildError, RecipeBuilder, RecipeTag, Source, Step,
+};
+
+/// The fields a client supplies to create a recipe, see `Cookbook::create`.
+///
+/// There is no id, the recipe and its ingredients are given fresh UUID v7 ids when it is created, nor any
+/// timestamps, version or status. Deserializing fails on any field not listed here, including `id`, so a
+/// client cannot choose its own id.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Deserialize))]
+#[cfg_attr(feature = "serde", serde(deny_unknown_fields))]
+pub struct NewRecipe {
+    pub name: String,
+    pub difficulty: Difficulty,
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub prep_minutes: Option<u16>,
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub cook_minutes: Option<u16>,
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub rest_minutes: Option<u16>,
+    pub servings: u16,
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub description: String,
+    pub category: Category,
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub ingredients: Vec<NewIngredient>,
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub steps: Vec<Step>,
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub tags: Vec<RecipeTag>,
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub nutrition: Option<Nutrition>,
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub allergens: HashSet<Allergen>,
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub equipment: HashSet<Equipment>,
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub cuisine: Option<Cuisine>,
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub course: Option<Course>,
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub source: Option<Source>,
+}
+
+impl NewRecipe {
+    /// A new recipe with just the fields every recipe needs, for setting the rest by hand.
+    pub fn new(name: impl Into<String>, difficulty: Difficulty, servings: u16, category: Category) -> Self {
+        Self {
+            name: name.into(),
+            difficulty,
+            prep_minutes: None,
+            cook_minutes: None,
+            rest_minutes: None,
+            servings,
+            description: String::new(),
+            category,
+            ingredients: Vec::new(),
+            steps: Vec::new(),
+            tags: Vec::new(),
+            nutrition: None,
+            allergens: HashSet::new(),
+            equipment: HashSet::new(),
+            cuisine: None,
+            course: None,
+            source: None,
+        }
+    }
+
+    /// A builder holding every supplied field, which generates the id when the recipe is built.
+    pub fn into_builder(self) -> RecipeBuilder {
+        let mut builder = Recipe::builder()
+            .name(self.name)
+            .difficulty(self.difficulty)
+            .servings(self.servings)
+            .description(self.description)
+            .category(self.category)
+            .ingredients(self.ingredients.into_iter().map(NewIngredient::into_ingredient))
+            .steps(self.steps)
+            .tags(self.tags)
+            .allergens(self.allergens)
+            .equipment(self.equipment);
+        if let Some(minutes) = self.prep_minutes {
+            builder = builder.prep_time(minutes);
+        }
+        if let Some(minutes) = self.cook_minutes {
+            builder = builder.cook_time(minutes);
+        }
+        if let Some(minutes) = self.rest_minutes {
+            builder = builder.rest_time(minutes);
+        }
+        if let Some(nutrition) = self.nutrition {
+            builder = builder.nutrition(nutrition);
+        }
+        if let Some(cuisine) = self.cuisine {
+            builder = builder.cuisine(cuisine);
+        }
+        if let Some(course) = self.course {
+            builder = builder.course(course);
+        }
+        if let Some(source) = self.source {
+            builder = builder.source(source);
+        }
+        builder
+    }
+
+    /// Builds a draft `Recipe` with a new id, see `RecipeBuilder::build`.
+    pub fn build(self) -> Result<Recipe, RecipeBuildError> {
+        self.into_builder().build()
+    }
+}
+
+/// The fields a client supplies for an ingredient of a `NewRecipe`, which is given a fresh UUID v7 id.
+/// Deserializing fails on any field not listed here, including `id`.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Deserialize))]
+#[cfg_attr(feature = "serde", serde(deny_unknown_fields))]
+pub struct NewIngredient {
+    pub name: String,
+    pub quantity: Quantity,
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub note: Option<String>,
+}
+
+impl NewIngredient {
+    /// The ingredient with a new id, a blank note is stored as `None`.
+    pub fn into_ingredient(self) -> Ingredient {
+        let ingredient = Ingredient::new(Uuid::now_v7(), self.name, self.quantity);
+        match self.note {
+            Some(note) => ingredient.with_note(note),
+            None => ingredient,
+        }
+    }
+}
+
+impl Cookbook {
+    /// Builds `recipe` with a new id and adds it, returning a copy of the stored recipe. The recipe is a
+    /// draft until it is published, see `Recipe::publish`.
+    pub fn create(&mut self, recipe: NewRecipe) -> Result<Recipe, RecipeBuildError> {
+        let recipe = recipe.build()?;
+        self.insert(recipe.clone());
+        Ok(recipe)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::testing::PNG;
+    use crate::models::{RecipeImage, RecipeStatus, Unit};
+
+    fn soup() -> NewRecipe {
+        let mut soup = NewRecipe::new("Soup", Difficulty::Easy, 2, Category::MainCourse);
+        soup.cook_minutes = Some(10);
+        soup.steps.push(Step::new("Simmer"));
+        let water = NewIngredient { name: "water".to_string(), quantity: Quantity::new(1.0, Unit::Liter), note: None };
+        soup.ingredients.push(water);
+        soup
+    }
+
+    #[test]
+    fn generated_ids_are_unique_uuid_v7s() {
+        let recipes: Vec<Recipe> = (0..1000).map(|_| soup().build().expect("the recipe should build")).collect();
+        let ids: HashSet<Uuid> = recipes.iter().map(Recipe::id).collect();
+        assert_eq!(ids.len(), 1000);
+        assert!(ids.iter().all(|id| id.get_version_num() == 7));
+        let ingredient_ids: HashSet<Uuid> = recipes.iter().flat_map(Recipe::ingredients).map(Ingredient::id).collect();
+        assert_eq!(ingredient_ids.len(), 1000);
+        assert!(ingredient_ids.iter().all(|id| id.get_version_num() == 7));
+        assert_eq!(RecipeImage::new(PNG.to_vec()).id().get_version_num(), 7);
+    }
+
+    #[test]
+    fn created_recipes_are_stored_as_drafts_with_the_given_fields() {
+        let mut cookbook = Cookbook::new();
+        let mut input = soup();
+        input.cook_minutes = Some(20);
+        input.cuisine = Some(Cuisine::Thai);
+        let created = cookbook.create(input).expect("the recipe should build");
+        assert_eq!(cookbook.get(&created.id()), Some(&created));
+        assert_eq!(created.status(), RecipeStatus::Draft);
+        assert_eq!((created.name(), created.cook_minutes(), created.cuisine()), ("Soup", 20, Some(&Cuisine::Thai)));
+
+        let mut untimed = soup();
+        untimed.cook_minutes = None;
+        assert_eq!(cookbook.create(untimed), Err(RecipeBuildError::MissingDuration));
+        assert_eq!(cookbook.len(), 1);
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn deserializing_rejects_a_client_supplied_id() {
+        let mut json = serde_json::json!({
+            "name": "Soup",
+            "difficulty": "easy",
+            "servings": 2,
+            "category": "main_course",
+            "ingredients": [{ "name": "water", "quantity": { "amount": 1.0, "unit": "liter" } }],
+        });
+        let parsed: NewRecipe = serde_json::from_value(json.clone()).expect("a recipe without an id should parse");
+        assert_eq!(parsed.ingredients[0].name, "water");
+
+        json["ingredients"][0]["id"] = serde_json::json!(Uuid::now_v7());
+        let err = serde_json::from_value::<NewRecipe>(json.clone()).expect_err("ingredient ids are rejected");
+        assert!(err.to_string().contains("unknown field `id`"), "{}", err);
+        json["ingredients"][0].as_object_mut().expect("an object").remove("id");
+        json["id"] = serde_json::json!(Uuid::now_v7());
+        let err = serde_json::from_value::<NewRecipe>(json).expect_err("recipe ids are rejected");
+        assert!(err.to_string().contains("unknown field `id`"), "{}", err);
+    }
+}
@@ -0,0 +1,115 @@
+use super::{Ingredient, Quantity};
+
+/// Words ending in "s" that are already singular, left alone by `normalize_ingredient_name`. Pass an
+/// extended list to `normalize_ingredient_name_with` to add more.
+pub const SINGULAR_EXCEPTIONS: &[&str] = &[
+    "asparagus", "bass", "citrus", "couscous", "grits", "hummus", "lemongrass", "molasses", "swiss",
+];
+
+/// Normalizes an ingredient name for matching, so `"Tomatoes"`, `"tomato"` and `" Roma  tomatoes "` become
+/// `"tomato"`, `"tomato"` and `"roma tomato"`. The name is trimmed, lowercased and its whitespace collapsed,
+/// and its last word is made singular unless it is in `SINGULAR_EXCEPTIONS`.
+pub fn normalize_ingredient_name(name: &str) -> String {
+    normalize_ingredient_name_with(name, SINGULAR_EXCEPTIONS)
+}
+
+/// Like `normalize_ingredient_name`, leaving the words in `exceptions` alone instead of those in
+/// `SINGULAR_EXCEPTIONS`. Exceptions are compared ignoring case.
+pub fn normalize_ingredient_name_with(name: &str, exceptions: &[&str]) -> String {
+    let mut words: Vec<String> = name.split_whitespace().map(str::to_lowercase).collect();
+    if let Some(last) = words.last_mut() {
+        if !exceptions.iter().any(|exception| exception.eq_ignore_ascii_case(last)) {
+            *last = singularize(last);
+        }
+    }
+    words.join(" ")
+}
+
+/// Makes a lowercase English noun singular with a few suffix rules, e.g. "berries", "tomatoes" and "eggs"
+/// become "berry", "tomato" and "egg".
+fn singularize(word: &str) -> String {
+    if let Some(stem) = word.strip_suffix("ies").filter(|stem| stem.len() > 1) {
+        return format!("{}y", stem);
+    }
+    for suffix in ["oes", "ches", "shes", "sses", "xes", "zes"] {
+        if word.len() > suffix.len() + 1 && word.ends_with(suffix) {
+            return word[..word.len() - 2].to_string();
+        }
+    }
+    match word.strip_suffix('s') {
+        Some(stem) if stem.len() > 1 && !stem.ends_with(['s', 'u', 'i']) => stem.to_string(),
+        _ => word.to_string(),
+    }
+}
+
+impl Ingredient {
+    /// Combines two entries for the same ingredient, returning `None` unless their names are the same once
+    /// normalized with `normalize_ingredient_name` and their quantities can be added up. Quantities in the
+    /// same unit are summed, and ones in different units of the same dimension, such as cups and
+    /// tablespoons, are converted into the unit of `a` first. The result keeps the id and name of `a`, and
+    /// its note unless it has none.
+    pub fn merge(a: &Ingredient, b: &Ingredient) -> Option<Ingredient> {
+        if normalize_ingredient_name(&a.name) != normalize_ingredient_name(&b.name) {
+            return None;
+        }
+        let unit = a.quantity.unit().clone();
+        let other = b.quantity.convert_to(unit.clone()).ok()?;
+        Some(Ingredient {
+            quantity: Quantity::new(a.quantity.amount() + other.amount(), unit),
+            note: a.note.clone().or_else(|| b.note.clone()),
+            ..a.clone()
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::testing::ingredient;
+    use crate::models::Unit;
+
+    #[test]
+    fn names_are_trimmed_lowercased_collapsed_and_singular() {
+        assert_eq!(normalize_ingredient_name("Tomatoes"), "tomato");
+        assert_eq!(normalize_ingredient_name("tomato"), "tomato");
+        assert_eq!(normalize_ingredient_name("  Roma   tomatoes "), "roma tomato");
+        assert_eq!(normalize_ingredient_name("Blueberries"), "blueberry");
+        assert_eq!(normalize_ingredient_name("peaches"), "peach");
+        assert_eq!(normalize_ingredient_name("eggs"), "egg");
+        assert_eq!(normalize_ingredient_name(""), "");
+    }
+
+    #[test]
+    fn exceptions_are_left_alone_and_can_be_extended() {
+        for word in ["Molasses", "couscous", "hummus", "asparagus"] {
+            assert_eq!(normalize_ingredient_name(word), word.to_lowercase());
+        }
+        assert_eq!(normalize_ingredient_name("Brussels"), "brussel");
+        let mut exceptions = SINGULAR_EXCEPTIONS.to_vec();
+        exceptions.push("brussels");
+        assert_eq!(normalize_ingredient_name_with("Brussels", &exceptions), "brussels");
+        assert_eq!(normalize_ingredient_name_with("molasses", &["brussels"]), "molass");
+    }
+
+    #[test]
+    fn ingredients_in_compatible_units_merge() {
+        let one = ingredient("Tomatoes", 1.0, Unit::Cup).with_note("diced");
+        let two = ingredient("tomato", 2.0, Unit::Cup);
+        let merged = Ingredient::merge(&one, &two).expect("the units are the same");
+        assert_eq!((merged.id(), merged.name(), merged.note()), (one.id(), "Tomatoes", Some("diced")));
+        assert_eq!(merged.quantity(), &Quantity::new(3.0, Unit::Cup));
+
+        let spoons = ingredient("milk", 4.0, Unit::Tablespoon);
+        let cup = ingredient("milk", 1.0, Unit::Cup);
+        let merged = Ingredient::merge(&cup, &spoons).expect("both are volumes");
+        assert_eq!(merged.quantity(), &Quantity::new(1.25, Unit::Cup));
+    }
+
+    #[test]
+    fn grams_and_cups_or_different_ingredients_do_not_merge() {
+        let grams = ingredient("flour", 200.0, Unit::Gram);
+        let cups = ingredient("flour", 1.0, Unit::Cup);
+        assert_eq!(Ingredient::merge(&grams, &cups), None);
+        assert_eq!(Ingredient::merge(&cups, &ingredient("sugar", 1.0, Unit::Cup)), None);
+    }
+}
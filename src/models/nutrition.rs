@@ -0,0 +1,131 @@
+use std::ops::Add;
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+/// Nutrition facts for a recipe, either for the whole recipe or for a single serving. Every value is
+/// optional so that "unknown" can be told apart from zero.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Nutrition {
+    /// The energy in kilocalories
+    pub calories: Option<f32>,
+    /// The protein in grams
+    pub protein_g: Option<f32>,
+    /// The carbohydrates in grams
+    pub carbs_g: Option<f32>,
+    /// The fat in grams
+    pub fat_g: Option<f32>,
+    /// The fiber in grams
+    pub fiber_g: Option<f32>,
+    /// The sugar in grams
+    pub sugar_g: Option<f32>,
+    /// The sodium in milligrams
+    pub sodium_mg: Option<f32>,
+    /// Whether the values are for a single serving rather than the whole recipe
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub per_serving: bool,
+}
+
+impl Nutrition {
+    /// Divides whole recipe nutrition facts into the facts for one of `servings` servings. Facts that are
+    /// already per serving are returned unchanged, and `None` is returned if `servings` is zero.
+    pub fn per_serving(&self, servings: u16) -> Option<Nutrition> {
+        if self.per_serving {
+            return Some(*self);
+        }
+        if servings == 0 {
+            return None;
+        }
+        let servings = f32::from(servings);
+        Some(Nutrition { per_serving: true, ..self.map(|value| value / servings) })
+    }
+
+    /// Applies `f` to every known value.
+    fn map<F: Fn(f32) -> f32>(&self, f: F) -> Nutrition {
+        Nutrition {
+            calories: self.calories.map(&f),
+            protein_g: self.protein_g.map(&f),
+            carbs_g: self.carbs_g.map(&f),
+            fat_g: self.fat_g.map(&f),
+            fiber_g: self.fiber_g.map(&f),
+            sugar_g: self.sugar_g.map(&f),
+            sodium_mg: self.sodium_mg.map(&f),
+            per_serving: self.per_serving,
+        }
+    }
+}
+
+impl Add for Nutrition {
+    type Output = Nutrition;
+
+    /// Combines the nutrition facts of two component recipes, which should be on the same basis. A value
+    /// that is unknown in either is unknown in the sum, and the sum is only per serving if both are.
+    fn add(self, other: Nutrition) -> Nutrition {
+        let sum = |a: Option<f32>, b: Option<f32>| Some(a? + b?);
+        Nutrition {
+            calories: sum(self.calories, other.calories),
+            protein_g: sum(self.protein_g, other.protein_g),
+            carbs_g: sum(self.carbs_g, other.carbs_g),
+            fat_g: sum(self.fat_g, other.fat_g),
+            fiber_g: sum(self.fiber_g, other.fiber_g),
+            sugar_g: sum(self.sugar_g, other.sugar_g),
+            sodium_mg: sum(self.sodium_mg, other.sodium_mg),
+            per_serving: self.per_serving && other.per_serving,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::testing::recipe;
+
+    #[test]
+    fn adding_nutrition_sums_known_values_and_keeps_unknown_ones_unknown() {
+        let pasta = Nutrition {
+            calories: Some(400.0),
+            protein_g: Some(12.0),
+            fat_g: Some(2.0),
+            ..Nutrition::default()
+        };
+        let sauce = Nutrition { calories: Some(150.0), protein_g: Some(3.0), ..Nutrition::default() };
+        let dinner = pasta + sauce;
+        assert_eq!(dinner.calories, Some(550.0));
+        assert_eq!(dinner.protein_g, Some(15.0));
+        assert_eq!(dinner.fat_g, None);
+        assert_eq!(dinner.sodium_mg, None);
+        assert!(!dinner.per_serving);
+    }
+
+    #[test]
+    fn whole_recipe_nutrition_divides_into_servings() {
+        let whole = Nutrition { calories: Some(1000.0), sodium_mg: Some(800.0), ..Nutrition::default() };
+        let serving = whole.per_serving(4).expect("four servings should divide");
+        assert_eq!(serving.calories, Some(250.0));
+        assert_eq!(serving.sodium_mg, Some(200.0));
+        assert_eq!(serving.protein_g, None);
+        assert!(serving.per_serving);
+        assert_eq!(serving.per_serving(4), Some(serving));
+        assert_eq!(whole.per_serving(0), None);
+    }
+
+    #[test]
+    fn the_builder_sets_the_nutrition_of_a_recipe() {
+        let nutrition = Nutrition { calories: Some(0.0), ..Nutrition::default() };
+        let built = recipe("Tea").nutrition(nutrition).build().expect("the recipe should build");
+        assert_eq!(built.nutrition(), Some(&nutrition));
+        assert_eq!(recipe("Tea").build().expect("the recipe should build").nutrition(), None);
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn unknown_nutrition_serializes_as_null_rather_than_zero() {
+        let unknown = serde_json::to_value(recipe("Tea").build().expect("the recipe should build"))
+            .expect("the recipe should serialize");
+        assert_eq!(unknown["nutrition"], serde_json::Value::Null);
+
+        let zero = Nutrition { calories: Some(0.0), ..Nutrition::default() };
+        let json = serde_json::to_value(zero).expect("the nutrition should serialize");
+        assert_eq!((&json["calories"], &json["protein_g"]), (&serde_json::json!(0.0), &serde_json::Value::Null));
+    }
+}
@@ -0,0 +1,227 @@
+use std::io::Cursor;
+use ::image::codecs::jpeg::JpegEncoder;
+use ::image::codecs::png::PngEncoder;
+use ::image::codecs::webp::WebPEncoder;
+use ::image::imageops::FilterType;
+use ::image::{DynamicImage, ImageDecoder, ImageReader};
+use super::{ImageData, ImageError, ImageFormat, Recipe, RecipeImage};
+
+/// The JPEG quality thumbnails are encoded with, from 1 to 100.
+const THUMBNAIL_QUALITY: u8 = 80;
+
+/// The JPEG quality images are re-encoded with when stripping their metadata, from 1 to 100.
+const STRIPPED_QUALITY: u8 = 90;
+
+/// Decodes `bytes` and applies the orientation recorded in their EXIF metadata, so the result is the
+/// right way up.
+fn decode(bytes: &[u8]) -> Result<DynamicImage, ImageError> {
+    if bytes.is_empty() {
+        return Err(ImageError::Empty);
+    }
+    let decode_error = |err: ::image::ImageError| ImageError::Decode(err.to_string());
+    let mut decoder = ImageReader::new(Cursor::new(bytes))
+        .with_guessed_format()
+        .map_err(|err| ImageError::Decode(err.to_string()))?
+        .into_decoder()
+        .map_err(decode_error)?;
+    let orientation = decoder.orientation().map_err(decode_error)?;
+    let mut img = DynamicImage::from_decoder(decoder).map_err(decode_error)?;
+    img.apply_orientation(orientation);
+    Ok(img)
+}
+
+/// Decodes `bytes`, shrinks the image so neither side is longer than `max_dim` pixels while keeping its
+/// aspect ratio, and re-encodes it as JPEG. Images that already fit are re-encoded without resizing, and a
+/// `max_dim` of 0 is treated as 1.
+fn thumbnail(bytes: &[u8], max_dim: u32) -> Result<Vec<u8>, ImageError> {
+    let max_dim = max_dim.max(1);
+    let img = decode(bytes)?;
+    let img = if img.width() > max_dim || img.height() > max_dim {
+        img.resize(max_dim, max_dim, FilterType::Triangle)
+    } else {
+        img
+    };
+    let mut out = Cursor::new(Vec::new());
+    JpegEncoder::new_with_quality(&mut out, THUMBNAIL_QUALITY)
+        .encode_image(&img.to_rgb8())
+        .map_err(|err| ImageError::Encode(err.to_string()))?;
+    Ok(out.into_inner())
+}
+
+impl ImageData {
+    /// A JPEG thumbnail of the image no larger than `max_dim` pixels on either side.
+    pub fn thumbnail(&self, max_dim: u32) -> Result<Vec<u8>, ImageError> {
+        thumbnail(self.bytes(), max_dim)
+    }
+
+    /// Re-encodes the image in the same format without its EXIF, XMP or other metadata, such as the GPS
+    /// position a phone photo was taken at. The EXIF orientation is applied to the pixels first so the
+    /// image stays the right way up. JPEG images are re-encoded lossily, WebP images losslessly. GIF images
+    /// are returned unchanged since they rarely carry such metadata and re-encoding would drop animation.
+    pub fn strip_metadata(&self) -> Result<ImageData, ImageError> {
+        let mut out = Cursor::new(Vec::new());
+        let encoded = match self.format() {
+            ImageFormat::Gif => return Ok(self.clone()),
+            ImageFormat::Jpeg => {
+                JpegEncoder::new_with_quality(&mut out, STRIPPED_QUALITY).encode_image(&decode(self.bytes())?.to_rgb8())
+            }
+            ImageFormat::Png => decode(self.bytes())?.write_with_encoder(PngEncoder::new(&mut out)),
+            ImageFormat::WebP => DynamicImage::from(decode(self.bytes())?.to_rgba8())
+                .write_with_encoder(WebPEncoder::new_lossless(&mut out)),
+        };
+        encoded.map_err(|err| ImageError::Encode(err.to_string()))?;
+        // Re-encoding can make the image larger than the original, so only its format is checked here and
+        // not the size limit it was accepted under.
+        ImageData::from_bytes_with_limit(out.into_inner(), usize::MAX)
+    }
+}
+
+impl RecipeImage {
+    /// A JPEG thumbnail of the image no larger than `max_dim` pixels on either side, cache it under
+    /// `thumbnail_cache_key`.
+    pub fn thumbnail(&self, max_dim: u32) -> Result<Vec<u8>, ImageError> {
+        thumbnail(self.bytes(), max_dim)
+    }
+}
+
+impl Recipe {
+    /// A JPEG thumbnail of the primary image no larger than `max_dim` pixels on either side, failing with
+    /// `ImageError::Empty` if there is no primary image.
+    pub fn thumbnail(&self, max_dim: u32) -> Result<Vec<u8>, ImageError> {
+        self.primary_image().ok_or(ImageError::Empty)?.thumbnail(max_dim)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ::image::{ImageFormat as Codec, RgbImage};
+    use crate::models::testing::recipe;
+
+    /// A PNG image of `width` by `height` pixels with a gradient, so it is not trivially compressible.
+    fn png(width: u32, height: u32) -> Vec<u8> {
+        let img = RgbImage::from_fn(width, height, |x, y| ::image::Rgb([(x % 256) as u8, (y % 256) as u8, 128]));
+        let mut out = Cursor::new(Vec::new());
+        img.write_to(&mut out, Codec::Png).expect("the image should encode");
+        out.into_inner()
+    }
+
+    /// A JPEG image of `width` by `height` pixels with an EXIF block recording `orientation` and a GPS
+    /// version, the way a phone camera writes one.
+    fn jpeg_with_exif(width: u32, height: u32, orientation: u8) -> Vec<u8> {
+        let img = RgbImage::from_fn(width, height, |x, _| ::image::Rgb([(x % 256) as u8, 0, 0]));
+        let mut jpeg = Cursor::new(Vec::new());
+        img.write_to(&mut jpeg, Codec::Jpeg).expect("the image should encode");
+        let jpeg = jpeg.into_inner();
+
+        let mut tiff = b"MM\x00\x2A\x00\x00\x00\x08".to_vec();
+        // IFD0 with the orientation and a pointer to the GPS IFD right after it.
+        tiff.extend([0x00, 0x02]);
+        tiff.extend([0x01, 0x12, 0x00, 0x03, 0x00, 0x00, 0x00, 0x01, 0x00, orientation, 0x00, 0x00]);
+        tiff.extend([0x88, 0x25, 0x00, 0x04, 0x00, 0x00, 0x00, 0x01, 0x00, 0x00, 0x00, 0x26]);
+        tiff.extend([0x00, 0x00, 0x00, 0x00]);
+        // The GPS IFD with its version.
+        tiff.extend([0x00, 0x01]);
+        tiff.extend([0x00, 0x00, 0x00, 0x01, 0x00, 0x00, 0x00, 0x04, 0x02, 0x02, 0x00, 0x00]);
+        tiff.extend([0x00, 0x00, 0x00, 0x00]);
+
+        let segment_len = u16::try_from(2 + 6 + tiff.len()).expect("the EXIF block is small");
+        let mut out = jpeg[..2].to_vec();
+        out.extend([0xFF, 0xE1]);
+        out.extend(segment_len.to_be_bytes());
+        out.extend(b"Exif\x00\x00");
+        out.extend(tiff);
+        out.extend(&jpeg[2..]);
+        out
+    }
+
+    fn exif(bytes: &[u8]) -> Option<Vec<u8>> {
+        let reader = ImageReader::new(Cursor::new(bytes)).with_guessed_format().expect("the format should be read");
+        let mut decoder = reader.into_decoder().expect("the image should decode");
+        decoder.exif_metadata().expect("the metadata should be read")
+    }
+
+    fn contains(haystack: &[u8], needle: &[u8]) -> bool {
+        haystack.windows(needle.len()).any(|window| window == needle)
+    }
+
+    fn dimensions(bytes: &[u8]) -> (u32, u32) {
+        let img = ::image::load_from_memory(bytes).expect("the image should decode");
+        (img.width(), img.height())
+    }
+
+    #[test]
+    fn thumbnails_never_exceed_max_dim_and_keep_the_aspect_ratio() {
+        let wide = ImageData::from_bytes(png(400, 100)).expect("the image should be accepted");
+        let thumb = wide.thumbnail(64).expect("the thumbnail should be made");
+        assert_eq!(ImageFormat::detect(&thumb), Some(ImageFormat::Jpeg));
+        assert_eq!(dimensions(&thumb), (64, 16));
+
+        let tall = RecipeImage::new(png(90, 300));
+        let (width, height) = dimensions(&tall.thumbnail(100).expect("the thumbnail should be made"));
+        assert!(width <= 100 && height <= 100, "{}x{} is larger than 100", width, height);
+        assert_eq!(height, 100);
+    }
+
+    #[test]
+    fn small_images_are_not_enlarged() {
+        let small = RecipeImage::new(png(20, 10));
+        assert_eq!(dimensions(&small.thumbnail(200).expect("the thumbnail should be made")), (20, 10));
+        assert_eq!(dimensions(&small.thumbnail(0).expect("the thumbnail should be made")), (1, 1));
+    }
+
+    #[test]
+    fn undecodable_images_are_errors_rather_than_panics() {
+        let truncated = RecipeImage::new(png(50, 50)[..40].to_vec());
+        assert!(matches!(truncated.thumbnail(10), Err(ImageError::Decode(_))));
+        assert!(matches!(RecipeImage::new(b"not an image".to_vec()).thumbnail(10), Err(ImageError::Decode(_))));
+        assert_eq!(RecipeImage::new(Vec::new()).thumbnail(10), Err(ImageError::Empty));
+    }
+
+    #[test]
+    fn the_recipe_thumbnail_uses_the_primary_image() {
+        let built = recipe("Soup").img(png(300, 300)).build().expect("the recipe should build");
+        assert_eq!(dimensions(&built.thumbnail(30).expect("the thumbnail should be made")), (30, 30));
+        let plain = recipe("Soup").build().expect("the recipe should build");
+        assert_eq!(plain.thumbnail(30), Err(ImageError::Empty));
+    }
+
+    #[test]
+    fn the_cache_key_is_the_image_id_and_the_size() {
+        let image = RecipeImage::new(png(2, 2));
+        assert_eq!(image.thumbnail_cache_key(128), format!("{}-128", image.id()));
+        assert_eq!(image.thumbnail_cache_key(128), image.thumbnail_cache_key(128));
+        assert_ne!(image.thumbnail_cache_key(128), image.thumbnail_cache_key(64));
+    }
+
+    #[test]
+    fn stripping_removes_the_exif_block_and_its_gps_position() {
+        let photo = ImageData::from_bytes(jpeg_with_exif(40, 20, 1)).expect("the image should be accepted");
+        let exif_block = exif(photo.bytes()).expect("the fixture has an EXIF block");
+        assert!(contains(&exif_block, &[0x88, 0x25]), "the fixture has a GPS IFD");
+
+        let stripped = photo.strip_metadata().expect("the metadata should be stripped");
+        assert_eq!(stripped.format(), ImageFormat::Jpeg);
+        assert_eq!(exif(stripped.bytes()), None);
+        assert!(!contains(stripped.bytes(), b"Exif\x00\x00"));
+        assert_eq!(dimensions(stripped.bytes()), (40, 20));
+    }
+
+    #[test]
+    fn stripping_applies_the_orientation_before_removing_it() {
+        // An orientation of 6 means the camera was turned a quarter, so the picture is shown rotated.
+        let rotated = ImageData::from_bytes(jpeg_with_exif(40, 20, 6)).expect("the image should be accepted");
+        let stripped = rotated.strip_metadata().expect("the metadata should be stripped");
+        assert_eq!(exif(stripped.bytes()), None);
+        assert_eq!(dimensions(stripped.bytes()), (20, 40));
+    }
+
+    #[test]
+    fn stripping_keeps_the_format_of_png_images_and_leaves_gif_images_alone() {
+        let png = ImageData::from_bytes(png(8, 4)).expect("the image should be accepted");
+        let stripped = png.strip_metadata().expect("the metadata should be stripped");
+        assert_eq!((stripped.format(), dimensions(stripped.bytes())), (ImageFormat::Png, (8, 4)));
+        let gif = ImageData::from_bytes(b"GIF89a\x01\x00".to_vec()).expect("the header should be accepted");
+        assert_eq!(gif.strip_metadata(), Ok(gif));
+    }
+}
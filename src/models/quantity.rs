@@ -0,0 +1,344 @@
+use std::error::Error;
+use std::fmt;
+use std::str::FromStr;
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+/// The amount of an ingredient needed for a recipe, e.g. `1.5 cups` or `200 g`.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Quantity {
+    /// The numeric amount, ignored when the unit is `Unit::ToTaste`
+    amount: f64,
+    /// The unit the amount is measured in
+    unit: Unit,
+}
+
+impl Quantity {
+    pub fn new(amount: f64, unit: Unit) -> Self {
+        Self { amount, unit }
+    }
+
+    /// A quantity that is left up to the cook, e.g. salt "to taste".
+    pub fn to_taste() -> Self {
+        Self { amount: 0.0, unit: Unit::ToTaste }
+    }
+
+    pub fn amount(&self) -> f64 {
+        self.amount
+    }
+
+    pub fn unit(&self) -> &Unit {
+        &self.unit
+    }
+
+    /// Returns the quantity with its amount multiplied by `factor`, quantities that are "to taste"
+    /// or a pinch are returned unchanged.
+    pub fn scale(&self, factor: f64) -> Quantity {
+        match self.unit {
+            Unit::ToTaste | Unit::Pinch => self.clone(),
+            _ => Quantity { amount: self.amount * factor, unit: self.unit.clone() },
+        }
+    }
+
+    /// The quantity as it would be written in a recipe with plain text fractions, e.g. `1 1/2 cup` or `1/2`
+    /// for half a piece, for text where the fraction characters used by `Display` may not render. Amounts are
+    /// rounded the same way, so `2.0` is shown as `2` and `1.33` as `1.33`.
+    pub fn to_plain_string(&self) -> String {
+        self.render(true)
+    }
+
+    fn render(&self, plain: bool) -> String {
+        if self.unit == Unit::ToTaste {
+            return self.unit.to_string();
+        }
+        let amount = format_amount(self.amount, plain);
+        match self.unit {
+            Unit::Piece => amount,
+            _ => format!("{} {}", amount, self.unit),
+        }
+    }
+
+    /// Parses a quantity from text such as `"1 1/2 cups"`, `"1.5 cups"`, `"200g"`, `"3"` or `"to taste"`.
+    ///
+    /// A leading amount may be a whole number, a decimal, a fraction or a mixed number. A missing amount
+    /// defaults to 1 (`"pinch"`), a missing unit defaults to `Unit::Piece` (`"3"`) and unrecognized units
+    /// are kept as `Unit::Other`.
+    pub fn parse(s: &str) -> Result<Self, ParseQuantityError> {
+        let s = s.trim();
+        if s.is_empty() {
+            return Err(ParseQuantityError::Empty);
+        }
+
+        let mut amount = None;
+        let mut rest = s;
+        while let Some(token) = rest.split_whitespace().next() {
+            // Split a token such as "200g" into its numeric prefix and the unit that follows it.
+            let split = token.find(|c: char| !(c.is_ascii_digit() || c == '.' || c == '/')).unwrap_or(token.len());
+            if split == 0 {
+                break;
+            }
+            let value = parse_amount(&token[..split])?;
+            amount = Some(amount.unwrap_or(0.0) + value);
+            rest = rest.trim_start()[split..].trim_start();
+            if split < token.len() {
+                break;
+            }
+        }
+
+        let unit = if rest.is_empty() { Unit::Piece } else { rest.parse().unwrap_or_else(|e| match e {}) };
+        if unit == Unit::ToTaste {
+            return Ok(Quantity::to_taste());
+        }
+        Ok(Quantity { amount: amount.unwrap_or(1.0), unit })
+    }
+}
+
+impl FromStr for Quantity {
+    type Err = ParseQuantityError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Quantity::parse(s)
+    }
+}
+
+/// Common fractions with the characters and the plain text used to display them.
+const FRACTIONS: &[(f64, &str, &str)] = &[
+    (1.0 / 8.0, "⅛", "1/8"),
+    (1.0 / 4.0, "¼", "1/4"),
+    (1.0 / 3.0, "⅓", "1/3"),
+    (3.0 / 8.0, "⅜", "3/8"),
+    (1.0 / 2.0, "½", "1/2"),
+    (5.0 / 8.0, "⅝", "5/8"),
+    (2.0 / 3.0, "⅔", "2/3"),
+    (3.0 / 4.0, "¾", "3/4"),
+    (7.0 / 8.0, "⅞", "7/8"),
+];
+
+impl fmt::Display for Quantity {
+    /// Displays the quantity as it would be written in a recipe, e.g. `1 ½ cup`. Amounts within 0.002 of a
+    /// common fraction are shown as that fraction, any others with at most two decimal places.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.render(false))
+    }
+}
+
+/// Formats `amount` as a whole number, a common fraction, a mixed number or a decimal with at most two
+/// decimal places. Fractions are written as characters such as `½`, or as text such as `1/2` if `plain`. A
+/// negative amount is its magnitude formatted this way with a leading `-`, unless that rounds to `0`.
+fn format_amount(amount: f64, plain: bool) -> String {
+    let magnitude = format_magnitude(amount.abs(), plain);
+    if amount < 0.0 && magnitude != "0" {
+        format!("-{}", magnitude)
+    } else {
+        magnitude
+    }
+}
+
+/// Formats a non-negative `amount` like `format_amount`.
+fn format_magnitude(amount: f64, plain: bool) -> String {
+    let whole = amount.trunc();
+    let fraction = amount - whole;
+    if fraction < 0.002 {
+        format!("{}", whole)
+    } else if fraction > 0.998 {
+        format!("{}", whole + 1.0)
+    } else if let Some((_, glyph, text)) = FRACTIONS.iter().find(|(value, _, _)| (fraction - value).abs() < 0.002) {
+        let fraction = if plain { text } else { glyph };
+        if whole == 0.0 { fraction.to_string() } else { format!("{} {}", whole, fraction) }
+    } else {
+        format!("{:.2}", amount).trim_end_matches('0').trim_end_matches('.').to_string()
+    }
+}
+
+/// Parses a single whole number, decimal or fraction such as `"2"`, `"1.5"` or `"1/2"`.
+pub(crate) fn parse_amount(s: &str) -> Result<f64, ParseQuantityError> {
+    let invalid = || ParseQuantityError::InvalidAmount(s.to_string());
+    let value = match s.split_once('/') {
+        Some((numerator, denominator)) => {
+            let numerator: f64 = numerator.parse().map_err(|_| invalid())?;
+            let denominator: f64 = denominator.parse().map_err(|_| invalid())?;
+            if denominator == 0.0 {
+                return Err(invalid());
+            }
+            numerator / denominator
+        }
+        None => s.parse().map_err(|_| invalid())?,
+    };
+    if value.is_finite() {
+        Ok(value)
+    } else {
+        Err(invalid())
+    }
+}
+
+/// The error returned when text cannot be parsed into a `Quantity`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ParseQuantityError {
+    /// The text was empty
+    Empty,
+    /// The amount could not be read as a number or fraction
+    InvalidAmount(String),
+}
+
+impl fmt::Display for ParseQuantityError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ParseQuantityError::Empty => write!(f, "cannot parse quantity from empty text"),
+            ParseQuantityError::InvalidAmount(amount) => write!(f, "invalid quantity amount \"{}\"", amount),
+        }
+    }
+}
+
+impl Error for ParseQuantityError {}
+
+/// A unit of measurement commonly used in cooking.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum Unit {
+    Teaspoon,
+    Tablespoon,
+    Cup,
+    FluidOunce,
+    Milliliter,
+    Liter,
+    Gram,
+    Kilogram,
+    Ounce,
+    Pound,
+    /// A count of whole items, e.g. 3 eggs
+    Piece,
+    Pinch,
+    ToTaste,
+    /// Any unit not covered by the other variants
+    Other(String),
+}
+
+impl FromStr for Unit {
+    type Err = std::convert::Infallible;
+
+    /// Parses a unit from its name or a common abbreviation, singular or plural. Unrecognized units are
+    /// returned as `Unit::Other`, so this never fails.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let normalized = s.trim().trim_end_matches('.').to_lowercase();
+        let unit = match normalized.as_str() {
+            "tsp" | "tsps" | "teaspoon" | "teaspoons" => Unit::Teaspoon,
+            "tbsp" | "tbsps" | "tbs" | "tablespoon" | "tablespoons" => Unit::Tablespoon,
+            "c" | "cup" | "cups" => Unit::Cup,
+            "fl oz" | "fl. oz" | "floz" | "fluid ounce" | "fluid ounces" => Unit::FluidOunce,
+            "ml" | "milliliter" | "milliliters" | "millilitre" | "millilitres" => Unit::Milliliter,
+            "l" | "liter" | "liters" | "litre" | "litres" => Unit::Liter,
+            "g" | "gram" | "grams" | "gramme" | "grammes" => Unit::Gram,
+            "kg" | "kgs" | "kilogram" | "kilograms" => Unit::Kilogram,
+            "oz" | "ounce" | "ounces" => Unit::Ounce,
+            "lb" | "lbs" | "pound" | "pounds" => Unit::Pound,
+            "" | "piece" | "pieces" | "pc" | "pcs" => Unit::Piece,
+            "pinch" | "pinches" => Unit::Pinch,
+            "to taste" => Unit::ToTaste,
+            _ => Unit::Other(s.trim().to_string()),
+        };
+        Ok(unit)
+    }
+}
+
+impl fmt::Display for Unit {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let s = match self {
+            Unit::Teaspoon => "tsp",
+            Unit::Tablespoon => "tbsp",
+            Unit::Cup => "cup",
+            Unit::FluidOunce => "fl oz",
+            Unit::Milliliter => "ml",
+            Unit::Liter => "l",
+            Unit::Gram => "g",
+            Unit::Kilogram => "kg",
+            Unit::Ounce => "oz",
+            Unit::Pound => "lb",
+            Unit::Piece => "piece",
+            Unit::Pinch => "pinch",
+            Unit::ToTaste => "to taste",
+            Unit::Other(unit) => unit,
+        };
+        f.write_str(s)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parsed(s: &str) -> Quantity {
+        Quantity::parse(s).unwrap_or_else(|err| panic!("{:?} should parse: {}", s, err))
+    }
+
+    #[test]
+    fn parses_fractions_decimals_and_mixed_numbers() {
+        assert_eq!(parsed("1 1/2 cups"), Quantity::new(1.5, Unit::Cup));
+        assert_eq!(parsed("1.5 cups"), Quantity::new(1.5, Unit::Cup));
+        assert_eq!(parsed("200g"), Quantity::new(200.0, Unit::Gram));
+        assert_eq!(parsed("2 fl oz"), Quantity::new(2.0, Unit::FluidOunce));
+    }
+
+    #[test]
+    fn parses_counts_pinches_and_to_taste() {
+        assert_eq!(parsed("3"), Quantity::new(3.0, Unit::Piece));
+        assert_eq!(parsed("pinch"), Quantity::new(1.0, Unit::Pinch));
+        assert_eq!(parsed("to taste"), Quantity::to_taste());
+    }
+
+    #[test]
+    fn keeps_unknown_units() {
+        assert_eq!(parsed("2 cloves"), Quantity::new(2.0, Unit::Other("cloves".to_string())));
+        assert_eq!(parsed("1 Handful").unit().to_string(), "Handful");
+    }
+
+    #[test]
+    fn rejects_empty_text_and_invalid_amounts() {
+        assert_eq!(Quantity::parse("  "), Err(ParseQuantityError::Empty));
+        assert_eq!(Quantity::parse("1/0 cup"), Err(ParseQuantityError::InvalidAmount("1/0".to_string())));
+        assert_eq!(Quantity::parse("1..5 cup"), Err(ParseQuantityError::InvalidAmount("1..5".to_string())));
+    }
+
+    #[test]
+    fn from_str_parses_fractions_and_spaced_units() {
+        assert_eq!("1/2 cup".parse(), Ok(Quantity::new(0.5, Unit::Cup)));
+        assert_eq!("3/4 tsp".parse(), Ok(Quantity::new(0.75, Unit::Teaspoon)));
+        assert_eq!("200 g".parse(), Ok(Quantity::new(200.0, Unit::Gram)));
+        assert_eq!("".parse::<Quantity>(), Err(ParseQuantityError::Empty));
+    }
+
+    #[test]
+    fn amounts_display_as_common_fractions_whole_numbers_or_trimmed_decimals() {
+        let piece = |amount| Quantity::new(amount, Unit::Piece).to_plain_string();
+        assert_eq!(piece(0.5), "1/2");
+        assert_eq!(piece(0.75), "3/4");
+        assert_eq!(piece(2.0), "2");
+        assert_eq!(piece(1.33), "1.33");
+        assert_eq!(piece(1.0 / 3.0), "1/3");
+        assert_eq!(piece(0.2501), "1/4");
+        assert_eq!(piece(2.999), "3");
+        assert_eq!(piece(1.1), "1.1");
+        assert_eq!(Quantity::new(1.5, Unit::Cup).to_plain_string(), "1 1/2 cup");
+        assert_eq!(Quantity::new(1.5, Unit::Cup).to_string(), "1 ½ cup");
+        assert_eq!(Quantity::to_taste().to_plain_string(), Unit::ToTaste.to_string());
+    }
+
+    #[test]
+    fn scaled_halves_display_as_a_fraction_rather_than_a_decimal() {
+        assert_eq!(Quantity::new(1.0, Unit::Cup).scale(0.5).to_plain_string(), "1/2 cup");
+        assert_eq!(Quantity::new(0.25, Unit::Cup).scale(3.0).to_plain_string(), "3/4 cup");
+    }
+
+    #[test]
+    fn negative_amounts_keep_their_sign_in_front_of_the_whole_amount() {
+        let piece = |amount| Quantity::new(amount, Unit::Piece);
+        assert_eq!(piece(-0.5).to_plain_string(), "-1/2");
+        assert_eq!(piece(-0.5).to_string(), "-½");
+        assert_eq!(Quantity::new(-1.5, Unit::Cup).to_string(), "-1 ½ cup");
+        assert_eq!(piece(-2.0).to_plain_string(), "-2");
+        assert_eq!(piece(-2.999).to_plain_string(), "-3");
+        assert_eq!(piece(-1.33).to_plain_string(), "-1.33");
+        assert_eq!(piece(-0.001).to_plain_string(), "0");
+    }
+}
@@ -0,0 +1,270 @@
+use std::collections::HashMap;
+use chrono::{DateTime, Utc};
+use uuid::Uuid;
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+use super::{Recipe, ValidationError, ValidationErrors};
+
+/// A user's rating of a recipe from one to five stars, with an optional written review.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Rating {
+    /// The id of the rating
+    id: Uuid,
+    /// The id of the rated recipe
+    recipe_id: Uuid,
+    /// The id of the user who rated the recipe
+    user_id: Uuid,
+    /// The number of stars, from `Rating::MIN_STARS` to `Rating::MAX_STARS`
+    stars: u8,
+    /// The written review, if any
+    #[cfg_attr(feature = "serde", serde(default, skip_serializing_if = "Option::is_none"))]
+    review: Option<String>,
+    /// When the rating was given, serialized as RFC 3339
+    created_at: DateTime<Utc>,
+}
+
+impl Rating {
+    /// The fewest stars a recipe can be given.
+    pub const MIN_STARS: u8 = 1;
+    /// The most stars a recipe can be given.
+    pub const MAX_STARS: u8 = 5;
+    /// The longest allowed review in characters.
+    pub const MAX_REVIEW_LEN: usize = 2000;
+
+    /// Rates the recipe `recipe_id` on behalf of `user_id` with a new id. A blank review is stored as none.
+    /// Fails if `stars` is outside `MIN_STARS..=MAX_STARS` or the review is longer than `MAX_REVIEW_LEN`.
+    pub fn new(recipe_id: Uuid, user_id: Uuid, stars: u8, review: Option<String>) -> Result<Rating, ValidationErrors> {
+        let rating = Rating {
+            id: Uuid::now_v7(),
+            recipe_id,
+            user_id,
+            stars,
+            review: review.filter(|review| !review.trim().is_empty()),
+            created_at: Utc::now(),
+        };
+        rating.validate()?;
+        Ok(rating)
+    }
+
+    pub fn id(&self) -> Uuid {
+        self.id
+    }
+
+    pub fn recipe_id(&self) -> Uuid {
+        self.recipe_id
+    }
+
+    pub fn user_id(&self) -> Uuid {
+        self.user_id
+    }
+
+    pub fn stars(&self) -> u8 {
+        self.stars
+    }
+
+    pub fn review(&self) -> Option<&str> {
+        self.review.as_deref()
+    }
+
+    pub fn created_at(&self) -> DateTime<Utc> {
+        self.created_at
+    }
+
+    /// Checks the stars and review, which matters for ratings that were deserialized rather than built
+    /// with `Rating::new`.
+    pub fn validate(&self) -> Result<(), ValidationErrors> {
+        let mut errors = Vec::new();
+        if self.stars == 0 {
+            errors.push(ValidationError::Zero { field: "stars" });
+        } else if self.stars > Rating::MAX_STARS {
+            errors.push(ValidationError::TooLarge {
+                field: "stars",
+                max: Rating::MAX_STARS.into(),
+                actual: self.stars.into(),
+            });
+        }
+        if let Some(review) = &self.review {
+            let actual = review.chars().count();
+            if actual > Rating::MAX_REVIEW_LEN {
+                errors.push(ValidationError::TooLong { field: "review", max: Rating::MAX_REVIEW_LEN, actual });
+            }
+        }
+        ValidationErrors::check(errors)
+    }
+}
+
+/// The ratings of a recipe summed up, obtained through `RatingSummary::of` or `Ratings::summary`.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct RatingSummary {
+    /// The mean number of stars, zero if there are no ratings
+    pub average: f32,
+    /// The number of ratings
+    pub count: u64,
+    /// How many ratings gave one to five stars, starting with one star
+    pub histogram: [u64; 5],
+}
+
+impl RatingSummary {
+    /// Sums up `ratings`, which should all be for the same recipe.
+    pub fn of<'a>(ratings: impl IntoIterator<Item = &'a Rating>) -> RatingSummary {
+        let mut summary = RatingSummary::default();
+        let mut total = 0u64;
+        for rating in ratings {
+            let stars = rating.stars.clamp(Rating::MIN_STARS, Rating::MAX_STARS);
+            summary.histogram[usize::from(stars - 1)] += 1;
+            summary.count += 1;
+            total += u64::from(stars);
+        }
+        if summary.count > 0 {
+            summary.average = (total as f64 / summary.count as f64) as f32;
+        }
+        summary
+    }
+}
+
+/// The ratings of every recipe, at most one per user per recipe.
+#[derive(Debug, Clone, Default)]
+pub struct Ratings {
+    ratings: HashMap<(Uuid, Uuid), Rating>,
+}
+
+impl Ratings {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds `rating`, replacing and returning the rating the same user gave the same recipe before.
+    pub fn upsert(&mut self, rating: Rating) -> Option<Rating> {
+        self.ratings.insert((rating.recipe_id, rating.user_id), rating)
+    }
+
+    /// Removes the rating `user_id` gave `recipe_id`, returning it if there was one.
+    pub fn remove(&mut self, recipe_id: &Uuid, user_id: &Uuid) -> Option<Rating> {
+        self.ratings.remove(&(*recipe_id, *user_id))
+    }
+
+    /// Removes every rating of `recipe_id`, e.g. when the recipe is deleted, returning them.
+    pub fn remove_recipe(&mut self, recipe_id: &Uuid) -> Vec<Rating> {
+        let (removed, kept) = std::mem::take(&mut self.ratings)
+            .into_iter()
+            .partition(|((recipe, _), _)| recipe == recipe_id);
+        self.ratings = kept;
+        removed.into_values().collect()
+    }
+
+    pub fn get(&self, recipe_id: &Uuid, user_id: &Uuid) -> Option<&Rating> {
+        self.ratings.get(&(*recipe_id, *user_id))
+    }
+
+    /// The ratings of `recipe_id`, newest first.
+    pub fn for_recipe(&self, recipe_id: &Uuid) -> Vec<&Rating> {
+        let mut ratings: Vec<&Rating> = self.ratings.values().filter(|rating| rating.recipe_id == *recipe_id).collect();
+        ratings.sort_by(|a, b| b.created_at.cmp(&a.created_at).then_with(|| a.id.cmp(&b.id)));
+        ratings
+    }
+
+    /// The ratings of `recipe_id` summed up, computed from the current ratings.
+    pub fn summary(&self, recipe_id: &Uuid) -> RatingSummary {
+        RatingSummary::of(self.ratings.values().filter(|rating| rating.recipe_id == *recipe_id))
+    }
+
+    pub fn len(&self) -> usize {
+        self.ratings.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.ratings.is_empty()
+    }
+}
+
+/// A recipe together with a summary of its ratings, for listing recipes. Serializes as the recipe with an
+/// added `rating` field.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+pub struct RecipeWithStats {
+    #[cfg_attr(feature = "serde", serde(flatten))]
+    pub recipe: Recipe,
+    pub rating: RatingSummary,
+}
+
+impl RecipeWithStats {
+    /// Pairs `recipe` with the summary of its ratings in `ratings`.
+    pub fn new(recipe: Recipe, ratings: &Ratings) -> Self {
+        let rating = ratings.summary(&recipe.id());
+        Self { recipe, rating }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rate(ratings: &mut Ratings, recipe_id: Uuid, user_id: Uuid, stars: u8) -> Option<Rating> {
+        ratings.upsert(Rating::new(recipe_id, user_id, stars, None).expect("the rating should be valid"))
+    }
+
+    #[test]
+    fn stars_outside_one_to_five_and_long_reviews_are_rejected() {
+        let (recipe_id, user_id) = (Uuid::now_v7(), Uuid::now_v7());
+        for stars in [0, 6] {
+            let errors = Rating::new(recipe_id, user_id, stars, None).expect_err("the stars are out of range");
+            assert_eq!(errors.errors()[0].field(), "stars");
+        }
+        let review = "a".repeat(Rating::MAX_REVIEW_LEN + 1);
+        let errors = Rating::new(recipe_id, user_id, 5, Some(review)).expect_err("the review is too long");
+        let too_long = ValidationError::TooLong { field: "review", max: Rating::MAX_REVIEW_LEN, actual: 2001 };
+        assert_eq!(errors.errors(), std::slice::from_ref(&too_long));
+        let longest = Rating::new(recipe_id, user_id, 5, Some("é".repeat(Rating::MAX_REVIEW_LEN)));
+        assert!(longest.is_ok());
+        let blank = Rating::new(recipe_id, user_id, 1, Some("  ".to_string())).expect("the rating should be valid");
+        assert_eq!(blank.review(), None);
+    }
+
+    #[test]
+    fn a_user_rating_a_recipe_again_replaces_their_rating() {
+        let mut ratings = Ratings::new();
+        let (recipe_id, user_id) = (Uuid::now_v7(), Uuid::now_v7());
+        assert_eq!(rate(&mut ratings, recipe_id, user_id, 2), None);
+        let replaced = rate(&mut ratings, recipe_id, user_id, 5).expect("the first rating is replaced");
+        assert_eq!(replaced.stars(), 2);
+        assert_eq!(ratings.len(), 1);
+        assert_eq!(ratings.get(&recipe_id, &user_id).map(Rating::stars), Some(5));
+        rate(&mut ratings, Uuid::now_v7(), user_id, 1);
+        assert_eq!((ratings.len(), ratings.for_recipe(&recipe_id).len()), (2, 1));
+    }
+
+    #[test]
+    fn the_summary_is_recomputed_after_a_rating_is_removed() {
+        let mut ratings = Ratings::new();
+        let recipe_id = Uuid::now_v7();
+        let users: Vec<Uuid> = (0..4).map(|_| Uuid::now_v7()).collect();
+        for (user, stars) in users.iter().zip([5, 4, 4, 1]) {
+            rate(&mut ratings, recipe_id, *user, stars);
+        }
+        rate(&mut ratings, Uuid::now_v7(), users[0], 1);
+        let summary = ratings.summary(&recipe_id);
+        assert_eq!((summary.average, summary.count, summary.histogram), (3.5, 4, [1, 0, 0, 2, 1]));
+
+        assert_eq!(ratings.remove(&recipe_id, &users[3]).map(|rating| rating.stars()), Some(1));
+        let summary = ratings.summary(&recipe_id);
+        assert!((summary.average - 13.0 / 3.0).abs() < 1e-6, "{}", summary.average);
+        assert_eq!((summary.count, summary.histogram), (3, [0, 0, 0, 2, 1]));
+
+        assert_eq!(ratings.remove_recipe(&recipe_id).len(), 3);
+        assert_eq!(ratings.summary(&recipe_id), RatingSummary::default());
+        assert_eq!(ratings.len(), 1);
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn recipes_with_stats_serialize_with_a_rating_field() {
+        let soup = crate::models::testing::recipe("Soup").build().expect("the recipe should build");
+        let mut ratings = Ratings::new();
+        rate(&mut ratings, soup.id(), Uuid::now_v7(), 4);
+        let json = serde_json::to_value(RecipeWithStats::new(soup, &ratings)).expect("should serialize");
+        assert_eq!(json["name"], "Soup");
+        assert_eq!((json["rating"]["count"].as_u64(), json["rating"]["average"].as_f64()), (Some(1), Some(4.0)));
+    }
+}
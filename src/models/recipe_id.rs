@@ -0,0 +1,95 @@
+use std::fmt;
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+use super::Recipe;
+
+/// The identity of a recipe, for keying maps and sets of recipes, e.g. a `HashMap<RecipeId, Recipe>`.
+///
+/// `Recipe` itself is neither `Eq` nor `Hash`: its `==` compares content, so two versions of the same recipe
+/// differ, and the content includes floating point nutrition facts, which are not `Eq`. Two recipes have the
+/// same `RecipeId` exactly when they have the same id, whatever their content. Serializes as the bare UUID.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(transparent))]
+pub struct RecipeId(Uuid);
+
+impl RecipeId {
+    pub fn new(id: Uuid) -> Self {
+        Self(id)
+    }
+
+    pub fn uuid(&self) -> Uuid {
+        self.0
+    }
+}
+
+impl From<Uuid> for RecipeId {
+    fn from(id: Uuid) -> Self {
+        Self(id)
+    }
+}
+
+impl From<RecipeId> for Uuid {
+    fn from(id: RecipeId) -> Self {
+        id.0
+    }
+}
+
+impl From<&Recipe> for RecipeId {
+    fn from(recipe: &Recipe) -> Self {
+        recipe.recipe_id()
+    }
+}
+
+impl fmt::Display for RecipeId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+impl Recipe {
+    /// The id of the recipe as a key that identifies it regardless of its content, see `RecipeId`.
+    pub fn recipe_id(&self) -> RecipeId {
+        RecipeId(self.id)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::{HashMap, HashSet};
+    use crate::models::testing::recipe;
+    use super::*;
+
+    #[test]
+    fn the_same_recipe_inserted_twice_is_in_a_set_once_whatever_its_content() {
+        let soup = recipe("Soup").build().expect("the recipe should build");
+        let mut renamed = soup.clone();
+        renamed.set_name("Stew");
+        let other = recipe("Soup").build().expect("the recipe should build");
+        let ids: HashSet<RecipeId> = [&soup, &soup, &renamed, &other].into_iter().map(RecipeId::from).collect();
+        assert_eq!(ids.len(), 2);
+        assert_ne!(soup, renamed);
+        assert_eq!(soup.recipe_id(), renamed.recipe_id());
+        let by_id: HashMap<RecipeId, Recipe> =
+            [&soup, &renamed].map(|recipe| (recipe.recipe_id(), recipe.clone())).into();
+        assert_eq!(by_id[&soup.recipe_id()].name(), "Stew");
+    }
+
+    #[test]
+    fn ids_convert_to_and_from_uuids_and_display_as_one() {
+        let uuid = Uuid::now_v7();
+        assert_eq!(Uuid::from(RecipeId::from(uuid)), uuid);
+        assert_eq!(RecipeId::new(uuid).uuid(), uuid);
+        assert_eq!(RecipeId::new(uuid).to_string(), uuid.to_string());
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn ids_serialize_as_the_bare_uuid() {
+        let uuid = Uuid::now_v7();
+        let json = serde_json::to_value(RecipeId::new(uuid)).expect("the id should serialize");
+        assert_eq!(json, serde_json::json!(uuid.to_string()));
+        assert_eq!(serde_json::from_value::<RecipeId>(json).expect("the id should deserialize"), RecipeId::new(uuid));
+    }
+}
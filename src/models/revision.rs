@@ -0,0 +1,203 @@
+use std::error::Error;
+use std::fmt;
+use chrono::{DateTime, Utc};
+use uuid::Uuid;
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Deserializer, Serialize};
+use super::{Recipe, RecipeDiff};
+
+/// A snapshot of a recipe at one of its versions, recorded in a `RecipeHistory`.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct RecipeRevision {
+    /// The recipe as it was at this version
+    pub recipe: Recipe,
+    /// Who made the change, if known
+    pub author: Option<String>,
+    /// When the revision was recorded, serialized as RFC 3339
+    pub recorded_at: DateTime<Utc>,
+}
+
+impl RecipeRevision {
+    pub fn version(&self) -> u32 {
+        self.recipe.version()
+    }
+}
+
+/// Every recorded version of a single recipe, oldest first. History is never rewritten, restoring an old
+/// version records it again as the newest one. A history always holds at least one revision, deserializing
+/// one without any fails.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct RecipeHistory {
+    #[cfg_attr(feature = "serde", serde(deserialize_with = "deserialize_revisions"))]
+    revisions: Vec<RecipeRevision>,
+}
+
+impl RecipeHistory {
+    /// Starts the history of `recipe` with its current version.
+    pub fn new(recipe: Recipe, author: Option<String>) -> Self {
+        Self { revisions: vec![RecipeRevision { recipe, author, recorded_at: Utc::now() }] }
+    }
+
+    /// A history of the given revisions, oldest first, or `None` if there are none.
+    #[cfg(feature = "serde")]
+    pub(crate) fn from_revisions(revisions: Vec<RecipeRevision>) -> Option<Self> {
+        (!revisions.is_empty()).then_some(Self { revisions })
+    }
+
+    pub fn recipe_id(&self) -> Uuid {
+        self.head().id()
+    }
+
+    /// The newest version of the recipe.
+    pub fn head(&self) -> &Recipe {
+        &self.revisions[self.revisions.len() - 1].recipe
+    }
+
+    /// Every revision, oldest first.
+    pub fn revisions(&self) -> &[RecipeRevision] {
+        &self.revisions
+    }
+
+    /// The revision recording `version`, if there is one.
+    pub fn get(&self, version: u32) -> Option<&RecipeRevision> {
+        self.revisions.iter().find(|revision| revision.version() == version)
+    }
+
+    /// Records `recipe` as the newest version, failing if it is a different recipe or its version is not
+    /// newer than the head.
+    pub fn record(&mut self, recipe: Recipe, author: Option<String>) -> Result<(), RevisionError> {
+        if recipe.id() != self.recipe_id() {
+            return Err(RevisionError::WrongRecipe { expected: self.recipe_id(), actual: recipe.id() });
+        }
+        if recipe.version() <= self.head().version() {
+            return Err(RevisionError::StaleVersion { head: self.head().version(), version: recipe.version() });
+        }
+        self.revisions.push(RecipeRevision { recipe, author, recorded_at: Utc::now() });
+        Ok(())
+    }
+
+    /// Makes the recipe as it was at `version` the head again, recorded as a new version after the current
+    /// head, and returns it.
+    pub fn restore(&mut self, version: u32, author: Option<String>) -> Result<&Recipe, RevisionError> {
+        let mut restored = self.get(version).ok_or(RevisionError::UnknownVersion(version))?.recipe.clone();
+        restored.version = self.head().version();
+        restored.touch();
+        self.revisions.push(RecipeRevision { recipe: restored, author, recorded_at: Utc::now() });
+        Ok(self.head())
+    }
+
+    /// What changed from `from` to `to`, or `None` if either version is unknown.
+    pub fn diff(&self, from: u32, to: u32) -> Option<RecipeDiff> {
+        Some(self.get(from)?.recipe.diff(&self.get(to)?.recipe))
+    }
+}
+
+/// Deserializes the revisions of a history, which must not be empty.
+#[cfg(feature = "serde")]
+fn deserialize_revisions<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Vec<RecipeRevision>, D::Error> {
+    let revisions = Vec::<RecipeRevision>::deserialize(deserializer)?;
+    if revisions.is_empty() {
+        return Err(serde::de::Error::invalid_length(0, &"at least one revision"));
+    }
+    Ok(revisions)
+}
+
+/// The error returned when a `RecipeHistory` cannot record or restore a version.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RevisionError {
+    /// The recipe recorded is not the one the history is for
+    WrongRecipe { expected: Uuid, actual: Uuid },
+    /// The recipe recorded is not newer than the head
+    StaleVersion { head: u32, version: u32 },
+    /// No revision records the version
+    UnknownVersion(u32),
+}
+
+impl fmt::Display for RevisionError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RevisionError::WrongRecipe { expected, actual } => {
+                write!(f, "cannot record recipe {} in the history of recipe {}", actual, expected)
+            }
+            RevisionError::StaleVersion { head, version } => {
+                write!(f, "cannot record version {}, the history is already at version {}", version, head)
+            }
+            RevisionError::UnknownVersion(version) => write!(f, "no revision for version {}", version),
+        }
+    }
+}
+
+impl Error for RevisionError {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::testing::recipe;
+
+    fn history_of_three_edits() -> RecipeHistory {
+        let mut soup = recipe("Soup").servings(2).build().expect("the recipe should build");
+        let mut history = RecipeHistory::new(soup.clone(), Some("ana".to_string()));
+        for servings in [4, 6] {
+            soup.set_servings(servings);
+            history.record(soup.clone(), Some("ben".to_string())).expect("the edit should be recorded");
+        }
+        soup.set_name("Stew");
+        history.record(soup, None).expect("the edit should be recorded");
+        history
+    }
+
+    #[test]
+    fn every_edit_is_recorded_oldest_first() {
+        let history = history_of_three_edits();
+        let versions: Vec<u32> = history.revisions().iter().map(RecipeRevision::version).collect();
+        assert_eq!(versions, [1, 2, 3, 4]);
+        let servings: Vec<u16> = history.revisions().iter().map(|revision| revision.recipe.servings()).collect();
+        assert_eq!(servings, [2, 4, 6, 6]);
+        assert_eq!(history.revisions()[0].author.as_deref(), Some("ana"));
+        assert_eq!(history.revisions()[3].author, None);
+        assert!(history.revisions().windows(2).all(|pair| pair[0].recorded_at <= pair[1].recorded_at));
+        assert_eq!((history.head().name(), history.head().version()), ("Stew", 4));
+        let diff = history.diff(1, 2).expect("both versions are recorded");
+        assert_eq!(diff.servings.map(|change| (change.old, change.new)), Some((2, 4)));
+        assert_eq!(diff.name, None);
+        assert!(history.diff(1, 9).is_none());
+    }
+
+    #[test]
+    fn restoring_records_a_new_head_version_without_rewriting_history() {
+        let mut history = history_of_three_edits();
+        let restored = history.restore(1, Some("ana".to_string())).expect("version 1 is recorded").clone();
+        assert_eq!((restored.version(), restored.name(), restored.servings()), (5, "Soup", 2));
+        assert_eq!(history.revisions().len(), 5);
+        assert_eq!(history.get(4).map(|revision| revision.recipe.name()), Some("Stew"));
+        assert_eq!(history.diff(1, 5).map(|diff| diff.is_empty()), Some(true));
+        assert_eq!(history.restore(9, None).err(), Some(RevisionError::UnknownVersion(9)));
+    }
+
+    #[test]
+    fn stale_versions_and_other_recipes_are_rejected() {
+        let mut history = history_of_three_edits();
+        let stale = history.get(2).expect("version 2 is recorded").recipe.clone();
+        assert_eq!(history.record(stale, None), Err(RevisionError::StaleVersion { head: 4, version: 2 }));
+        let other = recipe("Bread").build().expect("the recipe should build");
+        let other_id = other.id();
+        assert_eq!(
+            history.record(other, None),
+            Err(RevisionError::WrongRecipe { expected: history.recipe_id(), actual: other_id })
+        );
+        assert_eq!(history.revisions().len(), 4);
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn histories_survive_json_but_an_empty_one_is_rejected() {
+        let history = history_of_three_edits();
+        let json = serde_json::to_string(&history).expect("the history should serialize");
+        let parsed: RecipeHistory = serde_json::from_str(&json).expect("the history should deserialize");
+        assert_eq!(parsed.revisions().len(), 4);
+        assert_eq!((parsed.recipe_id(), parsed.head().version()), (history.recipe_id(), 4));
+        assert!(serde_json::from_str::<RecipeHistory>(r#"{"revisions":[]}"#).is_err());
+    }
+}
@@ -0,0 +1,78 @@
+use std::error::Error;
+use chrono::{DateTime, Utc};
+use sqlx::{ColumnIndex, Decode, FromRow, Row, Type};
+use uuid::Uuid;
+use super::{step, Category, Difficulty, Recipe, RecipeStatus};
+
+impl Recipe {
+    /// Reads the columns of a `recipes` row into a recipe without its ingredients or tags, which live in
+    /// join tables the caller loads and adds itself, e.g. with `Recipe::edit`.
+    ///
+    /// The row must have the columns `id`, `name`, `difficulty` as a small integer from 1 to 4, see the `u8`
+    /// conversion of `Difficulty`, `prep_minutes`, `cook_minutes`, `rest_minutes` and `servings` as integers,
+    /// `description`, `directions` as text with one step per line, `img` as bytes such as a Postgres `bytea`,
+    /// empty for no picture, and `created_at` and `updated_at` as timestamps. Every other field is left
+    /// unset, and the recipe is a published main course like other recipes stored before they had a status
+    /// or a category.
+    pub fn from_row_without_relations<'r, R>(row: &'r R) -> Result<Recipe, sqlx::Error>
+    where
+        R: Row,
+        &'r str: ColumnIndex<R>,
+        Uuid: Decode<'r, R::Database> + Type<R::Database>,
+        String: Decode<'r, R::Database> + Type<R::Database>,
+        i16: Decode<'r, R::Database> + Type<R::Database>,
+        i32: Decode<'r, R::Database> + Type<R::Database>,
+        Vec<u8>: Decode<'r, R::Database> + Type<R::Database>,
+        DateTime<Utc>: Decode<'r, R::Database> + Type<R::Database>,
+    {
+        let difficulty: i16 = row.try_get("difficulty")?;
+        let difficulty = u8::try_from(difficulty)
+            .map_err(|err| decode_error("difficulty", err))
+            .and_then(|value| Difficulty::try_from(value).map_err(|err| decode_error("difficulty", err)))?;
+        let minutes = |column: &'static str| -> Result<u16, sqlx::Error> {
+            let minutes: i32 = row.try_get(column)?;
+            u16::try_from(minutes).map_err(|err| decode_error(column, err))
+        };
+        let name: String = row.try_get("name")?;
+        let description: String = row.try_get("description")?;
+        let directions: String = row.try_get("directions")?;
+        Recipe::builder()
+            .id(row.try_get("id")?)
+            .name(name)
+            .difficulty(difficulty)
+            .prep_time(minutes("prep_minutes")?)
+            .cook_time(minutes("cook_minutes")?)
+            .rest_time(minutes("rest_minutes")?)
+            .servings(minutes("servings")?)
+            .description(description)
+            .steps(step::parse(&directions))
+            .img(row.try_get("img")?)
+            .created_at(row.try_get("created_at")?)
+            .updated_at(row.try_get("updated_at")?)
+            .status(RecipeStatus::Published)
+            .category(Category::default())
+            .assemble()
+            .map_err(|err| decode_error("recipes", err))
+    }
+}
+
+/// Reads a recipe without its ingredients or tags, see `Recipe::from_row_without_relations`.
+impl<'r, R> FromRow<'r, R> for Recipe
+where
+    R: Row,
+    &'r str: ColumnIndex<R>,
+    Uuid: Decode<'r, R::Database> + Type<R::Database>,
+    String: Decode<'r, R::Database> + Type<R::Database>,
+    i16: Decode<'r, R::Database> + Type<R::Database>,
+    i32: Decode<'r, R::Database> + Type<R::Database>,
+    Vec<u8>: Decode<'r, R::Database> + Type<R::Database>,
+    DateTime<Utc>: Decode<'r, R::Database> + Type<R::Database>,
+{
+    fn from_row(row: &'r R) -> Result<Self, sqlx::Error> {
+        Recipe::from_row_without_relations(row)
+    }
+}
+
+fn decode_error(column: &str, err: impl Error + Send + Sync + 'static) -> sqlx::Error {
+    sqlx::Error::ColumnDecode { index: column.to_string(), source: Box::new(err) }
+}
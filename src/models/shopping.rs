@@ -0,0 +1,278 @@
+use std::collections::HashMap;
+use std::fmt;
+use uuid::Uuid;
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+use super::text::escape_markdown;
+use super::{normalize_ingredient_name, InvalidScaleFactor, Quantity, Recipe, Unit};
+
+/// A line of a combined shopping list, see `ShoppingList`.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct ShoppingItem {
+    /// The name of the ingredient as it was first listed
+    pub name: String,
+    /// The summed amount of the ingredient
+    pub quantity: Quantity,
+    /// Set when the same ingredient is also listed in other units, e.g. `"also listed in cup"`
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub note: Option<String>,
+    /// The ids of the recipes that call for the ingredient in this unit, in the order they were added
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub recipes: Vec<Uuid>,
+    /// Whether the item has been checked off the list
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub purchased: bool,
+}
+
+impl fmt::Display for ShoppingItem {
+    /// Formats the item as e.g. `"500 g flour"` or `"500 g flour (also listed in cup)"`.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.quantity.unit() {
+            Unit::ToTaste => write!(f, "{} to taste", self.name)?,
+            _ => write!(f, "{} {}", self.quantity, self.name)?,
+        }
+        if let Some(note) = &self.note {
+            write!(f, " ({})", note)?;
+        }
+        Ok(())
+    }
+}
+
+/// The combined ingredients of one or more recipes, with a purchased flag per item for checking them off.
+///
+/// Ingredients with the same name once normalized with `normalize_ingredient_name`, so "Tomatoes" and
+/// "tomato", are combined into one item when their units measure the same thing, converting into the unit
+/// listed first, e.g. cups and tablespoons of milk are summed in cups. An ingredient listed in units that
+/// cannot be converted, such as cups and grams of flour, gets one item per unit, each noting the other
+/// units. Items are sorted by name and then unit.
+#[derive(Debug, Clone, PartialEq, Default)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct ShoppingList {
+    items: Vec<ShoppingItem>,
+}
+
+impl ShoppingList {
+    /// Combines the ingredients of `recipes` as written.
+    pub fn from_recipes(recipes: &[&Recipe]) -> ShoppingList {
+        ShoppingList::combine(recipes.iter().map(|recipe| (*recipe, 1.0)))
+    }
+
+    /// Combines the ingredients of `recipes`, each with its quantities multiplied by its factor first, e.g.
+    /// `(&pancakes, 2.0)` for a double batch. Quantities that are "to taste" or a pinch are not scaled.
+    /// Fails if a factor is zero, negative or not finite.
+    pub fn from_scaled(recipes: &[(&Recipe, f64)]) -> Result<ShoppingList, InvalidScaleFactor> {
+        if let Some((_, factor)) = recipes.iter().find(|(_, factor)| !factor.is_finite() || *factor <= 0.0) {
+            return Err(InvalidScaleFactor(*factor));
+        }
+        Ok(ShoppingList::combine(recipes.iter().copied()))
+    }
+
+    fn combine<'a>(recipes: impl Iterator<Item = (&'a Recipe, f64)>) -> ShoppingList {
+        let mut items: Vec<ShoppingItem> = Vec::new();
+        let mut by_name: HashMap<String, Vec<usize>> = HashMap::new();
+        for (recipe, factor) in recipes {
+            for ingredient in recipe.ingredients() {
+                let quantity = ingredient.quantity().scale(factor);
+                let indices = by_name.entry(normalize_ingredient_name(ingredient.name())).or_default();
+                let combined = indices.iter().find_map(|&i| {
+                    let item = &items[i];
+                    quantity.convert_to(item.quantity.unit().clone()).ok().map(|quantity| (i, quantity))
+                });
+                match combined {
+                    Some((i, quantity)) => {
+                        let item = &mut items[i];
+                        let amount = item.quantity.amount() + quantity.amount();
+                        item.quantity = Quantity::new(amount, quantity.unit().clone());
+                        if !item.recipes.contains(&recipe.id()) {
+                            item.recipes.push(recipe.id());
+                        }
+                    }
+                    None => {
+                        indices.push(items.len());
+                        items.push(ShoppingItem {
+                            name: ingredient.name().trim().to_string(),
+                            quantity,
+                            note: None,
+                            recipes: vec![recipe.id()],
+                            purchased: false,
+                        });
+                    }
+                }
+            }
+        }
+
+        for indices in by_name.values().filter(|indices| indices.len() > 1) {
+            for &i in indices {
+                let own = items[i].quantity.unit().to_string();
+                let mut others: Vec<String> = indices
+                    .iter()
+                    .map(|&j| items[j].quantity.unit().to_string())
+                    .filter(|unit| *unit != own)
+                    .collect();
+                others.sort_unstable();
+                items[i].note = Some(format!("also listed in {}", others.join(", ")));
+            }
+        }
+
+        items.sort_by(|a, b| {
+            normalize_ingredient_name(&a.name)
+                .cmp(&normalize_ingredient_name(&b.name))
+                .then_with(|| a.quantity.unit().to_string().cmp(&b.quantity.unit().to_string()))
+        });
+        ShoppingList { items }
+    }
+
+    pub fn items(&self) -> &[ShoppingItem] {
+        &self.items
+    }
+
+    pub fn into_items(self) -> Vec<ShoppingItem> {
+        self.items
+    }
+
+    pub fn len(&self) -> usize {
+        self.items.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.items.is_empty()
+    }
+
+    /// Checks the item at `index` off the list, or back on with `purchased` false. Returns `false` if there is
+    /// no such item.
+    pub fn set_purchased(&mut self, index: usize, purchased: bool) -> bool {
+        match self.items.get_mut(index) {
+            Some(item) => {
+                item.purchased = purchased;
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// The items not purchased yet.
+    pub fn remaining(&self) -> impl Iterator<Item = &ShoppingItem> {
+        self.items.iter().filter(|item| !item.purchased)
+    }
+
+    /// Renders the list as a Markdown task list, one item per line, e.g. `- [ ] 500 g flour` or
+    /// `- [x] 2 egg` once purchased.
+    pub fn to_markdown(&self) -> String {
+        let mut out = String::new();
+        for item in &self.items {
+            let checkbox = if item.purchased { "[x]" } else { "[ ]" };
+            out.push_str(&format!("- {} {}\n", checkbox, escape_markdown(&item.to_string())));
+        }
+        out
+    }
+}
+
+/// Combines the ingredients of `recipes` into the items of a shopping list, see `ShoppingList::from_recipes`.
+pub fn shopping_list(recipes: &[&Recipe]) -> Vec<ShoppingItem> {
+    ShoppingList::from_recipes(recipes).into_items()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::testing::{ingredient, recipe};
+
+    #[test]
+    fn flour_in_grams_from_two_recipes_is_summed() {
+        let bread = recipe("Bread")
+            .ingredient(ingredient("flour", 300.0, Unit::Gram))
+            .ingredient(ingredient("water", 200.0, Unit::Milliliter))
+            .build()
+            .expect("the recipe should build");
+        let cake = recipe("Cake")
+            .ingredient(ingredient("Flour", 200.0, Unit::Gram))
+            .ingredient(ingredient("eggs", 3.0, Unit::Piece))
+            .build()
+            .expect("the recipe should build");
+        let items = shopping_list(&[&bread, &cake]);
+        let listed: Vec<String> = items.iter().map(ShoppingItem::to_string).collect();
+        assert_eq!(listed, ["3 eggs", "500 g flour", "200 ml water"]);
+        assert_eq!(items[1].recipes, [bread.id(), cake.id()]);
+        assert!(items.iter().all(|item| item.note.is_none()));
+    }
+
+    #[test]
+    fn units_that_cannot_be_combined_stay_separate_with_a_note() {
+        let bread = recipe("Bread").ingredient(ingredient("flour", 300.0, Unit::Gram)).build().expect("should build");
+        let pancakes = recipe("Pancakes")
+            .ingredient(ingredient("flour", 1.0, Unit::Cup))
+            .ingredient(ingredient("milk", 1.0, Unit::Cup))
+            .build()
+            .expect("the recipe should build");
+        let waffles = recipe("Waffles").ingredient(ingredient("milk", 8.0, Unit::Tablespoon)).build().expect("builds");
+        let items = shopping_list(&[&bread, &pancakes, &waffles]);
+        let listed: Vec<String> = items.iter().map(ShoppingItem::to_string).collect();
+        assert_eq!(listed, ["1 cup flour (also listed in g)", "300 g flour (also listed in cup)", "1 ½ cup milk"]);
+    }
+
+    fn three_recipes() -> [Recipe; 3] {
+        let pancakes = recipe("Pancakes")
+            .ingredient(ingredient("milk", 1.0, Unit::Cup))
+            .ingredient(ingredient("Eggs", 2.0, Unit::Piece))
+            .ingredient(ingredient("flour", 200.0, Unit::Gram))
+            .build()
+            .expect("the recipe should build");
+        let omelette = recipe("Omelette")
+            .ingredient(ingredient("egg", 3.0, Unit::Piece))
+            .ingredient(ingredient("milk", 4.0, Unit::Tablespoon))
+            .ingredient(ingredient("salt", 0.0, Unit::ToTaste))
+            .build()
+            .expect("the recipe should build");
+        let bread = recipe("Bread")
+            .ingredient(ingredient("flour", 0.5, Unit::Kilogram))
+            .ingredient(ingredient("milk", 100.0, Unit::Gram))
+            .build()
+            .expect("the recipe should build");
+        [pancakes, omelette, bread]
+    }
+
+    #[test]
+    fn overlapping_ingredients_of_three_recipes_are_merged() {
+        let [pancakes, omelette, bread] = three_recipes();
+        let list = ShoppingList::from_recipes(&[&pancakes, &omelette, &bread]);
+        let listed: Vec<String> = list.items().iter().map(ShoppingItem::to_string).collect();
+        let milk = ["1 ¼ cup milk (also listed in g)", "100 g milk (also listed in cup)"];
+        assert_eq!(listed, ["5 Eggs", "700 g flour", milk[0], milk[1], "salt to taste"]);
+        assert_eq!(list.items()[0].recipes, [pancakes.id(), omelette.id()]);
+        assert_eq!(list.items()[1].recipes, [pancakes.id(), bread.id()]);
+        assert_eq!(list.items()[3].recipes, [bread.id()]);
+    }
+
+    #[test]
+    fn scaled_recipes_are_multiplied_before_merging() {
+        let [pancakes, omelette, _] = three_recipes();
+        let list = ShoppingList::from_scaled(&[(&pancakes, 2.0), (&omelette, 1.0)]).expect("the factors are valid");
+        let listed: Vec<String> = list.items().iter().map(ShoppingItem::to_string).collect();
+        assert_eq!(listed, ["7 Eggs", "400 g flour", "2 ¼ cup milk", "salt to taste"]);
+        assert_eq!(ShoppingList::from_scaled(&[(&pancakes, 0.0)]), Err(InvalidScaleFactor(0.0)));
+    }
+
+    #[test]
+    fn the_markdown_list_has_a_checkbox_per_item() {
+        let [pancakes, omelette, _] = three_recipes();
+        let mut list = ShoppingList::from_recipes(&[&pancakes, &omelette]);
+        assert!(list.set_purchased(1, true));
+        assert!(!list.set_purchased(9, true));
+        assert_eq!(
+            list.to_markdown(),
+            "- [ ] 5 Eggs\n- [x] 200 g flour\n- [ ] 1 ¼ cup milk\n- [ ] salt to taste\n"
+        );
+        assert_eq!(list.remaining().count(), 3);
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn purchased_items_survive_json() {
+        let [pancakes, _, _] = three_recipes();
+        let mut list = ShoppingList::from_recipes(&[&pancakes]);
+        list.set_purchased(0, true);
+        let json = serde_json::to_string(&list).expect("the list should serialize");
+        assert_eq!(serde_json::from_str::<ShoppingList>(&json).ok(), Some(list));
+    }
+}
@@ -0,0 +1,244 @@
+use std::collections::HashSet;
+use uuid::Uuid;
+use super::cookbook::by_name;
+use super::{normalize_ingredient_name, Cookbook, CookbookView, Course, Cuisine, Difficulty, Recipe, RecipeStatus};
+
+/// How much each aspect counts towards the similarity of two recipes, see `SimilarityWeights::similarity`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SimilarityWeights {
+    /// The weight of the overlap of ingredient names, normalized with `normalize_ingredient_name`
+    pub ingredients: f32,
+    /// The weight of the overlap of tags
+    pub tags: f32,
+    /// The weight of having the same cuisine
+    pub cuisine: f32,
+    /// The weight of having the same course
+    pub course: f32,
+    /// The weight of how close the total durations are
+    pub duration: f32,
+    /// The weight of how close the difficulties are
+    pub difficulty: f32,
+    /// The difference in total duration, in minutes, at which durations no longer count as close at all
+    pub duration_range: u16,
+}
+
+impl Default for SimilarityWeights {
+    fn default() -> Self {
+        Self {
+            ingredients: 0.5,
+            tags: 0.2,
+            cuisine: 0.1,
+            course: 0.05,
+            duration: 0.1,
+            difficulty: 0.05,
+            duration_range: 120,
+        }
+    }
+}
+
+impl SimilarityWeights {
+    /// How similar two recipes are, from 0.0 for recipes with nothing in common to 1.0 for identical ones.
+    ///
+    /// The score is the weighted mean of the Jaccard overlap of the ingredient names and of the tags, whether
+    /// the cuisines and courses are the same, and how close the total durations and the difficulties are.
+    /// Aspects neither recipe has, such as tags when both have none, are left out of the mean.
+    pub fn similarity(&self, a: &Recipe, b: &Recipe) -> f32 {
+        self.score(&Features::of(a), &Features::of(b))
+    }
+
+    fn score(&self, a: &Features, b: &Features) -> f32 {
+        let mut terms = Vec::with_capacity(6);
+        if let Some(overlap) = jaccard(&a.ingredients, &b.ingredients) {
+            terms.push((self.ingredients, overlap));
+        }
+        if let Some(overlap) = jaccard(&a.tags, &b.tags) {
+            terms.push((self.tags, overlap));
+        }
+        if a.cuisine.is_some() || b.cuisine.is_some() {
+            terms.push((self.cuisine, if a.cuisine == b.cuisine { 1.0 } else { 0.0 }));
+        }
+        if a.course.is_some() || b.course.is_some() {
+            terms.push((self.course, if a.course == b.course { 1.0 } else { 0.0 }));
+        }
+        let difference = f32::from(a.duration.abs_diff(b.duration));
+        let range = f32::from(self.duration_range.max(1));
+        terms.push((self.duration, (1.0 - difference / range).max(0.0)));
+        let steps = f32::from(u8::from(a.difficulty).abs_diff(u8::from(b.difficulty)));
+        terms.push((self.difficulty, 1.0 - steps / 3.0));
+
+        let total: f32 = terms.iter().map(|(weight, _)| weight).sum();
+        if total <= 0.0 {
+            return 0.0;
+        }
+        let score: f32 = terms.iter().map(|(weight, value)| weight * value).sum::<f32>() / total;
+        score.clamp(0.0, 1.0)
+    }
+}
+
+/// What `SimilarityWeights` compares, computed once per recipe.
+struct Features<'a> {
+    ingredients: HashSet<String>,
+    tags: HashSet<String>,
+    cuisine: Option<&'a Cuisine>,
+    course: Option<Course>,
+    duration: u16,
+    difficulty: Difficulty,
+}
+
+impl<'a> Features<'a> {
+    fn of(recipe: &'a Recipe) -> Self {
+        Self {
+            ingredients: recipe
+                .ingredients()
+                .iter()
+                .map(|ingredient| normalize_ingredient_name(ingredient.name()))
+                .collect(),
+            tags: recipe.tags().iter().map(|tag| tag.normalized()).collect(),
+            cuisine: recipe.cuisine(),
+            course: recipe.course(),
+            duration: recipe.total_duration(),
+            difficulty: *recipe.difficulty(),
+        }
+    }
+}
+
+/// The size of the intersection over the size of the union, or `None` if both sets are empty.
+fn jaccard(a: &HashSet<String>, b: &HashSet<String>) -> Option<f32> {
+    let union = a.union(b).count();
+    if union == 0 {
+        return None;
+    }
+    Some(a.intersection(b).count() as f32 / union as f32)
+}
+
+impl Recipe {
+    /// How similar this recipe is to `other` with the default `SimilarityWeights`, from 0.0 to 1.0.
+    pub fn similarity(&self, other: &Recipe) -> f32 {
+        SimilarityWeights::default().similarity(self, other)
+    }
+}
+
+impl Cookbook {
+    /// The ids of the at most `limit` published recipes most similar to the recipe with id `recipe_id`, see
+    /// `CookbookView::find_similar`.
+    pub fn find_similar(&self, recipe_id: &Uuid, limit: usize) -> Vec<(Uuid, f32)> {
+        self.with_status(RecipeStatus::Published).find_similar(recipe_id, limit)
+    }
+
+    /// Like `find_similar` with custom `weights`, see `CookbookView::find_similar_with`.
+    pub fn find_similar_with(&self, recipe_id: &Uuid, limit: usize, weights: &SimilarityWeights) -> Vec<(Uuid, f32)> {
+        self.with_status(RecipeStatus::Published).find_similar_with(recipe_id, limit, weights)
+    }
+}
+
+impl CookbookView<'_> {
+    /// The ids of the at most `limit` recipes most similar to the recipe with id `recipe_id` with the default
+    /// `SimilarityWeights`, with their scores, most similar first. The recipe itself may have any status. Empty
+    /// if there is no such recipe.
+    pub fn find_similar(&self, recipe_id: &Uuid, limit: usize) -> Vec<(Uuid, f32)> {
+        self.find_similar_with(recipe_id, limit, &SimilarityWeights::default())
+    }
+
+    /// Like `find_similar` with custom `weights`. Ties are broken by name ignoring case and then id.
+    pub fn find_similar_with(&self, recipe_id: &Uuid, limit: usize, weights: &SimilarityWeights) -> Vec<(Uuid, f32)> {
+        let Some(recipe) = self.recipes.get(recipe_id) else {
+            return Vec::new();
+        };
+        let target = Features::of(recipe);
+        let mut scored: Vec<(&Recipe, f32)> = self
+            .iter()
+            .filter(|other| other.id() != *recipe_id)
+            .map(|other| (other, weights.score(&target, &Features::of(other))))
+            .collect();
+        scored.sort_by(|(a, a_score), (b, b_score)| b_score.total_cmp(a_score).then_with(|| by_name(a, b)));
+        scored.into_iter().take(limit).map(|(recipe, score)| (recipe.id(), score)).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::testing::{ingredient, recipe, tag};
+    use crate::models::Unit;
+
+    fn dish(name: &str, ingredients: &[&str], tags: &[&str], cuisine: Cuisine) -> Recipe {
+        recipe(name)
+            .status(RecipeStatus::Published)
+            .ingredients(ingredients.iter().map(|name| ingredient(name, 1.0, Unit::Cup)))
+            .tags(tags.iter().map(|name| tag(name)))
+            .cuisine(cuisine)
+            .build()
+            .expect("the recipe should build")
+    }
+
+    #[test]
+    fn identical_recipes_score_one_and_unrelated_ones_zero() {
+        let curry = dish("Curry", &["rice", "coconut milk"], &["spicy"], Cuisine::Thai);
+        assert_eq!(curry.similarity(&curry.clone()), 1.0);
+
+        let cake = recipe("Cake")
+            .difficulty(Difficulty::Expert)
+            .cook_time(200)
+            .ingredient(ingredient("flour", 2.0, Unit::Cup))
+            .tag(tag("sweet"))
+            .cuisine(Cuisine::French)
+            .build()
+            .expect("the recipe should build");
+        assert_eq!(curry.similarity(&cake), 0.0);
+    }
+
+    #[test]
+    fn ingredient_names_are_normalized_before_they_are_compared() {
+        let a = dish("Fried rice", &["Eggs", "rice"], &[], Cuisine::Chinese);
+        let b = dish("Egg rice", &["egg ", "Rice"], &[], Cuisine::Chinese);
+        assert_eq!(a.similarity(&b), 1.0);
+    }
+
+    #[test]
+    fn similar_recipes_are_ranked_by_how_much_they_share() {
+        let curry = dish("Green curry", &["rice", "coconut milk", "basil"], &["spicy"], Cuisine::Thai);
+        let close = dish("Red curry", &["rice", "coconut milk", "chili"], &["spicy"], Cuisine::Thai);
+        let further = dish("Basil rice", &["rice", "basil"], &[], Cuisine::Italian);
+        let unrelated = dish("Toast", &["bread"], &["sweet"], Cuisine::French);
+        let draft = recipe("Yellow curry").ingredients([ingredient("rice", 1.0, Unit::Cup)]).build();
+        let cookbook: Cookbook = [curry.clone(), unrelated, further.clone(), close.clone()]
+            .into_iter()
+            .chain([draft.expect("the recipe should build")])
+            .collect();
+
+        let ranked = cookbook.find_similar(&curry.id(), 10);
+        let ids: Vec<Uuid> = ranked.iter().map(|(id, _)| *id).collect();
+        assert_eq!(&ids[..2], [close.id(), further.id()]);
+        assert_eq!(ranked.len(), 3, "the recipe itself and drafts are left out");
+        assert!(ranked.windows(2).all(|pair| pair[0].1 >= pair[1].1));
+        assert_eq!(cookbook.find_similar(&curry.id(), 1).len(), 1);
+        assert!(cookbook.find_similar(&Uuid::now_v7(), 10).is_empty());
+    }
+
+    #[test]
+    fn equally_similar_recipes_are_sorted_by_name_ignoring_case() {
+        let curry = dish("Green curry", &["rice"], &[], Cuisine::Thai);
+        let lower = dish("apple rice", &["rice"], &[], Cuisine::Thai);
+        let upper = dish("Banana rice", &["rice"], &[], Cuisine::Thai);
+        let cookbook: Cookbook = [curry.clone(), lower.clone(), upper.clone()].into_iter().collect();
+        let ids: Vec<Uuid> = cookbook.find_similar(&curry.id(), 10).iter().map(|(id, _)| *id).collect();
+        assert_eq!(ids, [lower.id(), upper.id()]);
+    }
+
+    #[test]
+    fn weights_change_what_counts() {
+        let a = dish("Pad thai", &["noodles"], &[], Cuisine::Thai);
+        let b = dish("Ramen", &["noodles"], &[], Cuisine::Japanese);
+        let only_cuisine = SimilarityWeights {
+            ingredients: 0.0,
+            tags: 0.0,
+            cuisine: 1.0,
+            course: 0.0,
+            duration: 0.0,
+            difficulty: 0.0,
+            ..SimilarityWeights::default()
+        };
+        assert_eq!(only_cuisine.similarity(&a, &b), 0.0);
+        assert!(SimilarityWeights::default().similarity(&a, &b) > 0.5);
+    }
+}
@@ -0,0 +1,204 @@
+use std::error::Error;
+use std::fmt;
+use url::Url;
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Deserializer, Serialize};
+
+/// Where a recipe came from, e.g. the blog or cookbook it was imported from.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Source {
+    /// The person who wrote the recipe
+    #[cfg_attr(feature = "serde", serde(default))]
+    author: Option<String>,
+    /// Where the recipe was published online, always http or https
+    #[cfg_attr(feature = "serde", serde(default, deserialize_with = "deserialize_url"))]
+    url: Option<Url>,
+    /// The cookbook, magazine or site the recipe was published in
+    #[cfg_attr(feature = "serde", serde(default))]
+    publication: Option<String>,
+    /// The license the recipe was published under
+    #[cfg_attr(feature = "serde", serde(default))]
+    license: Option<String>,
+}
+
+impl Source {
+    /// Creates a source with nothing recorded yet.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_author(mut self, author: impl Into<String>) -> Self {
+        self.author = Some(author.into());
+        self
+    }
+
+    /// Sets the url, which must be an absolute http or https url.
+    pub fn with_url(mut self, url: &str) -> Result<Self, SourceUrlError> {
+        self.url = Some(parse_url(url)?);
+        Ok(self)
+    }
+
+    pub fn with_publication(mut self, publication: impl Into<String>) -> Self {
+        self.publication = Some(publication.into());
+        self
+    }
+
+    pub fn with_license(mut self, license: impl Into<String>) -> Self {
+        self.license = Some(license.into());
+        self
+    }
+
+    pub fn author(&self) -> Option<&str> {
+        self.author.as_deref()
+    }
+
+    pub fn url(&self) -> Option<&Url> {
+        self.url.as_ref()
+    }
+
+    pub fn publication(&self) -> Option<&str> {
+        self.publication.as_deref()
+    }
+
+    pub fn license(&self) -> Option<&str> {
+        self.license.as_deref()
+    }
+
+    /// Whether nothing about the source is recorded.
+    pub fn is_empty(&self) -> bool {
+        self.author.is_none() && self.url.is_none() && self.publication.is_none() && self.license.is_none()
+    }
+}
+
+impl fmt::Display for Source {
+    /// Formats the attribution as e.g. `"by Jane Doe, Serious Eats (https://example.com/pie), CC BY 4.0"`,
+    /// leaving out whatever is not recorded.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut parts = Vec::new();
+        if let Some(author) = &self.author {
+            parts.push(format!("by {}", author));
+        }
+        match (&self.publication, &self.url) {
+            (Some(publication), Some(url)) => parts.push(format!("{} ({})", publication, url)),
+            (Some(publication), None) => parts.push(publication.clone()),
+            (None, Some(url)) => parts.push(url.to_string()),
+            (None, None) => {}
+        }
+        if let Some(license) = &self.license {
+            parts.push(license.clone());
+        }
+        write!(f, "{}", parts.join(", "))
+    }
+}
+
+/// Parses `url`, rejecting anything that is not an absolute http or https url.
+fn parse_url(url: &str) -> Result<Url, SourceUrlError> {
+    let parsed = Url::parse(url.trim()).map_err(SourceUrlError::Invalid)?;
+    match parsed.scheme() {
+        "http" | "https" => Ok(parsed),
+        scheme => Err(SourceUrlError::UnsupportedScheme(scheme.to_string())),
+    }
+}
+
+/// Deserializes an optional url, applying the same checks as `Source::with_url`.
+#[cfg(feature = "serde")]
+fn deserialize_url<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Option<Url>, D::Error> {
+    Option::<String>::deserialize(deserializer)?
+        .map(|url| parse_url(&url).map_err(serde::de::Error::custom))
+        .transpose()
+}
+
+/// Deserializes an optional source, a source with nothing recorded becomes `None`.
+#[cfg(feature = "serde")]
+pub(crate) fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Option<Source>, D::Error> {
+    Ok(Option::<Source>::deserialize(deserializer)?.filter(|source| !source.is_empty()))
+}
+
+/// The error returned when a source url is rejected.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SourceUrlError {
+    /// The url could not be parsed
+    Invalid(url::ParseError),
+    /// The url is not http or https
+    UnsupportedScheme(String),
+}
+
+impl fmt::Display for SourceUrlError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SourceUrlError::Invalid(err) => write!(f, "invalid source url: {}", err),
+            SourceUrlError::UnsupportedScheme(scheme) => {
+                write!(f, "unsupported source url scheme \"{}\", expected http or https", scheme)
+            }
+        }
+    }
+}
+
+impl Error for SourceUrlError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            SourceUrlError::Invalid(err) => Some(err),
+            SourceUrlError::UnsupportedScheme(_) => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::testing::full_recipe;
+
+    #[test]
+    fn only_absolute_http_and_https_urls_are_accepted() {
+        for url in ["https://example.com/pie", "http://example.com", " https://example.com/a?b=c "] {
+            assert!(Source::new().with_url(url).is_ok(), "{:?} should be accepted", url);
+        }
+        assert_eq!(
+            Source::new().with_url("ftp://example.com/pie"),
+            Err(SourceUrlError::UnsupportedScheme("ftp".to_string()))
+        );
+        let err = Source::new().with_url("javascript:alert(1)").expect_err("the scheme is not http");
+        assert_eq!(err.to_string(), "unsupported source url scheme \"javascript\", expected http or https");
+        for url in ["/recipes/pie", "example.com/pie", "", "https://"] {
+            assert!(matches!(Source::new().with_url(url), Err(SourceUrlError::Invalid(_))), "{:?}", url);
+        }
+    }
+
+    #[test]
+    fn attribution_leaves_out_what_is_not_recorded() {
+        let full = Source::new()
+            .with_author("Jane Doe")
+            .with_publication("Serious Eats")
+            .with_url("https://example.com/pie")
+            .expect("the url should be valid")
+            .with_license("CC BY 4.0");
+        assert_eq!(full.to_string(), "by Jane Doe, Serious Eats (https://example.com/pie), CC BY 4.0");
+        assert_eq!(Source::new().with_license("CC0").to_string(), "CC0");
+        assert!(Source::new().is_empty());
+    }
+
+    #[test]
+    fn exports_include_the_attribution() {
+        let recipe = full_recipe();
+        let line = "Source: by Jane Doe, https://example.com/pancakes, CC BY 4.0";
+        assert!(recipe.to_plain_text(80).ends_with(&format!("{}\n", line)), "{}", recipe.to_plain_text(80));
+        assert!(recipe.to_markdown().ends_with(&format!("\n{}\n", line)), "{}", recipe.to_markdown());
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn sources_round_trip_and_empty_ones_serialize_as_null() {
+        let source = full_recipe().source().cloned().expect("the recipe has a source");
+        let json = serde_json::to_value(&source).expect("the source should serialize");
+        assert_eq!(json["url"], "https://example.com/pancakes");
+        assert_eq!(serde_json::from_value::<Source>(json).ok(), Some(source));
+
+        let mut built = full_recipe();
+        built.set_source(Some(Source::new()));
+        let json = serde_json::to_value(&built).expect("the recipe should serialize");
+        assert!(json["source"].is_null());
+        let invalid = serde_json::json!({"url": "ftp://example.com"});
+        assert!(serde_json::from_value::<Source>(invalid).is_err());
+    }
+}
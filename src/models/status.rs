@@ -0,0 +1,161 @@
+use std::error::Error;
+use std::fmt;
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+use super::{Recipe, ValidationErrors};
+
+/// Where a recipe is in its lifecycle.
+///
+/// A recipe starts as a `Draft`, which may still be incomplete, and is made visible by publishing it. A
+/// published recipe or a draft can be archived, and an archived recipe has to be made a draft again with
+/// `Recipe::unarchive` before it can be published, so it is validated again first. `Cookbook` queries only
+/// return published recipes, the others are queried through `Cookbook::with_status`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "lowercase"))]
+pub enum RecipeStatus {
+    #[default]
+    Draft,
+    Published,
+    Archived,
+}
+
+impl fmt::Display for RecipeStatus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let s = match self {
+            RecipeStatus::Draft => "Draft",
+            RecipeStatus::Published => "Published",
+            RecipeStatus::Archived => "Archived",
+        };
+        write!(f, "{}", s)
+    }
+}
+
+/// The error returned when a recipe cannot change to another `RecipeStatus`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum StatusError {
+    /// The recipe cannot go from its current status to the requested one
+    InvalidTransition { from: RecipeStatus, to: RecipeStatus },
+    /// The draft cannot be published until these problems are fixed
+    Invalid(ValidationErrors),
+}
+
+impl fmt::Display for StatusError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            StatusError::InvalidTransition { from, to } => {
+                write!(f, "cannot change a recipe from {} to {}", from, to)
+            }
+            StatusError::Invalid(errors) => write!(f, "cannot publish an incomplete recipe: {}", errors),
+        }
+    }
+}
+
+impl Error for StatusError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            StatusError::InvalidTransition { .. } => None,
+            StatusError::Invalid(errors) => Some(errors),
+        }
+    }
+}
+
+impl Recipe {
+    pub fn status(&self) -> RecipeStatus {
+        self.status
+    }
+
+    /// Publishes a draft once it passes `Recipe::validate`, failing with every problem found otherwise.
+    /// Only drafts can be published.
+    pub fn publish(&mut self) -> Result<(), StatusError> {
+        self.expect_status(&[RecipeStatus::Draft], RecipeStatus::Published)?;
+        self.validate().map_err(StatusError::Invalid)?;
+        self.set_status(RecipeStatus::Published);
+        Ok(())
+    }
+
+    /// Archives a published recipe or a draft.
+    pub fn archive(&mut self) -> Result<(), StatusError> {
+        self.expect_status(&[RecipeStatus::Draft, RecipeStatus::Published], RecipeStatus::Archived)?;
+        self.set_status(RecipeStatus::Archived);
+        Ok(())
+    }
+
+    /// Makes an archived recipe a draft again, to be published with `Recipe::publish`.
+    pub fn unarchive(&mut self) -> Result<(), StatusError> {
+        self.expect_status(&[RecipeStatus::Archived], RecipeStatus::Draft)?;
+        self.set_status(RecipeStatus::Draft);
+        Ok(())
+    }
+
+    fn expect_status(&self, from: &[RecipeStatus], to: RecipeStatus) -> Result<(), StatusError> {
+        if from.contains(&self.status) {
+            Ok(())
+        } else {
+            Err(StatusError::InvalidTransition { from: self.status, to })
+        }
+    }
+
+    fn set_status(&mut self, status: RecipeStatus) {
+        self.status = status;
+        self.touch();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::testing::{ingredient, recipe};
+    use crate::models::{Unit, ValidationError};
+
+    fn draft() -> Recipe {
+        let builder = recipe("Soup").ingredient(ingredient("water", 1.0, Unit::Liter));
+        builder.build().expect("the recipe should build")
+    }
+
+    #[test]
+    fn recipes_move_between_statuses_and_each_move_bumps_the_version() {
+        let mut soup = draft();
+        assert_eq!(soup.status(), RecipeStatus::Draft);
+        let (version, updated_at) = (soup.version(), soup.updated_at());
+        soup.publish().expect("a complete draft can be published");
+        assert_eq!(soup.status(), RecipeStatus::Published);
+        assert!(soup.version() > version && soup.updated_at() >= updated_at);
+        soup.archive().expect("a published recipe can be archived");
+        assert_eq!(soup.status(), RecipeStatus::Archived);
+        soup.unarchive().expect("an archived recipe can be made a draft");
+        assert_eq!(soup.status(), RecipeStatus::Draft);
+        soup.archive().expect("a draft can be archived");
+        assert_eq!(soup.status(), RecipeStatus::Archived);
+    }
+
+    #[test]
+    fn illegal_transitions_are_rejected_without_changing_the_recipe() {
+        let mut soup = draft();
+        soup.archive().expect("a draft can be archived");
+        let version = soup.version();
+        let to_published = StatusError::InvalidTransition { from: RecipeStatus::Archived, to: RecipeStatus::Published };
+        assert_eq!(soup.publish(), Err(to_published));
+        let again = soup.archive().expect_err("an archived recipe cannot be archived again");
+        assert_eq!(again.to_string(), "cannot change a recipe from Archived to Archived");
+        assert_eq!((soup.status(), soup.version()), (RecipeStatus::Archived, version));
+
+        let mut soup = draft();
+        let to_draft = StatusError::InvalidTransition { from: RecipeStatus::Draft, to: RecipeStatus::Draft };
+        assert_eq!(soup.unarchive(), Err(to_draft));
+        soup.publish().expect("a complete draft can be published");
+        assert!(matches!(soup.publish(), Err(StatusError::InvalidTransition { .. })));
+    }
+
+    #[test]
+    fn only_a_draft_that_passes_validation_can_be_published() {
+        let mut empty = recipe("Soup").build().expect("an incomplete draft still builds");
+        let Err(StatusError::Invalid(errors)) = empty.publish() else {
+            panic!("a recipe without ingredients should not be published");
+        };
+        assert_eq!(errors.errors(), [ValidationError::Empty { field: "ingredients" }]);
+        assert_eq!(empty.status(), RecipeStatus::Draft);
+        empty.add_ingredient(ingredient("water", 1.0, Unit::Liter));
+        assert_eq!(empty.publish(), Ok(()));
+    }
+}
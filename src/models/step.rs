@@ -0,0 +1,193 @@
+use std::fmt;
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Deserializer, Serialize};
+
+/// A single step in the directions of a recipe.
+#[derive(Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Step {
+    /// The position of the step in the directions, starting at 1
+    #[cfg_attr(feature = "serde", serde(default))]
+    position: u16,
+    /// What to do in this step
+    text: String,
+    /// How long the step takes in minutes, if known
+    #[cfg_attr(feature = "serde", serde(default, skip_serializing_if = "Option::is_none"))]
+    duration_minutes: Option<u16>,
+    /// A picture of the step, empty if there is none
+    #[cfg_attr(feature = "serde", serde(default, with = "super::img_base64"))]
+    img: Vec<u8>,
+}
+
+impl Step {
+    /// Creates a step, its position is assigned once it is added to a recipe.
+    pub fn new(text: impl Into<String>) -> Self {
+        Self { position: 0, text: text.into(), duration_minutes: None, img: Vec::new() }
+    }
+
+    pub fn with_duration_minutes(mut self, duration_minutes: u16) -> Self {
+        self.duration_minutes = Some(duration_minutes);
+        self
+    }
+
+    pub fn with_img(mut self, img: Vec<u8>) -> Self {
+        self.img = img;
+        self
+    }
+
+    pub fn position(&self) -> u16 {
+        self.position
+    }
+
+    pub fn text(&self) -> &str {
+        &self.text
+    }
+
+    pub fn duration_minutes(&self) -> Option<u16> {
+        self.duration_minutes
+    }
+
+    pub fn img(&self) -> &[u8] {
+        &self.img
+    }
+}
+
+impl fmt::Debug for Step {
+    /// Formats every field except the image, which is summarized by its length.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Step")
+            .field("position", &self.position)
+            .field("text", &self.text)
+            .field("duration_minutes", &self.duration_minutes)
+            .field("img", &format_args!("<{} bytes>", self.img.len()))
+            .finish()
+    }
+}
+
+impl From<&str> for Step {
+    fn from(text: &str) -> Self {
+        Step::new(text)
+    }
+}
+
+impl From<String> for Step {
+    fn from(text: String) -> Self {
+        Step::new(text)
+    }
+}
+
+/// Splits free text directions into steps, one per non-empty line, dropping any leading "1." or "1)"
+/// numbering so text produced by `render` parses back into the same steps.
+pub(crate) fn parse(directions: &str) -> Vec<Step> {
+    let mut steps: Vec<Step> = directions
+        .lines()
+        .map(|line| {
+            let line = line.trim();
+            let digits = line.find(|c: char| !c.is_ascii_digit()).unwrap_or(line.len());
+            match line[digits..].strip_prefix(['.', ')']) {
+                Some(rest) if digits > 0 => rest.trim_start(),
+                _ => line,
+            }
+        })
+        .filter(|line| !line.is_empty())
+        .map(Step::new)
+        .collect();
+    renumber(&mut steps);
+    steps
+}
+
+/// Renders steps as numbered lines, e.g. `"1. Preheat the oven\n2. Mix the flour and sugar"`.
+pub(crate) fn render(steps: &[Step]) -> String {
+    steps
+        .iter()
+        .map(|step| format!("{}. {}", step.position, step.text))
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Reassigns positions so they match the order of `steps`, starting at 1.
+pub(crate) fn renumber(steps: &mut [Step]) {
+    for (i, step) in steps.iter_mut().enumerate() {
+        step.position = u16::try_from(i + 1).unwrap_or(u16::MAX);
+    }
+}
+
+/// Deserializes steps from either a list of steps or a legacy free text directions string.
+#[cfg(feature = "serde")]
+pub(crate) fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Vec<Step>, D::Error> {
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum Directions {
+        Legacy(String),
+        Steps(Vec<Step>),
+    }
+
+    Ok(match Directions::deserialize(deserializer)? {
+        Directions::Legacy(text) => parse(&text),
+        Directions::Steps(mut steps) => {
+            steps.sort_by_key(|step| step.position);
+            renumber(&mut steps);
+            steps
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::testing::recipe;
+
+    fn texts(steps: &[Step]) -> Vec<&str> {
+        steps.iter().map(Step::text).collect()
+    }
+
+    fn positions(steps: &[Step]) -> Vec<u16> {
+        steps.iter().map(Step::position).collect()
+    }
+
+    #[test]
+    fn legacy_directions_round_trip_through_numbered_text() {
+        let steps = parse("Preheat the oven\n\n  Mix the flour and sugar\n3) Bake");
+        assert_eq!(texts(&steps), ["Preheat the oven", "Mix the flour and sugar", "Bake"]);
+        assert_eq!(positions(&steps), [1, 2, 3]);
+        let rendered = render(&steps);
+        assert_eq!(rendered, "1. Preheat the oven\n2. Mix the flour and sugar\n3. Bake");
+        assert_eq!(parse(&rendered), steps);
+    }
+
+    #[test]
+    fn inserting_a_step_in_the_middle_renumbers_the_rest() {
+        let mut built = recipe("Bread").directions("Knead\nBake").build().expect("the recipe should build");
+        built.insert_step(1, Step::new("Prove"));
+        assert_eq!(texts(built.steps()), ["Knead", "Prove", "Bake"]);
+        assert_eq!(positions(built.steps()), [1, 2, 3]);
+    }
+
+    #[test]
+    fn moving_and_removing_steps_keeps_positions_in_order() {
+        let steps = ["Knead", "Prove", "Bake"].map(Step::new);
+        let mut built = recipe("Bread").directions("").steps(steps).build().expect("the recipe should build");
+        assert!(built.move_step(2, 0));
+        assert_eq!(texts(built.steps()), ["Bake", "Knead", "Prove"]);
+        assert!(!built.move_step(0, 3));
+        assert_eq!(built.remove_step(1).map(|step| step.text().to_string()), Some("Knead".to_string()));
+        assert_eq!(texts(built.steps()), ["Bake", "Prove"]);
+        assert_eq!(positions(built.steps()), [1, 2]);
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn steps_deserialize_from_legacy_text_or_a_list() {
+        #[derive(Deserialize)]
+        struct Directions {
+            #[serde(deserialize_with = "deserialize")]
+            steps: Vec<Step>,
+        }
+
+        let legacy: Directions = serde_json::from_str(r#"{"steps": "1. Knead\n2. Bake"}"#).expect("should parse");
+        assert_eq!(texts(&legacy.steps), ["Knead", "Bake"]);
+        let json = r#"{"steps": [{"position": 2, "text": "Bake"}, {"position": 1, "text": "Knead"}]}"#;
+        let listed: Directions = serde_json::from_str(json).expect("should parse");
+        assert_eq!(listed.steps, legacy.steps);
+    }
+}
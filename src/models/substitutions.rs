@@ -0,0 +1,292 @@
+use std::collections::HashMap;
+use uuid::Uuid;
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+use super::{normalize_ingredient_name, Ingredient, Quantity, Recipe, Unit};
+
+/// One ingredient used in place of another, as part of a `SubstitutionRule`.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Replacement {
+    /// The name of the replacing ingredient
+    pub name: String,
+    /// How much of the replacing ingredient to use per unit of the original
+    pub ratio: f64,
+    /// The unit of the replacing ingredient, `None` to use the unit of the original
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub unit: Option<Unit>,
+}
+
+impl Replacement {
+    /// A replacement measured in the same unit as the original.
+    pub fn new(name: impl Into<String>, ratio: f64) -> Self {
+        Self { name: name.into(), ratio, unit: None }
+    }
+
+    /// A replacement measured in `unit`, e.g. tablespoons of flaxseed per egg.
+    pub fn in_unit(name: impl Into<String>, ratio: f64, unit: Unit) -> Self {
+        Self { name: name.into(), ratio, unit: Some(unit) }
+    }
+}
+
+/// A way to replace an ingredient, e.g. butter with the same amount of coconut oil.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct SubstitutionRule {
+    /// The ingredient replaced, matched against names normalized with `normalize_ingredient_name`
+    pub from: String,
+    /// The ingredients used instead
+    pub replacements: Vec<Replacement>,
+    /// How to make the substitution, e.g. "let it stand for 5 minutes"
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub note: String,
+}
+
+impl SubstitutionRule {
+    pub fn new(from: impl Into<String>, replacements: Vec<Replacement>, note: impl Into<String>) -> Self {
+        Self { from: from.into(), replacements, note: note.into() }
+    }
+
+    /// Whether the rule replaces `ingredient`, either by name or as the last words of its name, so a rule for
+    /// butter matches "unsalted butter".
+    pub fn matches(&self, ingredient: &Ingredient) -> bool {
+        name_matches(&normalize_ingredient_name(ingredient.name()), &normalize_ingredient_name(&self.from))
+    }
+
+    /// The replacements for `ingredient` with their amounts scaled by their ratios.
+    pub fn apply(&self, ingredient: &Ingredient) -> Substitution {
+        let replacements = self.replacements
+            .iter()
+            .map(|replacement| {
+                let unit = replacement.unit.clone().unwrap_or_else(|| ingredient.quantity().unit().clone());
+                let quantity = match unit {
+                    Unit::ToTaste => Quantity::to_taste(),
+                    unit => Quantity::new(ingredient.quantity().amount() * replacement.ratio, unit),
+                };
+                Ingredient::new(Uuid::now_v7(), replacement.name.clone(), quantity)
+            })
+            .collect();
+        Substitution { replacements, note: self.note.clone() }
+    }
+}
+
+/// The ingredients to use in place of a particular ingredient, obtained through `suggest_substitutes` or
+/// `SubstitutionTable::suggest`.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+pub struct Substitution {
+    /// The replacing ingredients with their amounts, each with a new id
+    pub replacements: Vec<Ingredient>,
+    /// How to make the substitution
+    pub note: String,
+}
+
+/// Substitution rules keyed by the normalized name of the ingredient they replace. The default table holds
+/// the built-in rules, and rules can be added or overridden at runtime.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SubstitutionTable {
+    rules: HashMap<String, Vec<SubstitutionRule>>,
+}
+
+impl SubstitutionTable {
+    /// A table without any rules, not even the built-in ones.
+    pub fn empty() -> Self {
+        Self { rules: HashMap::new() }
+    }
+
+    /// Adds `rule` as another alternative for its ingredient.
+    pub fn add(&mut self, rule: SubstitutionRule) {
+        self.rules.entry(normalize_ingredient_name(&rule.from)).or_default().push(rule);
+    }
+
+    /// Makes `rule` the only rule for its ingredient, returning the rules it replaced.
+    pub fn insert(&mut self, rule: SubstitutionRule) -> Vec<SubstitutionRule> {
+        self.rules.insert(normalize_ingredient_name(&rule.from), vec![rule]).unwrap_or_default()
+    }
+
+    /// Removes every rule for the ingredient named `from`, returning them.
+    pub fn remove(&mut self, from: &str) -> Vec<SubstitutionRule> {
+        self.rules.remove(&normalize_ingredient_name(from)).unwrap_or_default()
+    }
+
+    /// The rules replacing the ingredient named `from` exactly.
+    pub fn rules(&self, from: &str) -> &[SubstitutionRule] {
+        self.rules.get(&normalize_ingredient_name(from)).map_or(&[], Vec::as_slice)
+    }
+
+    /// The ways to replace `ingredient`, with amounts scaled to its quantity. Rules matching the whole name
+    /// come before those matching only its last words, such as a rule for butter on "unsalted butter".
+    pub fn suggest(&self, ingredient: &Ingredient) -> Vec<Substitution> {
+        let name = normalize_ingredient_name(ingredient.name());
+        let mut matching: Vec<(&String, &Vec<SubstitutionRule>)> = self.rules
+            .iter()
+            .filter(|(from, _)| name_matches(&name, from))
+            .collect();
+        // Longer names match more specifically.
+        matching.sort_by(|(a, _), (b, _)| b.len().cmp(&a.len()).then_with(|| a.cmp(b)));
+        matching.into_iter().flat_map(|(_, rules)| rules).map(|rule| rule.apply(ingredient)).collect()
+    }
+
+    /// Inserts every rule in `json`, an array of `SubstitutionRule`s, overriding existing rules for the
+    /// same ingredients.
+    #[cfg(feature = "serde")]
+    pub fn extend_from_json(&mut self, json: &str) -> Result<(), serde_json::Error> {
+        let rules: Vec<SubstitutionRule> = serde_json::from_str(json)?;
+        let mut loaded = SubstitutionTable::empty();
+        for rule in rules {
+            loaded.add(rule);
+        }
+        self.rules.extend(loaded.rules);
+        Ok(())
+    }
+}
+
+impl Default for SubstitutionTable {
+    /// The built-in rules for common baking and dairy substitutions.
+    fn default() -> Self {
+        let mut table = SubstitutionTable::empty();
+        let rules = [
+            SubstitutionRule::new(
+                "buttermilk",
+                vec![Replacement::new("milk", 1.0), Replacement::new("lemon juice", 1.0 / 16.0)],
+                "Stir the lemon juice into the milk and let it stand for 5 minutes.",
+            ),
+            SubstitutionRule::new(
+                "butter",
+                vec![Replacement::new("coconut oil", 1.0)],
+                "Use refined coconut oil for a neutral taste.",
+            ),
+            SubstitutionRule::new(
+                "egg",
+                vec![
+                    Replacement::in_unit("ground flaxseed", 1.0, Unit::Tablespoon),
+                    Replacement::in_unit("water", 3.0, Unit::Tablespoon),
+                ],
+                "Mix and let it thicken for 5 minutes to make a flax egg.",
+            ),
+            SubstitutionRule::new("sour cream", vec![Replacement::new("greek yogurt", 1.0)], ""),
+            SubstitutionRule::new(
+                "heavy cream",
+                vec![Replacement::new("milk", 0.75), Replacement::new("melted butter", 0.25)],
+                "Not suitable for whipping.",
+            ),
+            SubstitutionRule::new(
+                "brown sugar",
+                vec![Replacement::new("white sugar", 1.0), Replacement::new("molasses", 1.0 / 16.0)],
+                "",
+            ),
+            SubstitutionRule::new(
+                "cake flour",
+                vec![Replacement::new("all-purpose flour", 0.875), Replacement::new("cornstarch", 0.125)],
+                "Sift together twice.",
+            ),
+            SubstitutionRule::new("honey", vec![Replacement::new("maple syrup", 1.0)], ""),
+        ];
+        for rule in rules {
+            table.add(rule);
+        }
+        table
+    }
+}
+
+/// Whether the normalized `name` is `from` or ends with it as whole words.
+fn name_matches(name: &str, from: &str) -> bool {
+    name == from || name.strip_suffix(from).is_some_and(|rest| rest.ends_with(' '))
+}
+
+/// The ways to replace `ingredient` using the built-in rules, see `SubstitutionTable::suggest`.
+pub fn suggest_substitutes(ingredient: &Ingredient) -> Vec<Substitution> {
+    SubstitutionTable::default().suggest(ingredient)
+}
+
+impl Recipe {
+    /// A copy of the recipe with the ingredient with id `from` replaced by the ingredients of `to`, or `None`
+    /// if the recipe has no such ingredient. The copy keeps the recipe's id.
+    pub fn with_substitution(&self, from: &Uuid, to: &Substitution) -> Option<Recipe> {
+        let original = self.ingredients.iter().find(|ingredient| ingredient.id() == *from)?.clone();
+        let mut substituted = self.clone();
+        substituted.ingredients.remove(&original);
+        substituted.ingredients.extend(to.replacements.iter().cloned());
+        substituted.touch();
+        Some(substituted)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::testing::{ingredient, recipe};
+
+    fn listed(substitution: &Substitution) -> Vec<String> {
+        substitution.replacements.iter().map(Ingredient::to_string).collect()
+    }
+
+    #[test]
+    fn built_in_substitutes_are_found_by_normalized_name() {
+        let suggested = suggest_substitutes(&ingredient("Unsalted Butter", 100.0, Unit::Gram));
+        assert_eq!(suggested.len(), 1);
+        assert_eq!(listed(&suggested[0]), ["100 g coconut oil"]);
+        assert_eq!(suggested[0].note, "Use refined coconut oil for a neutral taste.");
+        assert_eq!(suggest_substitutes(&ingredient("Eggs", 2.0, Unit::Piece)).len(), 1);
+        assert!(suggest_substitutes(&ingredient("saffron", 1.0, Unit::Pinch)).is_empty());
+        assert!(suggest_substitutes(&ingredient("peanut butter cups", 1.0, Unit::Cup)).is_empty());
+    }
+
+    #[test]
+    fn quantities_are_scaled_through_the_ratio() {
+        let buttermilk = suggest_substitutes(&ingredient("buttermilk", 2.0, Unit::Cup));
+        assert_eq!(listed(&buttermilk[0]), ["2 cup milk", "⅛ cup lemon juice"]);
+        let eggs = suggest_substitutes(&ingredient("eggs", 2.0, Unit::Piece));
+        let quantities: Vec<&Quantity> = eggs[0].replacements.iter().map(Ingredient::quantity).collect();
+        assert_eq!(quantities, [&Quantity::new(2.0, Unit::Tablespoon), &Quantity::new(6.0, Unit::Tablespoon)]);
+    }
+
+    #[test]
+    fn custom_rules_override_or_extend_the_built_in_ones() {
+        let butter = ingredient("butter", 1.0, Unit::Cup);
+        let mut table = SubstitutionTable::default();
+        let replaced = table.insert(SubstitutionRule::new("Butter", vec![Replacement::new("olive oil", 0.75)], ""));
+        assert_eq!(replaced.len(), 1);
+        assert_eq!(table.suggest(&butter).iter().map(listed).collect::<Vec<_>>(), [["¾ cup olive oil"]]);
+
+        table.add(SubstitutionRule::new("butter", vec![Replacement::new("applesauce", 0.5)], "For baking."));
+        assert_eq!(table.rules("butter").len(), 2);
+        assert_eq!(table.suggest(&butter).len(), 2);
+        assert_eq!(table.remove("BUTTER").len(), 2);
+        assert!(table.suggest(&butter).is_empty());
+        assert!(SubstitutionTable::empty().suggest(&butter).is_empty());
+    }
+
+    #[test]
+    fn rules_for_longer_names_come_first() {
+        let mut table = SubstitutionTable::empty();
+        table.add(SubstitutionRule::new("flour", vec![Replacement::new("almond flour", 1.0)], ""));
+        table.add(SubstitutionRule::new("cake flour", vec![Replacement::new("pastry flour", 1.0)], ""));
+        let suggested = table.suggest(&ingredient("cake flour", 1.0, Unit::Cup));
+        assert_eq!(suggested.iter().map(listed).collect::<Vec<_>>(), [["1 cup pastry flour"], ["1 cup almond flour"]]);
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn rules_load_from_json_over_the_built_in_ones() {
+        let mut table = SubstitutionTable::default();
+        let json = r#"[{ "from": "honey", "replacements": [{ "name": "agave syrup", "ratio": 0.75 }] }]"#;
+        table.extend_from_json(json).expect("the rules should parse");
+        let suggested = table.suggest(&ingredient("honey", 4.0, Unit::Tablespoon));
+        assert_eq!(suggested.iter().map(listed).collect::<Vec<_>>(), [["3 tbsp agave syrup"]]);
+        assert!(table.extend_from_json("{").is_err());
+    }
+
+    #[test]
+    fn with_substitution_replaces_the_ingredient_in_a_copy() {
+        let butter = ingredient("butter", 100.0, Unit::Gram);
+        let original = recipe("Cookies").ingredient(butter.clone()).build().expect("the recipe should build");
+        let substitution = &suggest_substitutes(&butter)[0];
+        let substituted = original.with_substitution(&butter.id(), substitution).expect("the butter is there");
+        let names: Vec<&str> = substituted.ingredients().iter().map(Ingredient::name).collect();
+        assert_eq!(names, ["coconut oil"]);
+        assert_eq!(substituted.id(), original.id());
+        assert!(original.ingredients().contains(&butter));
+        assert!(original.with_substitution(&Uuid::now_v7(), substitution).is_none());
+    }
+}
@@ -0,0 +1,68 @@
+//! Recipes, ingredients and tags for the tests of the models.
+
+use uuid::Uuid;
+use super::{
+    Allergen, Category, Course, Cuisine, Difficulty, Equipment, Ingredient, Nutrition, Quantity, Recipe,
+    RecipeBuilder, RecipeImage, RecipeTag, Source, Step, Unit,
+};
+
+/// The first bytes of a PNG file, enough for its format to be detected.
+pub(crate) const PNG: [u8; 8] = [0x89, b'P', b'N', b'G', 0x0D, 0x0A, 0x1A, 0x0A];
+
+/// The first bytes of a JPEG file, enough for its format to be detected.
+pub(crate) const JPEG: [u8; 4] = [0xFF, 0xD8, 0xFF, 0xE0];
+
+/// A builder for a recipe named `name` with every required field set, one step and nothing else.
+pub(crate) fn recipe(name: &str) -> RecipeBuilder {
+    Recipe::builder()
+        .name(name)
+        .difficulty(Difficulty::Easy)
+        .cook_time(10)
+        .servings(2)
+        .description("A recipe")
+        .category(Category::MainCourse)
+        .step("Cook")
+}
+
+/// A recipe with every optional field set as well, to check nothing is lost along the way.
+pub(crate) fn full_recipe() -> Recipe {
+    let source = Source::new()
+        .with_author("Jane Doe")
+        .with_url("https://example.com/pancakes")
+        .expect("the url should be valid")
+        .with_license("CC BY 4.0");
+    let nutrition = Nutrition { calories: Some(520.0), protein_g: Some(14.5), ..Nutrition::default() };
+    recipe("Pancakes")
+        .difficulty(Difficulty::Medium)
+        .prep_time(10)
+        .cook_time(15)
+        .rest_time(5)
+        .servings(4)
+        .description("Fluffy weekend pancakes")
+        .category(Category::Breakfast)
+        .directions("Whisk the batter")
+        .step(Step::new("Fry until golden").with_duration_minutes(8).with_img(JPEG.to_vec()))
+        .ingredient(ingredient("flour", 1.5, Unit::Cup).with_note("sifted"))
+        .ingredient(ingredient("salt", 0.0, Unit::ToTaste))
+        .tags([tag("breakfast"), tag("occasion:weekend")])
+        .image(RecipeImage::new(PNG.to_vec()).with_caption("stack").primary())
+        .image(RecipeImage::new(JPEG.to_vec()))
+        .nutrition(nutrition)
+        .allergens([Allergen::Gluten, Allergen::Eggs])
+        .equipment_item(Equipment::new("Frying pan").with_note("non-stick"))
+        .cuisine(Cuisine::Other("Scandinavian".to_string()))
+        .course(Course::Breakfast)
+        .source(source)
+        .build()
+        .expect("the recipe should build")
+}
+
+/// An ingredient named `name` with a fresh id.
+pub(crate) fn ingredient(name: &str, amount: f64, unit: Unit) -> Ingredient {
+    Ingredient::new(Uuid::now_v7(), name, Quantity::new(amount, unit))
+}
+
+/// A tag, which must be valid.
+pub(crate) fn tag(tag: &str) -> RecipeTag {
+    RecipeTag::new(tag).expect("the tag should be valid")
+}
@@ -0,0 +1,364 @@
+use std::fmt;
+use super::{DurationMinutes, Equipment, Recipe};
+
+impl Recipe {
+    /// Renders the recipe as a plain text card like its alternate `{:#}` display, with everything but the section
+    /// headings word-wrapped to `width` columns. Width is counted in characters, so wide characters
+    /// such as CJK take a single column, and a word longer than a line is split between characters.
+    pub fn to_plain_text(&self, width: usize) -> String {
+        self.render(width)
+    }
+
+    /// Renders the recipe as a Markdown document: the name as a heading, a line with the difficulty, time and
+    /// servings, the description, bulleted ingredient and equipment lists sorted by name, the numbered
+    /// directions, the tags as an italic line and the source as a trailing `Source:` line like the plain text
+    /// card has. Images are left out, see `Recipe::to_markdown_with_image`.
+    pub fn to_markdown(&self) -> String {
+        self.markdown(None)
+    }
+
+    /// Like `Recipe::to_markdown`, with an image referencing `image_path` below the heading. The picture
+    /// itself is never embedded.
+    pub fn to_markdown_with_image(&self, image_path: &str) -> String {
+        self.markdown(Some(image_path))
+    }
+
+    fn markdown(&self, image_path: Option<&str>) -> String {
+        let mut out = format!("# {}\n\n", escape_markdown(&self.name));
+        out.push_str(&format!(
+            "**Difficulty:** {} · **Time:** {} · **Serves:** {}\n",
+            self.difficulty,
+            DurationMinutes(self.duration()),
+            self.servings
+        ));
+        if let Some(path) = image_path {
+            out.push_str(&format!("\n![{}](<{}>)\n", escape_markdown(&self.name), path));
+        }
+        if !self.description.trim().is_empty() {
+            out.push('\n');
+            for line in self.description.trim().lines() {
+                out.push_str(&escape_markdown(line));
+                out.push('\n');
+            }
+        }
+
+        if !self.ingredients.is_empty() {
+            out.push_str("\n## Ingredients\n\n");
+            let mut ingredients: Vec<_> = self.ingredients.iter().collect();
+            ingredients.sort_by_key(|ingredient| ingredient.normalized_name());
+            for ingredient in ingredients {
+                out.push_str(&format!("- {}\n", escape_markdown(&ingredient.to_string())));
+            }
+        }
+
+        if !self.equipment.is_empty() {
+            out.push_str("\n## Equipment\n\n");
+            for equipment in self.sorted_equipment() {
+                out.push_str(&format!("- {}\n", escape_markdown(&equipment.to_string())));
+            }
+        }
+
+        out.push_str("\n## Directions\n\n");
+        for step in &self.steps {
+            let text = match step.duration_minutes() {
+                Some(minutes) => format!("{} ({})", step.text(), DurationMinutes(minutes)),
+                None => step.text().to_string(),
+            };
+            out.push_str(&format!("{}. {}\n", step.position(), escape_markdown(&text)));
+        }
+
+        if !self.tags.is_empty() {
+            let mut tags: Vec<&str> = self.tags.iter().map(|tag| tag.as_str()).collect();
+            tags.sort_by_key(|tag| tag.to_lowercase());
+            out.push_str(&format!("\n*Tags: {}*\n", escape_markdown(&tags.join(", "))));
+        }
+        if let Some(source) = self.source.as_ref() {
+            out.push_str(&format!("\nSource: {}\n", escape_markdown(&source.to_string())));
+        }
+        out
+    }
+
+    fn render(&self, width: usize) -> String {
+        let mut out = String::new();
+        wrap(&self.name, width, "", "", &mut out);
+        out.push_str(&"=".repeat(self.name.chars().count().min(width)));
+        out.push('\n');
+
+        wrap(&format!("Difficulty: {}", self.difficulty), width, "", "", &mut out);
+        let mut time = format!("Time: {}", DurationMinutes(self.duration()));
+        let times = [("prep", self.times.prep_minutes), ("cook", self.times.cook_minutes), ("rest", self.times.rest_minutes)];
+        let parts: Vec<String> = times
+            .into_iter()
+            .filter(|(_, minutes)| *minutes > 0)
+            .map(|(label, minutes)| format!("{} {}", label, DurationMinutes(minutes)))
+            .collect();
+        if parts.len() > 1 {
+            time.push_str(&format!(" ({})", parts.join(", ")));
+        }
+        wrap(&time, width, "", "", &mut out);
+        wrap(&format!("Serves: {}", self.servings), width, "", "", &mut out);
+
+        if !self.description.trim().is_empty() {
+            out.push('\n');
+            for line in self.description.lines() {
+                wrap(line, width, "", "", &mut out);
+            }
+        }
+
+        if !self.ingredients.is_empty() {
+            out.push_str("\nIngredients\n");
+            let mut ingredients: Vec<_> = self.ingredients.iter().collect();
+            ingredients.sort_by_key(|ingredient| ingredient.normalized_name());
+            for ingredient in ingredients {
+                wrap(&ingredient.to_string(), width, "- ", "  ", &mut out);
+            }
+        }
+
+        if !self.equipment.is_empty() {
+            out.push_str("\nEquipment\n");
+            for equipment in self.sorted_equipment() {
+                wrap(&equipment.to_string(), width, "- ", "  ", &mut out);
+            }
+        }
+
+        out.push_str("\nDirections\n");
+        for step in &self.steps {
+            let number = format!("{}. ", step.position());
+            let indent = " ".repeat(number.len());
+            let text = match step.duration_minutes() {
+                Some(minutes) => format!("{} ({})", step.text(), DurationMinutes(minutes)),
+                None => step.text().to_string(),
+            };
+            wrap(&text, width, &number, &indent, &mut out);
+        }
+
+        if !self.tags.is_empty() {
+            let mut tags: Vec<&str> = self.tags.iter().map(|tag| tag.as_str()).collect();
+            tags.sort_by_key(|tag| tag.to_lowercase());
+            out.push('\n');
+            wrap(&format!("Tags: {}", tags.join(", ")), width, "", "", &mut out);
+        }
+        if let Some(source) = self.source.as_ref() {
+            out.push('\n');
+            wrap(&format!("Source: {}", source), width, "", "", &mut out);
+        }
+        out
+    }
+
+    fn sorted_equipment(&self) -> Vec<&Equipment> {
+        let mut equipment: Vec<&Equipment> = self.equipment.iter().collect();
+        equipment.sort_by(|a, b| a.name().cmp(b.name()));
+        equipment
+    }
+}
+
+impl fmt::Display for Recipe {
+    /// Displays the recipe as a one-line summary for listings, e.g. `"Pad Thai (Medium, 30 min, 8 ingredients)"`.
+    ///
+    /// The alternate form `{:#}` displays it as a plain text card instead: the name, difficulty, time and
+    /// servings, the description, bulleted ingredient and equipment lists sorted by name, the numbered
+    /// directions, then the tags and source if any. Nothing is wrapped, see `Recipe::to_plain_text` for that.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if f.alternate() {
+            return f.write_str(self.render(usize::MAX).trim_end());
+        }
+        let count = self.ingredient_count();
+        let noun = if count == 1 { "ingredient" } else { "ingredients" };
+        write!(f, "{} ({}, {}, {} {})", self.name, self.difficulty, self.duration_display(), count, noun)
+    }
+}
+
+/// Escapes the characters Markdown would otherwise treat as formatting inside a line of text.
+pub(crate) fn escape_markdown(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        if matches!(c, '\\' | '`' | '*' | '_' | '[' | ']' | '<' | '>' | '#') {
+            escaped.push('\\');
+        }
+        escaped.push(c);
+    }
+    escaped
+}
+
+/// Word-wraps `text` to `width` characters and appends it to `out` line by line, starting the first line
+/// with `first` and every following line with `rest`.
+fn wrap(text: &str, width: usize, first: &str, rest: &str, out: &mut String) {
+    // Leave room for at least one character after the longer prefix.
+    let width = width.max(first.chars().count().max(rest.chars().count()) + 1);
+    let mut line = first.to_string();
+    let mut line_len = first.chars().count();
+    let mut line_empty = true;
+    for mut word in text.split_whitespace() {
+        loop {
+            let word_len = word.chars().count();
+            let needed = if line_empty { word_len } else { word_len + 1 };
+            if line_len.saturating_add(needed) <= width {
+                if !line_empty {
+                    line.push(' ');
+                }
+                line.push_str(word);
+                line_len += needed;
+                line_empty = false;
+                break;
+            }
+            if line_empty {
+                // The word does not fit on a line of its own, split it between characters.
+                let split = word.char_indices().nth(width - line_len).map_or(word.len(), |(i, _)| i);
+                line.push_str(&word[..split]);
+                word = &word[split..];
+            }
+            out.push_str(&line);
+            out.push('\n');
+            line = rest.to_string();
+            line_len = rest.chars().count();
+            line_empty = true;
+        }
+    }
+    out.push_str(line.trim_end());
+    out.push('\n');
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::testing::{ingredient, recipe, tag, PNG};
+    use crate::models::{Difficulty, Unit};
+
+    fn pancakes() -> Recipe {
+        recipe("Pancakes")
+            .prep_time(10)
+            .cook_time(15)
+            .servings(4)
+            .description("Fluffy pancakes for a slow weekend breakfast")
+            .directions("Whisk the flour, milk and eggs into a smooth batter\nFry until golden")
+            .ingredient(ingredient("flour", 1.5, Unit::Cup).with_note("sifted"))
+            .ingredient(ingredient("salt", 0.0, Unit::ToTaste))
+            .tags([tag("breakfast"), tag("Sweet")])
+            .build()
+            .expect("the recipe should build")
+    }
+
+    #[test]
+    fn the_summary_counts_one_ingredient_and_leaves_out_the_image() {
+        let pad_thai = recipe("Pad Thai")
+            .difficulty(Difficulty::Medium)
+            .prep_time(30)
+            .cook_time(60)
+            .ingredient(ingredient("noodles", 200.0, Unit::Gram))
+            .img(PNG.to_vec())
+            .build()
+            .expect("the recipe should build");
+        assert_eq!(format!("{}", pad_thai), "Pad Thai (Medium, 1 hr 30 min, 1 ingredient)");
+    }
+
+    #[test]
+    fn displays_a_one_line_summary_and_an_alternate_card() {
+        let pancakes = pancakes();
+        assert_eq!(pancakes.to_string(), "Pancakes (Easy, 25 min, 2 ingredients)");
+        assert_eq!(
+            format!("{:#}", pancakes),
+            "Pancakes\n\
+             ========\n\
+             Difficulty: Easy\n\
+             Time: 25 min (prep 10 min, cook 15 min)\n\
+             Serves: 4\n\
+             \n\
+             Fluffy pancakes for a slow weekend breakfast\n\
+             \n\
+             Ingredients\n\
+             - 1 ½ cup flour, sifted\n\
+             - salt to taste\n\
+             \n\
+             Directions\n\
+             1. Whisk the flour, milk and eggs into a smooth batter\n\
+             2. Fry until golden\n\
+             \n\
+             Tags: breakfast, sweet"
+        );
+    }
+
+    #[test]
+    fn plain_text_wraps_to_the_width() {
+        assert_eq!(
+            pancakes().to_plain_text(24),
+            "Pancakes\n\
+             ========\n\
+             Difficulty: Easy\n\
+             Time: 25 min (prep 10\n\
+             min, cook 15 min)\n\
+             Serves: 4\n\
+             \n\
+             Fluffy pancakes for a\n\
+             slow weekend breakfast\n\
+             \n\
+             Ingredients\n\
+             - 1 ½ cup flour, sifted\n\
+             - salt to taste\n\
+             \n\
+             Directions\n\
+             1. Whisk the flour, milk\n   \
+             and eggs into a\n   \
+             smooth batter\n\
+             2. Fry until golden\n\
+             \n\
+             Tags: breakfast, sweet\n"
+        );
+    }
+
+    #[test]
+    fn markdown_matches_the_expected_document() {
+        assert_eq!(
+            pancakes().to_markdown(),
+            "# Pancakes\n\
+             \n\
+             **Difficulty:** Easy · **Time:** 25 min · **Serves:** 4\n\
+             \n\
+             Fluffy pancakes for a slow weekend breakfast\n\
+             \n\
+             ## Ingredients\n\
+             \n\
+             - 1 ½ cup flour, sifted\n\
+             - salt to taste\n\
+             \n\
+             ## Directions\n\
+             \n\
+             1. Whisk the flour, milk and eggs into a smooth batter\n\
+             2. Fry until golden\n\
+             \n\
+             *Tags: breakfast, sweet*\n"
+        );
+    }
+
+    #[test]
+    fn markdown_references_images_by_path_and_escapes_formatting() {
+        let built = recipe("Mac *and* cheese").img(PNG.to_vec()).build().expect("the recipe should build");
+        let markdown = built.to_markdown_with_image("images/mac.png");
+        assert!(markdown.starts_with("# Mac \\*and\\* cheese\n"), "{}", markdown);
+        assert!(markdown.contains("\n![Mac \\*and\\* cheese](<images/mac.png>)\n"), "{}", markdown);
+        assert!(!built.to_markdown().contains("!["));
+    }
+
+    #[test]
+    fn wrapping_never_splits_a_character() {
+        let built = recipe("Crème brûlée")
+            .ingredient(ingredient("crème fraîche épaisse", 200.0, Unit::Gram))
+            .directions("Caraméliser soigneusement 焦糖焦糖焦糖焦糖焦糖焦糖")
+            .build()
+            .expect("the recipe should build");
+        let text = built.to_plain_text(12);
+        for line in text.lines() {
+            assert!(line.chars().count() <= 12, "{:?} is wider than 12 characters", line);
+        }
+        assert!(text.contains("- 200 g\n  crème\n  fraîche\n  épaisse\n"), "{}", text);
+        assert!(text.contains("1. Caramélis\n   er\n"), "{}", text);
+        assert!(text.contains("   焦糖焦糖焦糖焦糖焦\n   糖焦糖\n"), "{}", text);
+    }
+
+    #[test]
+    fn ingredients_and_difficulties_display_as_listed() {
+        assert_eq!(ingredient("flour", 2.0, Unit::Cup).to_string(), "2 cup flour");
+        assert_eq!(ingredient("salt", 0.0, Unit::ToTaste).to_string(), "salt to taste");
+        assert_eq!(ingredient("onion", 1.0, Unit::Piece).with_note("diced").to_string(), "1 onion, diced");
+        assert_eq!(Difficulty::Expert.to_string(), "Expert");
+    }
+}
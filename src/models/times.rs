@@ -0,0 +1,310 @@
+use std::error::Error;
+use std::fmt;
+use std::str::FromStr;
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+#[cfg(feature = "serde")]
+use serde::ser::SerializeStruct;
+
+/// How long a recipe takes, split into active preparation, cooking and resting time, all in minutes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct CookingTimes {
+    /// The active preparation time in minutes
+    pub prep_minutes: u16,
+    /// The cooking time in minutes
+    pub cook_minutes: u16,
+    /// The time the dish needs to rest, cool or set in minutes
+    pub rest_minutes: u16,
+}
+
+impl CookingTimes {
+    pub fn new(prep_minutes: u16, cook_minutes: u16, rest_minutes: u16) -> Self {
+        Self { prep_minutes, cook_minutes, rest_minutes }
+    }
+
+    /// The total time in minutes, saturating at `u16::MAX` rather than overflowing.
+    pub fn total(&self) -> u16 {
+        self.prep_minutes.saturating_add(self.cook_minutes).saturating_add(self.rest_minutes)
+    }
+}
+
+/// Serializes the three times along with their total as `duration`.
+#[cfg(feature = "serde")]
+impl Serialize for CookingTimes {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut state = serializer.serialize_struct("CookingTimes", 4)?;
+        state.serialize_field("prep_minutes", &self.prep_minutes)?;
+        state.serialize_field("cook_minutes", &self.cook_minutes)?;
+        state.serialize_field("rest_minutes", &self.rest_minutes)?;
+        state.serialize_field("duration", &self.total())?;
+        state.end()
+    }
+}
+
+/// Deserializes the three times, missing ones default to 0. Legacy data with only a single `duration`
+/// is read as cook time.
+#[cfg(feature = "serde")]
+impl<'de> Deserialize<'de> for CookingTimes {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        #[derive(Deserialize)]
+        struct Fields {
+            prep_minutes: Option<u16>,
+            cook_minutes: Option<u16>,
+            rest_minutes: Option<u16>,
+            duration: Option<u16>,
+        }
+
+        let fields = Fields::deserialize(deserializer)?;
+        let legacy = fields.prep_minutes.is_none() && fields.cook_minutes.is_none() && fields.rest_minutes.is_none();
+        let cook_minutes = if legacy { fields.duration } else { fields.cook_minutes };
+        Ok(CookingTimes {
+            prep_minutes: fields.prep_minutes.unwrap_or(0),
+            cook_minutes: cook_minutes.unwrap_or(0),
+            rest_minutes: fields.rest_minutes.unwrap_or(0),
+        })
+    }
+}
+
+/// A number of minutes, displayed and parsed in a human-friendly form such as `"2 hr 15 min"`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
+pub struct DurationMinutes(pub u16);
+
+impl DurationMinutes {
+    /// Parses a duration that must be longer than zero minutes.
+    pub fn parse_required(s: &str) -> Result<Self, ParseDurationError> {
+        match s.parse()? {
+            DurationMinutes(0) => Err(ParseDurationError::Zero),
+            duration => Ok(duration),
+        }
+    }
+}
+
+impl From<u16> for DurationMinutes {
+    fn from(minutes: u16) -> Self {
+        DurationMinutes(minutes)
+    }
+}
+
+impl fmt::Display for DurationMinutes {
+    /// Displays the duration as `"45 min"`, `"1 hr"` or `"2 hr 15 min"`.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let (hours, minutes) = (self.0 / 60, self.0 % 60);
+        match (hours, minutes) {
+            (0, minutes) => write!(f, "{} min", minutes),
+            (hours, 0) => write!(f, "{} hr", hours),
+            (hours, minutes) => write!(f, "{} hr {} min", hours, minutes),
+        }
+    }
+}
+
+impl FromStr for DurationMinutes {
+    type Err = ParseDurationError;
+
+    /// Parses a plain number of minutes (`"90"`), hours and minutes separated by a colon (`"1:30"`), or
+    /// amounts followed by hour and minute units (`"1h30m"`, `"90 minutes"`, `"2 hr 15 min"`). Durations
+    /// longer than `u16::MAX` minutes saturate.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || ParseDurationError::Invalid(s.to_string());
+        let input = s.trim().to_lowercase();
+        if input.is_empty() {
+            return Err(invalid());
+        }
+        let number = |digits: &str| -> Result<u64, ParseDurationError> {
+            if digits.is_empty() || !digits.bytes().all(|b| b.is_ascii_digit()) {
+                return Err(invalid());
+            }
+            // Digits only, so this can only fail by overflowing, which saturates.
+            Ok(digits.parse().unwrap_or(u64::MAX))
+        };
+
+        let minutes = if let Some((hours, minutes)) = input.split_once(':') {
+            let minutes = number(minutes)?;
+            if minutes >= 60 {
+                return Err(invalid());
+            }
+            number(hours)?.saturating_mul(60).saturating_add(minutes)
+        } else if input.bytes().all(|b| b.is_ascii_digit()) {
+            number(&input)?
+        } else {
+            let mut total = 0u64;
+            let mut rest = input.as_str();
+            while !rest.is_empty() {
+                let digits_end = rest.find(|c: char| !c.is_ascii_digit()).unwrap_or(rest.len());
+                let amount = number(&rest[..digits_end])?;
+                rest = rest[digits_end..].trim_start();
+                let unit_end = rest.find(|c: char| !c.is_alphabetic()).unwrap_or(rest.len());
+                let multiplier = match &rest[..unit_end] {
+                    "h" | "hr" | "hrs" | "hour" | "hours" => 60,
+                    "m" | "min" | "mins" | "minute" | "minutes" => 1,
+                    _ => return Err(invalid()),
+                };
+                total = total.saturating_add(amount.saturating_mul(multiplier));
+                rest = rest[unit_end..].trim_start();
+            }
+            total
+        };
+        Ok(DurationMinutes(u16::try_from(minutes).unwrap_or(u16::MAX)))
+    }
+}
+
+/// The error returned when text cannot be parsed into a `DurationMinutes`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ParseDurationError {
+    /// The text is not a recognized duration
+    Invalid(String),
+    /// The duration is zero where a non-zero duration is required
+    Zero,
+}
+
+impl fmt::Display for ParseDurationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ParseDurationError::Invalid(s) => write!(f, "invalid duration \"{}\"", s),
+            ParseDurationError::Zero => write!(f, "duration must be longer than zero minutes"),
+        }
+    }
+}
+
+impl Error for ParseDurationError {}
+
+/// Serializes the duration as a plain number of minutes.
+#[cfg(feature = "serde")]
+impl Serialize for DurationMinutes {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_u16(self.0)
+    }
+}
+
+/// Deserializes the duration from a number of minutes or any of the text forms accepted by `FromStr`.
+#[cfg(feature = "serde")]
+impl<'de> Deserialize<'de> for DurationMinutes {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        #[derive(Deserialize)]
+        #[serde(untagged)]
+        enum Repr {
+            Minutes(u64),
+            Text(String),
+        }
+
+        match Repr::deserialize(deserializer)? {
+            Repr::Minutes(minutes) => Ok(DurationMinutes(u16::try_from(minutes).unwrap_or(u16::MAX))),
+            Repr::Text(text) => text.parse().map_err(serde::de::Error::custom),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::testing::recipe;
+    use crate::models::{Category, Difficulty, Recipe, RecipeBuildError};
+
+    #[test]
+    fn total_is_the_saturating_sum_of_the_times() {
+        assert_eq!(CookingTimes::new(10, 480, 5).total(), 495);
+        assert_eq!(CookingTimes::new(u16::MAX, 1, 0).total(), u16::MAX);
+        let built = recipe("Chili").prep_time(10).cook_time(480).build().expect("the recipe should build");
+        assert_eq!(built.duration(), 490);
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn times_serialize_with_their_total() {
+        let json = serde_json::to_value(CookingTimes::new(10, 20, 5)).expect("the times should serialize");
+        let expected = serde_json::json!({"prep_minutes": 10, "cook_minutes": 20, "rest_minutes": 5, "duration": 35});
+        assert_eq!(json, expected);
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn legacy_json_with_only_a_duration_is_read_as_cook_time() {
+        let times: CookingTimes = serde_json::from_str(r#"{"duration": 45}"#).expect("the times should parse");
+        assert_eq!(times, CookingTimes::new(0, 45, 0));
+
+        let mut json = serde_json::to_value(recipe("Stew").build().expect("the recipe should build"))
+            .expect("the recipe should serialize");
+        let fields = json.as_object_mut().expect("a recipe serializes to an object");
+        for field in ["prep_minutes", "cook_minutes", "rest_minutes"] {
+            fields.remove(field);
+        }
+        fields.insert("duration".to_string(), serde_json::json!(90));
+        let parsed: crate::models::Recipe = serde_json::from_value(json).expect("the recipe should deserialize");
+        assert_eq!(parsed.times(), &CookingTimes::new(0, 90, 0));
+    }
+
+    #[test]
+    fn the_total_duration_is_the_sum_of_prep_and_cook_time() {
+        let built = recipe("Stew").prep_time(20).cook_time(95).build().expect("the recipe should build");
+        assert_eq!((built.times().prep_minutes, built.times().cook_minutes), (20, 95));
+        assert_eq!((built.total_duration(), built.duration()), (115, 115));
+    }
+
+    #[test]
+    #[allow(deprecated)]
+    fn the_builder_rejects_the_legacy_duration_along_with_split_times() {
+        let conflicting = Recipe::builder()
+            .name("Stew")
+            .difficulty(Difficulty::Easy)
+            .servings(2)
+            .description("")
+            .category(Category::MainCourse)
+            .directions("Simmer")
+            .duration(60)
+            .prep_time(10);
+        assert_eq!(conflicting.build().err(), Some(RecipeBuildError::ConflictingDuration));
+        assert_eq!(recipe("Stew").duration(45).build().err(), Some(RecipeBuildError::ConflictingDuration));
+    }
+
+    #[test]
+    fn durations_display_in_hours_and_minutes() {
+        assert_eq!(DurationMinutes(135).to_string(), "2 hr 15 min");
+        assert_eq!(DurationMinutes(45).to_string(), "45 min");
+        assert_eq!(DurationMinutes(60).to_string(), "1 hr");
+        assert_eq!(DurationMinutes(90).to_string(), "1 hr 30 min");
+        assert_eq!(DurationMinutes(0).to_string(), "0 min");
+    }
+
+    #[test]
+    fn recipes_display_their_total_duration_but_store_the_minutes() {
+        let cases = [(15, 30, "45 min"), (20, 40, "1 hr"), (30, 60, "1 hr 30 min"), (45, 90, "2 hr 15 min")];
+        for (prep, cook, display) in cases {
+            let built = recipe("Stew").prep_time(prep).cook_time(cook).build().expect("the recipe should build");
+            assert_eq!((built.duration(), built.duration_display().as_str()), (prep + cook, display));
+        }
+    }
+
+    #[test]
+    fn durations_parse_from_every_accepted_shape() {
+        for (text, minutes) in [
+            ("1h30m", 90),
+            ("90 minutes", 90),
+            ("1:30", 90),
+            ("90", 90),
+            ("2 hr 15 min", 135),
+            (" 1 Hour ", 60),
+            ("99999", u16::MAX),
+            ("2000 hours", u16::MAX),
+        ] {
+            assert_eq!(text.parse(), Ok(DurationMinutes(minutes)), "{:?}", text);
+        }
+    }
+
+    #[test]
+    fn malformed_and_zero_durations_are_rejected() {
+        for text in ["ninety minutes", "-5", "", "1:75", "1h30x", "1.5 hours"] {
+            assert_eq!(text.parse::<DurationMinutes>(), Err(ParseDurationError::Invalid(text.to_string())));
+        }
+        assert_eq!(DurationMinutes::parse_required("0 min"), Err(ParseDurationError::Zero));
+        assert_eq!(DurationMinutes::parse_required("5"), Ok(DurationMinutes(5)));
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn durations_serialize_as_minutes_and_deserialize_from_text() {
+        assert_eq!(serde_json::to_string(&DurationMinutes(90)).expect("should serialize"), "90");
+        for json in ["90", "\"1h30m\"", "\"1:30\""] {
+            assert_eq!(serde_json::from_str::<DurationMinutes>(json).ok(), Some(DurationMinutes(90)), "{}", json);
+        }
+        assert!(serde_json::from_str::<DurationMinutes>("\"ninety\"").is_err());
+    }
+}
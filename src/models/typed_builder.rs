@@ -0,0 +1,199 @@
+use std::marker::PhantomData;
+use uuid::Uuid;
+use super::{
+    Allergen, Category, Course, Cuisine, Difficulty, Ingredient, Nutrition, Recipe, RecipeBuilder, RecipeImage,
+    RecipeTag, Source, Step,
+};
+
+/// Marks a required field of a `TypedRecipeBuilder` that has not been set yet.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Missing;
+
+/// Marks a required field of a `TypedRecipeBuilder` that has been set.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Set;
+
+/// A builder for `Recipe` that checks at compile time that the required fields are set, obtained through
+/// `Recipe::typed_builder`.
+///
+/// Each type parameter tracks one required field: the name, difficulty, duration (a prep or cook time),
+/// servings, description, category and directions. `build` is only available once all of them are `Set`, so
+/// it cannot fail. Whether the fields make sense, e.g. that the directions are not blank, is left to
+/// `Recipe::validate`. The optional fields can be set in any state. Use `RecipeBuilder` when which
+/// fields are present is only known at runtime.
+pub struct TypedRecipeBuilder<Name, Diff, Time, Serv, Desc, Cat, Dirs> {
+    /// The builder holding the fields set so far
+    inner: RecipeBuilder,
+    /// Which required fields have been set
+    state: PhantomData<(Name, Diff, Time, Serv, Desc, Cat, Dirs)>,
+}
+
+impl Recipe {
+    pub fn typed_builder() -> TypedRecipeBuilder<Missing, Missing, Missing, Missing, Missing, Missing, Missing> {
+        TypedRecipeBuilder { inner: RecipeBuilder::new(), state: PhantomData }
+    }
+}
+
+impl<Name, Diff, Time, Serv, Desc, Cat, Dirs> TypedRecipeBuilder<Name, Diff, Time, Serv, Desc, Cat, Dirs> {
+    pub fn name(self, name: impl Into<String>) -> TypedRecipeBuilder<Set, Diff, Time, Serv, Desc, Cat, Dirs> {
+        TypedRecipeBuilder { inner: self.inner.name(name), state: PhantomData }
+    }
+
+    pub fn difficulty(self, difficulty: Difficulty) -> TypedRecipeBuilder<Name, Set, Time, Serv, Desc, Cat, Dirs> {
+        TypedRecipeBuilder { inner: self.inner.difficulty(difficulty), state: PhantomData }
+    }
+
+    pub fn prep_time(self, prep_minutes: u16) -> TypedRecipeBuilder<Name, Diff, Set, Serv, Desc, Cat, Dirs> {
+        TypedRecipeBuilder { inner: self.inner.prep_time(prep_minutes), state: PhantomData }
+    }
+
+    pub fn cook_time(self, cook_minutes: u16) -> TypedRecipeBuilder<Name, Diff, Set, Serv, Desc, Cat, Dirs> {
+        TypedRecipeBuilder { inner: self.inner.cook_time(cook_minutes), state: PhantomData }
+    }
+
+    pub fn servings(self, servings: u16) -> TypedRecipeBuilder<Name, Diff, Time, Set, Desc, Cat, Dirs> {
+        TypedRecipeBuilder { inner: self.inner.servings(servings), state: PhantomData }
+    }
+
+    pub fn description(
+        self,
+        description: impl Into<String>,
+    ) -> TypedRecipeBuilder<Name, Diff, Time, Serv, Set, Cat, Dirs> {
+        TypedRecipeBuilder { inner: self.inner.description(description), state: PhantomData }
+    }
+
+    pub fn category(self, category: Category) -> TypedRecipeBuilder<Name, Diff, Time, Serv, Desc, Set, Dirs> {
+        TypedRecipeBuilder { inner: self.inner.category(category), state: PhantomData }
+    }
+
+    /// Sets the directions from free text, one step per non-empty line.
+    pub fn directions(self, directions: &str) -> TypedRecipeBuilder<Name, Diff, Time, Serv, Desc, Cat, Set> {
+        TypedRecipeBuilder { inner: self.inner.directions(directions), state: PhantomData }
+    }
+
+    pub fn steps(
+        self,
+        steps: impl IntoIterator<Item = Step>,
+    ) -> TypedRecipeBuilder<Name, Diff, Time, Serv, Desc, Cat, Set> {
+        TypedRecipeBuilder { inner: self.inner.steps(steps), state: PhantomData }
+    }
+
+    /// Sets the id, only needed when rehydrating an existing recipe. A fresh id is generated otherwise.
+    pub fn id(mut self, id: Uuid) -> Self {
+        self.inner = self.inner.id(id);
+        self
+    }
+
+    pub fn rest_time(mut self, rest_minutes: u16) -> Self {
+        self.inner = self.inner.rest_time(rest_minutes);
+        self
+    }
+
+    pub fn ingredient(mut self, ingredient: Ingredient) -> Self {
+        self.inner = self.inner.ingredient(ingredient);
+        self
+    }
+
+    pub fn ingredients(mut self, ingredients: impl IntoIterator<Item = Ingredient>) -> Self {
+        self.inner = self.inner.ingredients(ingredients);
+        self
+    }
+
+    pub fn tag(mut self, tag: RecipeTag) -> Self {
+        self.inner = self.inner.tag(tag);
+        self
+    }
+
+    pub fn tags(mut self, tags: impl IntoIterator<Item = RecipeTag>) -> Self {
+        self.inner = self.inner.tags(tags);
+        self
+    }
+
+    /// Adds `img` as the primary image, replacing any earlier primary image.
+    pub fn img(mut self, img: Vec<u8>) -> Self {
+        self.inner = self.inner.img(img);
+        self
+    }
+
+    pub fn image(mut self, image: RecipeImage) -> Self {
+        self.inner = self.inner.image(image);
+        self
+    }
+
+    pub fn nutrition(mut self, nutrition: Nutrition) -> Self {
+        self.inner = self.inner.nutrition(nutrition);
+        self
+    }
+
+    pub fn allergen(mut self, allergen: Allergen) -> Self {
+        self.inner = self.inner.allergen(allergen);
+        self
+    }
+
+    pub fn cuisine(mut self, cuisine: Cuisine) -> Self {
+        self.inner = self.inner.cuisine(cuisine);
+        self
+    }
+
+    pub fn course(mut self, course: Course) -> Self {
+        self.inner = self.inner.course(course);
+        self
+    }
+
+    pub fn source(mut self, source: Source) -> Self {
+        self.inner = self.inner.source(source);
+        self
+    }
+}
+
+impl TypedRecipeBuilder<Set, Set, Set, Set, Set, Set, Set> {
+    pub fn build(self) -> Recipe {
+        self.inner.assemble().expect("every required field is set")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::testing::{ingredient, tag, PNG};
+    use crate::models::Unit;
+
+    #[test]
+    fn builds_once_every_required_field_is_set_in_any_order() {
+        let built = Recipe::typed_builder()
+            .directions("Boil the water\nSteep the tea")
+            .servings(2)
+            .ingredient(ingredient("tea", 2.0, Unit::Teaspoon))
+            .name("Tea")
+            .prep_time(5)
+            .difficulty(Difficulty::Easy)
+            .tag(tag("drinks"))
+            .description("A pot of tea")
+            .category(Category::Beverage)
+            .img(PNG.to_vec())
+            .build();
+        assert_eq!((built.name(), built.difficulty(), built.duration()), ("Tea", &Difficulty::Easy, 5));
+        assert_eq!((built.servings(), built.description(), built.steps().len()), (2, "A pot of tea", 2));
+        assert_eq!((built.ingredient_count(), built.tags().len(), built.has_img()), (1, 1, true));
+        assert_eq!(built.category(), Category::Beverage);
+        assert!(!built.id().is_nil());
+    }
+
+    #[test]
+    fn the_id_is_kept_when_set_and_fresh_otherwise() {
+        let id = Uuid::now_v7();
+        let typed = || {
+            Recipe::typed_builder()
+                .name("Tea")
+                .difficulty(Difficulty::Easy)
+                .cook_time(5)
+                .servings(1)
+                .category(Category::Beverage)
+        };
+        let with_id = typed().id(id).description("").steps([Step::new("Steep")]).build();
+        assert_eq!(with_id.id(), id);
+        let first = typed().description("").directions("Steep").build();
+        let second = typed().description("").directions("Steep").build();
+        assert_ne!(first.id(), second.id());
+    }
+}
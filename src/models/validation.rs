@@ -0,0 +1,499 @@
+use std::error::Error;
+use std::fmt;
+#[cfg(feature = "serde")]
+use serde::ser::{Serialize, SerializeStruct, Serializer};
+use super::{ImageError, Recipe, RecipeBuildError, RecipeBuilder, RecipeStatus, RecipeTag, TagError};
+
+/// A single problem found when validating a `Recipe`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ValidationError {
+    /// A required field was not set
+    Missing { field: &'static str },
+    /// A text field is empty or only whitespace
+    Blank { field: &'static str },
+    /// A collection that needs at least one entry is empty
+    Empty { field: &'static str },
+    /// A number that must be positive is zero
+    Zero { field: &'static str },
+    /// A text field is longer than allowed, lengths are counted in characters
+    TooLong { field: &'static str, max: usize, actual: usize },
+    /// A number is larger than allowed
+    TooLarge { field: &'static str, max: usize, actual: usize },
+    /// A collection has more entries than allowed
+    TooMany { field: &'static str, max: usize, actual: usize },
+    /// A text field contains a character that is not allowed
+    InvalidCharacter { field: &'static str, character: char },
+    /// A field was set along with others it cannot be combined with, named in `with`
+    Conflict { field: &'static str, with: &'static str },
+    /// A picture is not in any recognized image format
+    UnrecognizedImage { field: &'static str },
+    /// A picture is larger than allowed, sizes are counted in bytes
+    ImageTooLarge { field: &'static str, max: usize, actual: usize },
+}
+
+impl ValidationError {
+    /// The name of the field the problem was found in.
+    pub fn field(&self) -> &'static str {
+        match self {
+            ValidationError::Missing { field }
+            | ValidationError::Blank { field }
+            | ValidationError::Empty { field }
+            | ValidationError::Zero { field }
+            | ValidationError::TooLong { field, .. }
+            | ValidationError::TooLarge { field, .. }
+            | ValidationError::TooMany { field, .. }
+            | ValidationError::InvalidCharacter { field, .. }
+            | ValidationError::Conflict { field, .. }
+            | ValidationError::UnrecognizedImage { field }
+            | ValidationError::ImageTooLarge { field, .. } => field,
+        }
+    }
+
+    /// A stable machine-readable code for the kind of problem, e.g. `"too_long"`.
+    pub fn code(&self) -> &'static str {
+        match self {
+            ValidationError::Missing { .. } => "missing",
+            ValidationError::Blank { .. } => "blank",
+            ValidationError::Empty { .. } => "empty",
+            ValidationError::Zero { .. } => "zero",
+            ValidationError::TooLong { .. } => "too_long",
+            ValidationError::TooLarge { .. } => "too_large",
+            ValidationError::TooMany { .. } => "too_many",
+            ValidationError::InvalidCharacter { .. } => "invalid_character",
+            ValidationError::Conflict { .. } => "conflict",
+            ValidationError::UnrecognizedImage { .. } => "unrecognized_image",
+            ValidationError::ImageTooLarge { .. } => "image_too_large",
+        }
+    }
+}
+
+impl fmt::Display for ValidationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ValidationError::Missing { field } => write!(f, "{} is required", field),
+            ValidationError::Blank { field } => write!(f, "{} must not be blank", field),
+            ValidationError::Empty { field } => write!(f, "{} must not be empty", field),
+            ValidationError::Zero { field } => write!(f, "{} must be greater than zero", field),
+            ValidationError::TooLong { field, max, actual } => {
+                write!(f, "{} must be at most {} characters long, but is {}", field, max, actual)
+            }
+            ValidationError::TooLarge { field, max, actual } => {
+                write!(f, "{} must be at most {}, but is {}", field, max, actual)
+            }
+            ValidationError::TooMany { field, max, actual } => {
+                write!(f, "{} must have at most {} entries, but has {}", field, max, actual)
+            }
+            ValidationError::InvalidCharacter { field, character } => {
+                write!(f, "{} must not contain '{}'", field, character)
+            }
+            ValidationError::Conflict { field, with } => write!(f, "{} cannot be set along with {}", field, with),
+            ValidationError::UnrecognizedImage { field } => {
+                write!(f, "{} must be a PNG, JPEG, GIF or WebP image", field)
+            }
+            ValidationError::ImageTooLarge { field, max, actual } => {
+                write!(f, "{} must be at most {} bytes, but is {}", field, max, actual)
+            }
+        }
+    }
+}
+
+impl Error for ValidationError {}
+
+/// Serializes the error as `{"field": ..., "code": ..., "message": ...}`.
+#[cfg(feature = "serde")]
+impl Serialize for ValidationError {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut state = serializer.serialize_struct("ValidationError", 3)?;
+        state.serialize_field("field", self.field())?;
+        state.serialize_field("code", self.code())?;
+        state.serialize_field("message", &self.to_string())?;
+        state.end()
+    }
+}
+
+/// Every problem found when validating a `Recipe`, never empty. Serializes as `{"errors": [...]}`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct ValidationErrors {
+    errors: Vec<ValidationError>,
+}
+
+impl ValidationErrors {
+    pub fn errors(&self) -> &[ValidationError] {
+        &self.errors
+    }
+
+    pub fn into_errors(self) -> Vec<ValidationError> {
+        self.errors
+    }
+
+    pub fn len(&self) -> usize {
+        self.errors.len()
+    }
+
+    /// Always false, as there is at least one error.
+    pub fn is_empty(&self) -> bool {
+        self.errors.is_empty()
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &ValidationError> {
+        self.errors.iter()
+    }
+
+    /// The errors found in `field`.
+    pub fn for_field<'a>(&'a self, field: &'a str) -> impl Iterator<Item = &'a ValidationError> {
+        self.errors.iter().filter(move |err| err.field() == field)
+    }
+
+    /// `Ok` if nothing was collected, otherwise the collected errors.
+    pub(crate) fn check(errors: Vec<ValidationError>) -> Result<(), ValidationErrors> {
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(ValidationErrors { errors })
+        }
+    }
+}
+
+impl fmt::Display for ValidationErrors {
+    /// Formats the errors separated by semicolons, e.g. `"name must not be blank; servings must be greater than zero"`.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let messages: Vec<String> = self.errors.iter().map(ToString::to_string).collect();
+        write!(f, "{}", messages.join("; "))
+    }
+}
+
+impl Error for ValidationErrors {}
+
+impl IntoIterator for ValidationErrors {
+    type Item = ValidationError;
+    type IntoIter = std::vec::IntoIter<ValidationError>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.errors.into_iter()
+    }
+}
+
+/// Checks a recipe name is not blank and at most `Recipe::MAX_NAME_LEN` characters long.
+pub fn validate_name(name: &str) -> Result<(), ValidationError> {
+    if name.trim().is_empty() {
+        return Err(ValidationError::Blank { field: "name" });
+    }
+    check_len("name", name, Recipe::MAX_NAME_LEN)
+}
+
+/// Checks a recipe description is at most `Recipe::MAX_DESCRIPTION_LEN` characters long.
+pub fn validate_description(description: &str) -> Result<(), ValidationError> {
+    check_len("description", description, Recipe::MAX_DESCRIPTION_LEN)
+}
+
+/// Checks a total duration is positive and at most `Recipe::MAX_DURATION_MINUTES`.
+pub fn validate_duration(minutes: u16) -> Result<(), ValidationError> {
+    if minutes == 0 {
+        return Err(ValidationError::Zero { field: "duration" });
+    }
+    let max = Recipe::MAX_DURATION_MINUTES;
+    if minutes > max {
+        return Err(ValidationError::TooLarge { field: "duration", max: max.into(), actual: minutes.into() });
+    }
+    Ok(())
+}
+
+/// Checks a tag is valid for `RecipeTag::new`: not blank, at most `Recipe::MAX_TAG_LEN` characters long once
+/// trimmed and without commas.
+pub fn validate_tag(tag: &str) -> Result<(), ValidationError> {
+    RecipeTag::new(tag).map(|_| ()).map_err(|err| match err {
+        TagError::Empty => ValidationError::Blank { field: "tags" },
+        TagError::TooLong { max, actual } => ValidationError::TooLong { field: "tags", max, actual },
+        TagError::InvalidCharacter(character) => ValidationError::InvalidCharacter { field: "tags", character },
+    })
+}
+
+fn check_len(field: &'static str, text: &str, max: usize) -> Result<(), ValidationError> {
+    let actual = text.chars().count();
+    if actual > max {
+        return Err(ValidationError::TooLong { field, max, actual });
+    }
+    Ok(())
+}
+
+impl Recipe {
+    /// The longest allowed name in characters.
+    pub const MAX_NAME_LEN: usize = 200;
+    /// The longest allowed description in characters.
+    pub const MAX_DESCRIPTION_LEN: usize = 5000;
+    /// The longest allowed total duration in minutes, just under a week.
+    pub const MAX_DURATION_MINUTES: u16 = 10_079;
+    /// The most tags a recipe may have.
+    pub const MAX_TAGS: usize = 50;
+    /// The longest allowed tag in characters.
+    pub const MAX_TAG_LEN: usize = 40;
+
+    /// Checks the recipe against rules that go beyond required fields being present, collecting every
+    /// problem rather than stopping at the first so they can all be shown at once.
+    pub fn validate(&self) -> Result<(), ValidationErrors> {
+        let mut errors: Vec<ValidationError> = [
+            validate_name(&self.name),
+            validate_description(&self.description),
+            validate_duration(self.times.total()),
+        ]
+        .into_iter()
+        .filter_map(Result::err)
+        .collect();
+        if self.ingredients.is_empty() {
+            errors.push(ValidationError::Empty { field: "ingredients" });
+        }
+        if self.servings == 0 {
+            errors.push(ValidationError::Zero { field: "servings" });
+        }
+        if self.steps.iter().all(|step| step.text().trim().is_empty()) {
+            errors.push(ValidationError::Blank { field: "directions" });
+        }
+        for image in &self.images {
+            if image.format().is_none() {
+                errors.push(ValidationError::UnrecognizedImage { field: "images" });
+            }
+            if image.bytes().len() > Recipe::MAX_IMG_BYTES {
+                errors.push(ValidationError::ImageTooLarge {
+                    field: "images",
+                    max: Recipe::MAX_IMG_BYTES,
+                    actual: image.bytes().len(),
+                });
+            }
+        }
+        if self.tags.len() > Recipe::MAX_TAGS {
+            errors.push(ValidationError::TooMany { field: "tags", max: Recipe::MAX_TAGS, actual: self.tags.len() });
+        }
+        let mut tags: Vec<&str> = self.tags.iter().map(|tag| tag.as_str()).collect();
+        tags.sort_unstable();
+        errors.extend(tags.into_iter().filter_map(|tag| validate_tag(tag).err()));
+        ValidationErrors::check(errors)
+    }
+
+    /// Like `Recipe::validate`, but allows a draft to have no ingredients yet. Everything else is still
+    /// checked.
+    pub fn validate_draft(&self) -> Result<(), ValidationErrors> {
+        let Err(errors) = self.validate() else {
+            return Ok(());
+        };
+        ValidationErrors::check(errors.errors.into_iter().filter(|error| !is_unfinished(error)).collect())
+    }
+
+    /// Validates the recipe with `Recipe::validate_draft` if it is a draft and `Recipe::validate` otherwise.
+    pub fn validate_for_status(&self) -> Result<(), ValidationErrors> {
+        match self.status {
+            RecipeStatus::Draft => self.validate_draft(),
+            RecipeStatus::Published | RecipeStatus::Archived => self.validate(),
+        }
+    }
+}
+
+/// Whether `error` only says that a part a draft may leave out is still missing.
+fn is_unfinished(error: &ValidationError) -> bool {
+    matches!(error, ValidationError::Empty { field: "ingredients" })
+}
+
+/// The validation errors for a recipe that could not be built, at least one for every `RecipeBuildError`.
+fn build_errors(err: RecipeBuildError) -> Vec<ValidationError> {
+    match err {
+        RecipeBuildError::ConflictingDuration => {
+            vec![ValidationError::Conflict { field: "duration", with: "prep_time or cook_time" }]
+        }
+        RecipeBuildError::InvalidImage(err) => vec![image_error(err)],
+        err => err.fields().into_iter().map(|field| ValidationError::Missing { field }).collect(),
+    }
+}
+
+/// The validation error for an image rejected by `ImageData`.
+fn image_error(err: ImageError) -> ValidationError {
+    match err {
+        ImageError::Empty => ValidationError::Empty { field: "images" },
+        ImageError::TooLarge { max, actual } => ValidationError::ImageTooLarge { field: "images", max, actual },
+        ImageError::UnrecognizedFormat | ImageError::Decode(_) | ImageError::Encode(_) => {
+            ValidationError::UnrecognizedImage { field: "images" }
+        }
+    }
+}
+
+impl RecipeBuilder {
+    /// Builds the `Recipe` and validates it with `Recipe::validate`. If the recipe cannot be built, only the
+    /// reasons it cannot are returned, missing required fields as `ValidationError::Missing` and a deprecated
+    /// duration set along with a prep or cook time as `ValidationError::Conflict`, as there is no recipe yet
+    /// to validate any further.
+    pub fn build_validated(self) -> Result<Recipe, ValidationErrors> {
+        let recipe = self.build().map_err(|err| ValidationErrors { errors: build_errors(err) })?;
+        recipe.validate()?;
+        Ok(recipe)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::testing::{ingredient, recipe, tag};
+    use crate::models::Unit;
+
+    #[test]
+    fn validate_reports_every_problem_at_once() {
+        let built = recipe(" ")
+            .cook_time(0)
+            .directions("")
+            .step("   ")
+            .ingredient(ingredient("water", 1.0, Unit::Liter))
+            .build()
+            .expect("the recipe should build");
+        let errors = built.validate().expect_err("the recipe breaks three rules");
+        assert_eq!(
+            errors.errors(),
+            [
+                ValidationError::Blank { field: "name" },
+                ValidationError::Zero { field: "duration" },
+                ValidationError::Blank { field: "directions" },
+            ]
+        );
+        let messages: Vec<String> = errors.iter().map(ToString::to_string).collect();
+        assert_eq!(
+            messages,
+            ["name must not be blank", "duration must be greater than zero", "directions must not be blank"]
+        );
+    }
+
+    #[test]
+    fn names_one_character_too_long_are_rejected() {
+        let name = "a".repeat(Recipe::MAX_NAME_LEN + 1);
+        let max = Recipe::MAX_NAME_LEN;
+        assert_eq!(validate_name(&name), Err(ValidationError::TooLong { field: "name", max, actual: max + 1 }));
+        let built = recipe(&name).ingredient(ingredient("water", 1.0, Unit::Liter)).build().expect("should build");
+        let errors = built.validate().expect_err("the name is too long");
+        assert_eq!(errors.errors(), [ValidationError::TooLong { field: "name", max, actual: max + 1 }]);
+    }
+
+    #[test]
+    fn lengths_are_counted_in_characters_not_bytes() {
+        let name = "🍰".repeat(Recipe::MAX_NAME_LEN);
+        assert!(name.len() > Recipe::MAX_NAME_LEN);
+        assert_eq!(validate_name(&name), Ok(()));
+        let description = "é".repeat(Recipe::MAX_DESCRIPTION_LEN);
+        assert_eq!(validate_description(&description), Ok(()));
+        let actual = Recipe::MAX_DESCRIPTION_LEN + 1;
+        assert_eq!(
+            validate_description(&"é".repeat(actual)),
+            Err(ValidationError::TooLong { field: "description", max: Recipe::MAX_DESCRIPTION_LEN, actual })
+        );
+    }
+
+    #[test]
+    fn every_business_rule_is_reported_in_one_pass() {
+        let description = "x".repeat(Recipe::MAX_DESCRIPTION_LEN + 1);
+        let built = recipe("Feast")
+            .cook_time(Recipe::MAX_DURATION_MINUTES + 1)
+            .description(description)
+            .tags((0..=Recipe::MAX_TAGS).map(|i| tag(&format!("tag{}", i))))
+            .build()
+            .expect("the recipe should build");
+        let errors = built.validate().expect_err("the recipe breaks four rules");
+        let codes: Vec<(&str, &str)> = errors.iter().map(|err| (err.field(), err.code())).collect();
+        assert_eq!(
+            codes,
+            [("description", "too_long"), ("duration", "too_large"), ("ingredients", "empty"), ("tags", "too_many")]
+        );
+        assert_eq!(errors.len(), 4);
+        assert_eq!(errors.for_field("tags").count(), 1);
+    }
+
+    #[test]
+    fn validators_can_be_used_on_their_own() {
+        assert_eq!(validate_duration(Recipe::MAX_DURATION_MINUTES), Ok(()));
+        assert_eq!(validate_duration(0), Err(ValidationError::Zero { field: "duration" }));
+        assert_eq!(validate_tag("vegan"), Ok(()));
+        assert_eq!(validate_tag("  "), Err(ValidationError::Blank { field: "tags" }));
+        assert_eq!(validate_tag("a,b"), Err(ValidationError::InvalidCharacter { field: "tags", character: ',' }));
+        let long = "t".repeat(Recipe::MAX_TAG_LEN + 1);
+        let max = Recipe::MAX_TAG_LEN;
+        assert_eq!(validate_tag(&long), Err(ValidationError::TooLong { field: "tags", max, actual: max + 1 }));
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn errors_serialize_with_their_field_code_and_message() {
+        let built = recipe(" ").servings(0).ingredient(ingredient("water", 1.0, Unit::Liter)).build();
+        let errors = built.expect("the recipe should build").validate().expect_err("the recipe breaks two rules");
+        assert_eq!(
+            serde_json::to_value(&errors).expect("the errors should serialize"),
+            serde_json::json!({ "errors": [
+                { "field": "name", "code": "blank", "message": "name must not be blank" },
+                { "field": "servings", "code": "zero", "message": "servings must be greater than zero" },
+            ] })
+        );
+    }
+
+    #[test]
+    fn build_validated_reports_missing_fields_without_validating() {
+        let errors = Recipe::builder().name(" ").build_validated().expect_err("fields are missing");
+        let missing: Vec<&str> = errors.iter().map(ValidationError::field).collect();
+        assert_eq!(missing, ["difficulty", "duration", "servings", "description", "category", "directions"]);
+        assert!(errors.iter().all(|err| err.code() == "missing"));
+
+        let errors = recipe("Soup").name("").build().expect("the recipe should build").validate();
+        assert_eq!(errors.expect_err("the name is blank").errors()[0], ValidationError::Blank { field: "name" });
+    }
+
+    #[test]
+    #[allow(deprecated)]
+    fn build_validated_reports_a_conflicting_duration() {
+        let errors = recipe("Soup").duration(30).build_validated().expect_err("the durations conflict");
+        let conflict = ValidationError::Conflict { field: "duration", with: "prep_time or cook_time" };
+        assert_eq!(errors.errors(), std::slice::from_ref(&conflict));
+        assert_eq!(conflict.code(), "conflict");
+        assert_eq!(conflict.to_string(), "duration cannot be set along with prep_time or cook_time");
+    }
+
+    #[test]
+    fn every_build_error_becomes_at_least_one_validation_error() {
+        let cases = [
+            (RecipeBuildError::MissingName, ValidationError::Missing { field: "name" }),
+            (RecipeBuildError::MissingDifficulty, ValidationError::Missing { field: "difficulty" }),
+            (RecipeBuildError::MissingDuration, ValidationError::Missing { field: "duration" }),
+            (RecipeBuildError::MissingServings, ValidationError::Missing { field: "servings" }),
+            (RecipeBuildError::MissingDescription, ValidationError::Missing { field: "description" }),
+            (RecipeBuildError::MissingCategory, ValidationError::Missing { field: "category" }),
+            (RecipeBuildError::MissingDirections, ValidationError::Missing { field: "directions" }),
+            (RecipeBuildError::MissingFields(vec!["name"]), ValidationError::Missing { field: "name" }),
+            (
+                RecipeBuildError::ConflictingDuration,
+                ValidationError::Conflict { field: "duration", with: "prep_time or cook_time" },
+            ),
+            (RecipeBuildError::InvalidImage(ImageError::Empty), ValidationError::Empty { field: "images" }),
+            (
+                RecipeBuildError::InvalidImage(ImageError::UnrecognizedFormat),
+                ValidationError::UnrecognizedImage { field: "images" },
+            ),
+            (
+                RecipeBuildError::InvalidImage(ImageError::TooLarge { max: 4, actual: 5 }),
+                ValidationError::ImageTooLarge { field: "images", max: 4, actual: 5 },
+            ),
+            (
+                RecipeBuildError::InvalidImage(ImageError::Decode("truncated".to_string())),
+                ValidationError::UnrecognizedImage { field: "images" },
+            ),
+            (
+                RecipeBuildError::InvalidImage(ImageError::Encode("unsupported".to_string())),
+                ValidationError::UnrecognizedImage { field: "images" },
+            ),
+        ];
+        for (err, expected) in cases {
+            assert_eq!(build_errors(err.clone()), [expected], "{:?}", err);
+        }
+    }
+
+    #[test]
+    fn build_validated_validates_a_recipe_that_built() {
+        let built = recipe("Soup").ingredient(ingredient("water", 1.0, Unit::Liter)).build_validated();
+        assert_eq!(built.map(|recipe| recipe.name().to_string()), Ok("Soup".to_string()));
+        let errors = recipe("Soup").servings(0).build_validated().expect_err("the recipe breaks two rules");
+        assert_eq!(
+            errors.errors(),
+            [ValidationError::Empty { field: "ingredients" }, ValidationError::Zero { field: "servings" }]
+        );
+    }
+}
@@ -0,0 +1,182 @@
+use chrono::{DateTime, SubsecRound, Utc};
+use uuid::Uuid;
+use crate::models::{
+    Allergen, Category, ConcurrencyError, Course, Cuisine, Difficulty, Equipment, NewIngredient, NewRecipe,
+    Nutrition, Quantity, Recipe, RecipeImage, RecipeRevision, RecipeStatus, RecipeTag, RevisionError, Source, Step,
+    Unit,
+};
+use super::{ListQuery, Page, RecipeRepository, RepoError, SortOrder};
+
+/// The first bytes of a PNG file, enough for its format to be detected.
+const PNG: [u8; 8] = [0x89, b'P', b'N', b'G', 0x0D, 0x0A, 0x1A, 0x0A];
+
+/// The first bytes of a JPEG file, enough for its format to be detected.
+const JPEG: [u8; 4] = [0xFF, 0xD8, 0xFF, 0xE0];
+
+/// Checks that `repo` behaves like every `RecipeRepository` should, panicking on the first difference.
+///
+/// Meant to be called from each backend's tests with an empty repository with the default maximum page size,
+/// e.g. `conformance(&InMemoryRepository::new()).await`. It creates, updates, lists and deletes recipes of its own.
+pub async fn conformance<R: RecipeRepository>(repo: &R) {
+    let created = repo.create(new_recipe("Pancakes")).await.expect("create failed");
+    assert_eq!(created.id().get_version_num(), 7, "created ids should be UUID v7");
+    assert_eq!(created.status(), RecipeStatus::Draft, "created recipes should be drafts");
+    assert!(stored(repo, created.id()).await.img_eq(&created), "every field should be stored");
+    assert!(repo.get(Uuid::now_v7()).await.expect("get failed").is_none());
+    let nameless = NewRecipe::new("", Difficulty::Easy, 1, Category::MainCourse);
+    assert!(matches!(repo.create(nameless).await, Err(RepoError::Invalid(_))));
+
+    let drafts = ListQuery { status: Some(RecipeStatus::Draft), ..ListQuery::default() };
+    let listed = repo.list(ListQuery::default()).await.expect("list failed");
+    assert!(listed.items.is_empty(), "drafts should not be listed");
+    assert_eq!(repo.list(drafts).await.expect("list failed").total, 1);
+
+    let mut published = created
+        .clone()
+        .into_builder()
+        .image(RecipeImage::new(PNG.to_vec()).with_caption("stack").primary())
+        .image(RecipeImage::new(JPEG.to_vec()).with_caption("batter"))
+        .forked_from(Uuid::now_v7())
+        .build()
+        .expect("the recipe should build");
+    published.publish().expect("the recipe should be valid");
+    let stored_created_at = stored(repo, created.id()).await.created_at();
+    let tampered = published.clone().into_builder().created_at(DateTime::UNIX_EPOCH).updated_at(DateTime::UNIX_EPOCH);
+    let before_update = Utc::now().trunc_subsecs(6);
+    let tampered = tampered.build().expect("the recipe should build");
+    let updated = repo.update(tampered, created.version()).await.expect("update failed");
+    assert_eq!(updated, published);
+    assert!(updated.version() > created.version(), "updates should bump the version");
+    assert_eq!(updated.created_at(), stored_created_at, "updates should keep the stored created_at");
+    assert!(updated.updated_at() >= before_update, "updates should set updated_at to the time of the update");
+    let reloaded = stored(repo, created.id()).await;
+    assert!(reloaded.img_eq(&published), "every field and image should be stored");
+    assert_eq!(reloaded.created_at(), stored_created_at, "updates should keep the stored created_at");
+    assert!(reloaded.updated_at() >= before_update, "updates should store the time of the update");
+    match repo.update(created.clone(), created.version()).await {
+        Err(RepoError::Conflict(ConcurrencyError::VersionMismatch { expected, actual })) => {
+            assert_eq!((expected, actual), (created.version(), updated.version()))
+        }
+        other => panic!("an update based on a stale version should conflict, got {:?}", other),
+    }
+    let unchanged = repo.update(reloaded.clone(), reloaded.version()).await.expect("update failed");
+    assert!(unchanged.version() > reloaded.version(), "even an unchanged recipe should get a new version");
+    let unknown = new_recipe("Unknown").build().expect("the recipe should build");
+    let missing = repo.update(unknown.clone(), unknown.version()).await;
+    assert!(matches!(missing, Err(RepoError::NotFound(id)) if id == unknown.id()));
+
+    for name in ["waffles", "Crepes"] {
+        let mut recipe = repo.create(new_recipe(name)).await.expect("create failed");
+        let version = recipe.version();
+        recipe.publish().expect("the recipe should be valid");
+        repo.update(recipe, version).await.expect("update failed");
+    }
+    let page = repo.list(ListQuery { offset: 1, limit: 1, ..ListQuery::default() }).await.expect("list failed");
+    assert_eq!(page.total, 3);
+    assert_eq!(page.items.iter().map(|recipe| recipe.name()).collect::<Vec<_>>(), ["Pancakes"]);
+    assert!(page.has_more());
+    let page = |offset, limit| repo.list(ListQuery { offset, limit, ..ListQuery::default() });
+    let names = |page: &Page<Recipe>| page.items.iter().map(|recipe| recipe.name().to_string()).collect::<Vec<_>>();
+    let whole = page(0, 3).await.expect("list failed");
+    assert_eq!((names(&whole), whole.has_more()), (vec!["Crepes".into(), "Pancakes".into(), "waffles".into()], false));
+    let last = page(2, 1).await.expect("list failed");
+    assert_eq!((names(&last), last.total, last.has_more()), (vec!["waffles".into()], 3, false));
+    for offset in [3, 10, u64::MAX] {
+        let past = page(offset, 2).await.expect("list failed");
+        assert!(past.items.is_empty() && past.total == 3 && !past.has_more(), "a page past the end should be empty");
+    }
+    assert_eq!(page(0, 0).await.expect("list failed").total, 3);
+    assert!(page(0, ListQuery::MAX_LIMIT).await.is_ok());
+    match page(0, ListQuery::MAX_LIMIT + 1).await {
+        Err(RepoError::LimitTooLarge { max, given }) => {
+            assert_eq!((max, given), (ListQuery::MAX_LIMIT, ListQuery::MAX_LIMIT + 1))
+        }
+        other => panic!("a limit over the maximum should be rejected, got {:?}", other),
+    }
+    let newest = repo.list(ListQuery { sort: SortOrder::Newest, ..ListQuery::default() }).await;
+    assert_eq!(names(&newest.expect("list failed")), ["Crepes", "waffles", "Pancakes"]);
+    let updated = repo.list(ListQuery { sort: SortOrder::RecentlyUpdated, ..ListQuery::default() }).await;
+    assert_eq!(names(&updated.expect("list failed")), ["Crepes", "waffles", "Pancakes"]);
+    let searched = repo.list(ListQuery { search: Some("WAFFLES".into()), ..ListQuery::default() }).await;
+    assert_eq!(searched.expect("list failed").total, 1);
+    let tagged = repo.list(ListQuery { tag: Some("Breakfast".into()), ..ListQuery::default() }).await;
+    assert_eq!(tagged.expect("list failed").total, 3);
+    let mut accented = Vec::new();
+    for name in ["äggröra", "Åkerbär"] {
+        let mut recipe = repo.create(new_recipe(name)).await.expect("create failed");
+        let version = recipe.version();
+        recipe.publish().expect("the recipe should be valid");
+        accented.push(repo.update(recipe, version).await.expect("update failed"));
+    }
+    // Only ASCII letters are lowercased, so "Å" sorts before "ä" as it is written
+    let sorted = page(0, 10).await.expect("list failed");
+    assert_eq!(names(&sorted), ["Crepes", "Pancakes", "waffles", "Åkerbär", "äggröra"]);
+    let mut sorted_in_memory = sorted.items.clone();
+    sorted_in_memory.sort_by(|a, b| SortOrder::Name.compare(a, b));
+    assert_eq!(sorted_in_memory, sorted.items, "the order should be the one SortOrder::compare gives");
+    let searched = repo.list(ListQuery { search: Some("äGGRöRA".into()), ..ListQuery::default() }).await;
+    assert_eq!(names(&searched.expect("list failed")), ["äggröra"]);
+    for recipe in accented {
+        assert!(repo.delete(recipe.id()).await.expect("delete failed"));
+    }
+
+    let first = repo.create(new_recipe("Porridge")).await.expect("create failed");
+    let mut renamed = first.clone();
+    renamed.set_name("Oat porridge");
+    let renamed = repo.update(renamed, first.version()).await.expect("update failed");
+    let mut edited = renamed.clone();
+    edited.set_servings(6);
+    let edited = repo.update(edited, renamed.version()).await.expect("update failed");
+    let history = repo.history(first.id()).await.expect("history failed");
+    let versions: Vec<u32> = history.iter().map(RecipeRevision::version).collect();
+    assert_eq!(versions, [first.version(), renamed.version(), edited.version()], "every version should be recorded");
+    for (revision, recipe) in history.iter().zip([&first, &renamed, &edited]) {
+        assert!(revision.recipe.img_eq(recipe), "revisions should record every field");
+    }
+    let restored = repo.restore(first.id(), first.version()).await.expect("restore failed");
+    assert!(restored.version() > edited.version(), "restoring should create a new version");
+    assert_eq!((restored.name(), restored.servings()), (first.name(), first.servings()));
+    assert_eq!(stored(repo, first.id()).await, restored);
+    let history = repo.history(first.id()).await.expect("history failed");
+    let versions: Vec<u32> = history.iter().map(RecipeRevision::version).collect();
+    assert_eq!(versions, [first.version(), renamed.version(), edited.version(), restored.version()]);
+    assert!(history[2].recipe.img_eq(&edited), "restoring should not rewrite history");
+    match repo.restore(first.id(), 99).await {
+        Err(RepoError::Revision(RevisionError::UnknownVersion(99))) => {}
+        other => panic!("restoring an unrecorded version should fail, got {:?}", other),
+    }
+    assert!(matches!(repo.restore(Uuid::now_v7(), 1).await, Err(RepoError::NotFound(_))));
+    assert!(matches!(repo.history(Uuid::now_v7()).await, Err(RepoError::NotFound(_))));
+    assert!(repo.delete(first.id()).await.expect("delete failed"));
+    assert!(matches!(repo.history(first.id()).await, Err(RepoError::NotFound(_))), "history should be deleted");
+
+    assert!(repo.delete(created.id()).await.expect("delete failed"));
+    assert!(!repo.delete(created.id()).await.expect("delete failed"));
+    assert!(repo.get(created.id()).await.expect("get failed").is_none());
+}
+
+/// The stored recipe with the given id, which must be there.
+async fn stored<R: RecipeRepository>(repo: &R, id: Uuid) -> Recipe {
+    repo.get(id).await.expect("get failed").expect("the recipe should be stored")
+}
+
+/// A new recipe named `name` with every optional field set, to check none of them is lost.
+fn new_recipe(name: &str) -> NewRecipe {
+    let mut recipe = NewRecipe::new(name, Difficulty::Easy, 2, Category::Breakfast);
+    recipe.prep_minutes = Some(5);
+    recipe.cook_minutes = Some(10);
+    recipe.rest_minutes = Some(15);
+    recipe.description = "For the weekend".into();
+    recipe.steps = vec![Step::new("Mix").with_duration_minutes(3), Step::new("Cook").with_img(JPEG.to_vec())];
+    let quantity = Quantity::new(1.0, Unit::Cup);
+    recipe.ingredients = vec![NewIngredient { name: "flour".into(), quantity, note: Some("sifted".into()) }];
+    recipe.tags = vec![RecipeTag::new("breakfast").expect("the tag should be valid")];
+    recipe.nutrition = Some(Nutrition { calories: Some(520.0), protein_g: Some(14.5), ..Nutrition::default() });
+    recipe.allergens = [Allergen::Gluten, Allergen::Eggs].into();
+    recipe.equipment = [Equipment::new("Frying pan").with_note("non-stick")].into();
+    recipe.cuisine = Some(Cuisine::Other("Scandinavian".into()));
+    recipe.course = Some(Course::Breakfast);
+    let source = Source::new().with_author("Jane Doe").with_url("https://example.com/pancakes");
+    recipe.source = Some(source.expect("the url should be valid").with_license("CC BY 4.0"));
+    recipe
+}
@@ -0,0 +1,322 @@
+use std::collections::HashMap;
+use std::fs::{self, File};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::sync::{RwLock, RwLockReadGuard, RwLockWriteGuard};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use uuid::Uuid;
+use crate::models::{Cookbook, ImageFormat, NewRecipe, Recipe, RecipeHistory, RecipeImage, RecipeRevision};
+use super::repository::{list_page, record_revision};
+use super::{ListQuery, Page, RecipeRepository, RepoError};
+
+/// The extension of a file being written, which is renamed over the real file once it is complete.
+const TEMP_EXTENSION: &str = "tmp";
+
+/// The extension of the file holding the history of a recipe.
+const HISTORY_EXTENSION: &str = "history";
+
+/// A `RecipeRepository` keeping one JSON file per recipe in a directory, for small personal deployments.
+///
+/// A recipe is stored as `<id>.json` and each of its images next to it as `<id>.<image id>.<ext>`, e.g.
+/// `.png` or `.jpg`, which the JSON lists in place of the image data. Its history is stored as `<id>.history`,
+/// a JSON list of its revisions listing their images the same way, so an image file is kept as long as any
+/// revision of its recipe has the image. Every recipe is loaded into memory when the repository is opened, and
+/// every change is written to disk before it returns. A file is replaced
+/// by writing a temporary file, syncing it and renaming it over the old one, so a crash leaves either the
+/// old or the new file, and temporary files left behind by a crash are deleted when the repository is
+/// opened again. A recipe is written before its history and deleted after it, and a history missing the stored
+/// version after a crash has it recorded again when the repository is opened.
+#[derive(Debug)]
+pub struct FileRepository {
+    dir: PathBuf,
+    recipes: RwLock<Cookbook>,
+    /// The history of every recipe, always locked after `recipes`
+    histories: RwLock<HashMap<Uuid, RecipeHistory>>,
+    max_limit: u32,
+}
+
+/// An image as listed in the JSON file of its recipe.
+#[derive(Serialize, Deserialize)]
+struct ImageFile {
+    id: Uuid,
+    /// The name of the image file, in the same directory as the JSON file
+    file: String,
+    caption: Option<String>,
+    is_primary: bool,
+}
+
+/// A revision as listed in the history file of its recipe.
+#[derive(Serialize, Deserialize)]
+struct RevisionFile {
+    /// The recipe as in its JSON file, listing its images
+    recipe: Value,
+    author: Option<String>,
+    recorded_at: DateTime<Utc>,
+}
+
+impl FileRepository {
+    /// Opens the store in `dir`, creating the directory if it does not exist, and loads every recipe in it.
+    /// Fails if a recipe or one of its images cannot be read.
+    pub fn open(dir: impl Into<PathBuf>) -> Result<Self, RepoError> {
+        let dir = dir.into();
+        fs::create_dir_all(&dir).map_err(|err| io_error(&dir, err))?;
+        let mut recipes = Cookbook::new();
+        let mut histories = HashMap::new();
+        for entry in fs::read_dir(&dir).map_err(|err| io_error(&dir, err))? {
+            let path = entry.map_err(|err| io_error(&dir, err))?.path();
+            match path.extension().and_then(|extension| extension.to_str()) {
+                Some(TEMP_EXTENSION) => fs::remove_file(&path).map_err(|err| io_error(&path, err))?,
+                Some("json") => {
+                    recipes.insert(load(&dir, &path)?);
+                }
+                Some(HISTORY_EXTENSION) => {
+                    let history = load_history(&dir, &path)?;
+                    histories.insert(history.recipe_id(), history);
+                }
+                _ => {}
+            }
+        }
+        for recipe in recipes.iter() {
+            record_revision(&mut histories, recipe);
+        }
+        let (recipes, histories) = (RwLock::new(recipes), RwLock::new(histories));
+        Ok(Self { dir, recipes, histories, max_limit: ListQuery::MAX_LIMIT })
+    }
+
+    /// Sets the largest page `list` returns, `ListQuery::MAX_LIMIT` by default.
+    pub fn with_max_limit(mut self, max_limit: u32) -> Self {
+        self.max_limit = max_limit;
+        self
+    }
+
+    /// The directory the recipes are stored in.
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    fn read(&self) -> Result<RwLockReadGuard<'_, Cookbook>, RepoError> {
+        self.recipes.read().map_err(|_| RepoError::Backend("recipe store lock poisoned".into()))
+    }
+
+    fn write(&self) -> Result<RwLockWriteGuard<'_, Cookbook>, RepoError> {
+        self.recipes.write().map_err(|_| RepoError::Backend("recipe store lock poisoned".into()))
+    }
+
+    fn histories(&self) -> Result<RwLockWriteGuard<'_, HashMap<Uuid, RecipeHistory>>, RepoError> {
+        self.histories.write().map_err(|_| RepoError::Backend("recipe history lock poisoned".into()))
+    }
+
+    fn recipe_path(&self, id: Uuid) -> PathBuf {
+        self.dir.join(format!("{}.json", id))
+    }
+
+    fn history_path(&self, id: Uuid) -> PathBuf {
+        self.dir.join(format!("{}.{}", id, HISTORY_EXTENSION))
+    }
+
+    /// Records `recipe` in its history, then writes the images of `recipe` and of every revision that are not
+    /// on disk yet, its JSON file and its history file, then deletes the images no revision has.
+    fn save(&self, recipe: &Recipe, histories: &mut HashMap<Uuid, RecipeHistory>) -> Result<(), RepoError> {
+        record_revision(histories, recipe);
+        let path = self.recipe_path(recipe.id());
+        let (value, mut files) = self.to_value(recipe, &path)?;
+        let json = serde_json::to_vec_pretty(&value).map_err(|err| json_error(&path, err))?;
+        write_atomic(&path, &json)?;
+        let path = self.history_path(recipe.id());
+        let mut revisions = Vec::new();
+        for revision in histories.get(&recipe.id()).map(RecipeHistory::revisions).unwrap_or_default() {
+            let (value, revision_files) = self.to_value(&revision.recipe, &path)?;
+            files.extend(revision_files);
+            let (author, recorded_at) = (revision.author.clone(), revision.recorded_at);
+            revisions.push(RevisionFile { recipe: value, author, recorded_at });
+        }
+        let json = serde_json::to_vec_pretty(&revisions).map_err(|err| json_error(&path, err))?;
+        write_atomic(&path, &json)?;
+        self.remove_images(recipe.id(), &files)
+    }
+
+    /// Writes the images of `recipe` that are not on disk yet and returns the recipe as JSON listing them, for
+    /// storing in the file at `path`. The bytes of an image never change, so an image file is never rewritten.
+    fn to_value(&self, recipe: &Recipe, path: &Path) -> Result<(Value, Vec<ImageFile>), RepoError> {
+        let mut files = Vec::new();
+        for image in recipe.images() {
+            let file = image_file_name(recipe.id(), image);
+            let path = self.dir.join(&file);
+            if !path.exists() {
+                write_atomic(&path, image.bytes())?;
+            }
+            let caption = image.caption().map(str::to_string);
+            files.push(ImageFile { id: image.id(), file, caption, is_primary: image.is_primary() });
+        }
+        let mut value = serde_json::to_value(recipe).map_err(|err| json_error(path, err))?;
+        if let Some(fields) = value.as_object_mut() {
+            fields.insert("images".to_string(), serde_json::to_value(&files).map_err(|err| json_error(path, err))?);
+        }
+        Ok((value, files))
+    }
+
+    /// Deletes the image files of the recipe with the given id except those in `keep`.
+    fn remove_images(&self, id: Uuid, keep: &[ImageFile]) -> Result<(), RepoError> {
+        let prefix = format!("{}.", id);
+        for entry in fs::read_dir(&self.dir).map_err(|err| io_error(&self.dir, err))? {
+            let path = entry.map_err(|err| io_error(&self.dir, err))?.path();
+            let Some(name) = path.file_name().and_then(|name| name.to_str()) else {
+                continue;
+            };
+            let is_image = name.starts_with(&prefix)
+                && ![".json", HISTORY_EXTENSION, TEMP_EXTENSION].iter().any(|suffix| name.ends_with(suffix));
+            if is_image && !keep.iter().any(|image| image.file == name) {
+                fs::remove_file(&path).map_err(|err| io_error(&path, err))?;
+            }
+        }
+        Ok(())
+    }
+}
+
+impl RecipeRepository for FileRepository {
+    async fn create(&self, recipe: NewRecipe) -> Result<Recipe, RepoError> {
+        let recipe = recipe.build().map_err(RepoError::Invalid)?;
+        let mut recipes = self.write()?;
+        self.save(&recipe, &mut *self.histories()?)?;
+        recipes.insert(recipe.clone());
+        Ok(recipe)
+    }
+
+    async fn get(&self, id: Uuid) -> Result<Option<Recipe>, RepoError> {
+        Ok(self.read()?.get(&id).cloned())
+    }
+
+    async fn update(&self, mut recipe: Recipe, expected_version: u32) -> Result<Recipe, RepoError> {
+        let mut recipes = self.write()?;
+        let stored = recipes.get(&recipe.id()).ok_or(RepoError::NotFound(recipe.id()))?;
+        recipe.check_update(stored.version(), expected_version)?;
+        recipe.stamp_update(stored.created_at());
+        self.save(&recipe, &mut *self.histories()?)?;
+        recipes.insert(recipe.clone());
+        Ok(recipe)
+    }
+
+    async fn history(&self, id: Uuid) -> Result<Vec<RecipeRevision>, RepoError> {
+        let histories = self.histories.read().map_err(|_| RepoError::Backend("recipe history lock poisoned".into()))?;
+        let history = histories.get(&id).ok_or(RepoError::NotFound(id))?;
+        Ok(history.revisions().to_vec())
+    }
+
+    async fn delete(&self, id: Uuid) -> Result<bool, RepoError> {
+        let mut recipes = self.write()?;
+        if recipes.get(&id).is_none() {
+            return Ok(false);
+        }
+        let path = self.history_path(id);
+        match fs::remove_file(&path) {
+            Err(err) if err.kind() != io::ErrorKind::NotFound => return Err(io_error(&path, err)),
+            _ => {}
+        }
+        self.histories()?.remove(&id);
+        let path = self.recipe_path(id);
+        fs::remove_file(&path).and_then(|_| sync_dir(&self.dir)).map_err(|err| io_error(&path, err))?;
+        recipes.remove(&id);
+        self.remove_images(id, &[])?;
+        Ok(true)
+    }
+
+    async fn list(&self, query: ListQuery) -> Result<Page<Recipe>, RepoError> {
+        query.check_limit(self.max_limit)?;
+        Ok(list_page(&*self.read()?, &query))
+    }
+}
+
+/// Reads the recipe in the JSON file at `path` along with its images from `dir`.
+fn load(dir: &Path, path: &Path) -> Result<Recipe, RepoError> {
+    let json = fs::read(path).map_err(|err| io_error(path, err))?;
+    from_value(dir, path, serde_json::from_slice(&json).map_err(|err| json_error(path, err))?)
+}
+
+/// Reads the history in the file at `path` along with the images of its revisions from `dir`.
+fn load_history(dir: &Path, path: &Path) -> Result<RecipeHistory, RepoError> {
+    let json = fs::read(path).map_err(|err| io_error(path, err))?;
+    let revisions: Vec<RevisionFile> = serde_json::from_slice(&json).map_err(|err| json_error(path, err))?;
+    let revisions = revisions
+        .into_iter()
+        .map(|revision| {
+            let recipe = from_value(dir, path, revision.recipe)?;
+            Ok(RecipeRevision { recipe, author: revision.author, recorded_at: revision.recorded_at })
+        })
+        .collect::<Result<Vec<_>, RepoError>>()?;
+    let empty = || RepoError::Backend(format!("{}: no revisions", path.display()).into());
+    RecipeHistory::from_revisions(revisions).ok_or_else(empty)
+}
+
+/// Reads a recipe as stored in the file at `path`, along with its images from `dir`.
+fn from_value(dir: &Path, path: &Path, mut value: Value) -> Result<Recipe, RepoError> {
+    let images = value.as_object_mut().and_then(|fields| fields.remove("images"));
+    let images: Vec<ImageFile> =
+        serde_json::from_value(images.unwrap_or(Value::Array(Vec::new()))).map_err(|err| json_error(path, err))?;
+    let mut recipe: Recipe = serde_json::from_value(value).map_err(|err| json_error(path, err))?;
+    let images = images
+        .into_iter()
+        .map(|image| {
+            let path = dir.join(&image.file);
+            let bytes = fs::read(&path).map_err(|err| io_error(&path, err))?;
+            let mut restored = RecipeImage::new(bytes).with_id(image.id);
+            if let Some(caption) = image.caption {
+                restored = restored.with_caption(caption);
+            }
+            Ok(if image.is_primary { restored.primary() } else { restored })
+        })
+        .collect::<Result<Vec<_>, RepoError>>()?;
+    recipe.restore_images(images);
+    Ok(recipe)
+}
+
+/// The name of the file holding `image` of the recipe with the given id, with an extension for its format.
+fn image_file_name(id: Uuid, image: &RecipeImage) -> String {
+    let extension = match image.format() {
+        Some(ImageFormat::Png) => "png",
+        Some(ImageFormat::Jpeg) => "jpg",
+        Some(ImageFormat::Gif) => "gif",
+        Some(ImageFormat::WebP) => "webp",
+        None => "bin",
+    };
+    format!("{}.{}.{}", id, image.id(), extension)
+}
+
+/// Replaces the file at `path` with `bytes` through a synced temporary file renamed over it.
+fn write_atomic(path: &Path, bytes: &[u8]) -> Result<(), RepoError> {
+    let mut temp = path.as_os_str().to_owned();
+    temp.push(".");
+    temp.push(TEMP_EXTENSION);
+    let temp = PathBuf::from(temp);
+    let written = File::create(&temp)
+        .and_then(|mut file| {
+            file.write_all(bytes)?;
+            file.sync_all()
+        })
+        .and_then(|_| fs::rename(&temp, path))
+        .and_then(|_| sync_dir(path.parent().unwrap_or(Path::new("."))));
+    written.map_err(|err| {
+        let _ = fs::remove_file(&temp);
+        io_error(path, err)
+    })
+}
+
+/// Syncs the directory itself, so a file renamed or deleted in it stays so after a crash.
+#[cfg(unix)]
+fn sync_dir(dir: &Path) -> io::Result<()> {
+    File::open(dir)?.sync_all()
+}
+
+#[cfg(not(unix))]
+fn sync_dir(_dir: &Path) -> io::Result<()> {
+    Ok(())
+}
+
+fn io_error(path: &Path, err: io::Error) -> RepoError {
+    RepoError::Backend(Box::new(io::Error::new(err.kind(), format!("{}: {}", path.display(), err))))
+}
+
+fn json_error(path: &Path, err: serde_json::Error) -> RepoError {
+    RepoError::Backend(format!("{}: {}", path.display(), err).into())
+}
@@ -1,4 +1,25 @@
+mod conformance;
+#[cfg(feature = "serde")]
+mod file;
+#[cfg(feature = "postgres")]
+mod postgres;
+mod query;
+mod repository;
+#[cfg(any(feature = "postgres", feature = "sqlite"))]
+mod sql;
+#[cfg(feature = "sqlite")]
+mod sqlite;
+
+pub use conformance::conformance;
+#[cfg(feature = "serde")]
+pub use file::FileRepository;
+#[cfg(feature = "postgres")]
+pub use postgres::PgRecipeRepository;
+pub use query::{ListQuery, ListQueryError, Page, SortOrder};
+pub use repository::{InMemoryRepository, RecipeRepository, RepoError};
+#[cfg(feature = "sqlite")]
+pub use sqlite::SqliteRecipeRepository;
 
 trait RecipeDao {
 
-}
\ No newline at end of file
+}
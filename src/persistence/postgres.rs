@@ -0,0 +1,293 @@
+use std::collections::HashMap;
+use chrono::{DateTime, Utc};
+use sqlx::migrate::Migrator;
+use sqlx::postgres::{PgConnection, PgPool, PgPoolOptions, PgRow};
+use sqlx::{Postgres, QueryBuilder, Row, Transaction};
+use uuid::Uuid;
+use sqlx::types::Json;
+use crate::models::{Ingredient, NewRecipe, Recipe, RecipeRevision, RecipeStatus, RecipeTag};
+use super::sql::{
+    backend, ingredient_from_row, recipe_from_row, revision_from_row, status_name, tag_from_row, unrecorded_history,
+    write_error, Details,
+};
+use super::{ListQuery, Page, RecipeRepository, RepoError, SortOrder};
+
+/// The migrations creating the tables `PgRecipeRepository` uses.
+static MIGRATOR: Migrator = sqlx::migrate!("migrations/postgres");
+
+/// A `RecipeRepository` storing recipes in Postgres through a connection pool.
+///
+/// Recipes live in a `recipes` table, with their primary image as a `bytea`, their steps as text and every other
+/// field, like the nutrition, the secondary images and the durations and images of steps, as `jsonb` in its
+/// `details` column. Their ingredients and tags are in the `ingredients` and `recipe_tags` tables. A recipe is
+/// always written together with its ingredients and tags, and recorded in the `recipe_revisions` table as
+/// JSON, in one transaction.
+#[derive(Debug, Clone)]
+pub struct PgRecipeRepository {
+    pool: PgPool,
+    max_limit: u32,
+}
+
+impl PgRecipeRepository {
+    /// A repository using `pool`, whose database must already be migrated, see `migrate`.
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool, max_limit: ListQuery::MAX_LIMIT }
+    }
+
+    /// Sets the largest page `list` returns, `ListQuery::MAX_LIMIT` by default.
+    pub fn with_max_limit(mut self, max_limit: u32) -> Self {
+        self.max_limit = max_limit;
+        self
+    }
+
+    /// Connects to the database at `url`, e.g. `postgres://localhost/recipes`, and runs any pending migrations.
+    pub async fn connect(url: &str) -> Result<Self, RepoError> {
+        let pool = PgPoolOptions::new().connect(url).await.map_err(backend)?;
+        let repo = Self::new(pool);
+        repo.migrate().await?;
+        Ok(repo)
+    }
+
+    /// Creates or updates the tables, doing nothing if they are up to date.
+    pub async fn migrate(&self) -> Result<(), RepoError> {
+        MIGRATOR.run(&self.pool).await.map_err(backend)
+    }
+
+    pub fn pool(&self) -> &PgPool {
+        &self.pool
+    }
+}
+
+impl RecipeRepository for PgRecipeRepository {
+    async fn create(&self, recipe: NewRecipe) -> Result<Recipe, RepoError> {
+        let recipe = recipe.build().map_err(RepoError::Invalid)?;
+        let mut tx = self.pool.begin().await.map_err(backend)?;
+        sqlx::query(
+            "INSERT INTO recipes (id, name, difficulty, prep_minutes, cook_minutes, rest_minutes, servings, \
+             description, directions, img, status, version, created_at, updated_at, category, details) \
+             VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14, $15, $16)",
+        )
+        .bind(recipe.id())
+        .bind(recipe.name())
+        .bind(i16::from(u8::from(*recipe.difficulty())))
+        .bind(i32::from(recipe.prep_minutes()))
+        .bind(i32::from(recipe.cook_minutes()))
+        .bind(i32::from(recipe.rest_minutes()))
+        .bind(i32::from(recipe.servings()))
+        .bind(recipe.description())
+        .bind(recipe.directions_text())
+        .bind(recipe.img())
+        .bind(status_name(recipe.status()))
+        .bind(i64::from(recipe.version()))
+        .bind(recipe.created_at())
+        .bind(recipe.updated_at())
+        .bind(recipe.category().as_str())
+        .bind(Details::of(&recipe))
+        .execute(&mut *tx)
+        .await
+        .map_err(|err| write_error(err, recipe.id()))?;
+        insert_relations(&mut tx, &recipe).await?;
+        insert_revision(&mut tx, &recipe).await?;
+        tx.commit().await.map_err(backend)?;
+        Ok(recipe)
+    }
+
+    async fn get(&self, id: Uuid) -> Result<Option<Recipe>, RepoError> {
+        let mut conn = self.pool.acquire().await.map_err(backend)?;
+        let rows = sqlx::query("SELECT * FROM recipes WHERE id = $1").bind(id).fetch_all(&mut *conn).await;
+        Ok(load(&mut conn, rows.map_err(backend)?).await?.pop())
+    }
+
+    async fn update(&self, mut recipe: Recipe, expected_version: u32) -> Result<Recipe, RepoError> {
+        let mut tx = self.pool.begin().await.map_err(backend)?;
+        let stored: Option<(i64, DateTime<Utc>)> =
+            sqlx::query_as("SELECT version, created_at FROM recipes WHERE id = $1 FOR UPDATE")
+                .bind(recipe.id())
+                .fetch_optional(&mut *tx)
+                .await
+                .map_err(backend)?;
+        let (stored, created_at) = stored.ok_or(RepoError::NotFound(recipe.id()))?;
+        let stored = u32::try_from(stored).map_err(backend)?;
+        recipe.check_update(stored, expected_version)?;
+        recipe.stamp_update(created_at);
+        sqlx::query(
+            "UPDATE recipes SET name = $2, difficulty = $3, prep_minutes = $4, cook_minutes = $5, \
+             rest_minutes = $6, servings = $7, description = $8, directions = $9, img = $10, status = $11, \
+             version = $12, updated_at = $13, category = $14, details = $15 WHERE id = $1",
+        )
+        .bind(recipe.id())
+        .bind(recipe.name())
+        .bind(i16::from(u8::from(*recipe.difficulty())))
+        .bind(i32::from(recipe.prep_minutes()))
+        .bind(i32::from(recipe.cook_minutes()))
+        .bind(i32::from(recipe.rest_minutes()))
+        .bind(i32::from(recipe.servings()))
+        .bind(recipe.description())
+        .bind(recipe.directions_text())
+        .bind(recipe.img())
+        .bind(status_name(recipe.status()))
+        .bind(i64::from(recipe.version()))
+        .bind(recipe.updated_at())
+        .bind(recipe.category().as_str())
+        .bind(Details::of(&recipe))
+        .execute(&mut *tx)
+        .await
+        .map_err(backend)?;
+        for table in ["ingredients", "recipe_tags"] {
+            sqlx::query(&format!("DELETE FROM {} WHERE recipe_id = $1", table))
+                .bind(recipe.id())
+                .execute(&mut *tx)
+                .await
+                .map_err(backend)?;
+        }
+        insert_relations(&mut tx, &recipe).await?;
+        insert_revision(&mut tx, &recipe).await?;
+        tx.commit().await.map_err(backend)?;
+        Ok(recipe)
+    }
+
+    async fn history(&self, id: Uuid) -> Result<Vec<RecipeRevision>, RepoError> {
+        let rows = sqlx::query("SELECT * FROM recipe_revisions WHERE recipe_id = $1 ORDER BY version")
+            .bind(id)
+            .fetch_all(&self.pool)
+            .await
+            .map_err(backend)?;
+        if rows.is_empty() {
+            return Ok(unrecorded_history(self.get(id).await?.ok_or(RepoError::NotFound(id))?));
+        }
+        rows.iter().map(revision_from_row).collect()
+    }
+
+    async fn delete(&self, id: Uuid) -> Result<bool, RepoError> {
+        let result = sqlx::query("DELETE FROM recipes WHERE id = $1").bind(id).execute(&self.pool).await;
+        Ok(result.map_err(backend)?.rows_affected() > 0)
+    }
+
+    /// Filters, sorts and pages in SQL, counting the matches in the same repeatable read transaction so the
+    /// total agrees with the page.
+    async fn list(&self, query: ListQuery) -> Result<Page<Recipe>, RepoError> {
+        query.check_limit(self.max_limit)?;
+        let empty = Page { items: Vec::new(), total: 0, offset: query.offset, limit: query.limit };
+        let tag = match query.tag.as_deref().map(RecipeTag::new) {
+            Some(Ok(tag)) => Some(tag.normalized()),
+            Some(Err(_)) => return Ok(empty),
+            None => None,
+        };
+        let mut tx = self.pool.begin_with("BEGIN ISOLATION LEVEL REPEATABLE READ READ ONLY").await.map_err(backend)?;
+        let mut count = QueryBuilder::new("SELECT COUNT(*) FROM recipes");
+        push_filters(&mut count, &query, tag.as_deref());
+        let total: i64 = count.build_query_scalar().fetch_one(&mut *tx).await.map_err(backend)?;
+        let mut select = QueryBuilder::new("SELECT * FROM recipes");
+        push_filters(&mut select, &query, tag.as_deref());
+        select
+            .push(" ORDER BY ")
+            .push(order_by(query.sort))
+            .push(" LIMIT ")
+            .push_bind(i64::from(query.limit))
+            .push(" OFFSET ")
+            .push_bind(i64::try_from(query.offset).unwrap_or(i64::MAX));
+        let rows = select.build().fetch_all(&mut *tx).await.map_err(backend)?;
+        let items = load(&mut tx, rows).await?;
+        tx.commit().await.map_err(backend)?;
+        Ok(Page { items, total: u64::try_from(total).map_err(backend)?, ..empty })
+    }
+}
+
+/// The recipes in `rows` along with their ingredients and tags, in the same order.
+async fn load(conn: &mut PgConnection, rows: Vec<PgRow>) -> Result<Vec<Recipe>, RepoError> {
+    let ids: Vec<Uuid> = rows.iter().map(|row| row.try_get("id")).collect::<Result<_, _>>().map_err(backend)?;
+    let mut ingredients: HashMap<Uuid, Vec<Ingredient>> = HashMap::new();
+    let ingredient_rows = sqlx::query("SELECT * FROM ingredients WHERE recipe_id = ANY($1)")
+        .bind(&ids)
+        .fetch_all(&mut *conn)
+        .await
+        .map_err(backend)?;
+    for row in ingredient_rows {
+        let ingredient = ingredient_from_row(&row)?;
+        ingredients.entry(row.try_get("recipe_id").map_err(backend)?).or_default().push(ingredient);
+    }
+    let mut tags: HashMap<Uuid, Vec<RecipeTag>> = HashMap::new();
+    let tag_rows = sqlx::query("SELECT recipe_id, tag FROM recipe_tags WHERE recipe_id = ANY($1)")
+        .bind(&ids)
+        .fetch_all(&mut *conn)
+        .await
+        .map_err(backend)?;
+    for row in tag_rows {
+        let tag = tag_from_row(&row)?;
+        tags.entry(row.try_get("recipe_id").map_err(backend)?).or_default().push(tag);
+    }
+    rows.iter()
+        .map(|row| {
+            let id: Uuid = row.try_get("id").map_err(backend)?;
+            let ingredients = ingredients.remove(&id).unwrap_or_default();
+            recipe_from_row(row, ingredients, tags.remove(&id).unwrap_or_default())
+        })
+        .collect()
+}
+
+/// The `ORDER BY` columns sorting recipes like `SortOrder::compare`, lowercasing only ASCII letters and
+/// comparing by code point under the "C" collation whatever the database's collation is.
+fn order_by(sort: SortOrder) -> &'static str {
+    match sort {
+        SortOrder::Name => "lower(name COLLATE \"C\"), id",
+        SortOrder::Newest => "created_at DESC, id",
+        SortOrder::RecentlyUpdated => "updated_at DESC, id",
+    }
+}
+
+/// Adds the conditions of `query` as a `WHERE` clause, matching the way `ListQuery::matches` does. `lower`
+/// only lowercases ASCII letters under the "C" collation.
+fn push_filters<'a>(builder: &mut QueryBuilder<'a, Postgres>, query: &'a ListQuery, tag: Option<&'a str>) {
+    let status = query.status.unwrap_or(RecipeStatus::Published);
+    builder.push(" WHERE status = ").push_bind(status_name(status));
+    if let Some(tag) = tag {
+        builder
+            .push(" AND EXISTS (SELECT 1 FROM recipe_tags WHERE recipe_id = recipes.id AND normalized = ")
+            .push_bind(tag)
+            .push(")");
+    }
+    for word in query.search.iter().flat_map(|search| search.split_whitespace()) {
+        builder
+            .push(" AND strpos(lower((name || E'\\n' || description || E'\\n' || directions) COLLATE \"C\"), ")
+            .push_bind(word.to_ascii_lowercase())
+            .push(") > 0");
+    }
+}
+
+async fn insert_relations(tx: &mut Transaction<'_, Postgres>, recipe: &Recipe) -> Result<(), RepoError> {
+    for ingredient in recipe.ingredients() {
+        sqlx::query("INSERT INTO ingredients (id, recipe_id, name, amount, unit, note) VALUES ($1, $2, $3, $4, $5, $6)")
+            .bind(ingredient.id())
+            .bind(recipe.id())
+            .bind(ingredient.name())
+            .bind(ingredient.quantity().amount())
+            .bind(ingredient.quantity().unit().to_string())
+            .bind(ingredient.note())
+            .execute(&mut **tx)
+            .await
+            .map_err(|err| write_error(err, recipe.id()))?;
+    }
+    for tag in recipe.tags() {
+        sqlx::query("INSERT INTO recipe_tags (recipe_id, tag, normalized) VALUES ($1, $2, $3)")
+            .bind(recipe.id())
+            .bind(tag.as_str())
+            .bind(tag.normalized())
+            .execute(&mut **tx)
+            .await
+            .map_err(|err| write_error(err, recipe.id()))?;
+    }
+    Ok(())
+}
+
+/// Records `recipe` as it was stored in the `recipe_revisions` table, without an author.
+async fn insert_revision(tx: &mut Transaction<'_, Postgres>, recipe: &Recipe) -> Result<(), RepoError> {
+    sqlx::query("INSERT INTO recipe_revisions (recipe_id, version, recorded_at, recipe) VALUES ($1, $2, $3, $4)")
+        .bind(recipe.id())
+        .bind(i64::from(recipe.version()))
+        .bind(recipe.updated_at())
+        .bind(Json(recipe))
+        .execute(&mut **tx)
+        .await
+        .map_err(backend)?;
+    Ok(())
+}
@@ -0,0 +1,274 @@
+use std::cmp::Ordering;
+use std::error::Error;
+use std::fmt;
+use std::str::FromStr;
+use url::form_urlencoded;
+use crate::models::{Recipe, RecipeStatus, RecipeTag};
+use super::RepoError;
+
+/// Which recipes `RecipeRepository::list` returns and in what order. The default lists the first page of
+/// published recipes sorted by name.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ListQuery {
+    /// Only recipes whose name, description and directions together contain every word, ignoring the case of
+    /// ASCII letters
+    pub search: Option<String>,
+    /// Only recipes with this tag, compared the way tags compare
+    pub tag: Option<String>,
+    /// Only recipes with this status, published ones if `None`
+    pub status: Option<RecipeStatus>,
+    /// The order of the recipes, which every page is taken from
+    pub sort: SortOrder,
+    /// How many matching recipes to skip
+    pub offset: u64,
+    /// The most recipes to return, no more than the repository's maximum page size
+    pub limit: u32,
+}
+
+impl ListQuery {
+    /// The page size unless another limit is set.
+    pub const DEFAULT_LIMIT: u32 = 20;
+
+    /// The largest page size repositories accept unless configured otherwise.
+    pub const MAX_LIMIT: u32 = 100;
+
+    /// Reads a query from the query string of a URL, without the leading `?`, e.g. `offset=20&limit=10`.
+    ///
+    /// The parameters `offset`, `limit`, `sort`, `search` and `tag` are read, any others are ignored, and
+    /// missing ones keep their defaults. A limit above `max_limit` is rejected rather than lowered, so a
+    /// client asking for more than it may get finds out instead of silently receiving a short page.
+    pub fn from_query(query: &str, max_limit: u32) -> Result<ListQuery, ListQueryError> {
+        let mut list = ListQuery::default();
+        for (name, value) in form_urlencoded::parse(query.as_bytes()) {
+            let invalid = || ListQueryError::InvalidParameter { name: name.to_string(), value: value.to_string() };
+            match name.as_ref() {
+                "offset" => list.offset = value.parse().map_err(|_| invalid())?,
+                "limit" => list.limit = value.parse().map_err(|_| invalid())?,
+                "sort" => list.sort = value.parse().map_err(|_| invalid())?,
+                "search" => list.search = Some(value.into_owned()),
+                "tag" => list.tag = Some(value.into_owned()),
+                _ => {}
+            }
+        }
+        if list.limit > max_limit {
+            return Err(ListQueryError::LimitTooLarge { max: max_limit, given: list.limit });
+        }
+        Ok(list)
+    }
+
+    /// Fails with `RepoError::LimitTooLarge` if the limit is above `max_limit`, for repositories to check
+    /// queries against their maximum page size.
+    pub fn check_limit(&self, max_limit: u32) -> Result<(), RepoError> {
+        if self.limit > max_limit {
+            return Err(RepoError::LimitTooLarge { max: max_limit, given: self.limit });
+        }
+        Ok(())
+    }
+
+    /// Whether `recipe` matches every condition of the query, the offset and limit aside.
+    pub fn matches(&self, recipe: &Recipe) -> bool {
+        if recipe.status() != self.status.unwrap_or(RecipeStatus::Published) {
+            return false;
+        }
+        if let Some(tag) = &self.tag {
+            match RecipeTag::new(tag.as_str()) {
+                Ok(tag) => {
+                    if !recipe.tags().contains(&tag) {
+                        return false;
+                    }
+                }
+                Err(_) => return false,
+            }
+        }
+        self.matches_search(recipe.name(), recipe.description(), &recipe.directions_text())
+    }
+
+    /// Whether a recipe with this name, description and directions text matches `search`.
+    pub(crate) fn matches_search(&self, name: &str, description: &str, directions: &str) -> bool {
+        let Some(search) = &self.search else {
+            return true;
+        };
+        let text = format!("{}\n{}\n{}", name, description, directions).to_ascii_lowercase();
+        search.split_whitespace().all(|word| text.contains(&word.to_ascii_lowercase()))
+    }
+
+    /// The number of recipes to skip, as an index.
+    pub(crate) fn skip(&self) -> usize {
+        usize::try_from(self.offset).unwrap_or(usize::MAX)
+    }
+
+    /// The number of recipes to take, as a length.
+    pub(crate) fn take(&self) -> usize {
+        usize::try_from(self.limit).unwrap_or(usize::MAX)
+    }
+}
+
+impl Default for ListQuery {
+    fn default() -> Self {
+        Self {
+            search: None,
+            tag: None,
+            status: None,
+            sort: SortOrder::default(),
+            offset: 0,
+            limit: ListQuery::DEFAULT_LIMIT,
+        }
+    }
+}
+
+/// The order `RecipeRepository::list` returns recipes in. Recipes that tie are ordered by id, so pages
+/// never overlap or skip a recipe while the recipes stay the same.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum SortOrder {
+    /// By name ignoring the case of ASCII letters, so every backend sorts other letters the same way: by code
+    /// point, as they are written
+    #[default]
+    Name,
+    /// The most recently created first
+    Newest,
+    /// The most recently modified first
+    RecentlyUpdated,
+}
+
+impl SortOrder {
+    /// Compares two recipes in this order.
+    pub fn compare(&self, a: &Recipe, b: &Recipe) -> Ordering {
+        let ordering = match self {
+            SortOrder::Name => a.name().to_ascii_lowercase().cmp(&b.name().to_ascii_lowercase()),
+            SortOrder::Newest => b.created_at().cmp(&a.created_at()),
+            SortOrder::RecentlyUpdated => b.updated_at().cmp(&a.updated_at()),
+        };
+        ordering.then_with(|| a.id().cmp(&b.id()))
+    }
+}
+
+impl fmt::Display for SortOrder {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let s = match self {
+            SortOrder::Name => "name",
+            SortOrder::Newest => "newest",
+            SortOrder::RecentlyUpdated => "updated",
+        };
+        write!(f, "{}", s)
+    }
+}
+
+impl FromStr for SortOrder {
+    type Err = ListQueryError;
+
+    /// Parses the name shown by `Display`, ignoring case.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "name" => Ok(SortOrder::Name),
+            "newest" => Ok(SortOrder::Newest),
+            "updated" => Ok(SortOrder::RecentlyUpdated),
+            _ => Err(ListQueryError::InvalidParameter { name: "sort".to_string(), value: s.to_string() }),
+        }
+    }
+}
+
+/// A page of results along with how many there are in total.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Page<T> {
+    pub items: Vec<T>,
+    /// How many items matched in total, across every page
+    pub total: u64,
+    pub offset: u64,
+    pub limit: u32,
+}
+
+impl<T> Page<T> {
+    /// Whether there are more items after this page.
+    pub fn has_more(&self) -> bool {
+        self.offset.saturating_add(self.items.len() as u64) < self.total
+    }
+}
+
+/// The error returned when a `ListQuery` cannot be read from a query string.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ListQueryError {
+    /// The parameter is not a number, or not a known sort order
+    InvalidParameter { name: String, value: String },
+    /// The limit is above the maximum page size
+    LimitTooLarge { max: u32, given: u32 },
+}
+
+impl fmt::Display for ListQueryError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ListQueryError::InvalidParameter { name, value } => write!(f, "invalid {} '{}'", name, value),
+            ListQueryError::LimitTooLarge { max, given } => {
+                write!(f, "limit must be at most {}, but is {}", max, given)
+            }
+        }
+    }
+}
+
+impl Error for ListQueryError {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn invalid(name: &str, value: &str) -> ListQueryError {
+        ListQueryError::InvalidParameter { name: name.to_string(), value: value.to_string() }
+    }
+
+    #[test]
+    fn queries_default_to_the_first_page_of_twenty_sorted_by_name() {
+        let query = ListQuery::from_query("", ListQuery::MAX_LIMIT).expect("an empty query should parse");
+        assert_eq!(query, ListQuery::default());
+        assert_eq!((query.offset, query.limit, query.sort), (0, 20, SortOrder::Name));
+    }
+
+    #[test]
+    fn every_parameter_is_read_and_unknown_ones_are_ignored() {
+        let query = ListQuery::from_query("offset=40&limit=10&sort=Newest&search=tomato+soup&tag=vegan&page=3", 100);
+        let expected = ListQuery {
+            search: Some("tomato soup".to_string()),
+            tag: Some("vegan".to_string()),
+            sort: SortOrder::Newest,
+            offset: 40,
+            limit: 10,
+            ..ListQuery::default()
+        };
+        assert_eq!(query, Ok(expected));
+    }
+
+    #[test]
+    fn limits_up_to_the_maximum_are_accepted_and_larger_ones_rejected() {
+        assert_eq!(ListQuery::from_query("limit=100", 100).map(|query| query.limit), Ok(100));
+        assert_eq!(ListQuery::from_query("limit=0", 100).map(|query| query.limit), Ok(0));
+        let too_large = |max, given| Err(ListQueryError::LimitTooLarge { max, given });
+        assert_eq!(ListQuery::from_query("limit=101", 100), too_large(100, 101));
+        assert_eq!(ListQuery::from_query("limit=30", 25), too_large(25, 30));
+        let query = ListQuery { limit: 101, ..ListQuery::default() };
+        assert!(matches!(query.check_limit(100), Err(RepoError::LimitTooLarge { max: 100, given: 101 })));
+        assert!(query.check_limit(101).is_ok());
+    }
+
+    #[test]
+    fn malformed_numbers_and_sort_orders_are_rejected() {
+        assert_eq!(ListQuery::from_query("offset=-1", 100), Err(invalid("offset", "-1")));
+        assert_eq!(ListQuery::from_query("limit=lots", 100), Err(invalid("limit", "lots")));
+        assert_eq!(ListQuery::from_query("sort=oldest", 100), Err(invalid("sort", "oldest")));
+        assert_eq!(invalid("sort", "oldest").to_string(), "invalid sort 'oldest'");
+    }
+
+    #[test]
+    fn sort_orders_round_trip_through_their_names() {
+        for sort in [SortOrder::Name, SortOrder::Newest, SortOrder::RecentlyUpdated] {
+            assert_eq!(sort.to_string().parse(), Ok(sort));
+        }
+    }
+
+    #[test]
+    fn pages_have_more_until_they_reach_the_total() {
+        let page = |offset, items: usize, total| Page { items: vec![(); items], total, offset, limit: 2 };
+        assert!(page(0, 2, 3).has_more());
+        assert!(!page(2, 1, 3).has_more());
+        assert!(!page(1, 2, 3).has_more());
+        assert!(!page(10, 0, 3).has_more());
+        assert!(!page(u64::MAX, 0, 3).has_more());
+    }
+}
@@ -0,0 +1,274 @@
+use std::collections::HashMap;
+use std::error::Error;
+use std::fmt;
+use std::future::Future;
+use std::sync::RwLock;
+use uuid::Uuid;
+use crate::models::{
+    ConcurrencyError, Cookbook, NewRecipe, Recipe, RecipeBuildError, RecipeHistory, RecipeRevision, RevisionError,
+};
+use super::{ListQuery, Page};
+
+/// Where recipes are stored, implemented by each storage backend.
+///
+/// Recipes are created from a `NewRecipe` and given their id by the repository. An update names the
+/// version of the recipe it was based on and is accepted only if that is still the stored version, the same
+/// rule as `Cookbook::update`, so of two clients that read the same version only the first to update
+/// succeeds. Every version a recipe is created or updated with is recorded as a `RecipeRevision`, which can be
+/// restored later. Backends should pass `conformance`.
+pub trait RecipeRepository: Send + Sync {
+    /// Builds and stores a new recipe, returning it with its id.
+    fn create(&self, recipe: NewRecipe) -> impl Future<Output = Result<Recipe, RepoError>> + Send;
+
+    fn get(&self, id: Uuid) -> impl Future<Output = Result<Option<Recipe>, RepoError>> + Send;
+
+    /// Replaces the stored recipe with the same id if it is still at `expected_version`, the version the
+    /// caller read before editing, failing with `RepoError::Conflict` otherwise. The stored version always
+    /// goes up, the stored `created_at` is kept and `updated_at` is set to the time of the update, the
+    /// timestamps of `recipe` are ignored. Returns the recipe as stored.
+    fn update(&self, recipe: Recipe, expected_version: u32) -> impl Future<Output = Result<Recipe, RepoError>> + Send;
+
+    /// Every recorded version of the recipe with the given id, oldest first, the last being the stored one.
+    /// Fails with `RepoError::NotFound` if there is no such recipe.
+    fn history(&self, id: Uuid) -> impl Future<Output = Result<Vec<RecipeRevision>, RepoError>> + Send;
+
+    /// Stores the recipe with the given id as it was at `version` as a new version after the stored one, the
+    /// way `RecipeHistory::restore` does, without rewriting its history. Returns the recipe as stored. Fails
+    /// with `RepoError::NotFound` if there is no such recipe, `RepoError::Revision` if the version was never
+    /// recorded, and `RepoError::Conflict` if the recipe is updated while being restored.
+    fn restore(&self, id: Uuid, version: u32) -> impl Future<Output = Result<Recipe, RepoError>> + Send {
+        async move {
+            let mut history = self.history(id).await?;
+            let head = history.last().map_or(0, RecipeRevision::version);
+            let position = history.iter().position(|revision| revision.version() == version);
+            let position = position.ok_or(RepoError::Revision(RevisionError::UnknownVersion(version)))?;
+            self.update(history.swap_remove(position).recipe, head).await
+        }
+    }
+
+    /// Deletes the recipe with the given id along with its history, returning whether it was there.
+    fn delete(&self, id: Uuid) -> impl Future<Output = Result<bool, RepoError>> + Send;
+
+    /// The page of recipes matching `query` in its sort order, along with how many match in total. Fails with
+    /// `RepoError::LimitTooLarge` if the limit is above the repository's maximum page size, `ListQuery::MAX_LIMIT`
+    /// unless configured otherwise. A page past the end is empty.
+    fn list(&self, query: ListQuery) -> impl Future<Output = Result<Page<Recipe>, RepoError>> + Send;
+}
+
+/// The error returned by a `RecipeRepository`.
+#[derive(Debug)]
+pub enum RepoError {
+    /// There is no recipe with this id
+    NotFound(Uuid),
+    /// The stored recipe changed since the version the update was based on, always a
+    /// `ConcurrencyError::VersionMismatch`
+    Conflict(ConcurrencyError),
+    /// A recipe, or an ingredient or tag of the recipe with this id, is already stored. The SQL backends
+    /// report unique violations as this rather than as `Conflict`, which only ever means the recipe changed
+    /// since it was read, so a client can tell an edit to redo on a fresh copy from one that can never succeed
+    Duplicate(Uuid),
+    /// The new recipe is missing required fields
+    Invalid(RecipeBuildError),
+    /// The version to restore was never recorded, always a `RevisionError::UnknownVersion`
+    Revision(RevisionError),
+    /// The query asks for a larger page than the repository returns
+    LimitTooLarge { max: u32, given: u32 },
+    /// The storage itself failed
+    Backend(Box<dyn Error + Send + Sync>),
+}
+
+impl fmt::Display for RepoError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RepoError::NotFound(id) => write!(f, "recipe {} not found", id),
+            RepoError::Conflict(err) => write!(f, "{}", err),
+            RepoError::Duplicate(id) => write!(f, "recipe {} or one of its ingredients or tags is already stored", id),
+            RepoError::Invalid(err) => write!(f, "invalid recipe: {}", err),
+            RepoError::Revision(err) => write!(f, "{}", err),
+            RepoError::LimitTooLarge { max, given } => write!(f, "limit must be at most {}, but is {}", max, given),
+            RepoError::Backend(err) => write!(f, "storage failed: {}", err),
+        }
+    }
+}
+
+impl Error for RepoError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            RepoError::NotFound(_) | RepoError::Duplicate(_) | RepoError::LimitTooLarge { .. } => None,
+            RepoError::Conflict(err) => Some(err),
+            RepoError::Invalid(err) => Some(err),
+            RepoError::Revision(err) => Some(err),
+            RepoError::Backend(err) => Some(err.as_ref()),
+        }
+    }
+}
+
+impl From<ConcurrencyError> for RepoError {
+    fn from(err: ConcurrencyError) -> Self {
+        match err {
+            ConcurrencyError::NotFound(id) => RepoError::NotFound(id),
+            err => RepoError::Conflict(err),
+        }
+    }
+}
+
+/// A `RecipeRepository` keeping recipes and their histories in memory, for tests and prototyping.
+#[derive(Debug)]
+pub struct InMemoryRepository {
+    recipes: RwLock<Cookbook>,
+    /// The history of every recipe, always locked after `recipes`
+    histories: RwLock<HashMap<Uuid, RecipeHistory>>,
+    max_limit: u32,
+}
+
+impl InMemoryRepository {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the largest page `list` returns, `ListQuery::MAX_LIMIT` by default.
+    pub fn with_max_limit(mut self, max_limit: u32) -> Self {
+        self.max_limit = max_limit;
+        self
+    }
+
+    fn read(&self) -> Result<std::sync::RwLockReadGuard<'_, Cookbook>, RepoError> {
+        self.recipes.read().map_err(|_| RepoError::Backend("recipe store lock poisoned".into()))
+    }
+
+    fn write(&self) -> Result<std::sync::RwLockWriteGuard<'_, Cookbook>, RepoError> {
+        self.recipes.write().map_err(|_| RepoError::Backend("recipe store lock poisoned".into()))
+    }
+
+    fn histories(&self) -> Result<std::sync::RwLockWriteGuard<'_, HashMap<Uuid, RecipeHistory>>, RepoError> {
+        self.histories.write().map_err(|_| RepoError::Backend("recipe history lock poisoned".into()))
+    }
+}
+
+impl Default for InMemoryRepository {
+    fn default() -> Self {
+        Self::from(Cookbook::new())
+    }
+}
+
+/// Starts the history of every recipe in `cookbook` with its current version.
+impl From<Cookbook> for InMemoryRepository {
+    fn from(cookbook: Cookbook) -> Self {
+        let mut histories = HashMap::new();
+        for recipe in cookbook.iter() {
+            record_revision(&mut histories, recipe);
+        }
+        Self { recipes: RwLock::new(cookbook), histories: RwLock::new(histories), max_limit: ListQuery::MAX_LIMIT }
+    }
+}
+
+impl RecipeRepository for InMemoryRepository {
+    async fn create(&self, recipe: NewRecipe) -> Result<Recipe, RepoError> {
+        let recipe = self.write()?.create(recipe).map_err(RepoError::Invalid)?;
+        record_revision(&mut *self.histories()?, &recipe);
+        Ok(recipe)
+    }
+
+    async fn get(&self, id: Uuid) -> Result<Option<Recipe>, RepoError> {
+        Ok(self.read()?.get(&id).cloned())
+    }
+
+    async fn update(&self, mut recipe: Recipe, expected_version: u32) -> Result<Recipe, RepoError> {
+        let mut recipes = self.write()?;
+        let stored = recipes.get(&recipe.id()).ok_or(RepoError::NotFound(recipe.id()))?;
+        recipe.check_update(stored.version(), expected_version)?;
+        recipe.stamp_update(stored.created_at());
+        recipes.insert(recipe.clone());
+        record_revision(&mut *self.histories()?, &recipe);
+        Ok(recipe)
+    }
+
+    async fn history(&self, id: Uuid) -> Result<Vec<RecipeRevision>, RepoError> {
+        let histories = self.histories.read().map_err(|_| RepoError::Backend("recipe history lock poisoned".into()))?;
+        let history = histories.get(&id).ok_or(RepoError::NotFound(id))?;
+        Ok(history.revisions().to_vec())
+    }
+
+    async fn delete(&self, id: Uuid) -> Result<bool, RepoError> {
+        let mut recipes = self.write()?;
+        self.histories()?.remove(&id);
+        Ok(recipes.remove(&id).is_some())
+    }
+
+    async fn list(&self, query: ListQuery) -> Result<Page<Recipe>, RepoError> {
+        query.check_limit(self.max_limit)?;
+        Ok(list_page(&*self.read()?, &query))
+    }
+}
+
+/// Records `recipe` as the newest revision of its history in `histories`, starting the history if there is
+/// none. A version the history already reaches is not recorded again.
+pub(crate) fn record_revision(histories: &mut HashMap<Uuid, RecipeHistory>, recipe: &Recipe) {
+    match histories.get_mut(&recipe.id()) {
+        Some(history) => {
+            // Only fails for a version that is already recorded, the history is keyed by the recipe's id.
+            let _ = history.record(recipe.clone(), None);
+        }
+        None => {
+            histories.insert(recipe.id(), RecipeHistory::new(recipe.clone(), None));
+        }
+    }
+}
+
+/// The page of recipes in `recipes` matching `query`, for backends that keep every recipe in memory.
+pub(crate) fn list_page(recipes: &Cookbook, query: &ListQuery) -> Page<Recipe> {
+    let mut matches: Vec<&Recipe> = recipes.iter().filter(|recipe| query.matches(recipe)).collect();
+    matches.sort_by(|a, b| query.sort.compare(a, b));
+    Page {
+        total: matches.len() as u64,
+        items: matches.into_iter().skip(query.skip()).take(query.take()).cloned().collect(),
+        offset: query.offset,
+        limit: query.limit,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{Category, Difficulty, Step};
+    use crate::persistence::conformance;
+
+    #[tokio::test]
+    async fn in_memory_repository_conforms() {
+        conformance(&InMemoryRepository::new()).await;
+    }
+
+    #[tokio::test]
+    async fn a_configured_maximum_page_size_is_enforced() {
+        let repo = InMemoryRepository::new().with_max_limit(5);
+        assert!(repo.list(ListQuery { limit: 5, ..ListQuery::default() }).await.is_ok());
+        match repo.list(ListQuery::default()).await {
+            Err(RepoError::LimitTooLarge { max, given }) => assert_eq!((max, given), (5, ListQuery::DEFAULT_LIMIT)),
+            other => panic!("the default limit is over the maximum, got {:?}", other),
+        }
+    }
+
+    #[tokio::test]
+    async fn of_two_clients_editing_the_same_version_only_the_first_succeeds() {
+        let repo = InMemoryRepository::new();
+        let mut soup = NewRecipe::new("Soup", Difficulty::Easy, 2, Category::MainCourse);
+        soup.cook_minutes = Some(20);
+        soup.steps = vec![Step::new("Simmer")];
+        let created = repo.create(soup).await.expect("create failed");
+        let (mut a, mut b) = (created.clone(), created.clone());
+
+        a.set_name("Tomato soup");
+        repo.update(a, created.version()).await.expect("A read the current version");
+        b.set_servings(6);
+        let err = repo.update(b, created.version()).await.expect_err("B read a stale version");
+        let mismatch = ConcurrencyError::VersionMismatch { expected: created.version(), actual: created.version() + 1 };
+        assert!(matches!(err, RepoError::Conflict(ref conflict) if *conflict == mismatch), "{:?}", err);
+
+        let mut b = repo.get(created.id()).await.expect("get failed").expect("the recipe is stored");
+        let read_version = b.version();
+        b.set_servings(6);
+        let stored = repo.update(b, read_version).await.expect("B read the current version");
+        assert_eq!((stored.name(), stored.servings()), ("Tomato soup", 6));
+        assert!(stored.version() > read_version);
+    }
+}
@@ -0,0 +1,237 @@
+use std::collections::HashSet;
+use std::error::Error;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::types::Json;
+use sqlx::{ColumnIndex, Decode, Row, Type};
+use uuid::Uuid;
+use crate::models::{
+    Allergen, Course, Cuisine, Equipment, Ingredient, Nutrition, Quantity, Recipe, RecipeImage, RecipeRevision,
+    RecipeStatus, RecipeTag, Source, Step, Unit,
+};
+use super::RepoError;
+
+/// The fields of a recipe without a column or table of their own, stored as JSON in the `details` column of
+/// `recipes`. Rows stored before the column was added hold an empty object, and read as they did before.
+#[derive(Debug, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub(crate) struct Details {
+    nutrition: Option<Nutrition>,
+    allergens: HashSet<Allergen>,
+    equipment: HashSet<Equipment>,
+    cuisine: Option<Cuisine>,
+    course: Option<Course>,
+    source: Option<Source>,
+    forked_from: Option<Uuid>,
+    /// Every step with its duration and picture, the `directions` column only holds their text
+    steps: Vec<Step>,
+    /// Every picture of the recipe
+    images: Vec<StoredImage>,
+}
+
+/// A picture as listed in `Details`.
+#[derive(Debug, Serialize, Deserialize)]
+struct StoredImage {
+    id: Uuid,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    caption: Option<String>,
+    #[serde(default)]
+    is_primary: bool,
+    /// The picture as base64, left out for the primary image, whose bytes are in the `img` column
+    #[serde(default, skip_serializing_if = "Vec::is_empty", with = "crate::models::img_base64")]
+    bytes: Vec<u8>,
+}
+
+impl Details {
+    /// The details of `recipe`, for binding to the `details` column.
+    pub(crate) fn of(recipe: &Recipe) -> Json<Details> {
+        let images = recipe
+            .images()
+            .iter()
+            .map(|image| StoredImage {
+                id: image.id(),
+                caption: image.caption().map(str::to_string),
+                is_primary: image.is_primary(),
+                bytes: if image.is_primary() { Vec::new() } else { image.bytes().to_vec() },
+            })
+            .collect();
+        Json(Details {
+            nutrition: recipe.nutrition().copied(),
+            allergens: recipe.allergens().clone(),
+            equipment: recipe.equipment().clone(),
+            cuisine: recipe.cuisine().cloned(),
+            course: recipe.course(),
+            source: recipe.source().cloned(),
+            forked_from: recipe.forked_from(),
+            steps: recipe.steps().to_vec(),
+            images,
+        })
+    }
+}
+
+/// Reads a `recipes` row, including the version, status, category and `details` that
+/// `Recipe::from_row_without_relations` leaves out, along with the recipe's ingredients and tags loaded from
+/// their own tables.
+pub(crate) fn recipe_from_row<'r, R>(
+    row: &'r R,
+    ingredients: Vec<Ingredient>,
+    tags: Vec<RecipeTag>,
+) -> Result<Recipe, RepoError>
+where
+    R: Row,
+    &'r str: ColumnIndex<R>,
+    Uuid: Decode<'r, R::Database> + Type<R::Database>,
+    String: Decode<'r, R::Database> + Type<R::Database>,
+    i16: Decode<'r, R::Database> + Type<R::Database>,
+    i32: Decode<'r, R::Database> + Type<R::Database>,
+    i64: Decode<'r, R::Database> + Type<R::Database>,
+    Vec<u8>: Decode<'r, R::Database> + Type<R::Database>,
+    DateTime<Utc>: Decode<'r, R::Database> + Type<R::Database>,
+    Json<Details>: Decode<'r, R::Database> + Type<R::Database>,
+{
+    let recipe = Recipe::from_row_without_relations(row).map_err(backend)?;
+    let version: i64 = row.try_get("version").map_err(backend)?;
+    let status: String = row.try_get("status").map_err(backend)?;
+    let category: String = row.try_get("category").map_err(backend)?;
+    let Json(details): Json<Details> = row.try_get("details").map_err(backend)?;
+    let img = recipe.img().to_vec();
+    let mut builder = recipe
+        .into_builder()
+        .version(u32::try_from(version).map_err(backend)?)
+        .status(parse_status(&status)?)
+        .category(category.parse().map_err(backend)?)
+        .ingredients(ingredients)
+        .tags(tags)
+        .allergens(details.allergens)
+        .equipment(details.equipment);
+    if let Some(nutrition) = details.nutrition {
+        builder = builder.nutrition(nutrition);
+    }
+    if let Some(cuisine) = details.cuisine {
+        builder = builder.cuisine(cuisine);
+    }
+    if let Some(course) = details.course {
+        builder = builder.course(course);
+    }
+    if let Some(source) = details.source {
+        builder = builder.source(source);
+    }
+    if let Some(id) = details.forked_from {
+        builder = builder.forked_from(id);
+    }
+    if !details.steps.is_empty() {
+        builder = builder.directions("").steps(details.steps);
+    }
+    if !details.images.is_empty() {
+        builder = builder.img(Vec::new());
+        for stored in details.images {
+            let bytes = if stored.is_primary { img.clone() } else { stored.bytes };
+            let mut image = RecipeImage::new(bytes).with_id(stored.id);
+            if let Some(caption) = stored.caption {
+                image = image.with_caption(caption);
+            }
+            builder = builder.image(if stored.is_primary { image.primary() } else { image });
+        }
+    }
+    builder.build().map_err(backend)
+}
+
+/// Reads a `recipe_revisions` row.
+pub(crate) fn revision_from_row<'r, R>(row: &'r R) -> Result<RecipeRevision, RepoError>
+where
+    R: Row,
+    &'r str: ColumnIndex<R>,
+    String: Decode<'r, R::Database> + Type<R::Database>,
+    DateTime<Utc>: Decode<'r, R::Database> + Type<R::Database>,
+    Json<Recipe>: Decode<'r, R::Database> + Type<R::Database>,
+{
+    let Json(recipe): Json<Recipe> = row.try_get("recipe").map_err(backend)?;
+    let author = row.try_get("author").map_err(backend)?;
+    Ok(RecipeRevision { recipe, author, recorded_at: row.try_get("recorded_at").map_err(backend)? })
+}
+
+/// The history of a recipe stored before revisions were recorded, which is only its stored version.
+pub(crate) fn unrecorded_history(recipe: Recipe) -> Vec<RecipeRevision> {
+    let recorded_at = recipe.updated_at();
+    vec![RecipeRevision { recipe, author: None, recorded_at }]
+}
+
+/// Reads an `ingredients` row.
+pub(crate) fn ingredient_from_row<'r, R>(row: &'r R) -> Result<Ingredient, RepoError>
+where
+    R: Row,
+    &'r str: ColumnIndex<R>,
+    Uuid: Decode<'r, R::Database> + Type<R::Database>,
+    String: Decode<'r, R::Database> + Type<R::Database>,
+    f64: Decode<'r, R::Database> + Type<R::Database>,
+{
+    let unit: String = row.try_get("unit").map_err(backend)?;
+    let Ok(unit) = unit.parse::<Unit>();
+    let quantity = Quantity::new(row.try_get("amount").map_err(backend)?, unit);
+    let name: String = row.try_get("name").map_err(backend)?;
+    let ingredient = Ingredient::new(row.try_get("id").map_err(backend)?, name, quantity);
+    Ok(match row.try_get::<Option<String>, _>("note").map_err(backend)? {
+        Some(note) => ingredient.with_note(note),
+        None => ingredient,
+    })
+}
+
+/// Reads the `tag` column of a `recipe_tags` row.
+pub(crate) fn tag_from_row<'r, R>(row: &'r R) -> Result<RecipeTag, RepoError>
+where
+    R: Row,
+    &'r str: ColumnIndex<R>,
+    String: Decode<'r, R::Database> + Type<R::Database>,
+{
+    RecipeTag::new(row.try_get::<String, _>("tag").map_err(backend)?).map_err(backend)
+}
+
+/// The name a status is stored under, as in the `recipes_status_check` constraint.
+pub(crate) fn status_name(status: RecipeStatus) -> &'static str {
+    match status {
+        RecipeStatus::Draft => "draft",
+        RecipeStatus::Published => "published",
+        RecipeStatus::Archived => "archived",
+    }
+}
+
+fn parse_status(status: &str) -> Result<RecipeStatus, RepoError> {
+    match status {
+        "draft" => Ok(RecipeStatus::Draft),
+        "published" => Ok(RecipeStatus::Published),
+        "archived" => Ok(RecipeStatus::Archived),
+        _ => Err(RepoError::Backend(format!("unknown recipe status '{}'", status).into())),
+    }
+}
+
+/// A unique violation means a recipe, ingredient or tag is already stored, reported as a duplicate of the
+/// recipe with id `recipe_id`, see `RepoError::Duplicate`.
+pub(crate) fn write_error(err: sqlx::Error, recipe_id: Uuid) -> RepoError {
+    match &err {
+        sqlx::Error::Database(db) if db.is_unique_violation() => RepoError::Duplicate(recipe_id),
+        _ => backend(err),
+    }
+}
+
+pub(crate) fn backend(err: impl Error + Send + Sync + 'static) -> RepoError {
+    RepoError::Backend(Box::new(err))
+}
+
+#[cfg(all(test, feature = "sqlite"))]
+mod tests {
+    use sqlx::SqlitePool;
+    use super::*;
+
+    #[tokio::test]
+    async fn unique_violations_are_duplicates_and_other_errors_backend_errors() {
+        let pool = SqlitePool::connect("sqlite::memory:").await.expect("could not open the database");
+        sqlx::query("CREATE TABLE recipes (id BLOB PRIMARY KEY NOT NULL)").execute(&pool).await.expect("create failed");
+        let id = Uuid::now_v7();
+        let insert = || sqlx::query("INSERT INTO recipes (id) VALUES (?)").bind(id).execute(&pool);
+        insert().await.expect("the first insert should succeed");
+        let err = insert().await.expect_err("the second insert should violate the primary key");
+        assert!(matches!(write_error(err, id), RepoError::Duplicate(duplicate) if duplicate == id));
+        let err = sqlx::query("INSERT INTO missing (id) VALUES (1)").execute(&pool).await.expect_err("no such table");
+        assert!(matches!(write_error(err, id), RepoError::Backend(_)));
+    }
+}
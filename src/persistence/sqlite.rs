@@ -0,0 +1,375 @@
+use std::collections::HashMap;
+use std::future::Future;
+use std::path::Path;
+use std::time::Duration;
+use chrono::{DateTime, Utc};
+use sqlx::migrate::Migrator;
+use sqlx::sqlite::{
+    SqliteConnectOptions, SqliteConnection, SqliteJournalMode, SqlitePool, SqlitePoolOptions, SqliteRow,
+};
+use sqlx::types::Json;
+use sqlx::{QueryBuilder, Row, Sqlite, Transaction};
+use uuid::Uuid;
+use crate::models::{Ingredient, NewRecipe, Recipe, RecipeRevision, RecipeStatus, RecipeTag};
+use super::sql::{
+    backend, ingredient_from_row, recipe_from_row, revision_from_row, status_name, tag_from_row, unrecorded_history,
+    write_error, Details,
+};
+use super::{ListQuery, Page, RecipeRepository, RepoError, SortOrder};
+
+/// The migrations creating the tables `SqliteRecipeRepository` uses.
+static MIGRATOR: Migrator = sqlx::migrate!("migrations/sqlite");
+
+/// How many times a write is attempted while another connection holds the write lock.
+const MAX_ATTEMPTS: u32 = 10;
+
+/// How long to wait before the second attempt of a write, doubling before every further attempt.
+const INITIAL_BACKOFF: Duration = Duration::from_millis(5);
+
+/// The primary result codes of a database locked by another connection, `SQLITE_BUSY` and `SQLITE_LOCKED`.
+const BUSY_CODES: [i32; 2] = [5, 6];
+
+/// A `RecipeRepository` storing recipes in a single SQLite file, for embedded and single-user deployments.
+///
+/// The tables are the same as those of `PgRecipeRepository`, with the fields that have no column of their own
+/// kept as JSON text in the `details` column, so every field of a recipe is stored. The database is opened in
+/// WAL mode, so reads never wait for writes. Every write runs in its own immediate transaction, and a write that
+/// finds the database locked by another writer is retried with an exponential backoff, up to 10 attempts, before
+/// failing with `RepoError::Backend`.
+#[derive(Debug, Clone)]
+pub struct SqliteRecipeRepository {
+    pool: SqlitePool,
+    max_limit: u32,
+}
+
+impl SqliteRecipeRepository {
+    /// A repository using `pool`, whose database must already be migrated, see `migrate`.
+    pub fn new(pool: SqlitePool) -> Self {
+        Self { pool, max_limit: ListQuery::MAX_LIMIT }
+    }
+
+    /// Sets the largest page `list` returns, `ListQuery::MAX_LIMIT` by default.
+    pub fn with_max_limit(mut self, max_limit: u32) -> Self {
+        self.max_limit = max_limit;
+        self
+    }
+
+    /// Opens the database file at `path`, creating it if it does not exist, and runs any pending migrations.
+    pub async fn connect(path: impl AsRef<Path>) -> Result<Self, RepoError> {
+        Self::connect_with(SqliteConnectOptions::new().filename(path).create_if_missing(true)).await
+    }
+
+    /// Like `connect` with the given options, such as a different busy timeout. WAL mode and foreign keys
+    /// are turned on whatever the options say.
+    pub async fn connect_with(options: SqliteConnectOptions) -> Result<Self, RepoError> {
+        let options = options.journal_mode(SqliteJournalMode::Wal).foreign_keys(true);
+        let pool = SqlitePoolOptions::new().connect_with(options).await.map_err(backend)?;
+        let repo = Self::new(pool);
+        repo.migrate().await?;
+        Ok(repo)
+    }
+
+    /// Creates or updates the tables, doing nothing if they are up to date.
+    pub async fn migrate(&self) -> Result<(), RepoError> {
+        MIGRATOR.run(&self.pool).await.map_err(backend)
+    }
+
+    pub fn pool(&self) -> &SqlitePool {
+        &self.pool
+    }
+
+    async fn try_insert(&self, recipe: &Recipe) -> Result<(), RepoError> {
+        let mut tx = self.pool.begin_with("BEGIN IMMEDIATE").await.map_err(backend)?;
+        sqlx::query(
+            "INSERT INTO recipes (id, name, difficulty, prep_minutes, cook_minutes, rest_minutes, servings, \
+             description, directions, img, status, version, created_at, updated_at, category, details) \
+             VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)",
+        )
+        .bind(recipe.id())
+        .bind(recipe.name())
+        .bind(i16::from(u8::from(*recipe.difficulty())))
+        .bind(i32::from(recipe.prep_minutes()))
+        .bind(i32::from(recipe.cook_minutes()))
+        .bind(i32::from(recipe.rest_minutes()))
+        .bind(i32::from(recipe.servings()))
+        .bind(recipe.description())
+        .bind(recipe.directions_text())
+        .bind(recipe.img())
+        .bind(status_name(recipe.status()))
+        .bind(i64::from(recipe.version()))
+        .bind(recipe.created_at())
+        .bind(recipe.updated_at())
+        .bind(recipe.category().as_str())
+        .bind(Details::of(recipe))
+        .execute(&mut *tx)
+        .await
+        .map_err(|err| write_error(err, recipe.id()))?;
+        insert_relations(&mut tx, recipe).await?;
+        insert_revision(&mut tx, recipe).await?;
+        tx.commit().await.map_err(backend)
+    }
+
+    /// Stores `recipe` over the stored one if that is at `expected_version`, returning it with the version and
+    /// timestamps it was stored with.
+    async fn try_update(&self, recipe: &Recipe, expected_version: u32) -> Result<Recipe, RepoError> {
+        let mut tx = self.pool.begin_with("BEGIN IMMEDIATE").await.map_err(backend)?;
+        let stored: Option<(i64, DateTime<Utc>)> =
+            sqlx::query_as("SELECT version, created_at FROM recipes WHERE id = ?")
+                .bind(recipe.id())
+                .fetch_optional(&mut *tx)
+                .await
+                .map_err(backend)?;
+        let (stored, created_at) = stored.ok_or(RepoError::NotFound(recipe.id()))?;
+        let stored = u32::try_from(stored).map_err(backend)?;
+        let mut recipe = recipe.clone();
+        recipe.check_update(stored, expected_version)?;
+        recipe.stamp_update(created_at);
+        sqlx::query(
+            "UPDATE recipes SET name = ?, difficulty = ?, prep_minutes = ?, cook_minutes = ?, rest_minutes = ?, \
+             servings = ?, description = ?, directions = ?, img = ?, status = ?, version = ?, updated_at = ?, \
+             category = ?, details = ? WHERE id = ?",
+        )
+        .bind(recipe.name())
+        .bind(i16::from(u8::from(*recipe.difficulty())))
+        .bind(i32::from(recipe.prep_minutes()))
+        .bind(i32::from(recipe.cook_minutes()))
+        .bind(i32::from(recipe.rest_minutes()))
+        .bind(i32::from(recipe.servings()))
+        .bind(recipe.description())
+        .bind(recipe.directions_text())
+        .bind(recipe.img())
+        .bind(status_name(recipe.status()))
+        .bind(i64::from(recipe.version()))
+        .bind(recipe.updated_at())
+        .bind(recipe.category().as_str())
+        .bind(Details::of(&recipe))
+        .bind(recipe.id())
+        .execute(&mut *tx)
+        .await
+        .map_err(backend)?;
+        for table in ["ingredients", "recipe_tags"] {
+            sqlx::query(&format!("DELETE FROM {} WHERE recipe_id = ?", table))
+                .bind(recipe.id())
+                .execute(&mut *tx)
+                .await
+                .map_err(backend)?;
+        }
+        insert_relations(&mut tx, &recipe).await?;
+        insert_revision(&mut tx, &recipe).await?;
+        tx.commit().await.map_err(backend)?;
+        Ok(recipe)
+    }
+}
+
+impl RecipeRepository for SqliteRecipeRepository {
+    async fn create(&self, recipe: NewRecipe) -> Result<Recipe, RepoError> {
+        let recipe = recipe.build().map_err(RepoError::Invalid)?;
+        retry_busy(|| self.try_insert(&recipe)).await?;
+        Ok(recipe)
+    }
+
+    async fn get(&self, id: Uuid) -> Result<Option<Recipe>, RepoError> {
+        let mut conn = self.pool.acquire().await.map_err(backend)?;
+        let rows = sqlx::query("SELECT * FROM recipes WHERE id = ?").bind(id).fetch_all(&mut *conn).await;
+        Ok(load(&mut conn, rows.map_err(backend)?).await?.pop())
+    }
+
+    async fn update(&self, recipe: Recipe, expected_version: u32) -> Result<Recipe, RepoError> {
+        retry_busy(|| self.try_update(&recipe, expected_version)).await
+    }
+
+    async fn history(&self, id: Uuid) -> Result<Vec<RecipeRevision>, RepoError> {
+        let rows = sqlx::query("SELECT * FROM recipe_revisions WHERE recipe_id = ? ORDER BY version")
+            .bind(id)
+            .fetch_all(&self.pool)
+            .await
+            .map_err(backend)?;
+        if rows.is_empty() {
+            return Ok(unrecorded_history(self.get(id).await?.ok_or(RepoError::NotFound(id))?));
+        }
+        rows.iter().map(revision_from_row).collect()
+    }
+
+    async fn delete(&self, id: Uuid) -> Result<bool, RepoError> {
+        let deleted = retry_busy(|| async {
+            let result = sqlx::query("DELETE FROM recipes WHERE id = ?").bind(id).execute(&self.pool).await;
+            Ok(result.map_err(backend)?.rows_affected() > 0)
+        });
+        deleted.await
+    }
+
+    /// Filters, sorts and pages in SQL, counting the matches in the same read transaction so the total
+    /// agrees with the page.
+    async fn list(&self, query: ListQuery) -> Result<Page<Recipe>, RepoError> {
+        query.check_limit(self.max_limit)?;
+        let empty = Page { items: Vec::new(), total: 0, offset: query.offset, limit: query.limit };
+        let tag = match query.tag.as_deref().map(RecipeTag::new) {
+            Some(Ok(tag)) => Some(tag.normalized()),
+            Some(Err(_)) => return Ok(empty),
+            None => None,
+        };
+        let mut tx = self.pool.begin().await.map_err(backend)?;
+        let mut count = QueryBuilder::new("SELECT COUNT(*) FROM recipes");
+        push_filters(&mut count, &query, tag.as_deref());
+        let total: i64 = count.build_query_scalar().fetch_one(&mut *tx).await.map_err(backend)?;
+        let mut select = QueryBuilder::new("SELECT * FROM recipes");
+        push_filters(&mut select, &query, tag.as_deref());
+        select
+            .push(" ORDER BY ")
+            .push(order_by(query.sort))
+            .push(" LIMIT ")
+            .push_bind(i64::from(query.limit))
+            .push(" OFFSET ")
+            .push_bind(i64::try_from(query.offset).unwrap_or(i64::MAX));
+        let rows = select.build().fetch_all(&mut *tx).await.map_err(backend)?;
+        let items = load(&mut tx, rows).await?;
+        tx.commit().await.map_err(backend)?;
+        Ok(Page { items, total: u64::try_from(total).map_err(backend)?, ..empty })
+    }
+}
+
+/// The recipes in `rows` along with their ingredients and tags, in the same order.
+async fn load(conn: &mut SqliteConnection, rows: Vec<SqliteRow>) -> Result<Vec<Recipe>, RepoError> {
+    let ids: Vec<Uuid> = rows.iter().map(|row| row.try_get("id")).collect::<Result<_, _>>().map_err(backend)?;
+    if ids.is_empty() {
+        return Ok(Vec::new());
+    }
+    let mut ingredients: HashMap<Uuid, Vec<Ingredient>> = HashMap::new();
+    let ingredient_rows = select_by_ids("SELECT * FROM ingredients WHERE recipe_id", &ids)
+        .build()
+        .fetch_all(&mut *conn)
+        .await
+        .map_err(backend)?;
+    for row in ingredient_rows {
+        let ingredient = ingredient_from_row(&row)?;
+        ingredients.entry(row.try_get("recipe_id").map_err(backend)?).or_default().push(ingredient);
+    }
+    let mut tags: HashMap<Uuid, Vec<RecipeTag>> = HashMap::new();
+    let tag_rows = select_by_ids("SELECT recipe_id, tag FROM recipe_tags WHERE recipe_id", &ids)
+        .build()
+        .fetch_all(&mut *conn)
+        .await
+        .map_err(backend)?;
+    for row in tag_rows {
+        let tag = tag_from_row(&row)?;
+        tags.entry(row.try_get("recipe_id").map_err(backend)?).or_default().push(tag);
+    }
+    rows.iter()
+        .zip(ids)
+        .map(|(row, id)| {
+            let ingredients = ingredients.remove(&id).unwrap_or_default();
+            recipe_from_row(row, ingredients, tags.remove(&id).unwrap_or_default())
+        })
+        .collect()
+}
+
+/// The `ORDER BY` columns sorting recipes like `SortOrder::compare`.
+fn order_by(sort: SortOrder) -> &'static str {
+    match sort {
+        SortOrder::Name => "lower(name), id",
+        SortOrder::Newest => "created_at DESC, id",
+        SortOrder::RecentlyUpdated => "updated_at DESC, id",
+    }
+}
+
+/// Adds the conditions of `query` as a `WHERE` clause, matching the way `ListQuery::matches` does. SQLite's
+/// `lower` only lowercases ASCII letters.
+fn push_filters<'a>(builder: &mut QueryBuilder<'a, Sqlite>, query: &'a ListQuery, tag: Option<&'a str>) {
+    let status = query.status.unwrap_or(RecipeStatus::Published);
+    builder.push(" WHERE status = ").push_bind(status_name(status));
+    if let Some(tag) = tag {
+        builder
+            .push(" AND EXISTS (SELECT 1 FROM recipe_tags WHERE recipe_id = recipes.id AND normalized = ")
+            .push_bind(tag)
+            .push(")");
+    }
+    for word in query.search.iter().flat_map(|search| search.split_whitespace()) {
+        builder
+            .push(" AND instr(lower(name || char(10) || description || char(10) || directions), ")
+            .push_bind(word.to_ascii_lowercase())
+            .push(") > 0");
+    }
+}
+
+/// Runs `write` again while it fails because the database is locked, waiting twice as long before each
+/// attempt, and returns the result of the last attempt.
+async fn retry_busy<T, F, Fut>(mut write: F) -> Result<T, RepoError>
+where
+    F: FnMut() -> Fut,
+    Fut: Future<Output = Result<T, RepoError>>,
+{
+    let mut backoff = INITIAL_BACKOFF;
+    for _ in 1..MAX_ATTEMPTS {
+        match write().await {
+            Err(err) if is_busy(&err) => {
+                tokio::time::sleep(backoff).await;
+                backoff *= 2;
+            }
+            result => return result,
+        }
+    }
+    write().await
+}
+
+fn is_busy(err: &RepoError) -> bool {
+    let RepoError::Backend(err) = err else {
+        return false;
+    };
+    match err.downcast_ref::<sqlx::Error>() {
+        Some(sqlx::Error::Database(db)) => db
+            .code()
+            .and_then(|code| code.parse::<i32>().ok())
+            .is_some_and(|code| BUSY_CODES.contains(&(code & 0xff))),
+        _ => false,
+    }
+}
+
+/// A query selecting the rows whose column, ending `sql`, is one of `ids`.
+fn select_by_ids<'a>(sql: &str, ids: &[Uuid]) -> QueryBuilder<'a, Sqlite> {
+    let mut builder = QueryBuilder::new(sql);
+    builder.push(" IN (");
+    let mut separated = builder.separated(", ");
+    for id in ids {
+        separated.push_bind(*id);
+    }
+    builder.push(")");
+    builder
+}
+
+async fn insert_relations(tx: &mut Transaction<'_, Sqlite>, recipe: &Recipe) -> Result<(), RepoError> {
+    for ingredient in recipe.ingredients() {
+        sqlx::query("INSERT INTO ingredients (id, recipe_id, name, amount, unit, note) VALUES (?, ?, ?, ?, ?, ?)")
+            .bind(ingredient.id())
+            .bind(recipe.id())
+            .bind(ingredient.name())
+            .bind(ingredient.quantity().amount())
+            .bind(ingredient.quantity().unit().to_string())
+            .bind(ingredient.note())
+            .execute(&mut **tx)
+            .await
+            .map_err(|err| write_error(err, recipe.id()))?;
+    }
+    for tag in recipe.tags() {
+        sqlx::query("INSERT INTO recipe_tags (recipe_id, tag, normalized) VALUES (?, ?, ?)")
+            .bind(recipe.id())
+            .bind(tag.as_str())
+            .bind(tag.normalized())
+            .execute(&mut **tx)
+            .await
+            .map_err(|err| write_error(err, recipe.id()))?;
+    }
+    Ok(())
+}
+
+/// Records `recipe` as it was stored in the `recipe_revisions` table, without an author.
+async fn insert_revision(tx: &mut Transaction<'_, Sqlite>, recipe: &Recipe) -> Result<(), RepoError> {
+    sqlx::query("INSERT INTO recipe_revisions (recipe_id, version, recorded_at, recipe) VALUES (?, ?, ?, ?)")
+        .bind(recipe.id())
+        .bind(i64::from(recipe.version()))
+        .bind(recipe.updated_at())
+        .bind(Json(recipe))
+        .execute(&mut **tx)
+        .await
+        .map_err(backend)?;
+    Ok(())
+}
@@ -0,0 +1,12 @@
+//! The types most code working with recipes needs, for importing with `use recipe_api::prelude::*;`.
+//!
+//! The prelude holds the core recipe types, `Recipe`, `RecipeBuilder`, `Ingredient`, `Step`, `Quantity`,
+//! `Unit`, `Difficulty`, `RecipeTag`, `RecipeStatus` and `Cookbook`, and the errors returned when building,
+//! validating or changing them: `RecipeBuildError`, `ValidationError`, `ValidationErrors`, `TagError`,
+//! `StatusError`, `ConversionError` and `ParseQuantityError`. Everything stays available under its path in
+//! `models` as well.
+
+pub use crate::models::{
+    ConversionError, Cookbook, Difficulty, Ingredient, ParseQuantityError, Quantity, Recipe, RecipeBuildError,
+    RecipeBuilder, RecipeStatus, RecipeTag, StatusError, Step, TagError, Unit, ValidationError, ValidationErrors,
+};
@@ -0,0 +1,133 @@
+//! Runs the repository conformance suite against `FileRepository` and checks that it recovers from a crash
+//! part way through a write, with each test using its own directory in the temporary directory.
+#![cfg(feature = "serde")]
+
+use std::fs;
+use std::path::PathBuf;
+use recipe_api::models::{Category, Difficulty, NewRecipe, RecipeImage, RecipeRevision, Step};
+use recipe_api::persistence::{conformance, FileRepository, RecipeRepository};
+use uuid::Uuid;
+
+const PNG: [u8; 8] = [0x89, b'P', b'N', b'G', 0x0D, 0x0A, 0x1A, 0x0A];
+
+/// A directory that is deleted with everything in it when dropped.
+struct TempDir(PathBuf);
+
+impl TempDir {
+    fn new() -> Self {
+        TempDir(std::env::temp_dir().join(format!("recipe_api-{}", Uuid::new_v4())))
+    }
+
+    fn files(&self) -> Vec<String> {
+        let mut files: Vec<String> = fs::read_dir(&self.0)
+            .expect("could not list the store")
+            .map(|entry| entry.expect("could not list the store").file_name().to_string_lossy().into_owned())
+            .collect();
+        files.sort();
+        files
+    }
+}
+
+impl Drop for TempDir {
+    fn drop(&mut self) {
+        let _ = fs::remove_dir_all(&self.0);
+    }
+}
+
+fn new_recipe(name: &str) -> NewRecipe {
+    let mut recipe = NewRecipe::new(name, Difficulty::Easy, 2, Category::MainCourse);
+    recipe.cook_minutes = Some(10);
+    recipe.steps = vec![Step::new("Cook")];
+    recipe
+}
+
+#[tokio::test]
+async fn file_repository_conforms() {
+    let dir = TempDir::new();
+    conformance(&FileRepository::open(&dir.0).expect("could not open the store")).await;
+}
+
+#[tokio::test]
+async fn images_are_stored_next_to_the_json() {
+    let dir = TempDir::new();
+    let repo = FileRepository::open(&dir.0).expect("could not open the store");
+    let mut recipe = repo.create(new_recipe("Toast")).await.expect("create failed");
+    let created_version = recipe.version();
+    let image = RecipeImage::new(PNG.to_vec()).with_caption("golden").primary();
+    recipe.add_image(image.clone());
+    let recipe = repo.update(recipe, created_version).await.expect("update failed");
+    let image_file = format!("{}.{}.png", recipe.id(), image.id());
+    let (history_file, json_file) = (format!("{}.history", recipe.id()), format!("{}.json", recipe.id()));
+    assert_eq!(dir.files(), [image_file.clone(), history_file.clone(), json_file.clone()]);
+    let json = fs::read_to_string(dir.0.join(format!("{}.json", recipe.id()))).expect("could not read the recipe");
+    assert!(json.contains(&image_file));
+
+    let reopened = FileRepository::open(&dir.0).expect("could not reopen the store");
+    let loaded = reopened.get(recipe.id()).await.expect("get failed").expect("the recipe should be stored");
+    assert_eq!(loaded, recipe);
+    assert!(loaded.img_eq(&recipe));
+    assert_eq!(loaded.images(), recipe.images());
+
+    let mut recipe = loaded.clone();
+    let loaded_version = recipe.version();
+    recipe.remove_image(&image.id());
+    reopened.update(recipe.clone(), loaded_version).await.expect("update failed");
+    assert_eq!(dir.files(), [image_file, history_file.clone(), json_file.clone()], "the history still has the image");
+    let history = reopened.history(recipe.id()).await.expect("history failed");
+    assert_eq!(history[1].recipe.images(), loaded.images());
+    let restored = reopened.restore(recipe.id(), history[1].version()).await.expect("restore failed");
+    assert_eq!(restored.images(), loaded.images());
+    assert!(reopened.delete(recipe.id()).await.expect("delete failed"));
+    assert!(dir.files().is_empty());
+}
+
+#[tokio::test]
+async fn recovers_from_a_partial_write() {
+    let dir = TempDir::new();
+    let repo = FileRepository::open(&dir.0).expect("could not open the store");
+    let recipe = repo.create(new_recipe("Porridge")).await.expect("create failed");
+    drop(repo);
+    // A crash while updating the recipe, and another while creating one, leave truncated temporary files.
+    let path = dir.0.join(format!("{}.json", recipe.id()));
+    let json = fs::read(&path).expect("could not read the recipe");
+    fs::write(dir.0.join(format!("{}.json.tmp", recipe.id())), &json[..json.len() / 2]).expect("write failed");
+    fs::write(dir.0.join(format!("{}.json.tmp", Uuid::now_v7())), &json[..10]).expect("write failed");
+
+    let repo = FileRepository::open(&dir.0).expect("could not reopen the store");
+    assert_eq!(repo.get(recipe.id()).await.expect("get failed"), Some(recipe.clone()));
+    assert_eq!(dir.files(), [format!("{}.history", recipe.id()), format!("{}.json", recipe.id())]);
+    let mut updated = recipe.clone();
+    updated.set_name("Oat porridge");
+    let updated = repo.update(updated, recipe.version()).await.expect("update failed");
+    let reopened = FileRepository::open(&dir.0).expect("could not reopen the store");
+    assert_eq!(reopened.get(recipe.id()).await.expect("get failed"), Some(updated));
+}
+
+#[tokio::test]
+async fn histories_survive_reopening_and_catch_up_with_a_recipe_written_without_one() {
+    let dir = TempDir::new();
+    let repo = FileRepository::open(&dir.0).expect("could not open the store");
+    let mut recipe = repo.create(new_recipe("Porridge")).await.expect("create failed");
+    let created_version = recipe.version();
+    recipe.set_name("Oat porridge");
+    let updated = repo.update(recipe, created_version).await.expect("update failed");
+    let history = repo.history(updated.id()).await.expect("history failed");
+    drop(repo);
+    // A crash after writing the recipe, but before its history, leaves the history a version behind.
+    let path = dir.0.join(format!("{}.history", updated.id()));
+    let json = fs::read_to_string(&path).expect("could not read the history");
+    let mut revisions: Vec<serde_json::Value> = serde_json::from_str(&json).expect("the history should be JSON");
+    revisions.pop();
+    fs::write(&path, serde_json::to_string(&revisions).expect("the history should serialize")).expect("write failed");
+
+    let reopened = FileRepository::open(&dir.0).expect("could not reopen the store");
+    let reloaded = reopened.history(updated.id()).await.expect("history failed");
+    let versions = |history: &[RecipeRevision]| history.iter().map(RecipeRevision::version).collect::<Vec<_>>();
+    assert_eq!(versions(&reloaded), versions(&history));
+    assert_eq!(reloaded[0].recorded_at, history[0].recorded_at);
+    assert_eq!(reloaded[1].recipe.name(), "Oat porridge");
+    fs::remove_file(&path).expect("could not delete the history");
+    let reopened = FileRepository::open(&dir.0).expect("could not reopen the store");
+    let started = reopened.history(updated.id()).await.expect("history failed");
+    assert_eq!(versions(&started), [updated.version()], "a recipe without a history should start one");
+}
@@ -0,0 +1,17 @@
+//! Runs the repository conformance suite against Postgres. Needs `DATABASE_URL` to point at a database the
+//! tests may wipe, e.g. `DATABASE_URL=postgres://localhost/recipes_test cargo test --features postgres`, and
+//! is skipped when it is not set.
+#![cfg(feature = "postgres")]
+
+use recipe_api::persistence::{conformance, PgRecipeRepository};
+
+#[tokio::test]
+async fn postgres_repository_conforms() {
+    let Ok(url) = std::env::var("DATABASE_URL") else {
+        eprintln!("DATABASE_URL is not set, skipping the Postgres conformance suite");
+        return;
+    };
+    let repo = PgRecipeRepository::connect(&url).await.expect("could not connect to DATABASE_URL");
+    sqlx::query("TRUNCATE recipes CASCADE").execute(repo.pool()).await.expect("could not empty the tables");
+    conformance(&repo).await;
+}
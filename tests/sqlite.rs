@@ -0,0 +1,105 @@
+//! Runs the repository conformance suite against SQLite, with each test using its own database file in the
+//! temporary directory.
+#![cfg(feature = "sqlite")]
+
+use std::path::PathBuf;
+use std::time::Duration;
+use chrono::{TimeZone, Utc};
+use recipe_api::models::{Category, Difficulty, NewRecipe, Recipe, RecipeStatus, Step};
+use recipe_api::persistence::{conformance, ListQuery, RecipeRepository, SqliteRecipeRepository};
+use sqlx::sqlite::{SqliteConnectOptions, SqlitePool};
+use uuid::Uuid;
+
+/// A database file that is deleted, along with its WAL files, when dropped.
+struct TempDb(PathBuf);
+
+impl TempDb {
+    fn new() -> Self {
+        TempDb(std::env::temp_dir().join(format!("recipe_api-{}.db", Uuid::new_v4())))
+    }
+}
+
+impl Drop for TempDb {
+    fn drop(&mut self) {
+        for suffix in ["", "-wal", "-shm"] {
+            let _ = std::fs::remove_file(format!("{}{}", self.0.display(), suffix));
+        }
+    }
+}
+
+#[tokio::test]
+async fn sqlite_repository_conforms() {
+    let db = TempDb::new();
+    let repo = SqliteRecipeRepository::connect(&db.0).await.expect("could not open the database");
+    conformance(&repo).await;
+}
+
+#[tokio::test]
+async fn concurrent_writers_retry_while_busy() {
+    let db = TempDb::new();
+    // Without a busy timeout SQLite fails at once when another connection is writing, so every write that
+    // collides has to go through the retry.
+    let options = SqliteConnectOptions::new().filename(&db.0).create_if_missing(true).busy_timeout(Duration::ZERO);
+    let repo = SqliteRecipeRepository::connect_with(options).await.expect("could not open the database");
+    let writers = ["first", "second"].map(|writer| {
+        let repo = repo.clone();
+        tokio::spawn(async move {
+            for i in 0..25 {
+                let mut recipe = NewRecipe::new(format!("{} {}", writer, i), Difficulty::Easy, 1, Category::Side);
+                recipe.cook_minutes = Some(5);
+                recipe.steps = vec![Step::new("Cook")];
+                let mut created = repo.create(recipe).await.expect("create failed");
+                let version = created.version();
+                created.set_servings(2);
+                repo.update(created, version).await.expect("update failed");
+            }
+        })
+    });
+    for writer in writers {
+        writer.await.expect("a writer panicked");
+    }
+    let drafts = ListQuery { status: Some(RecipeStatus::Draft), ..ListQuery::default() };
+    let listed = repo.list(drafts).await.expect("list failed");
+    assert_eq!(listed.total, 50);
+    assert!(listed.items.iter().all(|recipe| recipe.servings() == 2));
+}
+
+#[tokio::test]
+async fn recipes_are_read_from_rows_without_their_relations() {
+    let pool = SqlitePool::connect("sqlite::memory:").await.expect("could not open the database");
+    sqlx::query(
+        "CREATE TABLE recipes (id BLOB PRIMARY KEY, name TEXT, difficulty INTEGER, prep_minutes INTEGER, \
+         cook_minutes INTEGER, rest_minutes INTEGER, servings INTEGER, description TEXT, directions TEXT, img BLOB, \
+         created_at TEXT, updated_at TEXT)",
+    )
+    .execute(&pool)
+    .await
+    .expect("could not create the table");
+    let insert = |id: Uuid, difficulty: i16| {
+        let created_at = Utc.with_ymd_and_hms(2024, 3, 1, 12, 0, 0).unwrap();
+        sqlx::query("INSERT INTO recipes VALUES (?, 'Soup', ?, 5, 20, 0, 4, 'Warming', ?, ?, ?, ?)")
+            .bind(id)
+            .bind(difficulty)
+            .bind("Chop the onions\nSimmer")
+            .bind(Vec::<u8>::new())
+            .bind(created_at)
+            .bind(created_at)
+            .execute(&pool)
+    };
+    let id = Uuid::now_v7();
+    insert(id, 2).await.expect("could not insert the recipe");
+
+    let recipe: Recipe = sqlx::query_as("SELECT * FROM recipes").fetch_one(&pool).await.expect("should decode");
+    assert_eq!((recipe.id(), recipe.name(), *recipe.difficulty()), (id, "Soup", Difficulty::Medium));
+    assert_eq!((recipe.prep_minutes(), recipe.cook_minutes(), recipe.servings()), (5, 20, 4));
+    assert_eq!(recipe.steps().iter().map(Step::text).collect::<Vec<_>>(), ["Chop the onions", "Simmer"]);
+    assert!(recipe.img().is_empty());
+    assert_eq!(recipe.created_at(), Utc.with_ymd_and_hms(2024, 3, 1, 12, 0, 0).unwrap());
+    assert!(recipe.ingredients().is_empty() && recipe.tags().is_empty());
+    assert_eq!((recipe.status(), recipe.category()), (RecipeStatus::Published, Category::MainCourse));
+
+    insert(Uuid::now_v7(), 9).await.expect("could not insert the recipe");
+    let rows = sqlx::query("SELECT * FROM recipes WHERE difficulty = 9").fetch_one(&pool).await.expect("should read");
+    let err = Recipe::from_row_without_relations(&rows).expect_err("9 is not a difficulty");
+    assert!(matches!(err, sqlx::Error::ColumnDecode { ref index, .. } if index == "difficulty"), "{}", err);
+}
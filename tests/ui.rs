@@ -0,0 +1,7 @@
+//! Checks that `TypedRecipeBuilder::build` does not compile until every required field is set.
+
+#[test]
+fn missing_required_fields_do_not_compile() {
+    let cases = trybuild::TestCases::new();
+    cases.compile_fail("tests/ui/*.rs");
+}
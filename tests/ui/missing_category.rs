@@ -0,0 +1,12 @@
+use recipe_api::models::{Difficulty, Recipe};
+
+fn main() {
+    let _recipe = Recipe::typed_builder()
+        .name("Soup")
+        .difficulty(Difficulty::Easy)
+        .cook_time(10)
+        .servings(2)
+        .description("A recipe")
+        .directions("Cook")
+        .build();
+}
@@ -0,0 +1,12 @@
+use recipe_api::models::{Category, Difficulty, Recipe};
+
+fn main() {
+    let _recipe = Recipe::typed_builder()
+        .difficulty(Difficulty::Easy)
+        .cook_time(10)
+        .servings(2)
+        .description("A recipe")
+        .category(Category::MainCourse)
+        .directions("Cook")
+        .build();
+}